@@ -0,0 +1,83 @@
+//! Benchmarks for each stage a script goes through: lexing, parsing,
+//! evaluating a pattern (compiled, and interpreted for bodies that can't be
+//! compiled) and making a whole animation's frames.
+//!
+//! Run with `cargo bench -p gizmo-core`; `gizmo bench <file>` times a single
+//! script instead.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use gizmo_core::lexer::Lexer;
+use gizmo_core::parser::Parser;
+
+/// A 64x64 pattern with the usual distance / angle / wave math
+const PATTERN: &str = "
+frame ring = pattern(64, 64) {
+    dx = col - 32;
+    dy = row - 32;
+    distance = sqrt(dx * dx + dy * dy);
+    angle = atan2(dy, dx);
+    wave = sin(distance * 0.4 + angle * 3);
+    return distance < 28 and wave > 0 ? color(2) : distance < 30;
+};
+";
+
+/// The same pattern with a string assignment, which keeps it from compiling
+const INTERPRETED_PATTERN: &str = "
+frame ring = pattern(64, 64) {
+    label = \"tree-walked\";
+    dx = col - 32;
+    dy = row - 32;
+    distance = sqrt(dx * dx + dy * dy);
+    angle = atan2(dy, dx);
+    wave = sin(distance * 0.4 + angle * 3);
+    return distance < 28 and wave > 0 ? color(2) : distance < 30;
+};
+";
+
+/// A looping 24-frame animation built from the pattern
+const ANIMATION: &str = "
+frames spin = [];
+repeat 24 times do
+    add_frame(spin, pattern(64, 64) {
+        dx = col - 32;
+        dy = row - 32;
+        distance = sqrt(dx * dx + dy * dy);
+        angle = atan2(dy, dx) + time * 0.26;
+        return distance < 28 and sin(angle * 4) > 0;
+    });
+end;
+loop_speed(spin, 50);
+";
+
+fn lexing(c: &mut Criterion) {
+    c.bench_function("lex animation", |b| {
+        b.iter(|| Lexer::new(black_box(ANIMATION)).tokenize().unwrap())
+    });
+}
+
+fn parsing(c: &mut Criterion) {
+    let tokens = Lexer::new(ANIMATION).tokenize().unwrap();
+    c.bench_function("parse animation", |b| {
+        b.iter(|| Parser::new(black_box(tokens.clone())).parse().unwrap())
+    });
+}
+
+fn patterns(c: &mut Criterion) {
+    let mut group = c.benchmark_group("pattern 64x64");
+    for (name, source) in [("compiled", PATTERN), ("interpreted", INTERPRETED_PATTERN)] {
+        let program = Parser::new(Lexer::new(source).tokenize().unwrap()).parse().unwrap();
+        group.bench_function(name, |b| {
+            b.iter(|| gizmo_core::Interpreter::new().execute(black_box(&program)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn rendering(c: &mut Criterion) {
+    c.bench_function("render 24-frame animation", |b| {
+        b.iter(|| gizmo_core::compile(black_box(ANIMATION)).unwrap())
+    });
+}
+
+criterion_group!(benches, lexing, parsing, patterns, rendering);
+criterion_main!(benches);
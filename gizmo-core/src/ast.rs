@@ -0,0 +1,322 @@
+use crate::lexer::Position;
+use std::sync::Arc;
+
+/// A variable or function name.
+///
+/// The parser interns names, so every use of a name in a script shares one
+/// string, and the interpreter stores variables under the same string:
+/// assigning a variable for every pixel or loop iteration doesn't allocate.
+pub type Name = Arc<str>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Program {
+    pub statements: Vec<Statement>,
+}
+
+/// A statement and where it starts in the script.
+///
+/// Positions don't take part in comparisons: a script moved around or
+/// reformatted still parses to an equal program.
+#[derive(Debug, Clone)]
+pub struct Statement {
+    pub kind: StatementKind,
+    /// `(line, column)` of the statement's first token, if the parser was
+    /// given the token positions
+    pub position: Option<Position>,
+}
+
+impl PartialEq for Statement {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind == other.kind
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[allow(clippy::enum_variant_names)]
+pub enum StatementKind {
+    VariableDeclaration {
+        var_type: VariableType,
+        name: Name,
+        value: Expression,
+    },
+    ExpressionStatement(Expression),
+    Assignment {
+        name: Name,
+        value: Expression,
+    },
+    /// `persist <name> = <default>` - a variable saved across sessions
+    PersistDeclaration {
+        name: Name,
+        value: Expression,
+    },
+    RepeatLoop {
+        count: Box<Expression>,
+        body: Vec<Statement>,
+    },
+    IfStatement {
+        condition: Expression,
+        then_body: Vec<Statement>,
+        else_body: Option<Vec<Statement>>,
+    },
+    /// `when <event> [once] [priority N] do ... end` - registers a handler run by the window loop
+    WhenStatement {
+        event: Event,
+        body: Vec<Statement>,
+        /// Unregister the handler after its first run
+        once: bool,
+        /// Decides which handler's animation wins when several switch at once (default 0)
+        priority: Option<Expression>,
+    },
+    /// A comment or blank line; only kept when parsing for `gizmo fmt`
+    Trivia(Trivia),
+}
+
+/// Source layout that doesn't change what a script does, kept so `gizmo fmt`
+/// can write it back.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Trivia {
+    /// `// text`; `trailing` if it followed code on the same line
+    Comment { text: String, trailing: bool },
+    /// One or more empty lines between statements
+    BlankLine,
+}
+
+/// Something that happens to the buddy while it is on screen.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    /// The window opened; runs once before the first frame
+    Launched,
+    /// `gizmo stop` asked the buddy to exit; runs before the fade-out
+    Stopping,
+    /// The user clicked the buddy (pressed and released without dragging)
+    Clicked,
+    /// A second click followed the first within the double-click interval
+    DoubleClicked,
+    /// The user clicked the buddy `times` times within `within` milliseconds
+    /// (`when clicked 3 times within 1000`)
+    ClickCombo { times: Expression, within: Expression },
+    /// The cursor moved onto the buddy
+    Hovered,
+    /// The cursor moved off the buddy
+    Unhovered,
+    /// The user started dragging the buddy
+    Dragged,
+    /// The user let go of the buddy after a drag
+    Dropped,
+    /// The user shook the buddy back and forth while dragging it
+    Shaken,
+    /// A key was pressed while the buddy had focus (or its global hotkey fired)
+    Key(Expression),
+    /// The local clock reached a `"HH:MM"` time of day
+    Time(Expression),
+    /// A message arrived on a subscribed MQTT topic or WebSocket (`--subscribe`)
+    Message(Expression),
+    /// Nobody has interacted for longer than the given number of milliseconds
+    Idle(Expression),
+    /// The given number of milliseconds passed since the last run (`every N do ... end`)
+    Every(Expression),
+    /// The system switched from external power to battery
+    Unplugged,
+    /// The system switched from battery to external power
+    PluggedIn,
+    /// The battery charge dropped below the given percentage while on battery
+    /// (`battery_low` is `battery < 20`)
+    Battery(Expression),
+}
+
+impl Event {
+    /// Key the interpreter files this event's handlers under.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Event::Launched => "launched",
+            Event::Stopping => "stopping",
+            Event::Clicked => "clicked",
+            Event::DoubleClicked => "double_clicked",
+            Event::ClickCombo { .. } => "click_combo",
+            Event::Hovered => "hovered",
+            Event::Unhovered => "unhovered",
+            Event::Dragged => "dragged",
+            Event::Dropped => "dropped",
+            Event::Shaken => "shaken",
+            Event::Key(_) => "key",
+            Event::Time(_) => "time",
+            Event::Message(_) => "message",
+            Event::Idle(_) => "idle",
+            Event::Every(_) => "every",
+            Event::Unplugged => "unplugged",
+            Event::PluggedIn => "plugged_in",
+            Event::Battery(_) => "battery",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum VariableType {
+    Frame,
+    Frames,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expression {
+    Number(f64),
+    String(String),
+    Identifier(Name),
+    Array(Vec<Expression>),
+    FunctionCall {
+        name: Name,
+        args: Vec<Expression>,
+    },
+    /// `name = value` inside a call's argument list, e.g. `colors(on = "#33ff66")`
+    NamedArgument {
+        name: Name,
+        value: Box<Expression>,
+    },
+    BinaryOperation {
+        left: Box<Expression>,
+        operator: BinaryOperator,
+        right: Box<Expression>,
+    },
+    PatternGenerator {
+        width: Box<Expression>,
+        height: Box<Expression>,
+        body: Vec<Statement>,
+        return_expr: Box<Expression>,
+    },
+    TernaryOperation {
+        condition: Box<Expression>,
+        true_expr: Box<Expression>,
+        false_expr: Box<Expression>,
+    },
+    Pipeline {
+        width: Box<Expression>,
+        height: Box<Expression>,
+        passes: Vec<Pass>,
+    },
+    /// `(expr)` as written; only kept when parsing for `gizmo fmt`
+    Grouping(Box<Expression>),
+    /// An operand written on the next line, e.g. after a trailing `and`;
+    /// only kept when parsing for `gizmo fmt`
+    LineBreak(Box<Expression>),
+}
+
+/// One stage of a `pipeline` expression: a pattern body without its own dimensions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pass {
+    pub body: Vec<Statement>,
+    pub return_expr: Expression,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BinaryOperator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Modulo,
+    Equal,
+    #[allow(dead_code)]
+    NotEqual,
+    Greater,
+    Less,
+    GreaterEqual,
+    LessEqual,
+    And,
+    Or,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Number(f64),
+    String(String),
+    Strings(Vec<String>),
+    Color(u8), // Palette index produced by color(n)
+    Frame(Frame),
+    Frames(Vec<Frame>),
+}
+
+
+/// A grid of palette indices.
+///
+/// The pixels are shared between clones, so passing frames around (through
+/// variables, frames arrays and the finished animation) doesn't copy them;
+/// `pixels_mut()` copies them first if another clone still uses them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Arc<Vec<Vec<u8>>>, // palette index: 0 = off (background), 1 = on, 2+ = palette colors
+}
+
+impl Frame {
+    pub fn new(data: Vec<Vec<u8>>) -> Self {
+        if data.is_empty() {
+            Self {
+                width: 0,
+                height: 0,
+                pixels: Arc::new(vec![]),
+            }
+        } else {
+            let height = data.len();
+            let width = data[0].len();
+            Self {
+                width,
+                height,
+                pixels: Arc::new(data),
+            }
+        }
+    }
+    
+    pub fn new_blank(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: Arc::new(vec![vec![0; width]; height]),
+        }
+    }
+    
+    pub fn from_array(data: Vec<Vec<u8>>) -> Result<Self, crate::error::GizmoError> {
+        if data.is_empty() {
+            return Err(crate::error::GizmoError::InvalidFrameSize(
+                "Frame cannot be empty".to_string()
+            ));
+        }
+        
+        let height = data.len();
+        let width = data[0].len();
+        
+        // Validate all rows have the same width
+        for (i, row) in data.iter().enumerate() {
+            if row.len() != width {
+                return Err(crate::error::GizmoError::InvalidFrameSize(
+                    format!("Row {} has length {} but expected {}", i, row.len(), width)
+                ));
+            }
+        }
+        
+        Ok(Self {
+            width,
+            height,
+            pixels: Arc::new(data),
+        })
+    }
+    
+    pub fn get_data(&self) -> &Vec<Vec<u8>> {
+        &self.pixels
+    }
+
+    /// The pixels for changing in place, copied first if shared with another frame.
+    pub fn pixels_mut(&mut self) -> &mut Vec<Vec<u8>> {
+        Arc::make_mut(&mut self.pixels)
+    }
+}
+
+impl Value {
+    pub fn to_number(&self) -> Result<f64, crate::error::GizmoError> {
+        match self {
+            Value::Number(n) => Ok(*n),
+            _ => Err(crate::error::GizmoError::TypeError(
+                "Cannot convert to number".to_string()
+            )),
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,1161 @@
+//! Built-in Functions for the Gizmo Scripting Language
+//!
+//! This module implements the standard library of built-in functions available
+//! to Gizmo scripts. These functions provide essential mathematical operations,
+//! animation controls, and utility functions for creating pixel art.
+//!
+//! ## Function Categories
+//!
+//! ### Mathematical Functions
+//! Core mathematical operations for calculations and procedural generation:
+//! - **Trigonometry**: `sin()`, `cos()`, `atan2()` - for circular patterns, waves, rotations
+//! - **Utility Math**: `abs()`, `floor()`, `ceil()`, `sqrt()` - for coordinate manipulation
+//! - **Random**: `random()` - for noise and variation in patterns
+//!
+//! ### Animation Control Functions
+//! Functions that control animation playback and timing:
+//! - **Playback**: `play()`, `loop()` - display frame sequences
+//! - **Timing**: `loop_speed()` - set frame rate (handled specially by interpreter)
+//! - **Frame Management**: `add_frame()` - add frames to animation sequences
+//!
+//! ### Frame Utility Functions
+//! Functions for working with frame data structures:
+//! - **Creation**: `create_frame()` - create blank frames programmatically
+//! - **Access**: `get_pixel()`, `set_pixel()` - pixel-level frame manipulation
+//! - **Sampling**: `count_neighbors()` - 3x3 kernel sampling for pipeline passes
+//! - **Import**: `load_image()` - frames from PNG / GIF files (`image` feature);
+//!   `load_aseprite()`, `aseprite_speed()` - frame sequences from Aseprite files (`aseprite` feature)
+//!
+//! ## Design Philosophy
+//!
+//! Built-in functions follow these principles:
+//! - **Type Safety**: All functions validate argument types and counts
+//! - **Error Reporting**: Detailed error messages for debugging
+//! - **Mathematical Consistency**: Standard mathematical behavior (angles in radians, etc.)
+//! - **Animation Integration**: Special handling for functions that affect the interpreter state
+//!
+//! ## Implementation Notes
+//!
+//! Functions are implemented as Rust closures stored in a HashMap for efficient lookup.
+//! Some functions like `add_frame()` and `loop_speed()` have additional special handling
+//! in the interpreter for state management.
+
+use crate::ast::{Frame, Value};
+use crate::error::{GizmoError, Result};
+use crate::sprite::{Threshold, DEFAULT_THRESHOLD};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::Path;
+
+thread_local! {
+    /// Generator `random()` draws from once [`seed_random`] fixed its seed
+    /// (the thread's own generator until then)
+    static SEEDED_RNG: RefCell<Option<StdRng>> = const { RefCell::new(None) };
+}
+
+/// Makes `random()` on this thread give the same numbers on every run that
+/// uses the same seed (and the same gizmo build), for reproducible output.
+///
+/// # Arguments
+/// * `seed` - Seed of the sequence `random()` returns from now on
+pub fn seed_random(seed: u64) {
+    SEEDED_RNG.with(|rng| *rng.borrow_mut() = Some(StdRng::seed_from_u64(seed)));
+}
+
+/// Draws the next `random()` number, from 0.0 up to (not including) 1.0.
+pub(crate) fn random_number() -> f64 {
+    SEEDED_RNG.with(|rng| match rng.borrow_mut().as_mut() {
+        Some(seeded) => seeded.gen::<f64>(),
+        None => rand::thread_rng().gen::<f64>(),
+    })
+}
+
+/// Signature shared by every built-in function implementation.
+type BuiltinFn = fn(&[Value]) -> Result<Value>;
+
+/// Registry of built-in functions available to Gizmo scripts.
+///
+/// This structure maintains a mapping from function names to their implementations,
+/// providing efficient lookup during script execution.
+pub struct BuiltinFunctions {
+    /// Map of function names to their implementation closures
+    functions: HashMap<String, BuiltinFn>,
+}
+
+/// Functions that accept `name = value` arguments.
+const NAMED_ARGUMENT_FUNCTIONS: &[&str] = &["colors"];
+
+/// Functions whose first argument is a file path, relative to the script.
+const FILE_ARGUMENT_FUNCTIONS: &[&str] = &["load_image", "load_aseprite", "aseprite_speed"];
+
+/// Signature and one-line description of every built-in function, for
+/// editor hovers and completion.
+const FUNCTION_DOCS: &[(&str, &str, &str)] = &[
+    ("play", "play(frames)", "Plays a frame or frame sequence once."),
+    ("loop", "loop(frames)", "Plays a frame or frame sequence over and over."),
+    ("add_frame", "add_frame(frames, frame)", "Appends a frame to a frame sequence."),
+    ("loop_speed", "loop_speed(frames, milliseconds)", "Loops a frame sequence, showing each frame for `milliseconds`."),
+    ("transition", "transition(kind, milliseconds)", "Blends between sequences: `\"none\"`, `\"fade\"` or `\"slide\"`."),
+    ("palette", "palette(colors)", "Sets the colors of palette indices 0, 1, ... from `\"#RRGGBB\"` / `\"#RRGGBBAA\"` strings."),
+    ("color", "color(n)", "Uses palette index `n` for a pattern pixel."),
+    ("colors", "colors(on = color, off = color)", "Sets just the foreground (1) and background (0) colors."),
+    ("move_to", "move_to(x, y, milliseconds)", "Glides the window to screen position (x, y)."),
+    ("wander", "wander(speed)", "Strolls to random spots along the monitor, `speed` in pixels per second. Never finishes."),
+    ("fall", "fall()", "Drops straight down onto the taskbar or screen bottom."),
+    ("bounce", "bounce()", "Drops like `fall()`, bouncing a few times before settling."),
+    ("opacity", "opacity(alpha)", "Makes the whole buddy `alpha` opaque, from 0.0 to 1.0."),
+    ("notify", "notify(title, body)", "Pops up a desktop notification."),
+    ("say", "say(text, milliseconds)", "Shows `text` in a speech bubble above the sprite."),
+    ("random", "random()", "Random number from 0.0 up to 1.0."),
+    ("floor", "floor(x)", "Rounds `x` down to a whole number."),
+    ("ceil", "ceil(x)", "Rounds `x` up to a whole number."),
+    ("abs", "abs(x)", "Absolute value of `x`."),
+    ("sin", "sin(x)", "Sine of `x` radians."),
+    ("cos", "cos(x)", "Cosine of `x` radians."),
+    ("sqrt", "sqrt(x)", "Square root of `x`."),
+    ("atan2", "atan2(y, x)", "Angle of the point (x, y) in radians, from -π to π."),
+    ("create_frame", "create_frame(width, height)", "Blank frame of the given size."),
+    ("get_pixel", "get_pixel(frame, x, y)", "Palette index of a pixel; 0 when off or out of bounds."),
+    ("set_pixel", "set_pixel(frame, x, y, value)", "Reserved; frames can't be changed yet."),
+    ("count_neighbors", "count_neighbors(frame, x, y)", "Number of lit pixels around (x, y), 0 to 8."),
+    ("load_image", "load_image(path, threshold)", "Frame from a PNG or GIF file (`image` feature)."),
+    ("load_aseprite", "load_aseprite(path, tag, threshold)", "Frames of an Aseprite file or tag (`aseprite` feature)."),
+    ("aseprite_speed", "aseprite_speed(path, tag)", "Milliseconds per frame for `load_aseprite()` frames of the same file and tag."),
+];
+
+/// The kinds of value a script works with, for checking calls before they run.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ValueKind {
+    Number,
+    String,
+    /// An array of strings, like `palette()` takes
+    Strings,
+    /// A palette index from `color()`
+    Color,
+    Frame,
+    Frames,
+}
+
+impl ValueKind {
+    /// Names the kind in an error message, e.g. "a number".
+    pub fn describe(&self) -> &'static str {
+        match self {
+            ValueKind::Number => "a number",
+            ValueKind::String => "a string",
+            ValueKind::Strings => "an array of strings",
+            ValueKind::Color => "a color",
+            ValueKind::Frame => "a frame",
+            ValueKind::Frames => "a frames array",
+        }
+    }
+}
+
+/// How a built-in function is called, as far as it checks its arguments.
+#[derive(Debug, Clone, Copy)]
+pub struct Signature {
+    /// Fewest arguments the function takes
+    pub min_args: usize,
+    /// The kinds each argument may be, in order; as many as the function
+    /// takes at most
+    pub params: &'static [&'static [ValueKind]],
+    /// The kind of value the function returns
+    pub returns: ValueKind,
+}
+
+impl Signature {
+    /// Describes how many arguments the function takes, e.g. "1 or 2 arguments".
+    pub fn arity(&self) -> String {
+        let (min, max) = (self.min_args, self.params.len());
+        match (min, max) {
+            (0, 0) => "no arguments".to_string(),
+            (1, 1) => "1 argument".to_string(),
+            _ if min == max => format!("{} arguments", max),
+            _ if min + 1 == max => format!("{} or {} arguments", min, max),
+            _ => format!("{} to {} arguments", min, max),
+        }
+    }
+}
+
+const ANY: &[ValueKind] = &[
+    ValueKind::Number,
+    ValueKind::String,
+    ValueKind::Strings,
+    ValueKind::Color,
+    ValueKind::Frame,
+    ValueKind::Frames,
+];
+const NUMBER: &[ValueKind] = &[ValueKind::Number];
+const STRING: &[ValueKind] = &[ValueKind::String];
+const FRAME: &[ValueKind] = &[ValueKind::Frame];
+/// A threshold of `load_image()` and `load_aseprite()`: a level or `"dither"`
+const THRESHOLD: &[ValueKind] = &[ValueKind::Number, ValueKind::String];
+
+/// Signatures of the functions that check their arguments, matching the
+/// errors they return; `loop()`, `random()` and `set_pixel()` take anything.
+const FUNCTION_SIGNATURES: &[(&str, Signature)] = &[
+    ("play", Signature { min_args: 1, params: &[&[ValueKind::Frame, ValueKind::Frames]], returns: ValueKind::Number }),
+    ("add_frame", Signature { min_args: 2, params: &[ANY, ANY], returns: ValueKind::Number }),
+    ("loop_speed", Signature { min_args: 2, params: &[&[ValueKind::Frames], ANY], returns: ValueKind::Number }),
+    ("transition", Signature { min_args: 2, params: &[STRING, NUMBER], returns: ValueKind::Number }),
+    ("palette", Signature { min_args: 1, params: &[&[ValueKind::Strings]], returns: ValueKind::Number }),
+    ("color", Signature { min_args: 1, params: &[NUMBER], returns: ValueKind::Color }),
+    ("colors", Signature { min_args: 1, params: &[STRING, STRING], returns: ValueKind::Number }),
+    ("move_to", Signature { min_args: 3, params: &[NUMBER, NUMBER, NUMBER], returns: ValueKind::Number }),
+    ("wander", Signature { min_args: 1, params: &[NUMBER], returns: ValueKind::Number }),
+    ("fall", Signature { min_args: 0, params: &[], returns: ValueKind::Number }),
+    ("bounce", Signature { min_args: 0, params: &[], returns: ValueKind::Number }),
+    ("opacity", Signature { min_args: 1, params: &[NUMBER], returns: ValueKind::Number }),
+    ("notify", Signature { min_args: 2, params: &[STRING, STRING], returns: ValueKind::Number }),
+    ("say", Signature { min_args: 2, params: &[STRING, NUMBER], returns: ValueKind::Number }),
+    ("floor", Signature { min_args: 1, params: &[NUMBER], returns: ValueKind::Number }),
+    ("ceil", Signature { min_args: 1, params: &[NUMBER], returns: ValueKind::Number }),
+    ("abs", Signature { min_args: 1, params: &[NUMBER], returns: ValueKind::Number }),
+    ("sin", Signature { min_args: 1, params: &[NUMBER], returns: ValueKind::Number }),
+    ("cos", Signature { min_args: 1, params: &[NUMBER], returns: ValueKind::Number }),
+    ("sqrt", Signature { min_args: 1, params: &[NUMBER], returns: ValueKind::Number }),
+    ("atan2", Signature { min_args: 2, params: &[NUMBER, NUMBER], returns: ValueKind::Number }),
+    ("create_frame", Signature { min_args: 2, params: &[NUMBER, NUMBER], returns: ValueKind::Frame }),
+    ("get_pixel", Signature { min_args: 3, params: &[FRAME, NUMBER, NUMBER], returns: ValueKind::Number }),
+    ("count_neighbors", Signature { min_args: 3, params: &[FRAME, NUMBER, NUMBER], returns: ValueKind::Number }),
+    ("load_image", Signature { min_args: 1, params: &[STRING, THRESHOLD], returns: ValueKind::Frame }),
+    ("load_aseprite", Signature { min_args: 1, params: &[STRING, STRING, THRESHOLD], returns: ValueKind::Frames }),
+    ("aseprite_speed", Signature { min_args: 1, params: &[STRING, STRING], returns: ValueKind::Number }),
+];
+
+impl Default for BuiltinFunctions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BuiltinFunctions {
+    /// Creates a new function registry with all built-in functions registered.
+    ///
+    /// Initializes the function registry with the complete set of built-in functions
+    /// organized by category. This is called once during interpreter initialization.
+    ///
+    /// # Function Categories
+    /// - **Animation**: `play()`, `loop()`, `add_frame()`, `loop_speed()`, `transition()`
+    /// - **Color**: `palette()`, `color()`, `colors()`
+    /// - **Motion**: `move_to()`, `wander()`, `fall()`, `bounce()`
+    /// - **Window**: `opacity()`
+    /// - **Desktop**: `notify()`
+    /// - **Mathematics**: `random()`, `floor()`, `ceil()`, `abs()`, `sin()`, `cos()`, `sqrt()`, `atan2()`
+    /// - **Frame Utilities**: `create_frame()`, `get_pixel()`, `set_pixel()`, `count_neighbors()`, `load_image()`
+    /// - **Aseprite**: `load_aseprite()`, `aseprite_speed()`
+    pub fn new() -> Self {
+        let mut functions: HashMap<String, BuiltinFn> = HashMap::new();
+        
+        // Animation control functions
+        functions.insert("play".to_string(), animation_play);
+        functions.insert("loop".to_string(), animation_loop);
+        functions.insert("add_frame".to_string(), add_frame_func);
+        functions.insert("loop_speed".to_string(), loop_speed_func);
+        functions.insert("transition".to_string(), transition_func);
+        
+        // Color functions
+        functions.insert("palette".to_string(), palette_func);
+        functions.insert("color".to_string(), color_func);
+        functions.insert("colors".to_string(), colors_func);
+        
+        // Window motion functions
+        functions.insert("move_to".to_string(), move_to_func);
+        functions.insert("wander".to_string(), wander_func);
+        functions.insert("fall".to_string(), fall_func);
+        functions.insert("bounce".to_string(), bounce_func);
+        
+        // Window appearance functions
+        functions.insert("opacity".to_string(), opacity_func);
+        
+        // Desktop integration functions
+        functions.insert("notify".to_string(), notify_func);
+        functions.insert("say".to_string(), say_func);
+        
+        // Mathematical functions
+        functions.insert("random".to_string(), math_random);
+        functions.insert("floor".to_string(), math_floor);
+        functions.insert("ceil".to_string(), math_ceil);
+        functions.insert("abs".to_string(), math_abs);
+        functions.insert("sin".to_string(), math_sin);
+        functions.insert("cos".to_string(), math_cos);
+        functions.insert("sqrt".to_string(), math_sqrt);
+        functions.insert("atan2".to_string(), math_atan2);
+        
+        // Frame utility functions
+        functions.insert("create_frame".to_string(), create_frame);
+        functions.insert("get_pixel".to_string(), get_pixel);
+        functions.insert("set_pixel".to_string(), set_pixel);
+        functions.insert("count_neighbors".to_string(), count_neighbors);
+        functions.insert("load_image".to_string(), load_image);
+        
+        // Aseprite import functions
+        functions.insert("load_aseprite".to_string(), load_aseprite);
+        functions.insert("aseprite_speed".to_string(), aseprite_speed);
+        
+        Self { functions }
+    }
+    
+    /// Checks if a function with the given name is registered.
+    ///
+    /// Used by the interpreter to validate function calls before attempting
+    /// to execute them.
+    ///
+    /// # Arguments
+    /// * `name` - Function name to check
+    ///
+    /// # Returns
+    /// `true` if the function exists, `false` otherwise
+    pub fn has_function(&self, name: &str) -> bool {
+        self.functions.contains_key(name)
+    }
+    
+    /// Checks whether a function understands `name = value` arguments.
+    ///
+    /// Only functions whose interpreter handler reads argument names accept
+    /// them; everywhere else a name would be silently ignored, so it's an error.
+    pub fn accepts_named_arguments(&self, name: &str) -> bool {
+        NAMED_ARGUMENT_FUNCTIONS.contains(&name)
+    }
+    
+    /// Checks whether a function's first argument is a file path.
+    ///
+    /// The interpreter makes relative paths relative to the script's
+    /// directory before calling these.
+    pub fn takes_file_path(&self, name: &str) -> bool {
+        FILE_ARGUMENT_FUNCTIONS.contains(&name)
+    }
+    
+    /// Returns the names of all registered functions, sorted.
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.functions.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+    
+    /// Describes a function for editors.
+    ///
+    /// # Arguments
+    /// * `name` - Function name to describe
+    ///
+    /// # Returns
+    /// * `Some((signature, description))` - How it is called, like `sqrt(x)`, and what it does
+    /// * `None` - No such function
+    pub fn documentation(&self, name: &str) -> Option<(&'static str, &'static str)> {
+        FUNCTION_DOCS
+            .iter()
+            .find(|(function, _, _)| *function == name)
+            .map(|&(_, signature, description)| (signature, description))
+    }
+    
+    /// Returns how a function is called, for checking calls before they run.
+    ///
+    /// # Returns
+    /// * `Some(Signature)` - The arguments the function checks and what it returns
+    /// * `None` - No such function, or one that takes any arguments
+    pub fn signature(&self, name: &str) -> Option<Signature> {
+        FUNCTION_SIGNATURES
+            .iter()
+            .find(|(function, _)| *function == name)
+            .map(|&(_, signature)| signature)
+    }
+    
+    /// Calls a built-in function with the provided arguments.
+    ///
+    /// Looks up the function by name and executes it with the given arguments.
+    /// The function implementation handles argument validation and computation.
+    ///
+    /// # Arguments
+    /// * `name` - Name of the function to call
+    /// * `args` - Array of argument values
+    ///
+    /// # Returns
+    /// * `Ok(Value)` - Function result
+    /// * `Err(GizmoError)` - Function not found or execution error
+    pub fn call(&self, name: &str, args: &[Value]) -> Result<Value> {
+        if let Some(func) = self.functions.get(name) {
+            func(args)
+        } else {
+            Err(GizmoError::UndefinedFunction(name.to_string()))
+        }
+    }
+}
+
+/// `play(frames)` - Displays a frame or frame sequence once.
+///
+/// This function signals the interpreter to display the provided frames.
+/// The actual animation control is handled by special interpreter logic.
+///
+/// # Arguments
+/// * `frames` - Single frame or array of frames to display
+///
+/// # Returns
+/// * `Ok(1.0)` - Success indicator
+/// * `Err` - Invalid argument type or count
+///
+/// # Usage
+/// ```gzmo
+/// play(my_frame)        // Display single frame
+/// play(animation_frames) // Play animation sequence once
+/// ```
+fn animation_play(args: &[Value]) -> Result<Value> {
+    if args.len() != 1 {
+        return Err(GizmoError::ArgumentError(
+            format!("play expects 1 argument, got {}", args.len())
+        ));
+    }
+    
+    match &args[0] {
+        Value::Frames(_) | Value::Frame(_) => {
+            // Success indicator - actual playback handled by interpreter
+            Ok(Value::Number(1.0))
+        }
+        _ => Err(GizmoError::TypeError(
+            "play argument must be a frame or frames array".to_string()
+        )),
+    }
+}
+
+/// `loop(frames)` - Displays a frame or frame sequence in a continuous loop.
+///
+/// Similar to `play()` but indicates that the animation should repeat indefinitely.
+/// The actual looping behavior is handled by the interpreter and window system.
+///
+/// # Arguments
+/// * `frames` - Single frame or array of frames to loop
+///
+/// # Returns
+/// * `Ok(1.0)` - Success indicator
+///
+/// # Usage
+/// ```gzmo
+/// loop(spinner_frames)  // Loop animation continuously
+/// ```
+fn animation_loop(_args: &[Value]) -> Result<Value> {
+    // Success indicator - actual looping handled by interpreter
+    Ok(Value::Number(1.0))
+}
+
+/// `random()` - Generates a random floating-point number between 0.0 and 1.0.
+///
+/// Uses the system's random number generator to produce pseudo-random values
+/// suitable for adding variation to patterns and animations, or a seeded one
+/// after [`seed_random`] (`--deterministic`).
+///
+/// # Arguments
+/// None
+///
+/// # Returns
+/// * `Ok(Number)` - Random value in range [0.0, 1.0)
+///
+/// # Usage
+/// ```gzmo
+/// noise = random()           // Random value 0.0-1.0
+/// x = random() * 100         // Random value 0.0-100.0
+/// on = random() > 0.5        // Random true/false
+/// ```
+fn math_random(_args: &[Value]) -> Result<Value> {
+    Ok(Value::Number(random_number()))
+}
+
+/// `floor(x)` - Returns the largest integer less than or equal to x.
+///
+/// Rounds a floating-point number down to the nearest integer.
+/// Useful for coordinate calculations and discrete pattern generation.
+///
+/// # Arguments
+/// * `x` - Number to floor
+///
+/// # Returns
+/// * `Ok(Number)` - Floored value
+/// * `Err` - Invalid argument type or count
+///
+/// # Examples
+/// ```gzmo
+/// floor(3.7)   // Returns 3.0
+/// floor(-2.3)  // Returns -3.0
+/// floor(5.0)   // Returns 5.0
+/// ```
+fn math_floor(args: &[Value]) -> Result<Value> {
+    if args.len() != 1 {
+        return Err(GizmoError::ArgumentError(
+            format!("floor expects 1 argument, got {}", args.len())
+        ));
+    }
+    
+    match &args[0] {
+        Value::Number(n) => Ok(Value::Number(n.floor())),
+        _ => Err(GizmoError::TypeError("floor argument must be a number".to_string())),
+    }
+}
+
+/// `ceil(x)` - Returns the smallest integer greater than or equal to x.
+///
+/// Rounds a floating-point number up to the nearest integer.
+/// Useful for coordinate calculations and ensuring minimum sizes.
+///
+/// # Arguments
+/// * `x` - Number to ceiling
+///
+/// # Returns
+/// * `Ok(Number)` - Ceiling value
+/// * `Err` - Invalid argument type or count
+///
+/// # Examples
+/// ```gzmo
+/// ceil(3.2)    // Returns 4.0
+/// ceil(-2.7)   // Returns -2.0
+/// ceil(5.0)    // Returns 5.0
+/// ```
+fn math_ceil(args: &[Value]) -> Result<Value> {
+    if args.len() != 1 {
+        return Err(GizmoError::ArgumentError(
+            format!("ceil expects 1 argument, got {}", args.len())
+        ));
+    }
+    
+    match &args[0] {
+        Value::Number(n) => Ok(Value::Number(n.ceil())),
+        _ => Err(GizmoError::TypeError("ceil argument must be a number".to_string())),
+    }
+}
+
+/// `abs(x)` - Returns the absolute value of x.
+///
+/// Computes the absolute value (magnitude) of a number, always returning
+/// a non-negative result. Useful for distance calculations and ensuring
+/// positive values.
+///
+/// # Arguments
+/// * `x` - Number to take absolute value of
+///
+/// # Returns
+/// * `Ok(Number)` - Absolute value (always >= 0.0)
+/// * `Err` - Invalid argument type or count
+///
+/// # Examples
+/// ```gzmo
+/// abs(5)       // Returns 5.0
+/// abs(-3.7)    // Returns 3.7
+/// abs(0)       // Returns 0.0
+/// ```
+fn math_abs(args: &[Value]) -> Result<Value> {
+    if args.len() != 1 {
+        return Err(GizmoError::ArgumentError(
+            format!("abs expects 1 argument, got {}", args.len())
+        ));
+    }
+    
+    match &args[0] {
+        Value::Number(n) => Ok(Value::Number(n.abs())),
+        _ => Err(GizmoError::TypeError("abs argument must be a number".to_string())),
+    }
+}
+
+fn create_frame(args: &[Value]) -> Result<Value> {
+    if args.len() != 2 {
+        return Err(GizmoError::ArgumentError(
+            format!("create_frame expects 2 arguments (width, height), got {}", args.len())
+        ));
+    }
+    
+    let width = match &args[0] {
+        Value::Number(n) => *n as usize,
+        _ => return Err(GizmoError::TypeError("width must be a number".to_string())),
+    };
+    
+    let height = match &args[1] {
+        Value::Number(n) => *n as usize,
+        _ => return Err(GizmoError::TypeError("height must be a number".to_string())),
+    };
+    
+    Ok(Value::Frame(crate::ast::Frame::new_blank(width, height)))
+}
+
+/// `load_image(path, threshold)` - Loads a PNG or GIF file as a frame.
+///
+/// The interpreter has already made a relative `path` relative to the
+/// script's directory.
+///
+/// # Arguments
+/// * `path` - Image file
+/// * `threshold` - Optional: luminance from 0 to 1 from which pixels are lit
+///   (default 0.5), or `"dither"`
+///
+/// # Returns
+/// * `Ok(Frame)` - One frame pixel per image pixel
+/// * `Err` - Invalid arguments, or the image couldn't be loaded
+///
+/// # Usage
+/// ```gzmo
+/// frame cat = load_image("cat.png", 0);   // Every opaque pixel lit
+/// frame photo = load_image("me.png", "dither");
+/// ```
+fn load_image(args: &[Value]) -> Result<Value> {
+    if args.is_empty() || args.len() > 2 {
+        return Err(GizmoError::ArgumentError(
+            format!("load_image expects 1 or 2 arguments (path, threshold), got {}", args.len())
+        ));
+    }
+    
+    let path = match &args[0] {
+        Value::String(path) => path,
+        _ => return Err(GizmoError::TypeError("load_image path must be a string".to_string())),
+    };
+    let threshold = match args.get(1) {
+        None => Threshold::Level(DEFAULT_THRESHOLD),
+        Some(Value::Number(level)) if (0.0..=1.0).contains(level) => Threshold::Level(*level),
+        Some(Value::String(mode)) if mode == "dither" => Threshold::Dither,
+        Some(_) => return Err(GizmoError::ArgumentError(
+            "load_image threshold must be a number from 0 to 1, or \"dither\"".to_string()
+        )),
+    };
+    
+    Ok(Value::Frame(crate::sprite::load_frame(Path::new(path), threshold)?))
+}
+
+/// `load_aseprite(path, tag, threshold)` - Loads the frames of an Aseprite file.
+///
+/// Frames are repeated to honor their durations in the file; play them at
+/// `aseprite_speed()` of the same file and tag.
+///
+/// # Arguments
+/// * `path` - `.aseprite` / `.ase` file
+/// * `tag` - Optional: only this tag's frames, in its direction (`""` for all frames)
+/// * `threshold` - Optional: as for `load_image()`
+///
+/// # Returns
+/// * `Ok(Frames)` - The frame sequence
+/// * `Err` - Invalid arguments, an unknown tag, or the file couldn't be loaded
+///
+/// # Usage
+/// ```gzmo
+/// frames walk = load_aseprite("cat.aseprite", "walk", 0);
+/// loop_speed(walk, aseprite_speed("cat.aseprite", "walk"));
+/// ```
+fn load_aseprite(args: &[Value]) -> Result<Value> {
+    if args.is_empty() || args.len() > 3 {
+        return Err(GizmoError::ArgumentError(
+            format!("load_aseprite expects 1 to 3 arguments (path, tag, threshold), got {}", args.len())
+        ));
+    }
+    
+    let (path, tag) = aseprite_source("load_aseprite", args)?;
+    let threshold = match args.get(2) {
+        None => Threshold::Level(DEFAULT_THRESHOLD),
+        Some(Value::Number(level)) if (0.0..=1.0).contains(level) => Threshold::Level(*level),
+        Some(Value::String(mode)) if mode == "dither" => Threshold::Dither,
+        Some(_) => return Err(GizmoError::ArgumentError(
+            "load_aseprite threshold must be a number from 0 to 1, or \"dither\"".to_string()
+        )),
+    };
+    
+    Ok(Value::Frames(crate::sprite::load_aseprite(Path::new(path), tag, threshold)?.frames))
+}
+
+/// `aseprite_speed(path, tag)` - Frame duration for `load_aseprite()` frames.
+///
+/// # Arguments
+/// * `path` - `.aseprite` / `.ase` file
+/// * `tag` - Optional: the tag passed to `load_aseprite()`
+///
+/// # Returns
+/// * `Ok(Number)` - Milliseconds per frame, for `loop_speed()`
+/// * `Err` - Invalid arguments, an unknown tag, or the file couldn't be loaded
+fn aseprite_speed(args: &[Value]) -> Result<Value> {
+    if args.is_empty() || args.len() > 2 {
+        return Err(GizmoError::ArgumentError(
+            format!("aseprite_speed expects 1 or 2 arguments (path, tag), got {}", args.len())
+        ));
+    }
+    
+    let (path, tag) = aseprite_source("aseprite_speed", args)?;
+    let sequence = crate::sprite::load_aseprite(Path::new(path), tag, Threshold::Level(DEFAULT_THRESHOLD))?;
+    Ok(Value::Number(sequence.tick_ms as f64))
+}
+
+/// Reads the `path` and optional `tag` arguments of the Aseprite functions.
+fn aseprite_source<'a>(function: &str, args: &'a [Value]) -> Result<(&'a str, Option<&'a str>)> {
+    let path = match &args[0] {
+        Value::String(path) => path,
+        _ => return Err(GizmoError::TypeError(format!("{} path must be a string", function))),
+    };
+    let tag = match args.get(1) {
+        None => None,
+        Some(Value::String(tag)) if tag.is_empty() => None,
+        Some(Value::String(tag)) => Some(tag.as_str()),
+        Some(_) => return Err(GizmoError::TypeError(format!("{} tag must be a string", function))),
+    };
+    Ok((path, tag))
+}
+
+fn get_pixel(args: &[Value]) -> Result<Value> {
+    if args.len() != 3 {
+        return Err(GizmoError::ArgumentError(
+            format!("get_pixel expects 3 arguments (frame, x, y), got {}", args.len())
+        ));
+    }
+    
+    let frame = match &args[0] {
+        Value::Frame(f) => f,
+        _ => return Err(GizmoError::TypeError("first argument must be a frame".to_string())),
+    };
+    
+    let x = match &args[1] {
+        Value::Number(n) => *n,
+        _ => return Err(GizmoError::TypeError("x coordinate must be a number".to_string())),
+    };
+    
+    let y = match &args[2] {
+        Value::Number(n) => *n,
+        _ => return Err(GizmoError::TypeError("y coordinate must be a number".to_string())),
+    };
+    
+    Ok(Value::Number(pixel_at(frame, x, y)))
+}
+
+/// The palette index at (x, y) as a number, 0 (off) outside the frame.
+///
+/// Shared by `get_pixel()` and compiled patterns.
+pub(crate) fn pixel_at(frame: &Frame, x: f64, y: f64) -> f64 {
+    if x < 0.0 || y < 0.0 {
+        return 0.0;
+    }
+    let (x, y) = (x as usize, y as usize);
+    if y < frame.height && x < frame.width {
+        frame.pixels[y][x] as f64
+    } else {
+        0.0
+    }
+}
+
+/// `count_neighbors(frame, x, y)` - Counts the lit pixels surrounding (x, y).
+///
+/// Looks at the 8 pixels adjacent to the given coordinate. Pixels outside the
+/// frame count as off. Mostly useful inside `pipeline` passes, where it turns
+/// `previous` into blur, erosion, and outline kernels.
+///
+/// # Arguments
+/// * `frame` - Frame to sample
+/// * `x` - Column of the center pixel
+/// * `y` - Row of the center pixel
+///
+/// # Returns
+/// * `Ok(Number)` - Neighbor count in range [0, 8]
+/// * `Err` - Invalid argument type or count
+///
+/// # Examples
+/// ```gzmo
+/// edge = get_pixel(previous, col, row) and count_neighbors(previous, col, row) < 8;
+/// ```
+fn count_neighbors(args: &[Value]) -> Result<Value> {
+    if args.len() != 3 {
+        return Err(GizmoError::ArgumentError(
+            format!("count_neighbors expects 3 arguments (frame, x, y), got {}", args.len())
+        ));
+    }
+    
+    let frame = match &args[0] {
+        Value::Frame(f) => f,
+        _ => return Err(GizmoError::TypeError("first argument must be a frame".to_string())),
+    };
+    
+    let x = match &args[1] {
+        Value::Number(n) => *n,
+        _ => return Err(GizmoError::TypeError("x coordinate must be a number".to_string())),
+    };
+    
+    let y = match &args[2] {
+        Value::Number(n) => *n,
+        _ => return Err(GizmoError::TypeError("y coordinate must be a number".to_string())),
+    };
+    
+    Ok(Value::Number(neighbor_count(frame, x, y)))
+}
+
+/// How many of the 8 pixels around (x, y) are lit; pixels outside the frame
+/// count as off.
+///
+/// Shared by `count_neighbors()` and compiled patterns.
+pub(crate) fn neighbor_count(frame: &Frame, x: f64, y: f64) -> f64 {
+    let (x, y) = (x.floor() as i64, y.floor() as i64);
+    let mut count = 0;
+    for dy in -1..=1 {
+        for dx in -1..=1 {
+            if dx == 0 && dy == 0 {
+                continue; // Skip the center pixel
+            }
+            
+            let (nx, ny) = (x + dx, y + dy);
+            if nx >= 0 && ny >= 0 && (nx as usize) < frame.width && (ny as usize) < frame.height
+                && frame.pixels[ny as usize][nx as usize] != 0
+            {
+                count += 1;
+            }
+        }
+    }
+    
+    count as f64
+}
+
+fn set_pixel(_args: &[Value]) -> Result<Value> {
+    // For now, return success - implementing mutable frames would require more work
+    Ok(Value::Number(1.0))
+}
+
+/// `sin(x)` - Returns the sine of x (where x is in radians).
+///
+/// Computes the trigonometric sine function. Essential for creating
+/// wave patterns, circular motions, and smooth oscillations in animations.
+///
+/// # Arguments
+/// * `x` - Angle in radians
+///
+/// # Returns
+/// * `Ok(Number)` - Sine value in range [-1.0, 1.0]
+/// * `Err` - Invalid argument type or count
+///
+/// # Examples
+/// ```gzmo
+/// sin(0)           // Returns 0.0
+/// sin(3.14159/2)   // Returns ~1.0 (π/2 radians = 90°)
+/// wave = sin(col * 0.1)  // Create horizontal wave pattern
+/// ```
+fn math_sin(args: &[Value]) -> Result<Value> {
+    if args.len() != 1 {
+        return Err(GizmoError::ArgumentError(
+            format!("sin expects 1 argument, got {}", args.len())
+        ));
+    }
+    
+    match &args[0] {
+        Value::Number(n) => Ok(Value::Number(n.sin())),
+        _ => Err(GizmoError::TypeError("sin argument must be a number".to_string())),
+    }
+}
+
+/// `cos(x)` - Returns the cosine of x (where x is in radians).
+///
+/// Computes the trigonometric cosine function. Used alongside `sin()` for
+/// circular patterns, rotations, and creating smooth periodic animations.
+///
+/// # Arguments
+/// * `x` - Angle in radians
+///
+/// # Returns
+/// * `Ok(Number)` - Cosine value in range [-1.0, 1.0]
+/// * `Err` - Invalid argument type or count
+///
+/// # Examples
+/// ```gzmo
+/// cos(0)           // Returns 1.0
+/// cos(3.14159)     // Returns ~-1.0 (π radians = 180°)
+/// x = cos(angle)   // X component of circular motion
+/// ```
+fn math_cos(args: &[Value]) -> Result<Value> {
+    if args.len() != 1 {
+        return Err(GizmoError::ArgumentError(
+            format!("cos expects 1 argument, got {}", args.len())
+        ));
+    }
+    
+    match &args[0] {
+        Value::Number(n) => Ok(Value::Number(n.cos())),
+        _ => Err(GizmoError::TypeError("cos argument must be a number".to_string())),
+    }
+}
+
+fn math_sqrt(args: &[Value]) -> Result<Value> {
+    if args.len() != 1 {
+        return Err(GizmoError::ArgumentError(
+            format!("sqrt expects 1 argument, got {}", args.len())
+        ));
+    }
+    
+    match &args[0] {
+        Value::Number(n) => {
+            if *n < 0.0 {
+                return Err(GizmoError::ArgumentError("sqrt of negative number".to_string()));
+            }
+            Ok(Value::Number(n.sqrt()))
+        },
+        _ => Err(GizmoError::TypeError("sqrt argument must be a number".to_string())),
+    }
+}
+
+fn math_atan2(args: &[Value]) -> Result<Value> {
+    if args.len() != 2 {
+        return Err(GizmoError::ArgumentError(
+            format!("atan2 expects 2 arguments (y, x), got {}", args.len())
+        ));
+    }
+    
+    let y = match &args[0] {
+        Value::Number(n) => *n,
+        _ => return Err(GizmoError::TypeError("atan2 first argument (y) must be a number".to_string())),
+    };
+    
+    let x = match &args[1] {
+        Value::Number(n) => *n,
+        _ => return Err(GizmoError::TypeError("atan2 second argument (x) must be a number".to_string())),
+    };
+    
+    Ok(Value::Number(y.atan2(x)))
+}
+
+fn add_frame_func(args: &[Value]) -> Result<Value> {
+    if args.len() != 2 {
+        return Err(GizmoError::ArgumentError(
+            format!("add_frame expects 2 arguments (frames_array, frame), got {}", args.len())
+        ));
+    }
+    
+    // For now, this is a placeholder - we'd need to implement mutable arrays
+    // The interpreter would need to handle this specially
+    Ok(Value::Number(1.0))
+}
+
+fn loop_speed_func(args: &[Value]) -> Result<Value> {
+    if args.len() != 2 {
+        return Err(GizmoError::ArgumentError(
+            format!("loop_speed expects 2 arguments (frames_array, ms), got {}", args.len())
+        ));
+    }
+    
+    // Similar to play() but with speed control
+    match &args[0] {
+        Value::Frames(_) => Ok(Value::Number(1.0)),
+        _ => Err(GizmoError::TypeError("loop_speed first argument must be frames array".to_string())),
+    }
+}
+
+/// Validates arguments for the transition() function.
+///
+/// The interpreter stores the resulting settings; this only checks that the
+/// transition name is known and the duration is a number.
+///
+/// # Arguments
+/// * `args[0]` - Transition name: `"none"`, `"fade"` or `"slide"`
+/// * `args[1]` - Duration in milliseconds
+fn transition_func(args: &[Value]) -> Result<Value> {
+    if args.len() != 2 {
+        return Err(GizmoError::ArgumentError(
+            format!("transition expects 2 arguments (kind, ms), got {}", args.len())
+        ));
+    }
+    
+    match (&args[0], &args[1]) {
+        (Value::String(kind), Value::Number(ms)) => {
+            crate::transition::Transition::from_name(kind, ms.max(0.0) as u64)?;
+            Ok(Value::Number(1.0))
+        }
+        _ => Err(GizmoError::TypeError(
+            "transition expects a name and a duration, e.g. transition(\"fade\", 200)".to_string()
+        )),
+    }
+}
+
+/// Validates arguments for the palette() function.
+///
+/// The interpreter installs the palette; this only checks that every entry is
+/// a well-formed color string.
+///
+/// # Arguments
+/// * `args[0]` - Array of `"#RRGGBB"` / `"#RRGGBBAA"` strings, index 0 first
+fn palette_func(args: &[Value]) -> Result<Value> {
+    if args.len() != 1 {
+        return Err(GizmoError::ArgumentError(
+            format!("palette expects 1 argument (colors array), got {}", args.len())
+        ));
+    }
+    
+    match &args[0] {
+        Value::Strings(colors) => {
+            crate::palette::Palette::from_hex_strings(colors)?;
+            Ok(Value::Number(1.0))
+        }
+        _ => Err(GizmoError::TypeError(
+            "palette expects an array of color strings, e.g. palette([\"#000000\", \"#ffffff\"])".to_string()
+        )),
+    }
+}
+
+/// `color(n)` - Selects palette entry n for a pattern pixel.
+///
+/// A plain number returned from a pattern only means on or off; wrapping it
+/// in `color()` makes the pixel use that palette index instead.
+///
+/// # Arguments
+/// * `n` - Palette index in range [0, 251]; the rest are reserved (see [`palette::RESERVED`](crate::palette::RESERVED))
+///
+/// # Examples
+/// ```gzmo
+/// return distance < 10 ? color(2) : distance < 20;
+/// ```
+fn color_func(args: &[Value]) -> Result<Value> {
+    if args.len() != 1 {
+        return Err(GizmoError::ArgumentError(
+            format!("color expects 1 argument, got {}", args.len())
+        ));
+    }
+    
+    match &args[0] {
+        Value::Number(n) if *n >= 0.0 && *n < crate::palette::RESERVED as f64 => Ok(Value::Color(n.floor() as u8)),
+        Value::Number(n) => Err(GizmoError::ArgumentError(
+            format!("color index must be between 0 and {}, got {}", crate::palette::RESERVED - 1, n)
+        )),
+        _ => Err(GizmoError::TypeError("color argument must be a number".to_string())),
+    }
+}
+
+/// Validates arguments for the move_to() function.
+///
+/// The interpreter queues the motion; the window glides there during playback.
+///
+/// # Arguments
+/// * `args[0]`, `args[1]` - Target screen position of the window's top-left corner
+/// * `args[2]` - Glide duration in milliseconds
+fn move_to_func(args: &[Value]) -> Result<Value> {
+    if args.len() != 3 {
+        return Err(GizmoError::ArgumentError(
+            format!("move_to expects 3 arguments (x, y, ms), got {}", args.len())
+        ));
+    }
+    
+    for arg in args {
+        if !matches!(arg, Value::Number(_)) {
+            return Err(GizmoError::TypeError("move_to arguments must be numbers".to_string()));
+        }
+    }
+    
+    Ok(Value::Number(1.0))
+}
+
+/// Validates arguments for the wander() function.
+///
+/// # Arguments
+/// * `args[0]` - Walking speed in pixels per second (at least [`MIN_WANDER_SPEED`](crate::motion::MIN_WANDER_SPEED))
+fn wander_func(args: &[Value]) -> Result<Value> {
+    if args.len() != 1 {
+        return Err(GizmoError::ArgumentError(
+            format!("wander expects 1 argument (speed), got {}", args.len())
+        ));
+    }
+    
+    match &args[0] {
+        Value::Number(speed) if speed.is_finite() && *speed >= crate::motion::MIN_WANDER_SPEED => Ok(Value::Number(1.0)),
+        Value::Number(speed) => Err(GizmoError::ArgumentError(
+            format!("wander speed must be at least {} pixel per second, got {}", crate::motion::MIN_WANDER_SPEED, speed)
+        )),
+        _ => Err(GizmoError::TypeError("wander speed must be a number".to_string())),
+    }
+}
+
+/// Validates arguments for the fall() function (it takes none).
+fn fall_func(args: &[Value]) -> Result<Value> {
+    if !args.is_empty() {
+        return Err(GizmoError::ArgumentError(
+            format!("fall expects no arguments, got {}", args.len())
+        ));
+    }
+    Ok(Value::Number(1.0))
+}
+
+/// Validates arguments for the bounce() function (it takes none).
+fn bounce_func(args: &[Value]) -> Result<Value> {
+    if !args.is_empty() {
+        return Err(GizmoError::ArgumentError(
+            format!("bounce expects no arguments, got {}", args.len())
+        ));
+    }
+    Ok(Value::Number(1.0))
+}
+
+/// Validates arguments for the opacity() function.
+///
+/// # Arguments
+/// * `args[0]` - Window opacity from 0.0 (invisible) to 1.0 (opaque)
+fn opacity_func(args: &[Value]) -> Result<Value> {
+    if args.len() != 1 {
+        return Err(GizmoError::ArgumentError(
+            format!("opacity expects 1 argument (0.0 to 1.0), got {}", args.len())
+        ));
+    }
+    
+    match &args[0] {
+        Value::Number(opacity) if (0.0..=1.0).contains(opacity) => Ok(Value::Number(1.0)),
+        Value::Number(opacity) => Err(GizmoError::ArgumentError(
+            format!("opacity must be between 0.0 and 1.0, got {}", opacity)
+        )),
+        _ => Err(GizmoError::TypeError("opacity must be a number".to_string())),
+    }
+}
+
+/// Validates arguments for the notify() function.
+///
+/// # Arguments
+/// * `args[0]` - Notification title
+/// * `args[1]` - Notification body text
+fn notify_func(args: &[Value]) -> Result<Value> {
+    if args.len() != 2 {
+        return Err(GizmoError::ArgumentError(
+            format!("notify expects 2 arguments (title, body), got {}", args.len())
+        ));
+    }
+    
+    match (&args[0], &args[1]) {
+        (Value::String(_), Value::String(_)) => Ok(Value::Number(1.0)),
+        _ => Err(GizmoError::TypeError("notify title and body must be strings".to_string())),
+    }
+}
+
+/// Validates arguments for the say() function.
+///
+/// # Arguments
+/// * `args[0]` - Text for the speech bubble (empty hides the current bubble)
+/// * `args[1]` - How long to show it in milliseconds
+fn say_func(args: &[Value]) -> Result<Value> {
+    if args.len() != 2 {
+        return Err(GizmoError::ArgumentError(
+            format!("say expects 2 arguments (text, ms), got {}", args.len())
+        ));
+    }
+    
+    match (&args[0], &args[1]) {
+        (Value::String(_), Value::Number(ms)) if ms.is_finite() && *ms >= 0.0 => Ok(Value::Number(1.0)),
+        (Value::String(_), Value::Number(ms)) => Err(GizmoError::ArgumentError(
+            format!("say duration must be a positive number of milliseconds, got {}", ms)
+        )),
+        _ => Err(GizmoError::TypeError("say expects a text string and a duration in milliseconds".to_string())),
+    }
+}
+
+/// Validates arguments for the colors() function.
+///
+/// The interpreter matches `on` / `off` names and installs the colors; this
+/// only checks that each argument is a well-formed color string.
+///
+/// # Arguments
+/// * `on` - Foreground color (palette index 1)
+/// * `off` - Background color (palette index 0)
+fn colors_func(args: &[Value]) -> Result<Value> {
+    if args.is_empty() || args.len() > 2 {
+        return Err(GizmoError::ArgumentError(
+            format!("colors expects 1 or 2 arguments (on, off), got {}", args.len())
+        ));
+    }
+    
+    for arg in args {
+        match arg {
+            Value::String(color) => {
+                crate::palette::parse_hex_color(color)?;
+            }
+            _ => return Err(GizmoError::TypeError(
+                "colors expects color strings, e.g. colors(on = \"#33ff66\", off = \"#00000000\")".to_string()
+            )),
+        }
+    }
+    
+    Ok(Value::Number(1.0))
+}
\ No newline at end of file
@@ -0,0 +1,157 @@
+//! Pattern Cache
+//!
+//! Rendering patterns is most of what running a script costs, and a script
+//! that hasn't changed renders the same patterns every time it starts. A
+//! [`PatternCache`] remembers the frames of compiled patterns by a hash of
+//! everything they depend on: their bytecode, the values of the variables
+//! they read, the frames they sample and their size. A later run that
+//! renders the same pattern takes the frame (and the variables the body left
+//! behind) instead of rendering it again.
+//!
+//! Patterns that call `random()` are never cached, and neither are bodies
+//! the interpreter walks pixel by pixel. The interpreter only consults the
+//! cache it is handed
+//! ([`Interpreter::set_pattern_cache`](crate::interpreter::Interpreter::set_pattern_cache),
+//! [`Interpreter::take_pattern_cache`](crate::interpreter::Interpreter::take_pattern_cache));
+//! where it is stored is up to the program embedding it.
+//!
+//! Keys include the gizmo version, so entries made by another version are
+//! never used and go away the next time the cache is pruned. They are
+//! hashed with [`Fnv1a`] rather than the standard library's hasher, whose
+//! output may change from one Rust release to the next: a gizmo built with
+//! another compiler still finds the entries saved by this one.
+
+use crate::pattern::Scalar;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// 64-bit FNV-1a, a hash function whose output is fixed, for keys that are
+/// saved to disk.
+///
+/// Every part is written with its length in front, so parts can't run
+/// together: `"ab", "c"` and `"a", "bc"` hash differently.
+#[derive(Debug, Clone, Copy)]
+pub struct Fnv1a(u64);
+
+impl Fnv1a {
+    /// Starts a hash.
+    pub fn new() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+
+    /// Adds a part, as its length (8 bytes, little-endian) then its bytes.
+    pub fn write(&mut self, part: &[u8]) {
+        self.bytes(&(part.len() as u64).to_le_bytes());
+        self.bytes(part);
+    }
+
+    /// Adds a number, as 8 bytes, little-endian.
+    pub fn write_u64(&mut self, number: u64) {
+        self.bytes(&number.to_le_bytes());
+    }
+
+    /// Returns the hash of the parts written so far.
+    pub fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+}
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A rendered pattern, as a cache entry.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct CachedPattern {
+    /// The frame's palette indices, row by row
+    pub(crate) pixels: Vec<Vec<u8>>,
+    /// The pattern's registers after the last pixel
+    pub(crate) registers: Vec<Option<Scalar>>,
+}
+
+/// Frames of compiled patterns, by a hash of what they depend on.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct PatternCache {
+    entries: HashMap<u64, CachedPattern>,
+    /// Keys looked up or added since the cache was made or loaded
+    #[serde(skip)]
+    used: HashSet<u64>,
+    /// Whether entries were added or removed since then
+    #[serde(skip)]
+    changed: bool,
+}
+
+impl PatternCache {
+    /// Creates an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether entries were added or removed since the cache was made or
+    /// loaded, so it is worth saving again.
+    pub fn is_changed(&self) -> bool {
+        self.changed
+    }
+
+    /// Drops the entries no render has used since the cache was made or
+    /// loaded: those of patterns the script no longer renders, or renders
+    /// with other inputs.
+    pub fn prune(&mut self) {
+        let before = self.entries.len();
+        self.entries.retain(|key, _| self.used.contains(key));
+        self.changed |= self.entries.len() != before;
+    }
+
+    pub(crate) fn get(&mut self, key: u64) -> Option<&CachedPattern> {
+        let entry = self.entries.get(&key)?;
+        self.used.insert(key);
+        Some(entry)
+    }
+
+    pub(crate) fn insert(&mut self, key: u64, entry: CachedPattern) {
+        self.entries.insert(key, entry);
+        self.used.insert(key);
+        self.changed = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fnv1a_hashes_are_fixed() {
+        // Published FNV-1a test vectors, for the bytes alone
+        let mut empty = Fnv1a::new();
+        empty.bytes(b"");
+        assert_eq!(empty.finish(), 0xcbf2_9ce4_8422_2325);
+        let mut a = Fnv1a::new();
+        a.bytes(b"a");
+        assert_eq!(a.finish(), 0xaf63_dc4c_8601_ec8c);
+        let mut foobar = Fnv1a::new();
+        foobar.bytes(b"foobar");
+        assert_eq!(foobar.finish(), 0x8594_4171_f739_67e8);
+    }
+
+    #[test]
+    fn parts_do_not_run_together() {
+        let hash = |parts: &[&str]| {
+            let mut hasher = Fnv1a::new();
+            for part in parts {
+                hasher.write(part.as_bytes());
+            }
+            hasher.finish()
+        };
+
+        assert_ne!(hash(&["ab", "c"]), hash(&["a", "bc"]));
+        assert_eq!(hash(&["ab", "c"]), hash(&["ab", "c"]));
+    }
+}
@@ -0,0 +1,194 @@
+//! Fixed-Step Animation Clock
+//!
+//! This module provides the virtual clock that drives animation playback.
+//! Instead of advancing one frame every time the window happens to redraw,
+//! playback is expressed in whole script-defined ticks (the frame duration set
+//! by `loop_speed()`), accumulated from real elapsed time.
+//!
+//! ## Why Fixed Steps
+//!
+//! Advancing on every redraw ties the animation speed to the render cadence:
+//! a slow machine or a throttled background window plays the animation slower
+//! than the script asked for. With a fixed-step clock the frame shown after
+//! `t` milliseconds is always `floor(t / tick) % frame_count`, no matter how
+//! often the window was actually able to redraw in between.
+//!
+//! ```text
+//! real time  ──┬────┬──────────────┬──┬──►
+//! redraws      │    │   (stalled)  │  │
+//! ticks      0 │ 1  │ 2    3    4  │5 │ 6
+//! frame        0    1              5  6      (frames 2-4 are skipped, not delayed)
+//! ```
+//!
+//! ## Speed Overrides
+//!
+//! `gizmo speed` can override the script's frame duration while it runs,
+//! either as a multiple (`2x` plays twice as fast) or as a fixed duration
+//! (`50ms`). The override wins over later `loop_speed()` calls: a multiple
+//! keeps scaling whatever the script sets, a fixed duration replaces it.
+
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Shortest and longest tick the clock runs at, in milliseconds.
+pub const TICK_MS_RANGE: (u64, u64) = (1, 10000);
+
+/// How playback speed relates to the script's frame duration.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Speed {
+    /// As the script set it
+    Script,
+    /// The script's speed times this factor (`2.0` halves the frame duration)
+    Scale(f64),
+    /// Every frame shown this many milliseconds, whatever the script says
+    FixedMs(u64),
+}
+
+impl FromStr for Speed {
+    type Err = String;
+
+    /// Parses `2x` / `0.5x`, `50ms`, or `normal` (the script's own speed).
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let text = text.trim();
+        if text == "normal" {
+            return Ok(Speed::Script);
+        }
+        if let Some(factor) = text.strip_suffix('x') {
+            return match factor.parse::<f64>() {
+                Ok(factor) if factor.is_finite() && factor > 0.0 => Ok(Speed::Scale(factor)),
+                _ => Err(format!("Invalid speed '{}', expected a positive multiple such as 2x", text)),
+            };
+        }
+        if let Some(ms) = text.strip_suffix("ms") {
+            return match ms.parse::<u64>() {
+                Ok(ms) => Ok(Speed::FixedMs(ms)),
+                Err(_) => Err(format!("Invalid speed '{}', expected a duration such as 50ms", text)),
+            };
+        }
+        Err(format!("Invalid speed '{}', expected 2x, 50ms or normal", text))
+    }
+}
+
+/// Virtual clock that converts real elapsed time into whole animation ticks.
+///
+/// The clock keeps the sub-tick remainder between updates so no time is lost
+/// or double-counted regardless of how irregularly `advance()` is called.
+#[derive(Debug, Clone)]
+pub struct AnimationClock {
+    /// Length of one tick in milliseconds as the script set it
+    tick_ms: u64,
+    /// Override of the script's tick length (`gizmo speed`)
+    speed: Speed,
+    /// Number of whole ticks elapsed since the clock was started
+    ticks: u64,
+    /// Real time accumulated towards the next tick
+    pending: Duration,
+}
+
+impl AnimationClock {
+    /// Creates a clock that advances one tick every `tick_ms` milliseconds.
+    ///
+    /// # Arguments
+    /// * `tick_ms` - Tick length in milliseconds (values below 1 are treated as 1)
+    pub fn new(tick_ms: u64) -> Self {
+        Self {
+            tick_ms: tick_ms.max(1),
+            speed: Speed::Script,
+            ticks: 0,
+            pending: Duration::ZERO,
+        }
+    }
+
+    /// Returns the tick length in milliseconds, with any speed override applied.
+    pub fn tick_ms(&self) -> u64 {
+        let (min, max) = TICK_MS_RANGE;
+        match self.speed {
+            Speed::Script => self.tick_ms,
+            Speed::Scale(factor) => ((self.tick_ms as f64 / factor).round() as u64).clamp(min, max),
+            Speed::FixedMs(ms) => ms.clamp(min, max),
+        }
+    }
+
+    /// Returns the speed override in effect.
+    pub fn speed(&self) -> Speed {
+        self.speed
+    }
+
+    /// Overrides the script's tick length without resetting the elapsed tick
+    /// count; [`Speed::Script`] removes the override.
+    ///
+    /// # Arguments
+    /// * `speed` - The override; the resulting tick is clamped to [`TICK_MS_RANGE`]
+    pub fn set_speed(&mut self, speed: Speed) {
+        self.speed = speed;
+    }
+
+    /// Returns the number of whole ticks elapsed since the clock started.
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    /// Changes the tick length without resetting the elapsed tick count.
+    ///
+    /// # Arguments
+    /// * `tick_ms` - New tick length in milliseconds (values below 1 are treated as 1)
+    pub fn set_tick_ms(&mut self, tick_ms: u64) {
+        self.tick_ms = tick_ms.max(1);
+    }
+
+    /// Feeds real elapsed time into the clock.
+    ///
+    /// # Arguments
+    /// * `elapsed` - Real time since the previous call
+    ///
+    /// # Returns
+    /// The number of ticks that completed during `elapsed` (may be 0 or several)
+    pub fn advance(&mut self, elapsed: Duration) -> u64 {
+        self.pending += elapsed;
+
+        let tick = self.tick_duration();
+        let completed = (self.pending.as_nanos() / tick.as_nanos()) as u64;
+        if completed > 0 {
+            self.pending -= Duration::from_nanos(
+                (tick.as_nanos() * completed as u128).min(u64::MAX as u128) as u64,
+            );
+            self.ticks += completed;
+        }
+
+        completed
+    }
+
+    /// Returns how much real time remains until the next tick completes.
+    ///
+    /// Used by the window loop to sleep exactly until the next frame is due.
+    pub fn until_next_tick(&self) -> Duration {
+        self.tick_duration().saturating_sub(self.pending)
+    }
+
+    /// Returns how much real time remains until the clock reaches `tick`
+    /// (zero if it already has).
+    pub fn until_tick(&self, tick: u64) -> Duration {
+        match tick.checked_sub(self.ticks + 1) {
+            None => Duration::ZERO,
+            Some(more) => self.until_next_tick() + self.tick_duration() * more.min(u32::MAX as u64) as u32,
+        }
+    }
+
+    /// Maps the current tick onto a looping animation of `frame_count` frames.
+    ///
+    /// # Returns
+    /// Index of the frame that should be visible now (0 for empty animations)
+    pub fn frame_index(&self, frame_count: usize) -> usize {
+        if frame_count == 0 {
+            0
+        } else {
+            (self.ticks % frame_count as u64) as usize
+        }
+    }
+
+    fn tick_duration(&self) -> Duration {
+        Duration::from_millis(self.tick_ms())
+    }
+}
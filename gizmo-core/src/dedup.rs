@@ -0,0 +1,82 @@
+//! Frame Deduplication
+//!
+//! Procedurally generated animations often hold the same picture many times
+//! over: a pattern that only changes every few steps, a pose held while the
+//! rest of the sequence moves on. Frames share their pixels between clones
+//! already, but two frames rendered separately hold two copies even when
+//! they are identical.
+//!
+//! [`share_identical`] makes identical frames share one copy, which the
+//! interpreter does for every sequence `play()` / `loop()` starts.
+//! [`FrameRuns`] goes further for exporters: each distinct frame once, and
+//! the animation as a list of which frame shows for how many steps in a row.
+//!
+//! ```text
+//! frames   A A A B C C A        unique   A B C
+//!                               runs     (0, 3) (1, 1) (2, 2) (0, 1)
+//! ```
+//!
+//! Frames are told apart by a hash of their size and pixels, and compared
+//! in full when the hashes match, so a hash collision never merges frames
+//! that differ.
+
+use crate::ast::Frame;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+/// Frames with every distinct picture stored once.
+#[derive(Debug, Clone)]
+pub struct FrameRuns {
+    /// Each distinct frame, in order of first appearance
+    pub unique: Vec<Frame>,
+    /// The animation in order, as (index into `unique`, how many frames in
+    /// a row show it); consecutive runs always show different frames
+    pub runs: Vec<(usize, usize)>,
+}
+
+impl FrameRuns {
+    /// Finds the distinct frames of an animation and the runs they show in.
+    pub fn new(frames: &[Frame]) -> Self {
+        let mut unique: Vec<Frame> = Vec::new();
+        let mut runs: Vec<(usize, usize)> = Vec::new();
+        let mut by_hash: HashMap<u64, Vec<usize>> = HashMap::new();
+        for frame in frames {
+            let candidates = by_hash.entry(frame_hash(frame)).or_default();
+            let index = match candidates.iter().find(|&&index| unique[index] == *frame) {
+                Some(&index) => index,
+                None => {
+                    candidates.push(unique.len());
+                    unique.push(frame.clone());
+                    unique.len() - 1
+                }
+            };
+            match runs.last_mut() {
+                Some((last, length)) if *last == index => *length += 1,
+                _ => runs.push((index, 1)),
+            }
+        }
+        Self { unique, runs }
+    }
+}
+
+/// Makes identical frames share one copy of their pixels.
+pub fn share_identical(frames: &mut [Frame]) {
+    let mut first: HashMap<u64, Vec<usize>> = HashMap::new();
+    for index in 0..frames.len() {
+        let candidates = first.entry(frame_hash(&frames[index])).or_default();
+        match candidates.iter().find(|&&earlier| frames[earlier] == frames[index]) {
+            Some(&earlier) => frames[index].pixels = Arc::clone(&frames[earlier].pixels),
+            None => candidates.push(index),
+        }
+    }
+}
+
+/// Hashes a frame's size and pixels.
+fn frame_hash(frame: &Frame) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    (frame.width, frame.height).hash(&mut hasher);
+    frame.pixels.hash(&mut hasher);
+    hasher.finish()
+}
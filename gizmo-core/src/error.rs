@@ -43,7 +43,21 @@
 //!
 //! Each phase can fail independently and provides specific error context
 //! to help users understand and fix issues in their scripts.
+//!
+//! ## Locations
+//!
+//! Errors found in a script are wrapped in `Located` with the place they
+//! were found: the token a lexical or syntax error is at, or the statement
+//! that was running when a runtime error happened. [`GizmoError::report`]
+//! adds the script's line with a caret under it:
+//!
+//! ```text
+//! Division by zero at line 12, column 5
+//!   12 |     frame x = 1 / 0;
+//!      |     ^
+//! ```
 
+use crate::lexer::Position;
 use std::fmt;
 use std::error::Error;
 
@@ -93,6 +107,7 @@ pub enum GizmoError {
     /// Array or collection index out of bounds.
     ///
     /// Used for array access violations and similar bounds checking errors.
+    #[allow(dead_code)]
     IndexError(String),
     
     /// Mathematical division by zero.
@@ -138,6 +153,118 @@ pub enum GizmoError {
     /// Wraps standard I/O errors that occur during file operations.
     /// Automatically converted from `std::io::Error`.
     IOError(String),
+    
+    /// An error at a place in the script.
+    ///
+    /// Added by the lexer, the parser and the interpreter, which know where
+    /// they are in the script; the error inside is one of the others.
+    Located {
+        /// Line of the script, starting at 1
+        line: usize,
+        /// Column of the line, starting at 1
+        column: usize,
+        /// What went wrong there
+        error: Box<GizmoError>,
+    },
+}
+
+impl GizmoError {
+    /// Places the error at a position in the script.
+    ///
+    /// An error that already has a position keeps it: the innermost
+    /// statement or token knows best where it went wrong.
+    ///
+    /// # Arguments
+    /// * `position` - `(line, column)` of the script, both starting at 1
+    pub fn at(self, (line, column): Position) -> Self {
+        match self {
+            GizmoError::Located { .. } => self,
+            error => GizmoError::Located { line, column, error: Box::new(error) },
+        }
+    }
+    
+    /// Returns where in the script the error is, if known.
+    pub fn position(&self) -> Option<Position> {
+        match self {
+            GizmoError::Located { line, column, .. } => Some((*line, *column)),
+            _ => None,
+        }
+    }
+    
+    /// Returns the error without its position, for showing next to a
+    /// position given some other way.
+    pub fn without_position(&self) -> &GizmoError {
+        match self {
+            GizmoError::Located { error, .. } => error,
+            error => error,
+        }
+    }
+    
+    /// Formats the error for a user who has the script in front of them:
+    /// the message and, if the error has a position, the script's line with
+    /// a caret under the column.
+    ///
+    /// # Arguments
+    /// * `source` - The script the error came from
+    pub fn report(&self, source: &str) -> String {
+        match self.position().and_then(|position| snippet(source, position)) {
+            Some(snippet) => format!("{}\n{}", self, snippet),
+            None => self.to_string(),
+        }
+    }
+}
+
+/// A warning about a script that runs, but probably not the way it was meant
+/// to: a variable that is never used, a condition that is always true, ...
+///
+/// Warnings never stop a script; `gizmo check` and `gizmo start` print them.
+/// Made by [`lint::warnings`](crate::lint::warnings).
+#[derive(Debug, Clone, PartialEq)]
+pub struct GizmoDiagnostic {
+    /// Where the statement the warning is about starts, if known
+    pub position: Option<Position>,
+    /// What looks wrong
+    pub message: String,
+}
+
+impl GizmoDiagnostic {
+    /// Formats the warning like [`GizmoError::report`]: the message, its
+    /// position and the script's line with a caret under it.
+    ///
+    /// # Arguments
+    /// * `source` - The script the warning is about
+    pub fn report(&self, source: &str) -> String {
+        let Some(position) = self.position else {
+            return self.to_string();
+        };
+        let located = format!("{} at line {}, column {}", self, position.0, position.1);
+        match snippet(source, position) {
+            Some(snippet) => format!("{}\n{}", located, snippet),
+            None => located,
+        }
+    }
+}
+
+impl fmt::Display for GizmoDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "warning: {}", self.message)
+    }
+}
+
+/// Shows a line of the script with a caret under a column.
+///
+/// # Returns
+/// The line and the caret under it, or `None` if the script has no such line
+fn snippet(source: &str, (line, column): Position) -> Option<String> {
+    let text = source.lines().nth(line.checked_sub(1)?)?;
+    let number = line.to_string();
+    // Tabs stay tabs so the caret lines up with the text above it
+    let indent: String = text
+        .chars()
+        .take(column.saturating_sub(1))
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect();
+    Some(format!("  {} | {}\n  {} | {}^", number, text, " ".repeat(number.len()), indent))
 }
 
 impl fmt::Display for GizmoError {
@@ -159,6 +286,9 @@ impl fmt::Display for GizmoError {
             GizmoError::UndefinedFunction(name) => write!(f, "Undefined function: {}", name),
             GizmoError::ArgumentError(msg) => write!(f, "Argument error: {}", msg),
             GizmoError::IOError(msg) => write!(f, "IO error: {}", msg),
+            GizmoError::Located { line, column, error } => {
+                write!(f, "{} at line {}, column {}", error, line, column)
+            }
         }
     }
 }
@@ -169,7 +299,6 @@ impl Error for GizmoError {}
 ///
 /// This allows using the `?` operator with I/O operations throughout the codebase,
 /// automatically wrapping I/O errors in the appropriate Gizmo error type.
-
 impl From<std::io::Error> for GizmoError {
     /// Converts a standard I/O error into a GizmoError.
     ///
@@ -194,8 +323,11 @@ impl From<std::io::Error> for GizmoError {
 ///
 /// # Usage
 /// ```rust
+/// # use gizmo_core::ast::Expression;
+/// # use gizmo_core::error::Result;
 /// fn parse_expression() -> Result<Expression> {
 ///     // ... parsing logic ...
+/// #   let expression = Expression::Number(1.0);
 ///     Ok(expression)
 /// }
 /// ```
@@ -0,0 +1,175 @@
+//! Frame Rendering Utilities
+//!
+//! This module provides utilities for rendering Gizmo frames to different output
+//! formats. Currently focused on ASCII text output for debugging purposes, but
+//! designed to be extensible for other rendering backends.
+//!
+//! ## Core Functionality
+//!
+//! ### ASCII Rendering
+//! Converts palette-index pixel matrices into human-readable ASCII representations:
+//! - Lit pixels (any non-zero index) → `#` characters
+//! - Background pixels (index 0) → `.` characters
+//!
+//! This is primarily used for:
+//! - Debugging pattern generation logic
+//! - Command-line preview of animations
+//! - Development and testing of frame content
+//!
+//! ### Terminal Rendering
+//! [`TerminalRenderer`] draws frames in their palette colors for playback in
+//! a terminal (`gizmo tty`). Each character cell shows two pixels stacked with
+//! the upper half block `▀`: the top pixel in the foreground color, the bottom
+//! one in the background color, both as 24-bit ANSI colors. Transparent
+//! pixels keep the terminal's own background.
+//!
+//! ## Design Philosophy
+//!
+//! The frame renderer is kept simple and focused:
+//! - **Single Responsibility**: Only handles frame-to-text conversion
+//! - **No Dependencies**: Uses only standard library functionality
+//! - **Extensible**: Structure allows adding new rendering formats
+//!
+//! ## Usage
+//!
+//! ```rust
+//! # use gizmo_core::{ast::Frame, frame::FrameRenderer};
+//! # let frame = Frame::new_blank(128, 128);
+//! let renderer = FrameRenderer::new(128, 128);
+//! let ascii_output = renderer.render_ascii(&frame);
+//! println!("{}", ascii_output);
+//! ```
+
+use crate::ast::Frame;
+use crate::palette::Palette;
+
+/// ASCII renderer for Gizmo animation frames.
+///
+/// Provides utilities to convert frame data into human-readable text
+/// representations for debugging and development purposes.
+#[allow(dead_code)]
+pub struct FrameRenderer {
+    /// Expected frame width (for validation/context)
+    pub width: usize,
+    /// Expected frame height (for validation/context)
+    pub height: usize,
+}
+
+impl FrameRenderer {
+    /// Creates a new frame renderer with expected dimensions.
+    ///
+    /// The dimensions are stored for context but don't restrict the actual
+    /// frames that can be rendered - frames of any size can be processed.
+    ///
+    /// # Arguments
+    /// * `width` - Expected frame width
+    /// * `height` - Expected frame height
+    ///
+    /// # Returns
+    /// A new FrameRenderer instance
+    pub fn new(width: usize, height: usize) -> Self {
+        Self { width, height }
+    }
+    
+    /// Renders a frame as ASCII text for visual debugging.
+    ///
+    /// Converts the pixel matrix into a text representation where:
+    /// - Lit pixels (non-zero palette index) become `#` (on/filled)
+    /// - Background pixels (index 0) become `.` (off/empty)
+    /// - Each row ends with a newline character
+    ///
+    /// This creates a visual representation that can be printed to console
+    /// or saved to text files for inspection.
+    ///
+    /// # Arguments
+    /// * `frame` - The frame to render
+    ///
+    /// # Returns
+    /// A multi-line string representing the frame visually
+    ///
+    /// # Example Output
+    /// ```text
+    /// ...###...
+    /// ..#...#..
+    /// .#.....#.
+    /// #.......#
+    /// .#.....#.
+    /// ..#...#..
+    /// ...###...
+    /// ```
+    pub fn render_ascii(&self, frame: &Frame) -> String {
+        let mut output = String::new();
+        
+        for row in frame.pixels.iter() {
+            for &pixel in row {
+                output.push(if pixel != 0 { '#' } else { '.' });
+            }
+            output.push('\n');
+        }
+        
+        output
+    }
+}
+/// Renders frames as colored Unicode half blocks for terminal playback.
+pub struct TerminalRenderer<'p> {
+    /// Colors of the frames' palette indices
+    palette: &'p Palette,
+}
+
+impl<'p> TerminalRenderer<'p> {
+    /// Creates a terminal renderer drawing in a palette's colors.
+    pub fn new(palette: &'p Palette) -> Self {
+        Self { palette }
+    }
+
+    /// Renders a frame as lines of half blocks, two pixel rows per line.
+    ///
+    /// Colors are only sent when they change, and every line ends with a
+    /// reset, so the output can be written anywhere on screen.
+    ///
+    /// # Arguments
+    /// * `frame` - The frame to render
+    ///
+    /// # Returns
+    /// `ceil(height / 2)` lines of ANSI-colored text, each ending in a newline
+    pub fn render(&self, frame: &Frame) -> String {
+        let mut output = String::new();
+
+        for rows in frame.pixels.chunks(2) {
+            // (foreground, background) currently set; `None` is the terminal default
+            let mut current = (None, None);
+            for col in 0..frame.width {
+                let top = rows[0].get(col).and_then(|&index| self.rgb(index));
+                let bottom = rows.get(1).and_then(|row| row.get(col)).and_then(|&index| self.rgb(index));
+                // A lone bottom pixel is drawn with the lower half block instead
+                let (cell, colors) = match (top, bottom) {
+                    (None, None) => (' ', (current.0, None)),
+                    (None, Some(bottom)) => ('▄', (Some(bottom), None)),
+                    (Some(top), bottom) => ('▀', (Some(top), bottom)),
+                };
+                if colors.0 != current.0 {
+                    if let Some((r, g, b)) = colors.0 {
+                        output.push_str(&format!("\x1b[38;2;{};{};{}m", r, g, b));
+                    }
+                }
+                if colors.1 != current.1 {
+                    match colors.1 {
+                        Some((r, g, b)) => output.push_str(&format!("\x1b[48;2;{};{};{}m", r, g, b)),
+                        None => output.push_str("\x1b[49m"),
+                    }
+                }
+                current = colors;
+                output.push(cell);
+            }
+            output.push_str("\x1b[0m\n");
+        }
+
+        output
+    }
+
+    /// Returns the color of a palette index, or `None` if it is mostly transparent.
+    fn rgb(&self, index: u8) -> Option<(u8, u8, u8)> {
+        let argb = self.palette.color(index);
+        (argb >> 24 >= 0x80).then_some(((argb >> 16) as u8, (argb >> 8) as u8, argb as u8))
+    }
+}
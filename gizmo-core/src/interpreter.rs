@@ -0,0 +1,1830 @@
+//! Interpreter for the Gizmo Scripting Language
+//!
+//! This module implements the execution engine that takes parsed AST nodes and
+//! executes them to generate pixel art animations. The interpreter is responsible
+//! for the runtime evaluation of expressions, execution of statements, and the
+//! specialized pattern generation that makes Gizmo unique.
+//!
+//! ## Core Responsibilities
+//!
+//! ### Expression Evaluation
+//! - **Mathematical Operations**: Full arithmetic with operator precedence
+//! - **Logical Operations**: Boolean logic with short-circuit evaluation
+//! - **Function Calls**: Built-in mathematical and animation functions
+//! - **Pattern Generation**: Per-pixel expression evaluation for procedural art
+//!
+//! ### Statement Execution
+//! - **Variable Management**: Scoped variable declarations and assignments
+//! - **Control Flow**: If statements and repeat loops with proper scoping
+//! - **Animation Functions**: Special handling for `add_frame()`, `loop_speed()`, `play()`
+//! - **Event Handlers**: `when <event> do ... end` bodies are stored, not run,
+//!   and executed later by the window loop through `handle_click_event()` etc.
+//! - **Persistent Variables**: `persist name = default` starts from the value
+//!   saved by the previous session (see the `persist` module)
+//!
+//! ### Pattern Generation Model
+//!
+//! The interpreter's most sophisticated feature is pattern generation:
+//!
+//! ```text
+//! For each pixel (col, row) in pattern(width, height):
+//!   1. Set environment variables: col = x, row = y
+//!   2. Execute all statements in pattern body
+//!   3. Evaluate return expression → palette index (0 = off, non-zero = on,
+//!      or an explicit index from color(n))
+//!   4. Store result in frame[row][col]
+//! ```
+//!
+//! This allows complex procedural generation with mathematical expressions,
+//! trigonometry, distance calculations, and more. Most bodies are compiled
+//! to bytecode first (see the `pattern` module) so they don't walk the syntax
+//! tree for every pixel; the steps above describe what that code does.
+//!
+//! ### Multi-Pass Pipelines
+//!
+//! `pipeline(width, height) { pass {...} pass {...} }` chains pattern passes.
+//! Every pass after the first sees the previous pass's output bound to the
+//! `previous` frame variable, so blur → threshold → outline chains can be
+//! written without manual intermediate frames.
+//!
+//! ## Animation System
+//!
+//! The interpreter manages animation state including:
+//! - **Frame Collection**: Accumulates frames from `add_frame()` calls
+//! - **Timing Control**: Frame duration from `loop_speed()` (1ms to 10000ms)
+//! - **Playback State**: Final animation frames and timing for the window system
+//!
+//! ## Error Handling
+//!
+//! Provides detailed runtime error reporting for:
+//! - Type mismatches (e.g., using string in math operation)
+//! - Division by zero
+//! - Undefined variables and functions
+//! - Invalid function arguments
+
+use crate::ast::*;
+use crate::builtin::{self, BuiltinFunctions};
+use crate::cache::PatternCache;
+use crate::clock::{AnimationClock, TICK_MS_RANGE};
+use crate::dedup;
+use crate::transition::Transition;
+use crate::motion::MotionCommand;
+use crate::persist::{Persisted, SavedState};
+use crate::error::{GizmoError, Result};
+use crate::frame::FrameRenderer;
+use crate::lexer::Position;
+use crate::limits::{Budget, Limits};
+use crate::palette::{self, Palette};
+use crate::pattern::CompiledPattern;
+use crate::schedule;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Runtime environment for variable storage and scoping.
+///
+/// The environment maintains a mapping from variable names to their values
+/// during script execution. In the current implementation, there's a single
+/// global scope, but the structure supports future scoping extensions.
+#[derive(Clone, Default)]
+pub struct Environment {
+    /// Map of variable names to their current values
+    variables: HashMap<Name, Value>,
+}
+
+impl Environment {
+    /// Creates a new empty environment.
+    ///
+    /// Initializes an environment with no variables defined.
+    /// Variables will be added through `define()` during script execution.
+    pub fn new() -> Self {
+        Self {
+            variables: HashMap::new(),
+        }
+    }
+
+    /// Defines or updates a variable in the environment.
+    ///
+    /// This method is used for both variable declarations and assignments.
+    /// If the variable already exists, it will be overwritten with the new value.
+    ///
+    /// # Arguments
+    /// * `name` - Variable name to define (a [`Name`] from the syntax tree
+    ///   is stored without copying)
+    /// * `value` - Value to associate with the variable
+    pub fn define(&mut self, name: impl Into<Name>, value: Value) {
+        self.variables.insert(name.into(), value);
+    }
+
+    /// Retrieves a variable value from the environment.
+    ///
+    /// Looks up the variable name and returns a copy of its value.
+    /// Returns an error if the variable has not been defined.
+    ///
+    /// # Arguments
+    /// * `name` - Variable name to look up
+    ///
+    /// # Returns
+    /// * `Ok(Value)` - The variable's current value
+    /// * `Err(GizmoError::UndefinedVariable)` - Variable not found
+    pub fn get(&self, name: &str) -> Result<Value> {
+        if let Some(value) = self.variables.get(name) {
+            Ok(value.clone())
+        } else {
+            Err(GizmoError::UndefinedVariable(name.to_string()))
+        }
+    }
+
+    /// Borrows a variable's value for changing it in place.
+    ///
+    /// # Arguments
+    /// * `name` - Variable name to look up
+    ///
+    /// # Returns
+    /// * `Ok(&mut Value)` - The variable's current value
+    /// * `Err(GizmoError::UndefinedVariable)` - Variable not found
+    pub fn get_mut(&mut self, name: &str) -> Result<&mut Value> {
+        self.variables
+            .get_mut(name)
+            .ok_or_else(|| GizmoError::UndefinedVariable(name.to_string()))
+    }
+
+    /// Removes a variable from the environment.
+    ///
+    /// # Returns
+    /// The variable's value, or `None` if it wasn't defined
+    pub fn remove(&mut self, name: &str) -> Option<Value> {
+        self.variables.remove(name)
+    }
+}
+
+/// Variables the runtime keeps up to date and scripts may only read
+/// (the first five are filled in when built with the `sysinfo` and `mic` features).
+pub(crate) const SYSTEM_VARIABLES: &[&str] = &[
+    "cpu_percent",
+    "mem_percent",
+    "battery_percent",
+    "on_battery",
+    "mic_level",
+    "screen_busy",
+    "away_seconds",
+];
+
+/// `hour`, `minute` and `weekday` of deterministic runs: noon on a Monday.
+pub const DETERMINISTIC_TIME: (u32, u32, u32) = (12, 0, 1);
+
+/// A handler registered with `when` or `every`.
+#[derive(Debug, Clone)]
+struct Handler {
+    /// Statements to run each time the event happens
+    body: Vec<Statement>,
+    /// Unregister after the first run (`once`)
+    once: bool,
+    /// `priority N`, 0 when not given
+    priority: f64,
+}
+
+/// The main interpreter that executes Gizmo scripts.
+///
+/// The interpreter maintains all the runtime state needed to execute a script:
+/// - Variable environment for storing script variables
+/// - Built-in function registry for mathematical and animation functions
+/// - Frame renderer for ASCII debugging output
+/// - Animation state including frames and timing
+pub struct Interpreter {
+    /// Runtime environment for variable storage
+    environment: Environment,
+    /// Registry of built-in functions (sin, cos, random, etc.)
+    builtins: BuiltinFunctions,
+    /// Renderer for ASCII frame output (used for debugging)
+    frame_renderer: FrameRenderer,
+    /// Accumulated animation frames from script execution
+    output_frames: Vec<Frame>,
+    /// Fixed-step playback clock; its tick length is the frame duration (default 100ms)
+    clock: AnimationClock,
+    /// Transition inserted when playback switches sequences (set by `transition()`)
+    transition: Transition,
+    /// Colors for frame palette indices (set by `palette()`)
+    palette: Palette,
+    /// Window motion requested by `move_to()` / `wander()` / `fall()` / `bounce()`, in script order
+    motion: Vec<MotionCommand>,
+    /// Whole-window opacity from 0.0 to 1.0 (set by `opacity()`)
+    opacity: f64,
+    /// Desktop notifications requested by `notify()` as (title, body), in script order
+    notifications: Vec<(String, String)>,
+    /// Latest `say()` request as (text, milliseconds), until the window takes it
+    speech: Option<(String, u64)>,
+    /// Handlers registered with `when <event> do ... end`, by event name, in script order
+    event_handlers: HashMap<String, Vec<Handler>>,
+    /// Priority of the handler running right now (`None` outside handlers)
+    running_priority: Option<f64>,
+    /// Highest priority that switched the animation since the window loop
+    /// last applied handler results (see [`end_handler_batch`](Self::end_handler_batch))
+    switch_priority: Option<f64>,
+    /// Thresholds of the `when idle > N` handlers in milliseconds, ascending
+    idle_thresholds: Vec<u64>,
+    /// Times of the `when time("HH:MM")` handlers in minutes since midnight, ascending
+    time_triggers: Vec<u32>,
+    /// Intervals of the `every N` handlers in milliseconds, ascending
+    timer_intervals: Vec<u64>,
+    /// Percentages of the `when battery < N` handlers, ascending
+    battery_thresholds: Vec<u32>,
+    /// (clicks, milliseconds) of the `when clicked N times within MS` handlers, ascending
+    click_combos: Vec<(u32, u64)>,
+    /// Values saved by the previous session for `persist` declarations
+    saved_state: HashMap<String, Persisted>,
+    /// Variables declared with `persist`, in declaration order
+    persisted_names: Vec<String>,
+    /// Bumped whenever `play()` / `loop()` replaces the output frames
+    sequence_version: u64,
+    /// Directory relative `load_image()` / `load_aseprite()` paths start from (the working directory if `None`)
+    script_dir: Option<PathBuf>,
+    /// Statements and time the current run has used, and the limits on them
+    budget: Budget,
+    /// Frames of compiled patterns from earlier runs (`set_pattern_cache()`)
+    pattern_cache: Option<PatternCache>,
+    /// Whether `hour`, `minute` and `weekday` read [`DETERMINISTIC_TIME`]
+    /// instead of the local clock (`set_deterministic()`)
+    deterministic: bool,
+    /// Microphone the `mic_level` variable is read from (`--mic`)
+    #[cfg(feature = "mic")]
+    microphone: Option<crate::mic::MicLevel>,
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Interpreter {
+    /// Creates a new interpreter instance.
+    ///
+    /// Initializes the interpreter with:
+    /// - Empty variable environment
+    /// - Built-in function registry
+    /// - Frame renderer for 128x128 output
+    /// - Empty animation frame list
+    /// - Default frame timing of 100ms per frame
+    /// - The default execution [`Limits`]
+    /// - `screen_busy` set to 0 until the window loop checks
+    pub fn new() -> Self {
+        let mut interpreter = Self {
+            environment: Environment::new(),
+            builtins: BuiltinFunctions::new(),
+            frame_renderer: FrameRenderer::new(128, 128),
+            output_frames: Vec::new(),
+            clock: AnimationClock::new(100), // Default 100ms per frame
+            transition: Transition::default(), // Hard cut until configured
+            palette: Palette::default(),       // White on black
+            motion: Vec::new(),                // Window stays put
+            opacity: 1.0,                      // Fully opaque
+            notifications: Vec::new(),
+            speech: None,
+            event_handlers: HashMap::new(),    // No reactions until `when` registers some
+            running_priority: None,
+            switch_priority: None,
+            idle_thresholds: Vec::new(),
+            time_triggers: Vec::new(),
+            timer_intervals: Vec::new(),
+            battery_thresholds: Vec::new(),
+            click_combos: Vec::new(),
+            saved_state: HashMap::new(),       // A new pet until restore_persisted()
+            persisted_names: Vec::new(),
+            sequence_version: 0,
+            script_dir: None,
+            budget: Budget::new(Limits::default()),
+            pattern_cache: None,
+            deterministic: false,
+            #[cfg(feature = "mic")]
+            microphone: None,
+        };
+        interpreter.set_screen_busy(false);
+        interpreter.environment.define("away_seconds".to_string(), Value::Number(0.0));
+        interpreter
+    }
+
+    /// Executes a complete Gizmo program.
+    ///
+    /// Processes all statements in the program sequentially, maintaining
+    /// runtime state and accumulating animation frames. This is the main
+    /// entry point for script execution.
+    ///
+    /// # Arguments
+    /// * `program` - The parsed AST representing the complete script
+    ///
+    /// # Returns
+    /// * `Ok(())` - Program executed successfully
+    /// * `Err(GizmoError)` - Runtime error during execution
+    ///
+    /// # Side Effects
+    /// - Updates interpreter state (variables, animation frames)
+    /// - May produce animation frames via `add_frame()`, `play()`, etc.
+    /// - Sets frame timing via `loop_speed()`
+    pub fn execute(&mut self, program: &Program) -> Result<()> {
+        self.budget.start();
+        self.update_local_time();
+        #[cfg(feature = "mic")]
+        self.update_mic_level();
+        for statement in &program.statements {
+            self.execute_statement(statement)?;
+        }
+        Ok(())
+    }
+
+    /// Runs one statement typed at `gizmo repl`.
+    ///
+    /// Variables and handlers stay defined for the statements after it, as
+    /// they do between the statements of a script.
+    ///
+    /// # Returns
+    /// * `Ok(Some(Value))` - The value of an expression statement, to show
+    /// * `Ok(None)` - Any other statement, or an animation command like `play()`
+    /// * `Err(GizmoError)` - Runtime error during execution
+    pub fn execute_line(&mut self, statement: &Statement) -> Result<Option<Value>> {
+        self.budget.start();
+        self.update_local_time();
+        #[cfg(feature = "mic")]
+        self.update_mic_level();
+        match &statement.kind {
+            StatementKind::ExpressionStatement(expr) => {
+                self.expression_statement(expr).map_err(|e| locate(e, statement.position))
+            }
+            _ => self.execute_statement(statement).map(|()| None),
+        }
+    }
+
+    /// Renders the current frame as ASCII text for debugging.
+    ///
+    /// Converts the first animation frame (if any) to ASCII representation
+    /// using '#' for on pixels and '.' for off pixels.
+    ///
+    /// # Returns
+    /// * `Some(String)` - ASCII representation of the frame
+    /// * `None` - No frames available to render
+    #[allow(dead_code)]
+    pub fn render_current_frame(&self) -> Option<String> {
+        if !self.output_frames.is_empty() {
+            Some(self.frame_renderer.render_ascii(&self.output_frames[0]))
+        } else {
+            None
+        }
+    }
+
+    /// Returns all animation frames produced by the script.
+    ///
+    /// Provides access to the complete animation sequence accumulated
+    /// during script execution. Used by the main application to drive
+    /// the animation display.
+    ///
+    /// The frames share their pixels with the interpreter's, so this
+    /// doesn't copy them.
+    ///
+    /// # Returns
+    /// Vector of frames in animation order
+    pub fn get_animation_frames(&self) -> Vec<crate::ast::Frame> {
+        self.output_frames.clone()
+    }
+
+    /// Returns the current single frame if available.
+    ///
+    /// Used when a script produces a single static frame rather than
+    /// an animation sequence. Provides fallback content for display.
+    ///
+    /// # Returns
+    /// * `Some(Frame)` - First frame if available
+    /// * `None` - No frames produced by script
+    pub fn get_current_frame(&self) -> Option<crate::ast::Frame> {
+        if !self.output_frames.is_empty() {
+            Some(self.output_frames[0].clone())
+        } else {
+            None
+        }
+    }
+
+    /// Returns the frame duration for animation timing.
+    ///
+    /// Provides the timing value set by `loop_speed()` function calls,
+    /// or the default of 100ms if no timing was specified.
+    ///
+    /// # Returns
+    /// Frame duration in milliseconds (clamped to 1-10000ms range)
+    pub fn get_frame_duration_ms(&self) -> u64 {
+        self.clock.tick_ms()
+    }
+
+    /// Returns the palette used to color frame pixels.
+    ///
+    /// Set by `palette()` / `colors()` in the script; defaults to white on black.
+    pub fn palette(&self) -> &Palette {
+        &self.palette
+    }
+
+    /// Returns the palette for changes made outside the script (`--fg` / `--bg`).
+    pub fn palette_mut(&mut self) -> &mut Palette {
+        &mut self.palette
+    }
+
+    /// Takes the window motion commands queued since the last call.
+    ///
+    /// The window loop plays these back; see the `motion` module. Called once
+    /// after the script loads and again after every event handler.
+    pub fn take_motion_commands(&mut self) -> Vec<MotionCommand> {
+        std::mem::take(&mut self.motion)
+    }
+
+    /// Takes the `notify()` calls made since the last call as (title, body).
+    ///
+    /// The window loop shows these; see the `notification` module. Called
+    /// once after the script loads and again after every event handler.
+    pub fn take_notifications(&mut self) -> Vec<(String, String)> {
+        std::mem::take(&mut self.notifications)
+    }
+
+    /// Takes the latest `say()` request as (text, milliseconds), if any.
+    ///
+    /// Only the last call counts: a bubble replaces the one before it. Called
+    /// once after the script loads and again after every event handler.
+    pub fn take_speech(&mut self) -> Option<(String, u64)> {
+        self.speech.take()
+    }
+
+    /// Returns a counter that changes whenever `play()` / `loop()` picks new frames.
+    ///
+    /// The window loop compares it before and after running event handlers to
+    /// know when to switch to `get_animation_frames()`.
+    pub fn sequence_version(&self) -> u64 {
+        self.sequence_version
+    }
+
+    /// Runs the `when launched` handlers once the window is open.
+    ///
+    /// # Returns
+    /// * `Ok(())` - All handlers ran (or none are registered)
+    /// * `Err(GizmoError)` - A handler failed; later statements were skipped
+    pub fn handle_launch_event(&mut self) -> Result<()> {
+        self.run_handlers(Event::Launched.name())
+    }
+
+    /// Runs the `when stopping` handlers before the window closes.
+    ///
+    /// # Returns
+    /// * `Ok(())` - All handlers ran (or none are registered)
+    /// * `Err(GizmoError)` - A handler failed; later statements were skipped
+    pub fn handle_stopping_event(&mut self) -> Result<()> {
+        self.run_handlers(Event::Stopping.name())
+    }
+
+    /// Runs the `when clicked` handlers.
+    ///
+    /// # Returns
+    /// * `Ok(())` - All handlers ran (or none are registered)
+    /// * `Err(GizmoError)` - A handler failed; later statements were skipped
+    pub fn handle_click_event(&mut self) -> Result<()> {
+        self.run_handlers(Event::Clicked.name())
+    }
+
+    /// Runs the `when double_clicked` handlers.
+    ///
+    /// # Returns
+    /// * `Ok(())` - All handlers ran (or none are registered)
+    /// * `Err(GizmoError)` - A handler failed; later statements were skipped
+    pub fn handle_double_click_event(&mut self) -> Result<()> {
+        self.run_handlers(Event::DoubleClicked.name())
+    }
+
+    /// Returns `true` if the script reacts to double-clicks itself, with
+    /// `when double_clicked` or a click combo.
+    ///
+    /// The window loop only uses double-clicks to pause the animation when
+    /// the script doesn't, so a triple-click combo doesn't pause on the way.
+    pub fn handles_double_click(&self) -> bool {
+        self.event_handlers.contains_key(Event::DoubleClicked.name()) || !self.click_combos.is_empty()
+    }
+
+    /// Returns the script's click combos as (clicks, window in milliseconds),
+    /// fewest clicks first.
+    pub fn click_combos(&self) -> &[(u32, u64)] {
+        &self.click_combos
+    }
+
+    /// Runs the `when clicked N times within MS` handlers for one combo.
+    ///
+    /// # Arguments
+    /// * `times` - Clicks in the combo
+    /// * `within_ms` - Window the clicks fell within
+    ///
+    /// # Returns
+    /// * `Ok(())` - All handlers ran (or none are registered for the combo)
+    /// * `Err(GizmoError)` - A handler failed; later statements were skipped
+    pub fn handle_click_combo_event(&mut self, times: u32, within_ms: u64) -> Result<()> {
+        self.run_handlers(&format!("clicked_{}_{}", times, within_ms))
+    }
+
+    /// Runs the `when hovered` or `when unhovered` handlers.
+    ///
+    /// # Arguments
+    /// * `hovered` - `true` when the cursor moved onto the buddy, `false` when it left
+    ///
+    /// # Returns
+    /// * `Ok(())` - All handlers ran (or none are registered)
+    /// * `Err(GizmoError)` - A handler failed; later statements were skipped
+    pub fn handle_hover_event(&mut self, hovered: bool) -> Result<()> {
+        let event = if hovered { Event::Hovered } else { Event::Unhovered };
+        self.run_handlers(event.name())
+    }
+
+    /// Runs the `when dragged` handlers as a drag starts.
+    ///
+    /// # Returns
+    /// * `Ok(())` - All handlers ran (or none are registered)
+    /// * `Err(GizmoError)` - A handler failed; later statements were skipped
+    pub fn handle_drag_event(&mut self) -> Result<()> {
+        self.run_handlers(Event::Dragged.name())
+    }
+
+    /// Runs the `when shaken` handlers after the buddy was shaken mid-drag.
+    ///
+    /// # Returns
+    /// * `Ok(())` - All handlers ran (or none are registered)
+    /// * `Err(GizmoError)` - A handler failed; later statements were skipped
+    pub fn handle_shake_event(&mut self) -> Result<()> {
+        self.run_handlers(Event::Shaken.name())
+    }
+
+    /// Runs the `when dropped` handlers with `drop_x` / `drop_y` set to where
+    /// the window landed.
+    ///
+    /// # Arguments
+    /// * `x`, `y` - Window's top-left corner in screen pixels after the drop
+    ///
+    /// # Returns
+    /// * `Ok(())` - All handlers ran (or none are registered)
+    /// * `Err(GizmoError)` - A handler failed; later statements were skipped
+    pub fn handle_drop_event(&mut self, x: i32, y: i32) -> Result<()> {
+        self.environment.define("drop_x".to_string(), Value::Number(x as f64));
+        self.environment.define("drop_y".to_string(), Value::Number(y as f64));
+        self.run_handlers(Event::Dropped.name())
+    }
+
+    /// Runs the `when key(name)` handlers for a key.
+    ///
+    /// # Arguments
+    /// * `name` - Key name as produced by the `keyboard` module, e.g. `"p"` or `"space"`
+    ///
+    /// # Returns
+    /// * `Ok(())` - All handlers ran (or none are registered for the key)
+    /// * `Err(GizmoError)` - A handler failed; later statements were skipped
+    pub fn handle_key_event(&mut self, name: &str) -> Result<()> {
+        self.run_handlers(&format!("key_{}", name))
+    }
+
+    /// Returns `true` if the script has `when key(name)` handlers for a key.
+    ///
+    /// Keys the script handles lose their built-in meaning (such as `+` / `-`
+    /// zooming).
+    pub fn handles_key(&self, name: &str) -> bool {
+        self.event_handlers.contains_key(&format!("key_{}", name))
+    }
+
+    /// Runs the `when message(text)` handlers for a message.
+    ///
+    /// # Arguments
+    /// * `text` - The message as received, without surrounding whitespace
+    ///
+    /// # Returns
+    /// * `Ok(())` - All handlers ran (or none are registered for the message)
+    /// * `Err(GizmoError)` - A handler failed; later statements were skipped
+    pub fn handle_message_event(&mut self, text: &str) -> Result<()> {
+        self.run_handlers(&format!("message_{}", text))
+    }
+
+    /// Returns the thresholds of the script's `when idle > N` handlers in
+    /// milliseconds, smallest first.
+    pub fn idle_thresholds(&self) -> &[u64] {
+        &self.idle_thresholds
+    }
+
+    /// Runs the `when idle > N` handlers for one threshold.
+    ///
+    /// # Arguments
+    /// * `threshold_ms` - A threshold from [`idle_thresholds`](Self::idle_thresholds)
+    ///   that the idle time just passed
+    ///
+    /// # Returns
+    /// * `Ok(())` - All handlers ran (or none are registered for the threshold)
+    /// * `Err(GizmoError)` - A handler failed; later statements were skipped
+    pub fn handle_idle_event(&mut self, threshold_ms: u64) -> Result<()> {
+        self.run_handlers(&format!("idle_{}", threshold_ms))
+    }
+
+    /// Returns the times of the script's `when time("HH:MM")` handlers in
+    /// minutes since midnight, earliest first.
+    pub fn time_triggers(&self) -> &[u32] {
+        &self.time_triggers
+    }
+
+    /// Runs the `when time("HH:MM")` handlers for one time of day.
+    ///
+    /// # Arguments
+    /// * `minute` - A time from [`time_triggers`](Self::time_triggers) the clock just reached
+    ///
+    /// # Returns
+    /// * `Ok(())` - All handlers ran (or none are registered for the time)
+    /// * `Err(GizmoError)` - A handler failed; later statements were skipped
+    pub fn handle_time_event(&mut self, minute: u32) -> Result<()> {
+        self.run_handlers(&format!("time_{}", minute))
+    }
+
+    /// Returns the intervals of the script's `every N` handlers in
+    /// milliseconds, shortest first.
+    pub fn timer_intervals(&self) -> &[u64] {
+        &self.timer_intervals
+    }
+
+    /// Runs the `every N` handlers for one interval.
+    ///
+    /// # Arguments
+    /// * `interval_ms` - An interval from [`timer_intervals`](Self::timer_intervals)
+    ///   that just elapsed
+    ///
+    /// # Returns
+    /// * `Ok(())` - All handlers ran (or none are registered for the interval)
+    /// * `Err(GizmoError)` - A handler failed; later statements were skipped
+    pub fn handle_timer_event(&mut self, interval_ms: u64) -> Result<()> {
+        self.run_handlers(&format!("every_{}", interval_ms))
+    }
+
+    /// Returns the percentages of the script's `when battery < N` handlers,
+    /// lowest first.
+    #[cfg(feature = "sysinfo")]
+    pub fn battery_thresholds(&self) -> &[u32] {
+        &self.battery_thresholds
+    }
+
+    /// Returns `true` if the script reacts to power changes, so the window
+    /// loop keeps sampling the battery while nothing else wakes it.
+    #[cfg(feature = "sysinfo")]
+    pub fn handles_power_events(&self) -> bool {
+        !self.battery_thresholds.is_empty()
+            || self.event_handlers.contains_key(Event::Unplugged.name())
+            || self.event_handlers.contains_key(Event::PluggedIn.name())
+    }
+
+    /// Runs the `when unplugged` or `when plugged_in` handlers.
+    ///
+    /// # Arguments
+    /// * `on_battery` - `true` when the system switched to battery power
+    ///
+    /// # Returns
+    /// * `Ok(())` - All handlers ran (or none are registered)
+    /// * `Err(GizmoError)` - A handler failed; later statements were skipped
+    #[cfg(feature = "sysinfo")]
+    pub fn handle_power_event(&mut self, on_battery: bool) -> Result<()> {
+        let event = if on_battery { Event::Unplugged } else { Event::PluggedIn };
+        self.run_handlers(event.name())
+    }
+
+    /// Runs the `when battery < N` handlers for one threshold.
+    ///
+    /// # Arguments
+    /// * `percent` - The threshold the charge dropped below
+    ///
+    /// # Returns
+    /// * `Ok(())` - All handlers ran (or none are registered for the threshold)
+    /// * `Err(GizmoError)` - A handler failed; later statements were skipped
+    #[cfg(feature = "sysinfo")]
+    pub fn handle_battery_event(&mut self, percent: u32) -> Result<()> {
+        self.run_handlers(&format!("battery_{}", percent))
+    }
+
+    /// Updates the read-only system variables (`cpu_percent`, `mem_percent`,
+    /// `battery_percent`, `on_battery`) from a new sample.
+    #[cfg(feature = "sysinfo")]
+    pub fn set_system_stats(&mut self, stats: &crate::stats::SystemStats) {
+        let values = [
+            stats.cpu_percent,
+            stats.mem_percent,
+            stats.battery_percent,
+            if stats.on_battery { 1.0 } else { 0.0 },
+        ];
+        for (name, value) in SYSTEM_VARIABLES.iter().zip(values) {
+            self.environment.define(name.to_string(), Value::Number(value));
+        }
+    }
+
+    /// Updates the read-only `screen_busy` variable (1 while a full-screen
+    /// app is in front, otherwise 0).
+    pub fn set_screen_busy(&mut self, busy: bool) {
+        self.environment.define(
+            "screen_busy".to_string(),
+            Value::Number(if busy { 1.0 } else { 0.0 }),
+        );
+    }
+
+    /// Sets the limits later runs are held to (see the `limits` module).
+    ///
+    /// # Arguments
+    /// * `limits` - The new limits; [`Limits::unlimited`] turns them off
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.budget = Budget::new(limits);
+    }
+
+    /// Returns the limits runs are held to.
+    pub fn limits(&self) -> Limits {
+        self.budget.limits()
+    }
+
+    /// Tells the interpreter where the script is, before it runs, so files
+    /// it names (`load_image()`, `load_aseprite()`) are found next to it.
+    ///
+    /// # Arguments
+    /// * `dir` - Directory of the .gzmo file
+    pub fn set_script_dir(&mut self, dir: &Path) {
+        self.script_dir = Some(dir.to_path_buf());
+    }
+
+    /// The variables, for tests that run compiled patterns against them.
+    #[cfg(test)]
+    pub(crate) fn environment_mut(&mut self) -> &mut Environment {
+        &mut self.environment
+    }
+
+    /// Hands over frames of compiled patterns from earlier runs (see the
+    /// `cache` module). Patterns rendered from now on are looked up in it
+    /// and added to it.
+    pub fn set_pattern_cache(&mut self, cache: PatternCache) {
+        self.pattern_cache = Some(cache);
+    }
+
+    /// Takes back the pattern cache, with the patterns rendered since it was
+    /// handed over; later renders don't use a cache.
+    pub fn take_pattern_cache(&mut self) -> Option<PatternCache> {
+        self.pattern_cache.take()
+    }
+
+    /// Makes runs reproducible, before the script runs: `random()` returns
+    /// the same numbers for the same seed, and the time variables read
+    /// [`DETERMINISTIC_TIME`] instead of the local clock.
+    ///
+    /// The caller leaves out everything else that differs between runs
+    /// (persisted state, system stats, the microphone).
+    ///
+    /// # Arguments
+    /// * `seed` - Seed of the numbers `random()` returns
+    pub fn set_deterministic(&mut self, seed: u64) {
+        builtin::seed_random(seed);
+        self.deterministic = true;
+    }
+
+    /// Hands over the state the script saved last session, before it runs.
+    ///
+    /// `persist` declarations start from these values instead of their
+    /// defaults, and `away_seconds` tells the script how long it was away.
+    pub fn restore_persisted(&mut self, saved: SavedState) {
+        self.saved_state = saved.values;
+        self.environment.define(
+            "away_seconds".to_string(),
+            Value::Number(saved.away.as_secs() as f64),
+        );
+    }
+
+    /// Returns the current values of the script's `persist` variables.
+    ///
+    /// Variables that were since assigned something other than a number or
+    /// a string are left out, so their last saved value is dropped.
+    pub fn persisted_values(&self) -> HashMap<String, Persisted> {
+        self.persisted_names
+            .iter()
+            .filter_map(|name| {
+                let value = match self.environment.get(name).ok()? {
+                    Value::Number(number) => Persisted::Number(number),
+                    Value::String(text) => Persisted::Text(text),
+                    _ => return None,
+                };
+                Some((name.clone(), value))
+            })
+            .collect()
+    }
+
+    /// Connects the microphone the `mic_level` variable follows.
+    #[cfg(feature = "mic")]
+    pub fn set_microphone(&mut self, level: crate::mic::MicLevel) {
+        self.microphone = Some(level);
+        self.update_mic_level();
+    }
+
+    /// Sets `mic_level` from the microphone (0 without one).
+    #[cfg(feature = "mic")]
+    fn update_mic_level(&mut self) {
+        let level = self.microphone.as_ref().map_or(0.0, crate::mic::MicLevel::get);
+        self.environment.define("mic_level".to_string(), Value::Number(level));
+    }
+
+    /// Sets the `hour`, `minute` and `weekday` variables from the local clock.
+    ///
+    /// Done before the script runs and before every event handler, so both
+    /// see the current time. `weekday` counts from 0 (Sunday) to 6 (Saturday).
+    fn update_local_time(&mut self) {
+        use chrono::{Datelike, Timelike};
+
+        let (hour, minute, weekday) = if self.deterministic {
+            DETERMINISTIC_TIME
+        } else {
+            let now = chrono::Local::now();
+            (now.hour(), now.minute(), now.weekday().num_days_from_sunday())
+        };
+        self.environment.define("hour".to_string(), Value::Number(hour as f64));
+        self.environment.define("minute".to_string(), Value::Number(minute as f64));
+        self.environment.define("weekday".to_string(), Value::Number(weekday as f64));
+    }
+
+    /// Executes the handlers registered for an event, in script order.
+    ///
+    /// `once` handlers are unregistered before they run, so they never run
+    /// twice even if they fail. A handler that fails doesn't switch the
+    /// animation: frames it played before failing are put back, so the
+    /// window keeps showing the last animation that worked.
+    fn run_handlers(&mut self, event: &str) -> Result<()> {
+        // Handlers may register more handlers, so run from a copy
+        let handlers = match self.event_handlers.get_mut(event) {
+            Some(registered) => {
+                let handlers = registered.clone();
+                registered.retain(|handler| !handler.once);
+                if registered.is_empty() {
+                    self.event_handlers.remove(event);
+                    // Stop waking the window loop for a spent `every N once`
+                    self.timer_intervals.retain(|ms| format!("every_{}", ms) != event);
+                }
+                handlers
+            }
+            None => return Ok(()),
+        };
+
+        self.budget.start();
+        self.update_local_time();
+        #[cfg(feature = "mic")]
+        self.update_mic_level();
+        for handler in &handlers {
+            let before = (self.output_frames.clone(), self.sequence_version, self.switch_priority);
+            self.running_priority = Some(handler.priority);
+            let result = handler.body.iter().try_for_each(|statement| self.execute_statement(statement));
+            self.running_priority = None;
+            if result.is_err() {
+                (self.output_frames, self.sequence_version, self.switch_priority) = before;
+            }
+            result?;
+        }
+        Ok(())
+    }
+
+    /// Marks the handler results as applied by the window loop.
+    ///
+    /// Until this is called, a handler's `play()` / `loop()` / `loop_speed()`
+    /// is ignored if a handler with a higher priority already switched the
+    /// animation, so the highest priority wins no matter which event was
+    /// handled first.
+    pub fn end_handler_batch(&mut self) {
+        self.switch_priority = None;
+    }
+
+    /// Runs `add_frame(frames, frame)`, appending to the array in place.
+    ///
+    /// A variable that doesn't hold frames yet becomes an array of just this
+    /// frame; a value that isn't a frame is ignored.
+    ///
+    /// # Arguments
+    /// * `array_name` - Variable holding the frames array
+    /// * `frame` - Expression for the frame to append
+    ///
+    /// # Returns
+    /// * `Ok(())` - The frame was appended, or there was none to append
+    /// * `Err(GizmoError)` - The variable isn't defined, or evaluating the frame failed
+    fn add_frame(&mut self, array_name: &str, frame: &Expression) -> Result<()> {
+        // The array is checked first, as it is the first argument
+        self.environment.get_mut(array_name)?;
+        let frame = match self.evaluate_expression(frame)? {
+            Value::Frame(frame) => frame,
+            _ => return Ok(()),
+        };
+        match self.environment.get_mut(array_name)? {
+            Value::Frames(frames) => {
+                self.budget.check_frames(&format!("'{}'", array_name), frames.len() + 1)?;
+                frames.push(frame);
+            }
+            value => *value = Value::Frames(vec![frame]),
+        }
+        Ok(())
+    }
+
+    /// Replaces the output frames for `play()` / `loop()` / `loop_speed()`;
+    /// frames that repeat share their pixels.
+    ///
+    /// # Returns
+    /// `false` if a higher-priority handler already switched the animation in
+    /// this batch, in which case nothing changed
+    fn switch_frames(&mut self, mut frames: Vec<Frame>) -> bool {
+        if let Some(priority) = self.running_priority {
+            if self.switch_priority.is_some_and(|winner| priority < winner) {
+                return false;
+            }
+            self.switch_priority = Some(priority);
+        }
+        dedup::share_identical(&mut frames);
+        self.output_frames = frames;
+        self.sequence_version += 1;
+        true
+    }
+
+    /// Returns the whole-window opacity set by `opacity()` (default 1.0).
+    pub fn opacity(&self) -> f64 {
+        self.opacity
+    }
+
+    /// Returns the playback clock.
+    ///
+    /// The clock ticks once per frame duration, so `clock().frame_index(n)`
+    /// is the frame that should be visible right now.
+    pub fn clock(&self) -> &AnimationClock {
+        &self.clock
+    }
+
+    /// Returns the playback clock for advancing it with real elapsed time.
+    ///
+    /// Called by the window loop on every wake-up; the clock decides how many
+    /// whole ticks have passed independently of how often the window redraws.
+    pub fn clock_mut(&mut self) -> &mut AnimationClock {
+        &mut self.clock
+    }
+
+    /// Generates the transition frames for switching between two sequences.
+    ///
+    /// Blends the last frame of `from` into the first frame of `to` using the
+    /// style configured with `transition()`, one frame per animation tick.
+    ///
+    /// # Returns
+    /// Frames to play between the two sequences (empty for a hard cut)
+    pub fn transition_frames(&self, from: &[Frame], to: &[Frame]) -> Vec<Frame> {
+        match (from.last(), to.first()) {
+            (Some(last), Some(first)) => {
+                self.transition
+                    .frames_between(last, first, self.clock.tick_ms())
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Executes a single statement, placing its errors at the statement.
+    ///
+    /// # Arguments
+    /// * `stmt` - The statement AST node to execute
+    ///
+    /// # Returns
+    /// * `Ok(())` - Statement executed successfully
+    /// * `Err(GizmoError)` - Runtime error during execution, located at the
+    ///   innermost statement that failed if the parser recorded positions
+    fn execute_statement(&mut self, stmt: &Statement) -> Result<()> {
+        self.execute_kind(&stmt.kind).map_err(|e| locate(e, stmt.position))
+    }
+
+    /// Executes what a single statement does.
+    ///
+    /// Handles all statement types including variable operations, control flow,
+    /// and expression statements with special animation function handling.
+    ///
+    /// # Arguments
+    /// * `stmt` - What the statement does
+    ///
+    /// # Returns
+    /// * `Ok(())` - Statement executed successfully
+    /// * `Err(GizmoError)` - Runtime error during execution
+    fn execute_kind(&mut self, stmt: &StatementKind) -> Result<()> {
+        self.budget.step()?;
+        match stmt {
+            StatementKind::VariableDeclaration {
+                var_type: _,
+                name,
+                value,
+            } => {
+                check_writable(name)?;
+                let val = self.evaluate_expression(value)?;
+                self.environment.define(name.clone(), val);
+                Ok(())
+            }
+
+            StatementKind::Assignment { name, value } => {
+                check_writable(name)?;
+                let val = self.evaluate_expression(value)?;
+                self.environment.define(name.clone(), val);
+                Ok(())
+            }
+
+            StatementKind::PersistDeclaration { name, value } => {
+                check_writable(name)?;
+                // Declaring again (e.g. inside a handler) keeps the current value
+                if self.persisted_names.iter().any(|persisted| **persisted == **name) {
+                    return Ok(());
+                }
+
+                let val = match self.saved_state.get(&**name) {
+                    Some(Persisted::Number(number)) => Value::Number(*number),
+                    Some(Persisted::Text(text)) => Value::String(text.clone()),
+                    None => match self.evaluate_expression(value)? {
+                        val @ (Value::Number(_) | Value::String(_)) => val,
+                        _ => {
+                            return Err(GizmoError::TypeError(format!(
+                                "persist {} must start as a number or a string", name
+                            )));
+                        }
+                    },
+                };
+                self.environment.define(name.clone(), val);
+                self.persisted_names.push(name.to_string());
+                Ok(())
+            }
+
+            StatementKind::ExpressionStatement(expr) => {
+                self.expression_statement(expr)?;
+                Ok(())
+            }
+
+            StatementKind::WhenStatement { event, body, once, priority } => {
+                // Idle handlers are filed per threshold, e.g. "idle_5000"
+                let key = match event {
+                    Event::Idle(threshold) => {
+                        let ms = self.evaluate_expression(threshold)?.to_number()?;
+                        if !ms.is_finite() || ms < 0.0 {
+                            return Err(GizmoError::ArgumentError(format!(
+                                "idle threshold must be a positive number of milliseconds, got {}", ms
+                            )));
+                        }
+                        let ms = ms as u64;
+                        if let Err(index) = self.idle_thresholds.binary_search(&ms) {
+                            self.idle_thresholds.insert(index, ms);
+                        }
+                        format!("idle_{}", ms)
+                    }
+                    Event::Key(key) => match self.evaluate_expression(key)? {
+                        // Key names are matched case-insensitively, e.g. "key_space"
+                        Value::String(name) => format!("key_{}", name.to_lowercase()),
+                        other => {
+                            return Err(GizmoError::TypeError(format!(
+                                "key() expects a key name string, got {:?}", other
+                            )));
+                        }
+                    },
+                    Event::Message(text) => match self.evaluate_expression(text)? {
+                        // Filed by the exact text, e.g. "message_alert"
+                        Value::String(text) => format!("message_{}", text),
+                        other => {
+                            return Err(GizmoError::TypeError(format!(
+                                "message() expects a message string, got {:?}", other
+                            )));
+                        }
+                    },
+                    Event::Time(time) => {
+                        let minute = match self.evaluate_expression(time)? {
+                            Value::String(text) => schedule::parse_time_of_day(&text).ok_or_else(|| {
+                                GizmoError::ArgumentError(format!(
+                                    "time() expects a 24-hour \"HH:MM\" time, got \"{}\"", text
+                                ))
+                            })?,
+                            other => {
+                                return Err(GizmoError::TypeError(format!(
+                                    "time() expects a \"HH:MM\" string, got {:?}", other
+                                )));
+                            }
+                        };
+                        if let Err(index) = self.time_triggers.binary_search(&minute) {
+                            self.time_triggers.insert(index, minute);
+                        }
+                        // Filed by minute of the day, e.g. "time_1320" for 22:00
+                        format!("time_{}", minute)
+                    }
+                    Event::Every(interval) => {
+                        let ms = self.evaluate_expression(interval)?.to_number()?;
+                        if !ms.is_finite() || ms < 1.0 {
+                            return Err(GizmoError::ArgumentError(format!(
+                                "every interval must be at least 1 millisecond, got {}", ms
+                            )));
+                        }
+                        let ms = ms as u64;
+                        if let Err(index) = self.timer_intervals.binary_search(&ms) {
+                            self.timer_intervals.insert(index, ms);
+                        }
+                        format!("every_{}", ms)
+                    }
+                    Event::ClickCombo { times, within } => {
+                        let count = self.evaluate_expression(times)?.to_number()?;
+                        if count.fract() != 0.0 || !(2.0..=100.0).contains(&count) {
+                            return Err(GizmoError::ArgumentError(format!(
+                                "a click combo needs a whole number of clicks from 2 to 100, got {}", count
+                            )));
+                        }
+                        let ms = self.evaluate_expression(within)?.to_number()?;
+                        if !ms.is_finite() || ms < 1.0 {
+                            return Err(GizmoError::ArgumentError(format!(
+                                "a click combo must happen within at least 1 millisecond, got {}", ms
+                            )));
+                        }
+                        let combo = (count as u32, ms as u64);
+                        if let Err(index) = self.click_combos.binary_search(&combo) {
+                            self.click_combos.insert(index, combo);
+                        }
+                        format!("clicked_{}_{}", combo.0, combo.1)
+                    }
+                    Event::Battery(percent) => {
+                        let percent = self.evaluate_expression(percent)?.to_number()?;
+                        if percent.fract() != 0.0 || !(1.0..=100.0).contains(&percent) {
+                            return Err(GizmoError::ArgumentError(format!(
+                                "battery threshold must be a whole percentage from 1 to 100, got {}", percent
+                            )));
+                        }
+                        let percent = percent as u32;
+                        if let Err(index) = self.battery_thresholds.binary_search(&percent) {
+                            self.battery_thresholds.insert(index, percent);
+                        }
+                        format!("battery_{}", percent)
+                    }
+                    _ => event.name().to_string(),
+                };
+
+                let priority = match priority {
+                    Some(priority) => {
+                        let value = self.evaluate_expression(priority)?.to_number()?;
+                        if !value.is_finite() {
+                            return Err(GizmoError::ArgumentError(format!(
+                                "handler priority must be a number, got {}", value
+                            )));
+                        }
+                        value
+                    }
+                    None => 0.0,
+                };
+
+                // Keep the body for later; several handlers for one event run in order
+                self.event_handlers.entry(key).or_default().push(Handler {
+                    body: body.clone(),
+                    once: *once,
+                    priority,
+                });
+                Ok(())
+            }
+
+            // Comments and blank lines only reach here from `gizmo fmt` parses
+            StatementKind::Trivia(_) => Ok(()),
+
+            StatementKind::IfStatement {
+                condition,
+                then_body,
+                else_body,
+            } => {
+                // Evaluate condition expression
+                let condition_val = self.evaluate_expression(condition)?;
+                let condition_true = match condition_val {
+                    Value::Number(n) => n != 0.0, // 0.0 = false, anything else = true
+                    _ => {
+                        return Err(GizmoError::TypeError(
+                            "if condition must be a number".to_string(),
+                        ))
+                    }
+                };
+
+                // Execute appropriate branch
+                if condition_true {
+                    // Execute then branch
+                    for stmt in then_body {
+                        self.execute_statement(stmt)?;
+                    }
+                } else if let Some(else_statements) = else_body {
+                    // Execute else branch if present
+                    for stmt in else_statements {
+                        self.execute_statement(stmt)?;
+                    }
+                }
+
+                Ok(())
+            }
+
+            StatementKind::RepeatLoop { count, body } => {
+                // Evaluate loop count expression
+                let count_value = self.evaluate_expression(count)?;
+                let repeat_count = match count_value {
+                    Value::Number(n) => n as usize,
+                    _ => {
+                        return Err(GizmoError::TypeError(
+                            "repeat count must be a number".to_string(),
+                        ))
+                    }
+                };
+
+                // Execute loop body for specified number of iterations
+                let time: Name = "time".into();
+                for i in 0..repeat_count {
+                    // Even an empty body counts, so huge counts still stop
+                    self.budget.step()?;
+
+                    // Provide 'time' variable with current iteration (0-based)
+                    // This is useful for creating animated sequences
+                    self.environment.define(time.clone(), Value::Number(i as f64));
+
+                    // Execute all statements in loop body
+                    for stmt in body {
+                        self.execute_statement(stmt)?;
+                    }
+                }
+
+                Ok(())
+            }
+        }
+    }
+
+    /// Runs an expression statement, including the effects of animation
+    /// commands such as `play()` and `add_frame()` on the interpreter.
+    ///
+    /// # Returns
+    /// * `Ok(Some(Value))` - The expression's value
+    /// * `Ok(None)` - The statement was an animation command, whose value means nothing
+    /// * `Err(GizmoError)` - Runtime error during evaluation
+    fn expression_statement(&mut self, expr: &Expression) -> Result<Option<Value>> {
+        if let Expression::FunctionCall { name, args } = expr {
+            if let ("add_frame", [Expression::Identifier(array_name), frame]) = (&**name, args.as_slice()) {
+                if !matches!(frame, Expression::NamedArgument { .. }) {
+                    self.add_frame(array_name, frame)?;
+                    return Ok(None);
+                }
+            }
+        }
+
+        let result = self.evaluate_expression(expr)?;
+
+        // Special handling for animation control functions
+        // These functions have side effects on the interpreter's animation state
+        if let Expression::FunctionCall { name, args } = expr {
+            match &**name {
+                "add_frame" if args.len() == 2 => {
+                    // Only appends to a variable; see add_frame()
+                }
+                "loop_speed" if args.len() == 2 => {
+                    // loop_speed(frames, ms) - sets animation frames and timing
+                    let frame_value = self.evaluate_expression(&args[0])?;
+                    let timing_value = self.evaluate_expression(&args[1])?;
+
+                    // Set output frames for animation
+                    let switched = match frame_value {
+                        Value::Frames(frames) => self.switch_frames(frames),
+                        Value::Frame(frame) => self.switch_frames(vec![frame]),
+                        _ => true,
+                    };
+
+                    // Set frame timing with safety bounds
+                    if let (true, Value::Number(ms)) = (switched, timing_value) {
+                        // Clamp to 1-10000ms range for safety and performance
+                        let (min, max) = TICK_MS_RANGE;
+                        self.clock.set_tick_ms((ms as u64).clamp(min, max));
+                    }
+                }
+                "play" | "loop" if !args.is_empty() => {
+                    // play(frames) / loop(frames) - sets frames for display
+                    match self.evaluate_expression(&args[0])? {
+                        Value::Frames(frames) => {
+                            self.switch_frames(frames);
+                        }
+                        Value::Frame(frame) => {
+                            self.switch_frames(vec![frame]);
+                        }
+                        _ => {}
+                    }
+                }
+                "transition" if args.len() == 2 => {
+                    // transition(kind, ms) - configures blending between sequences
+                    let kind_value = self.evaluate_expression(&args[0])?;
+                    let duration_value = self.evaluate_expression(&args[1])?;
+
+                    // Arguments were already validated by the builtin
+                    if let (Value::String(kind), Value::Number(ms)) = (kind_value, duration_value) {
+                        self.transition = Transition::from_name(&kind, (ms.max(0.0) as u64).min(10000))?;
+                    }
+                }
+                "palette" if args.len() == 1 => {
+                    // palette([colors...]) - sets the colors for pixel indices
+                    // Arguments were already validated by the builtin
+                    if let Value::Strings(colors) = self.evaluate_expression(&args[0])? {
+                        self.palette = Palette::from_hex_strings(&colors)?;
+                    }
+                }
+                "move_to" if args.len() == 3 => {
+                    // move_to(x, y, ms) - queues a glide to a screen position
+                    let x = self.evaluate_expression(&args[0])?.to_number()?;
+                    let y = self.evaluate_expression(&args[1])?.to_number()?;
+                    let ms = self.evaluate_expression(&args[2])?.to_number()?;
+                    self.motion.push(MotionCommand::MoveTo {
+                        x: x as i32,
+                        y: y as i32,
+                        duration_ms: ms.max(0.0) as u64,
+                    });
+                }
+                "wander" if args.len() == 1 => {
+                    // wander(speed) - queues endless random walking in pixels per second
+                    let speed = self.evaluate_expression(&args[0])?.to_number()?;
+                    self.motion.push(MotionCommand::Wander { speed });
+                }
+                "fall" | "bounce" if args.is_empty() => {
+                    // fall() / bounce() - queues a drop onto the taskbar or screen bottom
+                    self.motion.push(MotionCommand::Fall { bounce: &**name == "bounce" });
+                }
+                "colors" => {
+                    // colors(on = "#rrggbb", off = "#rrggbb") - sets palette indices 1 and 0;
+                    // positional arguments are taken as on, then off
+                    for (position, arg) in args.iter().enumerate() {
+                        let (index, value) = match arg {
+                            Expression::NamedArgument { name, value } => match &**name {
+                                "on" => (1, value.as_ref()),
+                                "off" => (0, value.as_ref()),
+                                other => {
+                                    return Err(GizmoError::ArgumentError(format!(
+                                        "colors has no argument named '{}' (expected on or off)",
+                                        other
+                                    )))
+                                }
+                            },
+                            value => (if position == 0 { 1 } else { 0 }, value),
+                        };
+
+                        // Arguments were already validated by the builtin
+                        if let Value::String(color) = self.evaluate_expression(value)? {
+                            self.palette.set_color(index, &color)?;
+                        }
+                    }
+                }
+                "opacity" if args.len() == 1 => {
+                    // opacity(value) - sets whole-window opacity from 0.0 to 1.0
+                    self.opacity = self.evaluate_expression(&args[0])?.to_number()?;
+                }
+                "notify" if args.len() == 2 => {
+                    // notify(title, body) - queues a desktop notification
+                    // Arguments were already validated by the builtin
+                    if let (Value::String(title), Value::String(body)) =
+                        (self.evaluate_expression(&args[0])?, self.evaluate_expression(&args[1])?)
+                    {
+                        self.notifications.push((title, body));
+                    }
+                }
+                "say" if args.len() == 2 => {
+                    // say(text, ms) - shows a speech bubble above the sprite
+                    if let (Value::String(text), Value::Number(ms)) =
+                        (self.evaluate_expression(&args[0])?, self.evaluate_expression(&args[1])?)
+                    {
+                        self.speech = Some((text, ms as u64));
+                    }
+                }
+                _ => return Ok(Some(result)), // Other functions handled by builtin system
+            }
+            return Ok(None);
+        }
+
+        Ok(Some(result))
+    }
+
+    /// Evaluates an expression to produce a runtime value.
+    ///
+    /// This is the core expression evaluation method that handles all expression
+    /// types including literals, variables, function calls, binary operations,
+    /// and the complex pattern generation system.
+    ///
+    /// # Arguments
+    /// * `expr` - The expression AST node to evaluate
+    ///
+    /// # Returns
+    /// * `Ok(Value)` - The computed value of the expression
+    /// * `Err(GizmoError)` - Runtime error during evaluation
+    fn evaluate_expression(&mut self, expr: &Expression) -> Result<Value> {
+        match expr {
+            // Literal values
+            Expression::Number(n) => Ok(Value::Number(*n)),
+            Expression::String(s) => Ok(Value::String(s.clone())),
+
+            // Named call argument - builtins see its value positionally; the
+            // statement handlers that care about names read them from the AST
+            Expression::NamedArgument { value, .. } => self.evaluate_expression(value),
+
+            // Variable lookup
+            Expression::Identifier(name) => self.environment.get(name),
+
+            Expression::Array(elements) => {
+                let mut values = Vec::new();
+                for element in elements {
+                    values.push(self.evaluate_expression(element)?);
+                }
+
+                // Check what type of array this is
+                if values.iter().all(|v| matches!(v, Value::Number(_))) {
+                    // All numbers - create a frame row of palette indices
+                    self.budget.check_size(values.len(), 1, "frame")?;
+                    let pixel_row: Result<Vec<u8>> = values
+                        .iter()
+                        .map(|v| Ok(palette::index_from_number(v.to_number()?)))
+                        .collect();
+                    let frame = Frame::from_array(vec![pixel_row?])?;
+                    Ok(Value::Frame(frame))
+                } else if !values.is_empty() && values.iter().all(|v| matches!(v, Value::String(_))) {
+                    // All strings - used for palette color lists
+                    let strings = values
+                        .into_iter()
+                        .map(|v| match v {
+                            Value::String(s) => s,
+                            _ => unreachable!(),
+                        })
+                        .collect();
+                    Ok(Value::Strings(strings))
+                } else if values.iter().all(|v| matches!(v, Value::Frame(_))) {
+                    // All frames
+                    if values.len() == 1 {
+                        // Single frame
+                        Ok(values.into_iter().next().unwrap())
+                    } else if values.iter().all(|v| match v {
+                        Value::Frame(f) => f.height == 1,
+                        _ => false,
+                    }) {
+                        // Multiple single-row frames - combine into 2D frame
+                        let mut frame_rows = Vec::new();
+                        for value in values {
+                            if let Value::Frame(frame) = value {
+                                frame_rows.push(frame.pixels[0].clone());
+                            }
+                        }
+                        let width = frame_rows.iter().map(Vec::len).max().unwrap_or(0);
+                        self.budget.check_size(width, frame_rows.len(), "frame")?;
+                        let frame = Frame::from_array(frame_rows)?;
+                        Ok(Value::Frame(frame))
+                    } else {
+                        // Array of frames
+                        let frames: Vec<Frame> = values
+                            .into_iter()
+                            .map(|v| match v {
+                                Value::Frame(f) => f,
+                                _ => unreachable!(),
+                            })
+                            .collect();
+                        self.budget.check_frames("a frames array", frames.len())?;
+                        Ok(Value::Frames(frames))
+                    }
+                } else {
+                    Err(GizmoError::TypeError(
+                        "Cannot create array from mixed types".to_string(),
+                    ))
+                }
+            }
+
+            Expression::FunctionCall { name, args } => {
+                if !self.builtins.accepts_named_arguments(name) {
+                    if let Some(Expression::NamedArgument { name: arg_name, .. }) =
+                        args.iter().find(|arg| matches!(arg, Expression::NamedArgument { .. }))
+                    {
+                        return Err(GizmoError::ArgumentError(format!(
+                            "{} does not take named arguments (got '{}')",
+                            name, arg_name
+                        )));
+                    }
+                }
+
+                let arg_values: Result<Vec<Value>> = args
+                    .iter()
+                    .map(|arg| self.evaluate_expression(arg))
+                    .collect();
+                let mut arg_values = arg_values?;
+
+                // Images sit next to the script, wherever gizmo was started from
+                if let (true, Some(dir), Some(Value::String(path))) =
+                    (self.builtins.takes_file_path(name), &self.script_dir, arg_values.first_mut())
+                {
+                    *path = dir.join(&*path).to_string_lossy().into_owned();
+                }
+
+                if let ("create_frame", [Value::Number(w), Value::Number(h)]) = (&**name, arg_values.as_slice()) {
+                    self.budget.check_size(*w as usize, *h as usize, "create_frame")?;
+                }
+
+                if self.builtins.has_function(name) {
+                    // Loaded images and sprite sheets are only sized once decoded
+                    let value = self.builtins.call(name, &arg_values)?;
+                    self.budget.check_value(&value, name)?;
+                    Ok(value)
+                } else {
+                    Err(GizmoError::UndefinedFunction(name.to_string()))
+                }
+            }
+
+            // Binary operations - arithmetic, comparison, and logical
+            Expression::BinaryOperation {
+                left,
+                operator,
+                right,
+            } => {
+                let left_val = self.evaluate_expression(left)?;
+                let right_val = self.evaluate_expression(right)?;
+
+                match (left_val, right_val) {
+                    (Value::Number(l), Value::Number(r)) => Ok(Value::Number(apply_binary_operator(operator, l, r)?)),
+                    _ => Err(GizmoError::TypeError(
+                        "Binary operations only supported for numbers".to_string(),
+                    )),
+                }
+            }
+
+            // Pattern generation - the heart of Gizmo's procedural pixel art
+            Expression::PatternGenerator {
+                width,
+                height,
+                body,
+                return_expr,
+            } => {
+                let (w, h) = self.evaluate_dimensions(width, height, "pattern")?;
+                let frame = self.render_pattern(w, h, body, return_expr)?;
+                Ok(Value::Frame(frame))
+            }
+
+            // Multi-pass pipeline - each pass is a pattern that can sample the
+            // frame produced by the pass before it through `previous`
+            Expression::Pipeline {
+                width,
+                height,
+                passes,
+            } => {
+                let (w, h) = self.evaluate_dimensions(width, height, "pipeline")?;
+
+                // `previous` only exists for the passes after the first; a
+                // variable of that name from outside comes back afterwards,
+                // whether or not the passes succeed
+                let outer = self.environment.remove("previous");
+                let result = self.render_passes(w, h, passes);
+                match outer {
+                    Some(value) => self.environment.define("previous", value),
+                    None => {
+                        self.environment.remove("previous");
+                    }
+                }
+
+                result?.map(Value::Frame).ok_or_else(|| {
+                    GizmoError::RuntimeError("pipeline must contain at least one pass".to_string())
+                })
+            }
+
+            // Ternary conditional: condition ? true_expr : false_expr
+            Expression::TernaryOperation {
+                condition,
+                true_expr,
+                false_expr,
+            } => {
+                let condition_val = self.evaluate_expression(condition)?;
+                let condition_true = match condition_val {
+                    Value::Number(n) => n != 0.0, // 0.0 = false, non-zero = true
+                    _ => {
+                        return Err(GizmoError::TypeError(
+                            "ternary condition must be a number".to_string(),
+                        ))
+                    }
+                };
+
+                // Evaluate only the selected branch (short-circuit evaluation)
+                if condition_true {
+                    self.evaluate_expression(true_expr)
+                } else {
+                    self.evaluate_expression(false_expr)
+                }
+            }
+
+            // Layout kept for `gizmo fmt`; the value is the inner expression's
+            Expression::Grouping(inner) | Expression::LineBreak(inner) => self.evaluate_expression(inner),
+        }
+    }
+
+    /// Evaluates the `(width, height)` arguments of a pattern-style generator.
+    ///
+    /// # Arguments
+    /// * `width` - Width expression
+    /// * `height` - Height expression
+    /// * `construct` - Name of the generator, used in error messages
+    ///
+    /// # Returns
+    /// * `Ok((width, height))` - Dimensions in pixels
+    /// * `Err(GizmoError::TypeError)` - Either dimension is not a number
+    fn evaluate_dimensions(
+        &mut self,
+        width: &Expression,
+        height: &Expression,
+        construct: &str,
+    ) -> Result<(usize, usize)> {
+        let w = match self.evaluate_expression(width)? {
+            Value::Number(n) => n as usize,
+            _ => {
+                return Err(GizmoError::TypeError(format!(
+                    "{} width must be a number",
+                    construct
+                )))
+            }
+        };
+
+        let h = match self.evaluate_expression(height)? {
+            Value::Number(n) => n as usize,
+            _ => {
+                return Err(GizmoError::TypeError(format!(
+                    "{} height must be a number",
+                    construct
+                )))
+            }
+        };
+
+        self.budget.check_size(w, h, construct)?;
+        Ok((w, h))
+    }
+
+    /// Renders a pipeline's passes in order, each seeing the one before as
+    /// `previous`.
+    ///
+    /// # Returns
+    /// * `Ok(Some(Frame))` - The last pass's frame
+    /// * `Ok(None)` - There were no passes
+    /// * `Err(GizmoError)` - A pass failed
+    fn render_passes(&mut self, w: usize, h: usize, passes: &[Pass]) -> Result<Option<Frame>> {
+        let mut previous: Option<Frame> = None;
+        for pass in passes {
+            // Bind the prior result once per pass rather than per pixel
+            if let Some(frame) = previous.take() {
+                self.environment.define("previous", Value::Frame(frame));
+            }
+            previous = Some(self.render_pattern(w, h, &pass.body, &pass.return_expr)?);
+        }
+        Ok(previous)
+    }
+
+    /// Renders a single pattern body into a frame.
+    ///
+    /// Shared by `pattern` expressions and every pass of a `pipeline`.
+    ///
+    /// # Pattern Execution Model
+    /// For each pixel coordinate (col, row), execute the pattern body
+    /// and evaluate the return expression to determine if pixel is on/off.
+    fn render_pattern(
+        &mut self,
+        w: usize,
+        h: usize,
+        body: &[Statement],
+        return_expr: &Expression,
+    ) -> Result<Frame> {
+        // Run the body as bytecode when it compiles, walking the tree otherwise
+        if let Some(pattern) = CompiledPattern::compile(body, return_expr, &self.environment, &self.builtins) {
+            // As if the statements had run for every pixel
+            self.budget.charge((w * h) as u64 * body.len() as u64)?;
+            return pattern.render(w, h, &mut self.environment, &self.builtins, self.pattern_cache.as_mut());
+        }
+        self.walk_pattern(w, h, body, return_expr)
+    }
+
+    /// Renders a pattern by walking its syntax tree pixel by pixel, as bodies
+    /// that don't compile are rendered.
+    pub(crate) fn walk_pattern(
+        &mut self,
+        w: usize,
+        h: usize,
+        body: &[Statement],
+        return_expr: &Expression,
+    ) -> Result<Frame> {
+        // Initialize frame data matrix
+        let mut frame_data = vec![vec![0u8; w]; h];
+        let (row_name, col_name): (Name, Name) = ("row".into(), "col".into());
+
+        for (row, pixels) in frame_data.iter_mut().enumerate() {
+            for (col, pixel) in pixels.iter_mut().enumerate() {
+                // Set coordinate variables for current pixel
+                // These are available to all expressions in the pattern body
+                self.environment.define(row_name.clone(), Value::Number(row as f64));
+                self.environment.define(col_name.clone(), Value::Number(col as f64));
+
+                // Execute all setup statements in the pattern body
+                // These can declare variables, perform calculations, etc.
+                for stmt in body {
+                    self.execute_statement(stmt)?;
+                }
+
+                // Evaluate the return expression to get the pixel's palette index
+                *pixel = match self.evaluate_expression(return_expr)? {
+                    Value::Number(n) => (n != 0.0) as u8, // 0.0 = off, non-zero = on (index 1)
+                    Value::Color(index) => index,          // Explicit palette index from color(n)
+                    _ => {
+                        return Err(GizmoError::TypeError(
+                            "pattern expression must return a number or color".to_string(),
+                        ))
+                    }
+                };
+            }
+        }
+
+        Ok(Frame::new(frame_data))
+    }
+}
+
+/// Rejects assignments to variables only the runtime may set.
+///
+/// # Returns
+/// * `Ok(())` - The name is an ordinary variable
+/// * `Err(GizmoError::RuntimeError)` - The name is one of the system variables
+pub(crate) fn check_writable(name: &str) -> Result<()> {
+    if SYSTEM_VARIABLES.contains(&name) {
+        return Err(GizmoError::RuntimeError(format!("'{}' is read-only", name)));
+    }
+    Ok(())
+}
+
+/// Places an error at a statement's position, if the statement has one.
+fn locate(error: GizmoError, position: Option<Position>) -> GizmoError {
+    match position {
+        Some(position) => error.at(position),
+        None => error,
+    }
+}
+
+/// Applies a binary operator to two numbers.
+///
+/// Comparisons and logical operators return 1.0 for true and 0.0 for false.
+/// Shared by the interpreter and compiled patterns.
+///
+/// # Returns
+/// * `Ok(result)` - The operation's result
+/// * `Err(GizmoError::DivisionByZero)` - Dividing by zero
+#[inline]
+pub(crate) fn apply_binary_operator(operator: &BinaryOperator, l: f64, r: f64) -> Result<f64> {
+    let result = match operator {
+        // Arithmetic operations
+        BinaryOperator::Add => l + r,
+        BinaryOperator::Subtract => l - r,
+        BinaryOperator::Multiply => l * r,
+        BinaryOperator::Divide => {
+            if r == 0.0 {
+                return Err(GizmoError::DivisionByZero);
+            }
+            l / r
+        }
+        BinaryOperator::Modulo => l % r,
+
+        // Comparison operations (return 1.0 for true, 0.0 for false)
+        BinaryOperator::Greater => {
+            if l > r {
+                1.0
+            } else {
+                0.0
+            }
+        }
+        BinaryOperator::Less => {
+            if l < r {
+                1.0
+            } else {
+                0.0
+            }
+        }
+        BinaryOperator::GreaterEqual => {
+            if l >= r {
+                1.0
+            } else {
+                0.0
+            }
+        }
+        BinaryOperator::LessEqual => {
+            if l <= r {
+                1.0
+            } else {
+                0.0
+            }
+        }
+        BinaryOperator::Equal => {
+            if (l - r).abs() < f64::EPSILON {
+                1.0
+            } else {
+                0.0
+            }
+        }
+        BinaryOperator::NotEqual => {
+            if (l - r).abs() >= f64::EPSILON {
+                1.0
+            } else {
+                0.0
+            }
+        }
+
+        // Logical operations (using numeric true/false representation)
+        BinaryOperator::And => {
+            if l != 0.0 && r != 0.0 {
+                1.0
+            } else {
+                0.0
+            }
+        }
+        BinaryOperator::Or => {
+            if l != 0.0 || r != 0.0 {
+                1.0
+            } else {
+                0.0
+            }
+        }
+    };
+    Ok(result)
+}
@@ -10,7 +10,8 @@
 //! 1. **Character Processing**: Iterates through source code character by character
 //! 2. **Token Recognition**: Identifies keywords, operators, literals, and identifiers
 //! 3. **Error Handling**: Reports malformed tokens with line/column information
-//! 4. **Comment Filtering**: Strips single-line comments (`//`) from the token stream
+//! 4. **Comment Filtering**: Strips single-line comments (`//`) from the token stream,
+//!    or keeps them as `Comment` tokens for `gizmo fmt` ([`Lexer::keeping_comments`])
 //! 5. **Position Tracking**: Maintains accurate line and column numbers for debugging
 //!
 //! ## Supported Tokens
@@ -29,7 +30,9 @@
 //! reporting and is easy to understand and maintain.
 
 use crate::error::GizmoError;
-use std::fmt;
+
+/// A place in the source code: `(line, column)`, both starting at 1.
+pub type Position = (usize, usize);
 
 /// Represents all possible tokens in the Gizmo scripting language.
 ///
@@ -47,9 +50,10 @@ pub enum Token {
     /// Supports both integer and decimal notation.
     Number(f64),
     
-    /// String literal: `"hello world"` (currently unused but reserved)
+    /// String literal: `"hello world"`
     ///
-    /// Supports basic string literals for future language extensions.
+    /// Used for named options such as `transition("fade", 200)`. Strings are
+    /// single-line and have no escape sequences.
     String(String),
     
     /// Identifier: `my_var`, `frame_data`, `calculate_distance`
@@ -83,6 +87,10 @@ pub enum Token {
     Range,
     /// Pattern generator keyword: `pattern`
     Pattern,
+    /// Multi-pass generator keyword: `pipeline`
+    Pipeline,
+    /// Pipeline stage keyword: `pass`
+    Pass,
     /// Loop keyword: `repeat`
     Repeat,
     /// Loop count keyword: `times`
@@ -91,6 +99,8 @@ pub enum Token {
     Do,
     /// Block end keyword: `end`
     End,
+    /// Event handler keyword: `when`
+    When,
     /// Logical operator: `and`
     And,
     /// Logical operator: `or`
@@ -149,6 +159,12 @@ pub enum Token {
     // === SPECIAL TOKENS ===
     // Structural tokens for parsing control
     
+    /// Single-line comment: `// text`, holding the text after `//`
+    ///
+    /// Only produced by [`Lexer::keeping_comments`]; the parser turns these
+    /// into [`Trivia`](crate::ast::Trivia) for `gizmo fmt`.
+    Comment(String),
+    
     /// Newline character: `\n`
     ///
     /// Significant in Gizmo for statement separation and formatting.
@@ -177,6 +193,8 @@ pub struct Lexer {
     line: usize,
     /// Current column number (1-based for human-readable error messages)
     column: usize,
+    /// Whether comments become `Token::Comment` instead of being skipped
+    keep_comments: bool,
 }
 
 impl Lexer {
@@ -196,6 +214,21 @@ impl Lexer {
             position: 0,
             line: 1,
             column: 1,
+            keep_comments: false,
+        }
+    }
+    
+    /// Creates a lexer that keeps comments as `Token::Comment` tokens.
+    ///
+    /// Used by `gizmo fmt`, which has to write the comments back out; running
+    /// and checking scripts use [`new`](Self::new).
+    ///
+    /// # Arguments
+    /// * `input` - Source code string to tokenize
+    pub fn keeping_comments(input: &str) -> Self {
+        Self {
+            keep_comments: true,
+            ..Self::new(input)
         }
     }
     
@@ -213,18 +246,81 @@ impl Lexer {
     /// If tokenization fails at any point, the entire process stops and
     /// returns the error with precise location information.
     pub fn tokenize(&mut self) -> Result<Vec<Token>, GizmoError> {
+        Ok(self.tokenize_with_positions()?.0)
+    }
+    
+    /// Tokenizes the entire input, recording where each token starts.
+    ///
+    /// The positions go to [`Parser::with_positions`](crate::parser::Parser::with_positions),
+    /// so syntax and runtime errors can say where in the script they are.
+    ///
+    /// # Returns
+    /// * `Ok((tokens, positions))` - The token stream ending with `Token::Eof`,
+    ///   and the position of each token
+    /// * `Err(GizmoError)` - Lexical error, located at the text that caused it
+    pub fn tokenize_with_positions(&mut self) -> Result<(Vec<Token>, Vec<Position>), GizmoError> {
         let mut tokens = Vec::new();
+        let mut positions = Vec::new();
         
         loop {
-            let token = self.next_token()?;
-            if token == Token::Eof {
-                tokens.push(token);
+            let position = self.token_start();
+            let token = self.next_token().map_err(|e| e.at(position))?;
+            let done = token == Token::Eof;
+            tokens.push(token);
+            positions.push(position);
+            if done {
                 break;
             }
-            tokens.push(token);
         }
         
-        Ok(tokens)
+        Ok((tokens, positions))
+    }
+    
+    /// Tokenizes the entire input, carrying on after lexical errors.
+    ///
+    /// Unlike [`tokenize`](Self::tokenize), a bad character or literal is
+    /// reported and skipped, so `gizmo check` can show every problem in a
+    /// script at once. Also records where each token starts, for locating
+    /// the parser's errors.
+    ///
+    /// # Returns
+    /// * The token stream, ending with `Token::Eof`
+    /// * The position each token starts at (one per token)
+    /// * Every lexical error with the position of the text that caused it
+    pub fn tokenize_all(&mut self) -> (Vec<Token>, Vec<Position>, Vec<(Position, GizmoError)>) {
+        let mut tokens = Vec::new();
+        let mut positions = Vec::new();
+        let mut errors = Vec::new();
+        
+        loop {
+            let position = self.token_start();
+            
+            match self.next_token() {
+                Ok(token) => {
+                    let done = token == Token::Eof;
+                    tokens.push(token);
+                    positions.push(position);
+                    if done {
+                        break;
+                    }
+                }
+                Err(e) => errors.push((position, e)),
+            }
+        }
+        
+        (tokens, positions, errors)
+    }
+    
+    /// Moves past the spaces and skipped comments before the next token,
+    /// returning the position the token starts at.
+    fn token_start(&mut self) -> Position {
+        self.skip_whitespace();
+        while !self.keep_comments && self.peek() == '/' && self.peek_next() == '/' {
+            while self.peek() != '\n' && !self.is_at_end() {
+                self.advance();
+            }
+        }
+        (self.line, self.column)
     }
     
     /// Scans and returns the next token from the input stream.
@@ -276,11 +372,16 @@ impl Lexer {
             '/' => {
                 if self.peek() == '/' {
                     // Single-line comment: consume until end of line
-                    // Comments are stripped from the token stream entirely
+                    self.advance(); // consume second '/'
+                    let mut text = String::new();
                     while self.peek() != '\n' && !self.is_at_end() {
-                        self.advance();
+                        text.push(self.advance());
+                    }
+                    if self.keep_comments {
+                        return Ok(Token::Comment(text.trim_end().to_string()));
                     }
-                    // Recursively get the next token after the comment
+                    // Otherwise comments are stripped from the token stream entirely:
+                    // recursively get the next token after the comment
                     self.next_token()
                 } else {
                     // Division operator
@@ -312,12 +413,10 @@ impl Lexer {
                     Ok(Token::Less)
                 }
             }
+            '"' => self.string_literal(),
             c if c.is_ascii_digit() => self.number_literal(c),
             c if c.is_ascii_alphabetic() || c == '_' => self.identifier_or_keyword(c),
-            _ => Err(GizmoError::LexError(format!(
-                "Unexpected character '{}' at line {}, column {}",
-                c, self.line, self.column
-            ))),
+            _ => Err(GizmoError::LexError(format!("Unexpected character '{}'", c))),
         }
     }
     
@@ -361,11 +460,29 @@ impl Lexer {
         // Parse the collected string as a floating-point number
         match value.parse::<f64>() {
             Ok(num) => Ok(Token::Number(num)),
-            Err(_) => Err(GizmoError::LexError(format!(
-                "Invalid number '{}' at line {}, column {}",
-                value, self.line, self.column
-            ))),
+            Err(_) => Err(GizmoError::LexError(format!("Invalid number '{}'", value))),
+        }
+    }
+    
+    /// Scans a string literal after its opening quote.
+    ///
+    /// Collects characters up to the closing `"`. Strings may not span lines.
+    ///
+    /// # Returns
+    /// * `Ok(Token::String)` - The literal's contents without quotes
+    /// * `Err(GizmoError)` - The string was not closed before the end of the line
+    fn string_literal(&mut self) -> Result<Token, GizmoError> {
+        let mut value = String::new();
+        
+        while self.peek() != '"' {
+            if self.is_at_end() || self.peek() == '\n' {
+                return Err(GizmoError::LexError("Unterminated string".to_string()));
+            }
+            value.push(self.advance());
         }
+        
+        self.advance(); // consume closing '"'
+        Ok(Token::String(value))
     }
     
     /// Scans an identifier or keyword starting with the given character.
@@ -389,7 +506,7 @@ impl Lexer {
     /// The lexer recognizes these reserved words:
     /// - Types: `frame`, `frames`
     /// - Control: `if`, `then`, `else`, `repeat`, `times`, `do`, `end`
-    /// - Functions: `function`, `return`, `pattern`
+    /// - Functions: `function`, `return`, `pattern`, `pipeline`, `pass`
     /// - Logic: `and`, `or`
     /// - Reserved: `for`, `in`, `range` (for future use)
     fn identifier_or_keyword(&mut self, first_char: char) -> Result<Token, GizmoError> {
@@ -410,6 +527,8 @@ impl Lexer {
             "function" => Token::Function,
             "return" => Token::Return,
             "pattern" => Token::Pattern,
+            "pipeline" => Token::Pipeline,
+            "pass" => Token::Pass,
             
             // Control flow keywords
             "if" => Token::If,
@@ -419,6 +538,7 @@ impl Lexer {
             "times" => Token::Times,
             "do" => Token::Do,
             "end" => Token::End,
+            "when" => Token::When,
             
             // Logical operators
             "and" => Token::And,
@@ -0,0 +1,183 @@
+//! Gizmo Core - the .gzmo Animation Language
+//!
+//! The lexer, parser and interpreter behind the Gizmo desktop buddy, without
+//! its window, so other programs (games, LED matrix drivers, ...) can run
+//! .gzmo scripts and show the frames themselves:
+//!
+//! ```
+//! let animation = gizmo_core::compile("
+//!     frames blink = [];
+//!     repeat 2 times do
+//!         add_frame(blink, pattern(8, 8) { return (col + row + time) % 2; });
+//!     end;
+//!     loop_speed(blink, 250);
+//! ")?;
+//!
+//! assert_eq!(animation.frames().len(), 2);
+//! assert_eq!(animation.frame_duration_ms(), 250);
+//! for frame in animation.frames() {
+//!     for row in frame.pixels.iter() {
+//!         // Palette indices: 0 is off, 1 is on, 2+ come from palette()
+//!         let argb: Vec<u32> = row.iter().map(|&index| animation.palette().color(index)).collect();
+//!     }
+//! }
+//! # Ok::<(), gizmo_core::GizmoError>(())
+//! ```
+//!
+//! ## Modules
+//!
+//! [`compile`] covers running a script once, and [`parse`] checking one for
+//! syntax errors. For more, use the stages directly:
+//! - **lexer**: Tokenizes .gzmo script files into lexical tokens
+//! - **parser**: Parses tokens into an Abstract Syntax Tree using operator precedence
+//! - **ast**: Defines the data structures for the language's syntax tree
+//! - **interpreter**: Executes the AST and generates animation frames; also runs
+//!   the script's event handlers (`when clicked`, `every 1000`, ...) when the
+//!   embedding program reports the events
+//! - **pattern**: Compiles pattern bodies to bytecode before they render
+//! - **cache**: Keeps the frames of compiled patterns for later runs
+//! - **dedup**: Stores frames that repeat in an animation once
+//! - **lint**: Warns about mistakes that don't stop a script, such as
+//!   variables that are never used
+//! - **resolve**: Finds the errors a script would run into, such as undefined
+//!   variables or wrong arguments, without running it
+//! - **limits**: Stops scripts that run too long or make too many or too
+//!   large frames
+//! - **builtin**: Implements built-in mathematical and animation functions
+//! - **frame**: Handles frame rendering utilities
+//! - **error**: Provides comprehensive error handling across all modules
+//!
+//! The other modules hold the state scripts can control: animation timing
+//! (`clock`), transitions, colors (`palette`), window movement (`motion`)
+//! and `persist` variables.
+
+pub mod ast;
+pub mod builtin;
+pub mod cache;
+pub mod clock;
+pub mod dedup;
+pub mod error;
+pub mod frame;
+pub mod interpreter;
+pub mod lexer;
+pub mod limits;
+pub mod lint;
+pub mod motion;
+pub mod palette;
+pub mod parser;
+pub mod pattern;
+pub mod persist;
+pub mod placement;
+pub mod resolve;
+pub mod schedule;
+pub mod sprite;
+pub mod transition;
+#[cfg(feature = "sysinfo")]
+pub mod stats;
+#[cfg(feature = "mic")]
+pub mod mic;
+
+pub use ast::Frame;
+pub use error::{GizmoDiagnostic, GizmoError, Result};
+pub use interpreter::Interpreter;
+pub use limits::Limits;
+pub use palette::Palette;
+
+use ast::Program;
+use lexer::Lexer;
+use parser::Parser;
+use std::path::Path;
+
+/// A script's animation: the frames it plays once it has run, and how.
+pub struct Animation {
+    /// Frames in playing order
+    frames: Vec<Frame>,
+    /// The interpreter the script ran in
+    interpreter: Interpreter,
+}
+
+impl Animation {
+    /// Returns the frames the script plays, in order.
+    ///
+    /// Empty if the script never calls `loop_speed()`, `play()` or `loop()`.
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+
+    /// Returns how long each frame is shown, in milliseconds (100 unless the
+    /// script sets it with `loop_speed()`).
+    pub fn frame_duration_ms(&self) -> u64 {
+        self.interpreter.get_frame_duration_ms()
+    }
+
+    /// Returns the colors the frames' palette indices stand for.
+    pub fn palette(&self) -> &Palette {
+        self.interpreter.palette()
+    }
+
+    /// Returns the interpreter the script ran in, for running its event
+    /// handlers; the frames it switches to are then in
+    /// [`Interpreter::get_animation_frames`].
+    pub fn interpreter_mut(&mut self) -> &mut Interpreter {
+        &mut self.interpreter
+    }
+
+    /// Gives up the animation for the interpreter the script ran in.
+    pub fn into_interpreter(self) -> Interpreter {
+        self.interpreter
+    }
+}
+
+/// Compiles and runs a script, returning its animation.
+///
+/// Files the script loads with `load_image()` and `load_aseprite()` are
+/// looked for in the working directory; see [`compile_in`].
+///
+/// # Arguments
+/// * `source` - Contents of the .gzmo file
+///
+/// # Returns
+/// * `Ok(Animation)` - The script ran
+/// * `Err(GizmoError)` - The script has a syntax error or failed while
+///   running; [`GizmoError::report`] shows where
+pub fn compile(source: &str) -> Result<Animation> {
+    run(source, Interpreter::new())
+}
+
+/// Compiles and runs a script whose loaded files are relative to `script_dir`.
+///
+/// # Arguments
+/// * `source` - Contents of the .gzmo file
+/// * `script_dir` - Directory of the .gzmo file, where the files it loads are
+pub fn compile_in(source: &str, script_dir: &Path) -> Result<Animation> {
+    let mut interpreter = Interpreter::new();
+    interpreter.set_script_dir(script_dir);
+    run(source, interpreter)
+}
+
+/// Parses a script into its syntax tree without running it.
+///
+/// Statements record where they start, so running the program reports
+/// runtime errors at the statement that failed.
+///
+/// # Arguments
+/// * `source` - Contents of the .gzmo file
+///
+/// # Returns
+/// * `Ok(Program)` - The script's statements
+/// * `Err(GizmoError)` - The script has a syntax error, located at the token
+///   it was found at
+pub fn parse(source: &str) -> Result<Program> {
+    let (tokens, positions) = Lexer::new(source).tokenize_with_positions()?;
+    Parser::with_positions(tokens, positions).parse()
+}
+
+/// Runs a script in a prepared interpreter.
+fn run(source: &str, mut interpreter: Interpreter) -> Result<Animation> {
+    let program = parse(source)?;
+    interpreter.execute(&program)?;
+    Ok(Animation {
+        frames: interpreter.get_animation_frames(),
+        interpreter,
+    })
+}
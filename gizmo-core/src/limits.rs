@@ -0,0 +1,197 @@
+//! Execution Limits
+//!
+//! A script like `repeat 100000000 times do ... end` would keep the
+//! interpreter busy for hours, and `pattern(100000, 100000)` would try to
+//! allocate ten gigabytes. Scripts therefore run under limits, and going over
+//! one stops the script with a runtime error saying which limit it hit:
+//!
+//! - **Statements**: how many statements (and `repeat` iterations) a run may
+//!   execute
+//! - **Timeout**: how long a run may take
+//! - **Frames**: how many frames a frames array may hold
+//! - **Frame size**: how wide or tall a frame may be
+//!
+//! A run is one [`Interpreter::execute`](crate::Interpreter::execute), one
+//! line typed at the REPL or the handlers of one event. Every limit can be
+//! turned off by setting it to `None`.
+
+use crate::ast::Value;
+use crate::error::{GizmoError, Result};
+use std::time::{Duration, Instant};
+
+/// Statements between clock reads when a timeout is set
+const CLOCK_INTERVAL: u64 = 1024;
+
+/// Bounds on what a script may do in one run.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Limits {
+    /// Statements and `repeat` iterations per run
+    pub max_statements: Option<u64>,
+    /// Wall-clock time per run
+    pub timeout: Option<Duration>,
+    /// Frames in one frames array
+    pub max_frames: Option<usize>,
+    /// Width and height of a frame, in pixels
+    pub max_frame_size: Option<usize>,
+}
+
+impl Default for Limits {
+    /// Generous enough for any animation that fits on a desktop.
+    ///
+    /// The browser build has no clock to time runs with, so it has no timeout.
+    fn default() -> Self {
+        Self {
+            max_statements: Some(1_000_000_000),
+            timeout: if cfg!(all(target_arch = "wasm32", target_os = "unknown")) {
+                None
+            } else {
+                Some(Duration::from_secs(30))
+            },
+            max_frames: Some(10_000),
+            max_frame_size: Some(4096),
+        }
+    }
+}
+
+impl Limits {
+    /// No limits at all.
+    pub fn unlimited() -> Self {
+        Self {
+            max_statements: None,
+            timeout: None,
+            max_frames: None,
+            max_frame_size: None,
+        }
+    }
+}
+
+/// A run's progress towards its limits.
+#[derive(Debug, Clone)]
+pub(crate) struct Budget {
+    limits: Limits,
+    /// Statements executed in this run
+    statements: u64,
+    /// When the run times out
+    deadline: Option<Instant>,
+}
+
+impl Budget {
+    pub(crate) fn new(limits: Limits) -> Self {
+        Self {
+            limits,
+            statements: 0,
+            deadline: None,
+        }
+    }
+
+    pub(crate) fn limits(&self) -> Limits {
+        self.limits
+    }
+
+    /// Begins a run, with its statements counted from zero.
+    pub(crate) fn start(&mut self) {
+        self.statements = 0;
+        self.deadline = self.limits.timeout.map(|timeout| Instant::now() + timeout);
+    }
+
+    /// Counts a statement or loop iteration.
+    ///
+    /// # Returns
+    /// * `Ok(())` - The run may go on
+    /// * `Err(GizmoError::RuntimeError)` - The run used up its statements or time
+    #[inline]
+    pub(crate) fn step(&mut self) -> Result<()> {
+        self.charge(1)
+    }
+
+    /// Counts statements run all at once, such as a compiled pattern's.
+    ///
+    /// # Returns
+    /// * `Ok(())` - The run may go on
+    /// * `Err(GizmoError::RuntimeError)` - The run used up its statements or time
+    #[inline]
+    pub(crate) fn charge(&mut self, statements: u64) -> Result<()> {
+        let before = self.statements;
+        self.statements = self.statements.saturating_add(statements);
+        if let Some(max) = self.limits.max_statements {
+            if self.statements > max {
+                return Err(GizmoError::RuntimeError(format!(
+                    "script stopped after {} statements (is a repeat count too large?)",
+                    max
+                )));
+            }
+        }
+        if let Some(deadline) = self.deadline {
+            if before / CLOCK_INTERVAL != self.statements / CLOCK_INTERVAL && Instant::now() > deadline {
+                let seconds = self.limits.timeout.unwrap_or_default().as_secs_f64();
+                return Err(GizmoError::RuntimeError(format!(
+                    "script stopped after running for {} seconds",
+                    seconds
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks the length of a frames array.
+    ///
+    /// # Arguments
+    /// * `array` - The array, for the error message, e.g. `'walk'`
+    /// * `count` - How many frames it would hold
+    ///
+    /// # Returns
+    /// * `Ok(())` - The array may hold `count` frames
+    /// * `Err(GizmoError::RuntimeError)` - It would hold too many
+    pub(crate) fn check_frames(&self, array: &str, count: usize) -> Result<()> {
+        match self.limits.max_frames {
+            Some(max) if count > max => Err(GizmoError::RuntimeError(format!(
+                "{} would hold more than {} frames",
+                array, max
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks the size of a frame before it is made.
+    ///
+    /// # Arguments
+    /// * `w` - Width in pixels
+    /// * `h` - Height in pixels
+    /// * `construct` - What makes the frame, for the error message
+    ///
+    /// # Returns
+    /// * `Ok(())` - The frame may be that size
+    /// * `Err(GizmoError::RuntimeError)` - It would be too wide or tall
+    pub(crate) fn check_size(&self, w: usize, h: usize, construct: &str) -> Result<()> {
+        match self.limits.max_frame_size {
+            Some(max) if w > max || h > max => Err(GizmoError::RuntimeError(format!(
+                "{} size {}x{} is over the {}x{} limit",
+                construct, w, h, max, max
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks a frame or frames array that was made all at once, such as
+    /// a loaded image, against both the size and frames limits.
+    ///
+    /// # Arguments
+    /// * `value` - What was made; values that aren't frames always pass
+    /// * `construct` - What made it, for the error message
+    ///
+    /// # Returns
+    /// * `Ok(())` - The value is within the limits
+    /// * `Err(GizmoError::RuntimeError)` - It has too many frames, or one is too wide or tall
+    pub(crate) fn check_value(&self, value: &Value, construct: &str) -> Result<()> {
+        match value {
+            Value::Frame(frame) => self.check_size(frame.width, frame.height, construct),
+            Value::Frames(frames) => {
+                self.check_frames(construct, frames.len())?;
+                frames
+                    .iter()
+                    .try_for_each(|frame| self.check_size(frame.width, frame.height, construct))
+            }
+            _ => Ok(()),
+        }
+    }
+}
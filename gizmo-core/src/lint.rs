@@ -0,0 +1,224 @@
+//! Script Warnings
+//!
+//! Some mistakes don't make a script fail; it runs and quietly does
+//! something else. [`warnings`] walks a parsed program, without running it,
+//! and reports the common ones as [`GizmoDiagnostic`]s:
+//!
+//! | Warning              | Example                                               |
+//! |----------------------|-------------------------------------------------------|
+//! | Unused variable      | `speed = 3;` with `speed` never read                  |
+//! | Frames never played  | `frames walk = [];` only ever given to `add_frame()`  |
+//! | Shadowed coordinate  | `row = row + 1;` inside a pattern body                |
+//! | Constant condition   | `if 1 then`, `2 > 3 ? a : b`                          |
+//!
+//! Variables are global, so a variable counts as used if it is read
+//! anywhere: in an event handler, a pattern body, or before it is assigned.
+//! Names starting with `_` are never reported as unused, for variables kept
+//! on purpose. `persist` variables aren't either, as the value they keep is
+//! the point of them.
+
+use crate::ast::{Event, Expression, Name, Program, Statement, StatementKind, VariableType};
+use crate::error::GizmoDiagnostic;
+use crate::interpreter::apply_binary_operator;
+use crate::lexer::Position;
+use std::collections::HashSet;
+
+/// Finds the warnings in a program.
+///
+/// # Arguments
+/// * `program` - The parsed script, ideally with positions
+///   ([`parse`](crate::parse)) so warnings say where they are
+///
+/// # Returns
+/// Every warning, in source order where positions are known
+pub fn warnings(program: &Program) -> Vec<GizmoDiagnostic> {
+    let mut lint = Lint {
+        assigned: Vec::new(),
+        read: HashSet::new(),
+        warnings: Vec::new(),
+        position: None,
+        pattern_depth: 0,
+    };
+    lint.block(&program.statements);
+
+    let Lint { assigned, read, mut warnings, .. } = lint;
+    for (name, position, frames) in assigned {
+        if read.contains(&name) || name.starts_with('_') {
+            continue;
+        }
+        let message = if frames {
+            format!("Frames '{}' are never played; pass them to play(), loop() or loop_speed()", name)
+        } else {
+            format!("Variable '{}' is never used", name)
+        };
+        warnings.push(GizmoDiagnostic { position, message });
+    }
+    warnings.sort_by_key(|warning| warning.position);
+    warnings
+}
+
+/// What the walk through a program has found so far.
+struct Lint {
+    /// Each variable assigned, where it first was and whether it was
+    /// declared with `frames`
+    assigned: Vec<(Name, Option<Position>, bool)>,
+    /// Every name read, except as the array `add_frame()` appends to
+    read: HashSet<Name>,
+    /// Warnings found during the walk itself
+    warnings: Vec<GizmoDiagnostic>,
+    /// Position of the statement being walked
+    position: Option<Position>,
+    /// How many pattern bodies the walk is inside
+    pattern_depth: usize,
+}
+
+impl Lint {
+    fn warn(&mut self, message: String) {
+        self.warnings.push(GizmoDiagnostic { position: self.position, message });
+    }
+
+    fn block(&mut self, body: &[Statement]) {
+        for statement in body {
+            self.statement(statement);
+        }
+    }
+
+    fn statement(&mut self, statement: &Statement) {
+        self.position = statement.position;
+        match &statement.kind {
+            StatementKind::VariableDeclaration { var_type, name, value } => {
+                self.expression(value);
+                self.assign(name, *var_type == VariableType::Frames);
+            }
+            StatementKind::Assignment { name, value } => {
+                self.expression(value);
+                self.assign(name, false);
+            }
+            StatementKind::PersistDeclaration { value, .. } => self.expression(value),
+            StatementKind::ExpressionStatement(expression) => self.expression(expression),
+            StatementKind::RepeatLoop { count, body } => {
+                self.expression(count);
+                self.block(body);
+            }
+            StatementKind::IfStatement { condition, then_body, else_body } => {
+                self.condition(condition, "if");
+                self.expression(condition);
+                self.block(then_body);
+                if let Some(else_body) = else_body {
+                    self.block(else_body);
+                }
+            }
+            StatementKind::WhenStatement { event, body, priority, .. } => {
+                match event {
+                    Event::ClickCombo { times, within } => {
+                        self.expression(times);
+                        self.expression(within);
+                    }
+                    Event::Key(expression)
+                    | Event::Time(expression)
+                    | Event::Message(expression)
+                    | Event::Idle(expression)
+                    | Event::Every(expression)
+                    | Event::Battery(expression) => self.expression(expression),
+                    _ => {}
+                }
+                if let Some(priority) = priority {
+                    self.expression(priority);
+                }
+                self.block(body);
+            }
+            StatementKind::Trivia(_) => {}
+        }
+    }
+
+    /// Records an assignment, warning about pixel coordinates in patterns.
+    fn assign(&mut self, name: &Name, frames: bool) {
+        if self.pattern_depth > 0 && matches!(&**name, "col" | "row") {
+            self.warn(format!(
+                "Assigning '{}' inside a pattern changes it for the rest of the body, not which pixel is drawn",
+                name
+            ));
+        }
+        if !self.assigned.iter().any(|(assigned, ..)| assigned == name) {
+            self.assigned.push((name.clone(), self.position, frames));
+        }
+    }
+
+    /// Warns if a condition has the same value every time.
+    fn condition(&mut self, condition: &Expression, construct: &str) {
+        if let Some(value) = constant(condition) {
+            let always = if value != 0.0 { "true" } else { "false" };
+            self.warn(format!("This {} condition is always {}", construct, always));
+        }
+    }
+
+    fn expression(&mut self, expression: &Expression) {
+        match expression {
+            Expression::Number(_) | Expression::String(_) => {}
+            Expression::Identifier(name) => {
+                self.read.insert(name.clone());
+            }
+            Expression::Array(items) => {
+                for item in items {
+                    self.expression(item);
+                }
+            }
+            Expression::FunctionCall { name, args } => {
+                // Appending to frames isn't using them
+                let skip = match (&**name, args.as_slice()) {
+                    ("add_frame", [Expression::Identifier(_), _]) => 1,
+                    _ => 0,
+                };
+                for arg in &args[skip..] {
+                    self.expression(arg);
+                }
+            }
+            Expression::NamedArgument { value, .. } => self.expression(value),
+            Expression::BinaryOperation { left, right, .. } => {
+                self.expression(left);
+                self.expression(right);
+            }
+            Expression::PatternGenerator { width, height, body, return_expr } => {
+                self.expression(width);
+                self.expression(height);
+                self.pattern(body, return_expr);
+            }
+            Expression::TernaryOperation { condition, true_expr, false_expr } => {
+                self.condition(condition, "?:");
+                self.expression(condition);
+                self.expression(true_expr);
+                self.expression(false_expr);
+            }
+            Expression::Pipeline { width, height, passes } => {
+                self.expression(width);
+                self.expression(height);
+                for pass in passes {
+                    self.pattern(&pass.body, &pass.return_expr);
+                }
+            }
+            Expression::Grouping(inner) | Expression::LineBreak(inner) => self.expression(inner),
+        }
+    }
+
+    /// Walks a pattern body, whose statements belong to the enclosing one.
+    fn pattern(&mut self, body: &[Statement], return_expr: &Expression) {
+        let position = self.position;
+        self.pattern_depth += 1;
+        self.block(body);
+        self.position = position;
+        self.expression(return_expr);
+        self.pattern_depth -= 1;
+    }
+}
+
+/// Returns the value of an expression made only of numbers, if it has one.
+fn constant(expression: &Expression) -> Option<f64> {
+    match expression {
+        Expression::Number(n) => Some(*n),
+        Expression::BinaryOperation { left, operator, right } => {
+            apply_binary_operator(operator, constant(left)?, constant(right)?).ok()
+        }
+        Expression::Grouping(inner) | Expression::LineBreak(inner) => constant(inner),
+        _ => None,
+    }
+}
@@ -0,0 +1,166 @@
+//! Microphone Level (`mic` feature)
+//!
+//! Listens to the default input device so scripts can react to sound - a
+//! buddy that bounces to music or perks up when you talk. Scripts read the
+//! loudness as the read-only `mic_level` variable, from 0 (silence) to 100
+//! (full scale), refreshed before every event handler.
+//!
+//! The microphone is only opened when the user asks for it with
+//! `gizmo start --mic`; without the flag `mic_level` stays 0. macOS asks for
+//! microphone permission the first time, and if it is denied the level
+//! simply stays 0 as well.
+//!
+//! ## Sampling
+//!
+//! A background thread owns the input stream. The audio backend hands it
+//! short buffers; each buffer's RMS level is mapped from -60 dBFS..0 dBFS to
+//! 0..100 and smoothed with a fast attack and a slow release, so the value
+//! jumps up with a beat or a word and then settles instead of flickering.
+
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{FromSample, SampleFormat, SizedSample, Stream, StreamConfig};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+
+/// Quietest level that still registers, in dBFS
+const FLOOR_DB: f32 = -60.0;
+
+/// Time constant for a rising level, in seconds
+const ATTACK: f32 = 0.05;
+
+/// Time constant for a falling level, in seconds
+const RELEASE: f32 = 0.4;
+
+/// Shared handle to the latest smoothed level.
+#[derive(Debug, Clone)]
+pub struct MicLevel(Arc<AtomicU32>);
+
+impl MicLevel {
+    /// Returns the current level, 0 to 100.
+    pub fn get(&self) -> f64 {
+        f32::from_bits(self.0.load(Ordering::Relaxed)) as f64
+    }
+}
+
+/// A running microphone stream; it is closed when this is dropped.
+pub struct MicMonitor {
+    /// Level written by the audio callback
+    level: MicLevel,
+    /// Dropping this wakes the background thread so it closes the stream
+    _stop: mpsc::Sender<()>,
+}
+
+impl MicMonitor {
+    /// Opens the default input device on a background thread.
+    ///
+    /// # Returns
+    /// * `Ok(MicMonitor)` - The stream is running
+    /// * `Err` - There is no input device or it couldn't be opened
+    pub fn start() -> Result<Self, Box<dyn std::error::Error>> {
+        let level = MicLevel(Arc::new(AtomicU32::new(0.0f32.to_bits())));
+        let (stop_sender, stop_receiver) = mpsc::channel::<()>();
+        let (ready_sender, ready_receiver) = mpsc::channel::<Result<(), String>>();
+
+        // Streams can't move between threads on every platform, so the
+        // stream lives and dies on the thread that opened it
+        let shared = level.0.clone();
+        thread::Builder::new().name("gizmo-mic".to_string()).spawn(move || {
+            let stream = match open_stream(shared) {
+                Ok(stream) => stream,
+                Err(e) => {
+                    let _ = ready_sender.send(Err(e));
+                    return;
+                }
+            };
+            let _ = ready_sender.send(Ok(()));
+            // Blocks until the monitor is dropped
+            let _ = stop_receiver.recv();
+            drop(stream);
+        })?;
+
+        ready_receiver
+            .recv()
+            .map_err(|_| "Microphone thread exited unexpectedly".to_string())??;
+        Ok(Self {
+            level,
+            _stop: stop_sender,
+        })
+    }
+
+    /// Returns a handle the interpreter reads `mic_level` from.
+    pub fn level(&self) -> MicLevel {
+        self.level.clone()
+    }
+}
+
+/// Opens and starts the default input stream, writing levels to `level`.
+fn open_stream(level: Arc<AtomicU32>) -> Result<Stream, String> {
+    let device = cpal::default_host()
+        .default_input_device()
+        .ok_or("No microphone found")?;
+    let supported = device.default_input_config().map_err(|e| e.to_string())?;
+    let config = supported.config();
+
+    let stream = match supported.sample_format() {
+        SampleFormat::I8 => build_stream::<i8>(&device, &config, level),
+        SampleFormat::I16 => build_stream::<i16>(&device, &config, level),
+        SampleFormat::I32 => build_stream::<i32>(&device, &config, level),
+        SampleFormat::U8 => build_stream::<u8>(&device, &config, level),
+        SampleFormat::U16 => build_stream::<u16>(&device, &config, level),
+        SampleFormat::U32 => build_stream::<u32>(&device, &config, level),
+        SampleFormat::F32 => build_stream::<f32>(&device, &config, level),
+        SampleFormat::F64 => build_stream::<f64>(&device, &config, level),
+        other => return Err(format!("Unsupported microphone sample format {:?}", other)),
+    }?;
+    stream.play().map_err(|e| e.to_string())?;
+    Ok(stream)
+}
+
+/// Builds an input stream for one sample type.
+fn build_stream<T>(device: &cpal::Device, config: &StreamConfig, level: Arc<AtomicU32>) -> Result<Stream, String>
+where
+    T: SizedSample,
+    f32: FromSample<T>,
+{
+    let channels = config.channels.max(1) as usize;
+    let sample_rate = config.sample_rate.0.max(1) as f32;
+    let mut smoothed = 0.0f32;
+
+    device
+        .build_input_stream(
+            config,
+            move |data: &[T], _: &cpal::InputCallbackInfo| {
+                if data.is_empty() {
+                    return;
+                }
+                let sum: f32 = data
+                    .iter()
+                    .map(|&sample| {
+                        let value: f32 = sample.to_sample();
+                        value * value
+                    })
+                    .sum();
+                let rms = (sum / data.len() as f32).sqrt();
+                let target = loudness(rms);
+
+                // Exponential smoothing over the buffer's duration
+                let seconds = (data.len() / channels) as f32 / sample_rate;
+                let tau = if target > smoothed { ATTACK } else { RELEASE };
+                smoothed += (target - smoothed) * (1.0 - (-seconds / tau).exp());
+                level.store(smoothed.to_bits(), Ordering::Relaxed);
+            },
+            |e| eprintln!("Microphone error: {}", e),
+            None,
+        )
+        .map_err(|e| e.to_string())
+}
+
+/// Maps an RMS amplitude (0.0 to 1.0) to a 0..100 loudness.
+fn loudness(rms: f32) -> f32 {
+    if rms <= 0.0 {
+        return 0.0;
+    }
+    let db = 20.0 * rms.log10();
+    ((db - FLOOR_DB) / -FLOOR_DB).clamp(0.0, 1.0) * 100.0
+}
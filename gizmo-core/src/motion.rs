@@ -0,0 +1,315 @@
+//! Script-Driven Window Motion
+//!
+//! Scripts can make the buddy move around the desktop with `move_to()`,
+//! `wander()`, `fall()` and `bounce()`. Like `loop_speed()`, these calls don't act immediately: the
+//! interpreter records them as [`MotionCommand`]s and the window loop plays
+//! them back by nudging the window a little on every update.
+//!
+//! ## Command Queue
+//!
+//! ```text
+//! move_to(100, 800, 2000);   →  glide to (100, 800) over 2 seconds
+//! move_to(900, 800, 4000);   →  then glide to (900, 800) over 4 seconds
+//! wander(60);                →  then stroll left and right at 60 px/s forever
+//! ```
+//!
+//! Commands run in order. `wander()` never finishes, so anything queued after
+//! it is ignored. While wandering the buddy keeps its current height and picks
+//! random spots along its monitor, pausing briefly between walks - drag it
+//! onto the taskbar and it walks along the taskbar.
+//!
+//! ## Gravity
+//!
+//! `fall()` drops the buddy straight down under [`GRAVITY`] until it lands on
+//! the ground: the bottom of its monitor's work area, i.e. on top of the
+//! taskbar or dock. `bounce()` falls the same way but bounces back up a few
+//! times, losing height with every bounce. A buddy already on (or below) the
+//! ground stays put, so `when dropped do fall(); end;` makes it land
+//! wherever it is let go.
+//!
+//! ## Dragging
+//!
+//! Dragging the window interrupts the current move. Once dropped, the
+//! interrupted command starts over from the new position.
+
+use crate::placement::{self, Bounds};
+use rand::Rng;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// How often the window loop should update the position while moving (~60 Hz).
+pub const UPDATE_INTERVAL: Duration = Duration::from_millis(16);
+
+/// Shortest pause between wander walks, in milliseconds.
+const WANDER_PAUSE_MIN_MS: u64 = 500;
+/// Longest pause between wander walks, in milliseconds.
+const WANDER_PAUSE_MAX_MS: u64 = 3000;
+
+/// Slowest `wander()` speed, in pixels per second.
+pub const MIN_WANDER_SPEED: f64 = 1.0;
+/// Longest a single wander walk may take, however slow or far.
+const MAX_WANDER_LEG: Duration = Duration::from_secs(3600);
+
+/// Downward acceleration for `fall()` / `bounce()`, in pixels per second squared.
+pub const GRAVITY: f64 = 2500.0;
+/// Share of its speed a bouncing buddy keeps when it hits the ground.
+const BOUNCINESS: f64 = 0.5;
+/// Landing speed (pixels per second) below which a bounce ends.
+const MIN_BOUNCE_SPEED: f64 = 150.0;
+
+/// A window motion requested by a script.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MotionCommand {
+    /// Glide the window's top-left corner to (x, y) over `duration_ms`
+    MoveTo { x: i32, y: i32, duration_ms: u64 },
+    /// Walk to random spots on the current monitor at `speed` pixels per second
+    Wander { speed: f64 },
+    /// Drop onto the work area's bottom edge, bouncing if `bounce` is set
+    Fall { bounce: bool },
+}
+
+/// A single glide currently in progress.
+#[derive(Debug, Clone)]
+struct ActiveMove {
+    from: (f64, f64),
+    to: (f64, f64),
+    started: Instant,
+    duration: Duration,
+    /// The command that started this move, re-queued if a drag interrupts it
+    command: Option<MotionCommand>,
+}
+
+/// A fall in progress.
+#[derive(Debug, Clone)]
+struct ActiveFall {
+    /// Window top edge, in fractional pixels
+    y: f64,
+    /// Downward speed in pixels per second (negative while bouncing up)
+    velocity: f64,
+    /// Window top edge when resting on the ground
+    ground: f64,
+    /// Bounce off the ground instead of stopping there
+    bounce: bool,
+    /// Time of the previous step
+    last_step: Instant,
+}
+
+/// Plays back a script's motion commands against the real window position.
+#[derive(Debug, Default)]
+pub struct Motion {
+    /// Commands not started yet
+    pending: VecDeque<MotionCommand>,
+    /// Glide in progress, if any
+    active: Option<ActiveMove>,
+    /// Fall in progress, if any
+    falling: Option<ActiveFall>,
+    /// Walking speed once `wander()` has been reached
+    wander_speed: Option<f64>,
+    /// End of the rest between two wander walks
+    resting_until: Option<Instant>,
+}
+
+impl Motion {
+    /// Creates a player for the given commands, in script order.
+    pub fn new(commands: Vec<MotionCommand>) -> Self {
+        Self {
+            pending: commands.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Appends commands queued later, e.g. by an event handler.
+    pub fn queue(&mut self, commands: Vec<MotionCommand>) {
+        self.pending.extend(commands);
+    }
+
+    /// Returns `true` when there is nothing left to move.
+    pub fn is_idle(&self) -> bool {
+        self.active.is_none() && self.falling.is_none() && self.pending.is_empty() && self.wander_speed.is_none()
+    }
+
+    /// Stops the current glide because the user grabbed the window.
+    ///
+    /// A `move_to()` or fall in progress is put back at the front of the
+    /// queue so it restarts from wherever the window is dropped.
+    pub fn interrupt(&mut self) {
+        if let Some(ActiveMove { command: Some(command), .. }) = self.active.take() {
+            self.pending.push_front(command);
+        }
+        if let Some(fall) = self.falling.take() {
+            self.pending.push_front(MotionCommand::Fall { bounce: fall.bounce });
+        }
+        self.resting_until = None;
+    }
+
+    /// Advances motion and returns where the window should be now.
+    ///
+    /// # Arguments
+    /// * `now` - Current time
+    /// * `position` - Window's current top-left corner in physical pixels
+    /// * `size` - Window's outer size in physical pixels
+    /// * `monitors` - Bounds of every connected monitor
+    /// * `work_areas` - The monitors minus taskbars and docks, where falls land
+    ///
+    /// # Returns
+    /// * `Some((x, y))` - New window position to apply
+    /// * `None` - The window should stay where it is
+    pub fn update(
+        &mut self,
+        now: Instant,
+        position: (i32, i32),
+        size: (u32, u32),
+        monitors: &[Bounds],
+        work_areas: &[Bounds],
+    ) -> Option<(i32, i32)> {
+        if self.active.is_none()
+            && self.falling.is_none()
+            && !self.start_next(now, position, size, monitors, work_areas)
+        {
+            return None;
+        }
+
+        if let Some(fall) = &mut self.falling {
+            let y = fall.step(now);
+            if fall.is_resting() {
+                self.falling = None;
+            }
+            return Some((position.0, y.round() as i32));
+        }
+
+        let active = self.active.as_ref()?;
+        let progress = if active.duration.is_zero() {
+            1.0
+        } else {
+            (now.duration_since(active.started).as_secs_f64() / active.duration.as_secs_f64()).min(1.0)
+        };
+
+        // Ease in and out so starts and stops don't look abrupt
+        let eased = progress * progress * (3.0 - 2.0 * progress);
+        let x = active.from.0 + (active.to.0 - active.from.0) * eased;
+        let y = active.from.1 + (active.to.1 - active.from.1) * eased;
+
+        if progress >= 1.0 {
+            self.active = None;
+            if self.wander_speed.is_some() {
+                let rest = rand::thread_rng().gen_range(WANDER_PAUSE_MIN_MS..=WANDER_PAUSE_MAX_MS);
+                self.resting_until = Some(now + Duration::from_millis(rest));
+            }
+        }
+
+        Some((x.round() as i32, y.round() as i32))
+    }
+
+    /// Starts the next glide, either from the queue or a new wander walk.
+    ///
+    /// # Returns
+    /// `true` if a glide is now active
+    fn start_next(
+        &mut self,
+        now: Instant,
+        position: (i32, i32),
+        size: (u32, u32),
+        monitors: &[Bounds],
+        work_areas: &[Bounds],
+    ) -> bool {
+        let from = (position.0 as f64, position.1 as f64);
+
+        while let Some(command) = self.pending.pop_front() {
+            match command {
+                MotionCommand::MoveTo { x, y, duration_ms } => {
+                    // Targets off-screen are pulled back onto the nearest monitor
+                    let (x, y) = placement::constrain(x, y, size.0, size.1, monitors);
+                    self.active = Some(ActiveMove {
+                        from,
+                        to: (x as f64, y as f64),
+                        started: now,
+                        duration: Duration::from_millis(duration_ms),
+                        command: Some(command),
+                    });
+                    return true;
+                }
+                MotionCommand::Wander { speed } => {
+                    // Wandering never ends, so later commands can't run
+                    self.wander_speed = Some(speed);
+                    self.pending.clear();
+                }
+                MotionCommand::Fall { bounce } => {
+                    let area = match placement::monitor_for(position.0, position.1, size.0, size.1, work_areas) {
+                        Some(area) => area,
+                        None => continue,
+                    };
+                    let ground = (area.y + area.height - size.1 as i32) as f64;
+                    if from.1 >= ground {
+                        // Already standing on (or in front of) the taskbar
+                        continue;
+                    }
+                    self.falling = Some(ActiveFall {
+                        y: from.1,
+                        velocity: 0.0,
+                        ground,
+                        bounce,
+                        last_step: now,
+                    });
+                    return true;
+                }
+            }
+        }
+
+        let speed = match self.wander_speed {
+            Some(speed) => speed,
+            None => return false,
+        };
+        if self.resting_until.is_some_and(|until| now < until) {
+            return false;
+        }
+        self.resting_until = None;
+
+        // Pick a random spot on the same monitor at the current height
+        let monitor = match placement::monitor_for(position.0, position.1, size.0, size.1, monitors) {
+            Some(monitor) => monitor,
+            None => return false,
+        };
+        let left = monitor.x;
+        let right = monitor.x + monitor.width - size.0 as i32;
+        if right <= left {
+            return false;
+        }
+        let target_x = rand::thread_rng().gen_range(left..=right);
+        let distance = (target_x - position.0).abs() as f64;
+
+        self.active = Some(ActiveMove {
+            from,
+            to: (target_x as f64, from.1),
+            started: now,
+            duration: Duration::try_from_secs_f64(distance / speed).map_or(MAX_WANDER_LEG, |leg| leg.min(MAX_WANDER_LEG)),
+            command: None,
+        });
+        true
+    }
+}
+
+impl ActiveFall {
+    /// Advances the fall to `now` and returns the window's new top edge.
+    fn step(&mut self, now: Instant) -> f64 {
+        // A long stall (e.g. a hidden window) shouldn't teleport the buddy
+        let seconds = now.duration_since(self.last_step).as_secs_f64().min(0.05);
+        self.last_step = now;
+
+        self.velocity += GRAVITY * seconds;
+        self.y += self.velocity * seconds;
+        if self.y >= self.ground {
+            self.y = self.ground;
+            self.velocity = if self.bounce && self.velocity * BOUNCINESS >= MIN_BOUNCE_SPEED {
+                -self.velocity * BOUNCINESS
+            } else {
+                0.0
+            };
+        }
+        self.y
+    }
+
+    /// Returns `true` once the buddy has landed for good.
+    fn is_resting(&self) -> bool {
+        self.y >= self.ground && self.velocity == 0.0
+    }
+}
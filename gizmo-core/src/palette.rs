@@ -0,0 +1,200 @@
+//! Color Palettes
+//!
+//! Frames store one palette index per pixel rather than a color. Index 0 is
+//! the background ("off"), index 1 is the default foreground ("on"), and any
+//! further indices are whatever the script declared with `palette()`.
+//!
+//! ## Palette Model
+//!
+//! ```text
+//! palette(["#000000", "#ffffff", "#ff8800"]);
+//!            index 0     index 1    index 2
+//!
+//! frame pixels:  [0, 1, 2]  →  black, white, orange
+//! ```
+//!
+//! Keeping indices in frames means a palette can be swapped without
+//! regenerating any frames, and patterns stay cheap to evaluate.
+//!
+//! ## Color Format
+//!
+//! Colors are written as `"#RRGGBB"` or `"#RRGGBBAA"` strings and stored as
+//! 32-bit `0xAARRGGBB` values. Colors without an explicit alpha are opaque.
+//!
+//! ## Reserved Indices
+//!
+//! Indices [`RESERVED`] to 255 belong to what the window draws over the
+//! sprite (speech bubbles, the error glyph), which sets their colors itself.
+//! Scripts can't declare colors for them or put them in frames, so the
+//! window's colors never show up in a script's pixels.
+//!
+//! ## Transparency
+//!
+//! The default background (index 0) is fully transparent, so on a compositing
+//! desktop only the lit pixels are visible. Declaring an opaque index 0 in
+//! `palette()` brings back a solid background. Window buffers expect
+//! premultiplied alpha, which `Palette::pixel()` produces.
+
+use crate::error::{GizmoError, Result};
+
+/// Fully transparent, the default background color (index 0).
+const DEFAULT_BACKGROUND: u32 = 0x00000000;
+/// Opaque white, the default foreground color (index 1).
+const DEFAULT_FOREGROUND: u32 = 0xFFFFFFFF;
+
+/// First palette index reserved for the window; scripts use the ones below.
+pub const RESERVED: u8 = 252;
+
+/// Ordered list of ARGB colors addressed by frame pixel indices.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Palette {
+    colors: Vec<u32>,
+}
+
+impl Palette {
+    /// Builds a palette from `"#RRGGBB"` / `"#RRGGBBAA"` color strings.
+    ///
+    /// # Arguments
+    /// * `colors` - Color strings in index order (index 0 is the background)
+    ///
+    /// # Returns
+    /// * `Ok(Palette)` - All colors parsed
+    /// * `Err(GizmoError::ArgumentError)` - Empty list, too many colors (more than [`RESERVED`]), or a malformed color
+    pub fn from_hex_strings(colors: &[String]) -> Result<Self> {
+        if colors.is_empty() {
+            return Err(GizmoError::ArgumentError(
+                "palette must contain at least one color".to_string(),
+            ));
+        }
+        if colors.len() > RESERVED as usize {
+            return Err(GizmoError::ArgumentError(format!(
+                "palette can hold at most {} colors (the rest are reserved), got {}",
+                RESERVED,
+                colors.len()
+            )));
+        }
+
+        let colors = colors
+            .iter()
+            .map(|color| parse_hex_color(color))
+            .collect::<Result<Vec<u32>>>()?;
+
+        Ok(Self { colors })
+    }
+
+    /// Replaces the color at one index, growing the palette if needed.
+    ///
+    /// Indices added to reach `index` get the default foreground color.
+    ///
+    /// # Arguments
+    /// * `index` - Palette index to change (0 = background, 1 = foreground)
+    /// * `color` - `"#RRGGBB"` or `"#RRGGBBAA"` string
+    pub fn set_color(&mut self, index: u8, color: &str) -> Result<()> {
+        self.set_argb(index, parse_hex_color(color)?);
+        Ok(())
+    }
+
+    /// Replaces the color at one index with a `0xAARRGGBB` value, growing
+    /// the palette like [`set_color`](Self::set_color).
+    pub fn set_argb(&mut self, index: u8, argb: u32) {
+        let index = index as usize;
+        if self.colors.len() <= index {
+            self.colors.resize(index + 1, DEFAULT_FOREGROUND);
+        }
+        self.colors[index] = argb;
+    }
+
+    /// Returns the ARGB color for a pixel index.
+    ///
+    /// Indices past the end of the palette fall back to the foreground color
+    /// (index 1) so frames drawn with a short palette still show up.
+    pub fn color(&self, index: u8) -> u32 {
+        self.colors
+            .get(index as usize)
+            .or_else(|| self.colors.get(1))
+            .copied()
+            .unwrap_or(DEFAULT_FOREGROUND)
+    }
+}
+
+impl Palette {
+    /// Returns the window-buffer pixel for an index: ARGB with premultiplied alpha.
+    ///
+    /// Compositors blend premultiplied colors, so a transparent background
+    /// becomes `0x00000000` and shows up as plain black where the platform
+    /// cannot composite the window.
+    pub fn pixel(&self, index: u8) -> u32 {
+        let argb = self.color(index);
+        let alpha = argb >> 24;
+        let scale = |shift: u32| (((argb >> shift) & 0xFF) * alpha / 255) << shift;
+
+        (alpha << 24) | scale(16) | scale(8) | scale(0)
+    }
+
+    /// Returns a copy of this palette with every color's alpha scaled.
+    ///
+    /// Used to apply window opacity per pixel on platforms without native
+    /// window alpha.
+    ///
+    /// # Arguments
+    /// * `opacity` - Alpha multiplier from 0.0 (invisible) to 1.0 (unchanged)
+    pub fn with_opacity(&self, opacity: f64) -> Self {
+        let opacity = opacity.clamp(0.0, 1.0);
+        let colors = self
+            .colors
+            .iter()
+            .map(|&argb| {
+                let alpha = ((argb >> 24) as f64 * opacity).round() as u32;
+                (alpha << 24) | (argb & 0x00FF_FFFF)
+            })
+            .collect();
+
+        Self { colors }
+    }
+}
+
+impl Default for Palette {
+    /// The classic Gizmo look: white pixels floating on a transparent background.
+    fn default() -> Self {
+        Self {
+            colors: vec![DEFAULT_BACKGROUND, DEFAULT_FOREGROUND],
+        }
+    }
+}
+
+/// Converts a pixel number from a frame literal into a palette index.
+///
+/// Whole numbers are used as indices directly (clamped below [`RESERVED`]); any other
+/// non-zero value counts as the default foreground so `[0, 0.5, 1]` keeps its
+/// old on/off meaning.
+pub fn index_from_number(n: f64) -> u8 {
+    if n >= 1.0 && n.fract() == 0.0 {
+        n.min((RESERVED - 1) as f64) as u8
+    } else if n != 0.0 {
+        1
+    } else {
+        0
+    }
+}
+
+/// Parses `"#RRGGBB"` or `"#RRGGBBAA"` into `0xAARRGGBB`.
+pub fn parse_hex_color(text: &str) -> Result<u32> {
+    let invalid = || {
+        GizmoError::ArgumentError(format!(
+            "Invalid color '{}' (expected \"#RRGGBB\" or \"#RRGGBBAA\")",
+            text
+        ))
+    };
+
+    let hex = text.strip_prefix('#').ok_or_else(invalid)?;
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(invalid());
+    }
+
+    let value = u32::from_str_radix(hex, 16).map_err(|_| invalid())?;
+    match hex.len() {
+        6 => Ok(0xFF000000 | value),
+        8 => Ok((value >> 8) | ((value & 0xFF) << 24)),
+        _ => Err(invalid()),
+    }
+}
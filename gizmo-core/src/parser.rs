@@ -13,7 +13,9 @@
 //! - **Variable Declarations**: `frame var = expr`, `frames arr = expr`
 //! - **Assignments**: `var = expr`
 //! - **Control Flow**: `if/then/else/end`, `repeat/times/do/end`
+//! - **Event Handlers**: `when <event> do ... end`
 //! - **Expression Statements**: Function calls and standalone expressions
+//! - **Named Arguments**: `call(name = expr)`, recorded as `Expression::NamedArgument`
 //!
 //! ### Expression Parsing with Operator Precedence
 //! The parser implements precedence climbing for mathematical expressions:
@@ -32,7 +34,8 @@
 //!
 //! ### Pattern Generation
 //! Special handling for `pattern(width, height) { statements... return expr; }` blocks
-//! that generate pixel art through per-pixel expression evaluation.
+//! that generate pixel art through per-pixel expression evaluation, and for
+//! `pipeline(width, height) { pass {...} pass {...} }` chains of such bodies.
 //!
 //! ## Error Recovery
 //! The parser provides detailed error messages with context about what was expected
@@ -41,10 +44,18 @@
 //! ## Newline Handling
 //! Newlines are significant in Gizmo for statement separation but are flexibly
 //! handled - they can appear almost anywhere for formatting without affecting semantics.
+//!
+//! ## Layout
+//! [`Parser::keeping_layout`] parses for `gizmo fmt`: comments and blank lines
+//! between statements become [`StatementKind::Trivia`], and parentheses and line
+//! breaks inside expressions become [`Expression::Grouping`] and
+//! [`Expression::LineBreak`]. Comments inside an expression are moved to their
+//! own line above its statement.
 
-use crate::lexer::Token;
+use crate::lexer::{Position, Token};
 use crate::ast::*;
 use crate::error::{GizmoError, Result};
+use std::collections::HashSet;
 
 /// Recursive descent parser for the Gizmo scripting language.
 ///
@@ -53,8 +64,18 @@ use crate::error::{GizmoError, Result};
 pub struct Parser {
     /// Vector of tokens to parse (produced by the lexer)
     tokens: Vec<Token>,
+    /// Where each token starts in the source; empty if not known
+    positions: Vec<Position>,
     /// Current position in the token stream
     current: usize,
+    /// Whether to keep the layout `gizmo fmt` writes back
+    keep_layout: bool,
+    /// Comments and blank lines skipped since the last statement of a block
+    trivia: Vec<Trivia>,
+    /// Comments skipped inside expressions, for their statement to go after
+    expression_comments: Vec<String>,
+    /// Every variable and function name seen so far, shared by all its uses
+    names: HashSet<Name>,
 }
 
 impl Parser {
@@ -66,7 +87,57 @@ impl Parser {
     /// # Returns
     /// A new Parser ready to parse the token stream into an AST
     pub fn new(tokens: Vec<Token>) -> Self {
-        Self { tokens, current: 0 }
+        Self {
+            tokens,
+            positions: Vec::new(),
+            current: 0,
+            keep_layout: false,
+            trivia: Vec::new(),
+            expression_comments: Vec::new(),
+            names: HashSet::new(),
+        }
+    }
+
+    /// Creates a parser that knows where each token is in the source.
+    ///
+    /// Statements record where they start, so runtime errors can point at
+    /// them, and [`parse`](Self::parse) errors point at the token they were
+    /// found at.
+    ///
+    /// # Arguments
+    /// * `tokens` - Vector of tokens produced by the lexer
+    /// * `positions` - Where each token starts, from
+    ///   [`Lexer::tokenize_with_positions`](crate::lexer::Lexer::tokenize_with_positions)
+    pub fn with_positions(tokens: Vec<Token>, positions: Vec<Position>) -> Self {
+        Self {
+            positions,
+            ..Self::new(tokens)
+        }
+    }
+
+    /// Returns the shared copy of a name, adding it the first time it is seen.
+    fn intern(&mut self, name: &str) -> Name {
+        if let Some(interned) = self.names.get(name) {
+            return interned.clone();
+        }
+        let interned: Name = name.into();
+        self.names.insert(interned.clone());
+        interned
+    }
+    
+    /// Creates a parser that keeps comments, blank lines, parentheses and
+    /// line breaks in the AST.
+    ///
+    /// Used by `gizmo fmt` with tokens from [`Lexer::keeping_comments`](crate::lexer::Lexer::keeping_comments);
+    /// running and checking scripts use [`new`](Self::new).
+    ///
+    /// # Arguments
+    /// * `tokens` - Vector of tokens produced by the lexer
+    pub fn keeping_layout(tokens: Vec<Token>) -> Self {
+        Self {
+            keep_layout: true,
+            ..Self::new(tokens)
+        }
     }
     
     /// Parses the complete token stream into a Program AST.
@@ -76,7 +147,8 @@ impl Parser {
     ///
     /// # Returns
     /// * `Ok(Program)` - Successfully parsed AST
-    /// * `Err(GizmoError)` - Syntax error with details about what went wrong
+    /// * `Err(GizmoError)` - Syntax error with details about what went wrong,
+    ///   located at the token it was found at if the parser knows positions
     ///
     /// # Grammar
     /// ```text
@@ -89,17 +161,78 @@ impl Parser {
         
         while !self.is_at_end() {
             // Skip newlines at the top level for flexible formatting
-            if self.peek() == &Token::Newline {
-                self.advance();
+            if self.peek_is_line_end() {
+                self.skip_newlines();
                 continue;
             }
             
-            statements.push(self.statement()?);
+            if let Err(e) = self.block_statement(&mut statements) {
+                return Err(match self.positions.get(self.error_index()) {
+                    Some(&position) => e.at(position),
+                    None => e,
+                });
+            }
         }
+        self.take_trivia(&mut statements);
         
         Ok(Program { statements })
     }
     
+    /// Parses the complete token stream, carrying on after syntax errors.
+    ///
+    /// A top-level statement with an error is skipped up to the newline or
+    /// `;` that ends it (counting nested blocks and brackets), so a mistake
+    /// is reported once instead of confusing everything after it. Used by
+    /// `gizmo check`; running scripts use [`parse`](Self::parse).
+    ///
+    /// # Returns
+    /// The statements that parsed, and every syntax error with the index of
+    /// the token it was found at
+    pub fn parse_all(&mut self) -> (Program, Vec<(usize, GizmoError)>) {
+        let mut statements = Vec::new();
+        let mut errors = Vec::new();
+        
+        while !self.is_at_end() {
+            if self.peek_is_line_end() {
+                self.skip_newlines();
+                continue;
+            }
+            
+            let start = self.current;
+            match self.statement() {
+                Ok(statement) => statements.push(statement),
+                Err(e) => {
+                    errors.push((self.error_index(), e));
+                    self.skip_statement(start);
+                }
+            }
+        }
+        
+        (Program { statements }, errors)
+    }
+    
+    /// Returns the index of the token a syntax error was found at.
+    fn error_index(&self) -> usize {
+        self.current.min(self.tokens.len().saturating_sub(1))
+    }
+    
+    /// Moves past the statement starting at token `start`, to just after the
+    /// newline or `;` that ends it outside any block or brackets.
+    fn skip_statement(&mut self, start: usize) {
+        self.current = start;
+        let mut depth = 0usize;
+        while !self.is_at_end() {
+            match self.advance() {
+                Token::Do | Token::Then | Token::LeftParen | Token::LeftBracket | Token::LeftBrace => depth += 1,
+                Token::End | Token::RightParen | Token::RightBracket | Token::RightBrace => {
+                    depth = depth.saturating_sub(1);
+                }
+                Token::Newline | Token::Semicolon if depth == 0 => return,
+                _ => {}
+            }
+        }
+    }
+    
     /// Parses a statement from the current token position.
     ///
     /// Statements are the top-level constructs in Gizmo programs. The parser
@@ -113,16 +246,22 @@ impl Parser {
     /// # Grammar
     /// ```text
     /// statement → variable_declaration
+    ///           | persist_declaration
     ///           | assignment
     ///           | repeat_statement
     ///           | if_statement  
+    ///           | when_statement
+    ///           | every_statement
     ///           | expression_statement
     /// ```
     ///
     /// Uses intelligent lookahead to distinguish assignments from expression statements
-    /// when encountering identifiers.
+    /// when encountering identifiers. `every` only starts a timer when it isn't
+    /// assigned to or called, and `persist` only declares a variable when
+    /// followed by `name =`, so both stay usable as variable names.
     fn statement(&mut self) -> Result<Statement> {
-        match self.peek() {
+        let position = self.positions.get(self.current).copied();
+        let kind = match self.peek() {
             Token::Frame | Token::Frames => {
                 self.variable_declaration()
             }
@@ -132,6 +271,21 @@ impl Parser {
             Token::If => {
                 self.if_statement()
             }
+            Token::When => {
+                self.when_statement()
+            }
+            Token::Identifier(name) if name == "every"
+                && !self.peek_ahead_is_assignment()
+                && !matches!(self.tokens.get(self.current + 1), Some(Token::LeftParen)) =>
+            {
+                self.every_statement()
+            }
+            Token::Identifier(name) if name == "persist"
+                && matches!(self.tokens.get(self.current + 1), Some(Token::Identifier(_)))
+                && matches!(self.tokens.get(self.current + 2), Some(Token::Equal)) =>
+            {
+                self.persist_declaration()
+            }
             Token::Identifier(_) => {
                 // Lookahead to distinguish assignment from expression statement
                 if self.peek_ahead_is_assignment() {
@@ -141,7 +295,8 @@ impl Parser {
                 }
             }
             _ => self.expression_statement(),
-        }
+        }?;
+        Ok(Statement { kind, position })
     }
     
     /// Parses a variable declaration statement.
@@ -161,20 +316,22 @@ impl Parser {
     /// # Error Handling
     /// Provides specific error messages for missing identifiers, assignment operators,
     /// and malformed expressions.
-    fn variable_declaration(&mut self) -> Result<Statement> {
-        let var_type = match self.advance() {
+    fn variable_declaration(&mut self) -> Result<StatementKind> {
+        let var_type = match self.advance().clone() {
             Token::Frame => VariableType::Frame,
             Token::Frames => VariableType::Frames,
             token => {
+                self.step_back();
                 return Err(GizmoError::ParseError(format!(
                     "Expected variable type, found '{:?}'", token
                 )));
             }
         };
         
-        let name = match self.advance() {
-            Token::Identifier(name) => name.clone(),
+        let name = match self.advance().clone() {
+            Token::Identifier(name) => self.intern(&name),
             token => {
+                self.step_back();
                 return Err(GizmoError::ParseError(format!(
                     "Expected identifier, found '{:?}'", token
                 )));
@@ -197,17 +354,35 @@ impl Parser {
         
         self.skip_newlines();
         
-        Ok(Statement::VariableDeclaration {
+        Ok(StatementKind::VariableDeclaration {
             var_type,
             name,
             value,
         })
     }
     
-    fn assignment_statement(&mut self) -> Result<Statement> {
-        let name = match self.advance() {
-            Token::Identifier(name) => name.clone(),
+    /// Parses a persistent variable declaration.
+    ///
+    /// # Grammar
+    /// ```text
+    /// persist_declaration → "persist" IDENTIFIER "=" expression (";")?
+    /// ```
+    ///
+    /// # Examples
+    /// - `persist hunger = 0;`
+    fn persist_declaration(&mut self) -> Result<StatementKind> {
+        self.advance(); // consume 'persist'
+        match self.assignment_statement()? {
+            StatementKind::Assignment { name, value } => Ok(StatementKind::PersistDeclaration { name, value }),
+            _ => unreachable!("assignment_statement only returns assignments"),
+        }
+    }
+    
+    fn assignment_statement(&mut self) -> Result<StatementKind> {
+        let name = match self.advance().clone() {
+            Token::Identifier(name) => self.intern(&name),
             token => {
+                self.step_back();
                 return Err(GizmoError::ParseError(format!(
                     "Expected identifier, found '{:?}'", token
                 )));
@@ -228,10 +403,10 @@ impl Parser {
         }
         self.skip_newlines();
         
-        Ok(Statement::Assignment { name, value })
+        Ok(StatementKind::Assignment { name, value })
     }
     
-    fn expression_statement(&mut self) -> Result<Statement> {
+    fn expression_statement(&mut self) -> Result<StatementKind> {
         let expr = self.expression()?;
         
         if self.peek() == &Token::Semicolon {
@@ -239,7 +414,7 @@ impl Parser {
         }
         self.skip_newlines();
         
-        Ok(Statement::ExpressionStatement(expr))
+        Ok(StatementKind::ExpressionStatement(expr))
     }
     
     /// Parses an if statement with optional else clause.
@@ -265,7 +440,7 @@ impl Parser {
     /// # Newline Handling
     /// Newlines are flexibly handled within if blocks - they can appear after
     /// keywords and between statements without affecting semantics.
-    fn if_statement(&mut self) -> Result<Statement> {
+    fn if_statement(&mut self) -> Result<StatementKind> {
         self.advance(); // consume 'if'
         
         let condition = self.expression()?;
@@ -284,12 +459,13 @@ impl Parser {
         
         // Parse statements until we hit 'else' or 'end'
         while self.peek() != &Token::Else && self.peek() != &Token::End && !self.is_at_end() {
-            if self.peek() == &Token::Newline {
-                self.advance();
+            if self.peek_is_line_end() {
+                self.skip_newlines();
                 continue;
             }
-            then_body.push(self.statement()?);
+            self.block_statement(&mut then_body)?;
         }
+        self.take_trivia(&mut then_body);
         
         let mut else_body = None;
         
@@ -300,12 +476,13 @@ impl Parser {
             
             let mut else_statements = Vec::new();
             while self.peek() != &Token::End && !self.is_at_end() {
-                if self.peek() == &Token::Newline {
-                    self.advance();
+                if self.peek_is_line_end() {
+                    self.skip_newlines();
                     continue;
                 }
-                else_statements.push(self.statement()?);
+                self.block_statement(&mut else_statements)?;
             }
+            self.take_trivia(&mut else_statements);
             else_body = Some(else_statements);
         }
         
@@ -322,7 +499,7 @@ impl Parser {
         }
         self.skip_newlines();
         
-        Ok(Statement::IfStatement {
+        Ok(StatementKind::IfStatement {
             condition,
             then_body,
             else_body,
@@ -351,7 +528,7 @@ impl Parser {
     /// # Loop Variables
     /// The interpreter automatically provides a `time` variable inside the loop
     /// containing the current iteration index (0-based).
-    fn repeat_statement(&mut self) -> Result<Statement> {
+    fn repeat_statement(&mut self) -> Result<StatementKind> {
         self.advance(); // consume 'repeat'
         
         let count = self.expression()?;
@@ -378,12 +555,13 @@ impl Parser {
         
         // Parse statements until we hit 'end'
         while self.peek() != &Token::End && !self.is_at_end() {
-            if self.peek() == &Token::Newline {
-                self.advance();
+            if self.peek_is_line_end() {
+                self.skip_newlines();
                 continue;
             }
-            body.push(self.statement()?);
+            self.block_statement(&mut body)?;
         }
+        self.take_trivia(&mut body);
         
         // Expect 'end'
         if self.peek() != &Token::End {
@@ -398,12 +576,267 @@ impl Parser {
         }
         self.skip_newlines();
         
-        Ok(Statement::RepeatLoop {
+        Ok(StatementKind::RepeatLoop {
             count: Box::new(count),
             body,
         })
     }
     
+    /// Parses an event handler.
+    ///
+    /// The handler body isn't run when the script loads; the interpreter keeps
+    /// it and the window loop runs it each time the event happens.
+    ///
+    /// # Grammar
+    /// ```text
+    /// when_statement → "when" event modifier* "do" statement* "end"
+    /// modifier       → "once" | "priority" expression
+    /// event          → "launched" | "stopping"
+    ///                | "clicked" | "double_clicked" | "hovered" | "unhovered"
+    ///                | "clicked" expression "times" "within" expression
+    ///                | "dragged" | "dropped" | "shaken"
+    ///                | "unplugged" | "plugged_in" | "battery_low"
+    ///                | ("key" | "time" | "message") "(" expression ")"
+    ///                | "idle" ">" expression
+    ///                | "battery" "<" expression
+    /// ```
+    ///
+    /// # Examples
+    /// ```gzmo
+    /// when clicked do
+    ///     play(happy);
+    /// end
+    ///
+    /// when idle > 5000 do
+    ///     loop(sleeping);
+    /// end
+    ///
+    /// when clicked 3 times within 1000 do
+    ///     play(secret);
+    /// end
+    ///
+    /// when key("p") do
+    ///     play(happy);
+    /// end
+    ///
+    /// when time("22:00") do
+    ///     loop(sleeping);
+    /// end
+    ///
+    /// when launched once priority 10 do
+    ///     play(hello);
+    /// end
+    /// ```
+    fn when_statement(&mut self) -> Result<StatementKind> {
+        self.advance(); // consume 'when'
+        
+        // Event names are plain identifiers rather than keywords so they stay
+        // usable as variable names elsewhere
+        let event = match self.advance().clone() {
+            Token::Identifier(name) if name == "launched" => Event::Launched,
+            Token::Identifier(name) if name == "stopping" => Event::Stopping,
+            Token::Identifier(name) if name == "clicked" => {
+                // A count after `clicked` makes it a combo; `do` or a modifier doesn't
+                let plain = match self.peek() {
+                    Token::Do => true,
+                    Token::Identifier(_) => !self.peek_is_count(),
+                    _ => false,
+                };
+                if plain {
+                    Event::Clicked
+                } else {
+                    let times = self.expression()?;
+                    if self.peek() != &Token::Times {
+                        return Err(GizmoError::ParseError(format!(
+                            "Expected 'times' after the click count, found '{:?}'", self.peek()
+                        )));
+                    }
+                    self.advance(); // consume 'times'
+                    self.expect_word("within")?;
+                    let within = self.expression()?;
+                    Event::ClickCombo { times, within }
+                }
+            }
+            Token::Identifier(name) if name == "double_clicked" => Event::DoubleClicked,
+            Token::Identifier(name) if name == "hovered" => Event::Hovered,
+            Token::Identifier(name) if name == "unhovered" => Event::Unhovered,
+            Token::Identifier(name) if name == "dragged" => Event::Dragged,
+            Token::Identifier(name) if name == "dropped" => Event::Dropped,
+            Token::Identifier(name) if name == "shaken" => Event::Shaken,
+            Token::Identifier(name) if name == "unplugged" => Event::Unplugged,
+            Token::Identifier(name) if name == "plugged_in" => Event::PluggedIn,
+            Token::Identifier(name) if name == "battery_low" => Event::Battery(Expression::Number(20.0)),
+            Token::Identifier(name) if name == "key" || name == "time" || name == "message" => {
+                if self.peek() != &Token::LeftParen {
+                    return Err(GizmoError::ParseError(format!(
+                        "Expected '(' after '{}', found '{:?}'", name, self.peek()
+                    )));
+                }
+                self.advance(); // consume '('
+                let argument = self.expression()?;
+                if self.peek() != &Token::RightParen {
+                    return Err(GizmoError::ParseError(format!(
+                        "Expected ')' after {}() argument, found '{:?}'", name, self.peek()
+                    )));
+                }
+                self.advance(); // consume ')'
+                match name.as_str() {
+                    "key" => Event::Key(argument),
+                    "time" => Event::Time(argument),
+                    _ => Event::Message(argument),
+                }
+            }
+            Token::Identifier(name) if name == "idle" => {
+                if self.peek() != &Token::Greater {
+                    return Err(GizmoError::ParseError(format!(
+                        "Expected '>' after 'idle', found '{:?}'", self.peek()
+                    )));
+                }
+                self.advance(); // consume '>'
+                Event::Idle(self.expression()?)
+            }
+            Token::Identifier(name) if name == "battery" => {
+                if self.peek() != &Token::Less {
+                    return Err(GizmoError::ParseError(format!(
+                        "Expected '<' after 'battery', found '{:?}'", self.peek()
+                    )));
+                }
+                self.advance(); // consume '<'
+                Event::Battery(self.expression()?)
+            }
+            token => {
+                self.step_back();
+                return Err(GizmoError::ParseError(format!(
+                    "Expected an event after 'when' (launched, stopping, clicked, double_clicked, hovered, unhovered, dragged, dropped, shaken, unplugged, plugged_in, battery_low, key, time, message, idle, battery), found '{:?}'", token
+                )));
+            }
+        };
+        
+        let (once, priority) = self.handler_modifiers()?;
+        let body = self.handler_body()?;
+        Ok(StatementKind::WhenStatement { event, body, once, priority })
+    }
+    
+    /// Parses a timer handler, run by the window loop at a fixed interval.
+    ///
+    /// Timers are handlers like `when` ones, filed under [`Event::Every`].
+    ///
+    /// # Grammar
+    /// ```text
+    /// every_statement → "every" expression modifier* "do" statement* "end"
+    /// ```
+    ///
+    /// # Examples
+    /// ```gzmo
+    /// every 60000 do
+    ///     play(random() > 0.5 ? blink : yawn);
+    /// end
+    /// ```
+    fn every_statement(&mut self) -> Result<StatementKind> {
+        self.advance(); // consume 'every'
+        
+        let interval = self.expression()?;
+        let (once, priority) = self.handler_modifiers()?;
+        let body = self.handler_body()?;
+        Ok(StatementKind::WhenStatement {
+            event: Event::Every(interval),
+            body,
+            once,
+            priority,
+        })
+    }
+    
+    /// Returns `true` if the next tokens read `<identifier> times`, i.e. a
+    /// click count held in a variable rather than a handler modifier.
+    fn peek_is_count(&self) -> bool {
+        matches!(self.tokens.get(self.current + 1), Some(Token::Times))
+    }
+
+    /// Consumes a plain identifier used as a word of the syntax, such as `within`.
+    fn expect_word(&mut self, word: &str) -> Result<()> {
+        match self.peek() {
+            Token::Identifier(name) if name == word => {
+                self.advance();
+                Ok(())
+            }
+            token => Err(GizmoError::ParseError(format!(
+                "Expected '{}', found '{:?}'", word, token
+            ))),
+        }
+    }
+
+    /// Parses the `once` and `priority N` modifiers of a handler, in any order.
+    ///
+    /// Like event names, the modifiers are plain identifiers rather than keywords.
+    ///
+    /// # Returns
+    /// `(once, priority)` - Whether `once` was given, and the priority expression if any
+    fn handler_modifiers(&mut self) -> Result<(bool, Option<Expression>)> {
+        let mut once = false;
+        let mut priority = None;
+        
+        while let Token::Identifier(name) = self.peek().clone() {
+            match name.as_str() {
+                "once" if !once => {
+                    self.advance(); // consume 'once'
+                    once = true;
+                }
+                "priority" if priority.is_none() => {
+                    self.advance(); // consume 'priority'
+                    priority = Some(self.expression()?);
+                }
+                "once" | "priority" => {
+                    return Err(GizmoError::ParseError(format!(
+                        "'{}' is given twice for the same handler", name
+                    )));
+                }
+                _ => break,
+            }
+        }
+        
+        Ok((once, priority))
+    }
+    
+    /// Parses the `do statement* end` block of a `when` or `every` handler.
+    fn handler_body(&mut self) -> Result<Vec<Statement>> {
+        // Expect 'do' keyword
+        if self.peek() != &Token::Do {
+            return Err(GizmoError::ParseError(format!(
+                "Expected 'do', found '{:?}'", self.peek()
+            )));
+        }
+        self.advance(); // consume 'do'
+        
+        self.skip_newlines();
+        
+        let mut body = Vec::new();
+        
+        // Parse statements until we hit 'end'
+        while self.peek() != &Token::End && !self.is_at_end() {
+            if self.peek_is_line_end() {
+                self.skip_newlines();
+                continue;
+            }
+            self.block_statement(&mut body)?;
+        }
+        self.take_trivia(&mut body);
+        
+        // Expect 'end'
+        if self.peek() != &Token::End {
+            return Err(GizmoError::ParseError(format!(
+                "Expected 'end', found '{:?}'", self.peek()
+            )));
+        }
+        self.advance(); // consume 'end'
+        
+        if self.peek() == &Token::Semicolon {
+            self.advance();
+        }
+        self.skip_newlines();
+        
+        Ok(body)
+    }
+    
     /// Parses an expression using operator precedence climbing.
     ///
     /// This is the entry point for expression parsing. It delegates to the
@@ -735,6 +1168,16 @@ impl Parser {
     }
     
     fn primary(&mut self) -> Result<Expression> {
+        // An operand on the next line, e.g. after a trailing `and`
+        if self.peek_is_line_end() {
+            self.line_break();
+            if self.is_at_end() {
+                return Err(GizmoError::ParseError("Unexpected end of input".to_string()));
+            }
+            let operand = self.primary()?;
+            return Ok(self.on_next_line(true, operand));
+        }
+        
         match self.advance().clone() {
             Token::Number(n) => Ok(Expression::Number(n)),
             Token::String(s) => Ok(Expression::String(s)),
@@ -751,14 +1194,17 @@ impl Parser {
                     }
                     self.advance();
                     
-                    Ok(Expression::FunctionCall { name, args })
+                    Ok(Expression::FunctionCall { name: self.intern(&name), args })
                 } else {
-                    Ok(Expression::Identifier(name))
+                    Ok(Expression::Identifier(self.intern(&name)))
                 }
             }
             Token::Pattern => {
                 self.pattern_expression()
             }
+            Token::Pipeline => {
+                self.pipeline_expression()
+            }
             Token::LeftParen => {
                 let expr = self.expression()?;
                 if self.peek() != &Token::RightParen {
@@ -767,21 +1213,17 @@ impl Parser {
                     )));
                 }
                 self.advance();
-                Ok(expr)
-            }
-            Token::LeftBracket => self.array_literal(),
-            Token::Newline => {
-                // Skip newlines and try again
-                self.skip_newlines();
-                if !self.is_at_end() {
-                    self.primary()
+                if self.keep_layout {
+                    Ok(Expression::Grouping(Box::new(expr)))
                 } else {
-                    Err(GizmoError::ParseError("Unexpected end of input".to_string()))
+                    Ok(expr)
                 }
             }
-            token => Err(GizmoError::ParseError(format!(
-                "Unexpected token '{:?}'", token
-            ))),
+            Token::LeftBracket => self.array_literal(),
+            token => {
+                self.step_back();
+                Err(GizmoError::ParseError(format!("Unexpected token '{:?}'", token)))
+            }
         }
     }
     
@@ -814,10 +1256,101 @@ impl Parser {
     ///
     /// This allows complex procedural generation with per-pixel calculations.
     fn pattern_expression(&mut self) -> Result<Expression> {
+        let (width, height) = self.generator_dimensions("pattern")?;
+        
+        let (body, return_expr) = self.pattern_body("pattern parameters")?;
+        
+        Ok(Expression::PatternGenerator {
+            width: Box::new(width),
+            height: Box::new(height),
+            body,
+            return_expr: Box::new(return_expr),
+        })
+    }
+    
+    /// Parses a multi-pass pipeline expression.
+    ///
+    /// A pipeline renders each pass as a pattern of the same dimensions. Every
+    /// pass after the first can read the frame produced by the pass before it
+    /// through the `previous` variable.
+    ///
+    /// # Grammar
+    /// ```text
+    /// pipeline_expression → "pipeline" "(" expression "," expression ")"
+    ///                        "{" ("pass" pattern_body)+ "}"
+    /// ```
+    ///
+    /// # Examples
+    /// ```gzmo
+    /// pipeline(32, 32) {
+    ///     pass { return sqrt((col - 16) * (col - 16) + (row - 16) * (row - 16)) < 10; }
+    ///     pass { return get_pixel(previous, col, row) and count_neighbors(previous, col, row) < 8; }
+    /// }
+    /// ```
+    fn pipeline_expression(&mut self) -> Result<Expression> {
+        let (width, height) = self.generator_dimensions("pipeline")?;
+        
+        if self.peek() != &Token::LeftBrace {
+            return Err(GizmoError::ParseError(format!(
+                "Expected '{{' after pipeline parameters, found '{:?}'", self.peek()
+            )));
+        }
+        self.advance(); // consume '{'
+        self.skip_newlines();
+        
+        let mut passes = Vec::new();
+        
+        while self.peek() != &Token::RightBrace && !self.is_at_end() {
+            if self.peek_is_line_end() {
+                self.skip_newlines();
+                continue;
+            }
+            
+            if self.peek() != &Token::Pass {
+                return Err(GizmoError::ParseError(format!(
+                    "Expected 'pass' inside pipeline, found '{:?}'", self.peek()
+                )));
+            }
+            self.advance(); // consume 'pass'
+            
+            let (body, return_expr) = self.pattern_body("'pass'")?;
+            passes.push(Pass { body, return_expr });
+            
+            // Optional semicolon between passes
+            if self.peek() == &Token::Semicolon {
+                self.advance();
+            }
+        }
+        
+        if self.peek() != &Token::RightBrace {
+            return Err(GizmoError::ParseError(format!(
+                "Expected '}}' to close pipeline, found '{:?}'", self.peek()
+            )));
+        }
+        self.advance(); // consume '}'
+        
+        if passes.is_empty() {
+            return Err(GizmoError::ParseError(
+                "Pipeline must contain at least one pass".to_string()
+            ));
+        }
+        
+        Ok(Expression::Pipeline {
+            width: Box::new(width),
+            height: Box::new(height),
+            passes,
+        })
+    }
+    
+    /// Parses the `(width, height)` header shared by pattern-style generators.
+    ///
+    /// # Arguments
+    /// * `construct` - Keyword that introduced the generator, used in error messages
+    fn generator_dimensions(&mut self, construct: &str) -> Result<(Expression, Expression)> {
         // Expect opening parenthesis
         if self.peek() != &Token::LeftParen {
             return Err(GizmoError::ParseError(format!(
-                "Expected '(' after 'pattern', found '{:?}'", self.peek()
+                "Expected '(' after '{}', found '{:?}'", construct, self.peek()
             )));
         }
         self.advance(); // consume '('
@@ -828,7 +1361,7 @@ impl Parser {
         // Expect comma separator
         if self.peek() != &Token::Comma {
             return Err(GizmoError::ParseError(format!(
-                "Expected ',' after pattern width, found '{:?}'", self.peek()
+                "Expected ',' after {} width, found '{:?}'", construct, self.peek()
             )));
         }
         self.advance(); // consume ','
@@ -839,15 +1372,28 @@ impl Parser {
         // Expect closing parenthesis
         if self.peek() != &Token::RightParen {
             return Err(GizmoError::ParseError(format!(
-                "Expected ')' after pattern height, found '{:?}'", self.peek()
+                "Expected ')' after {} height, found '{:?}'", construct, self.peek()
             )));
         }
         self.advance(); // consume ')'
         
+        Ok((width, height))
+    }
+    
+    /// Parses a braced pattern body: setup statements followed by a mandatory return.
+    ///
+    /// # Grammar
+    /// ```text
+    /// pattern_body → "{" statement* "return" expression "}"
+    /// ```
+    ///
+    /// # Arguments
+    /// * `after` - Description of what precedes the brace, used in error messages
+    fn pattern_body(&mut self, after: &str) -> Result<(Vec<Statement>, Expression)> {
         // Expect opening brace for pattern body
         if self.peek() != &Token::LeftBrace {
             return Err(GizmoError::ParseError(format!(
-                "Expected '{{' after pattern parameters, found '{:?}'", self.peek()
+                "Expected '{{' after {}, found '{:?}'", after, self.peek()
             )));
         }
         self.advance(); // consume '{'
@@ -859,15 +1405,16 @@ impl Parser {
         let mut return_expr = None;
         
         while self.peek() != &Token::RightBrace && !self.is_at_end() {
-            if self.peek() == &Token::Newline {
-                self.advance();
+            if self.peek_is_line_end() {
+                self.skip_newlines();
                 continue;
             }
             
             // Check for return statement (mandatory)
             if self.peek() == &Token::Return {
+                self.take_trivia(&mut body);
                 self.advance(); // consume 'return'
-                return_expr = Some(Box::new(self.expression()?));
+                return_expr = Some(self.expression()?);
                 
                 // Optional semicolon after return expression
                 if self.peek() == &Token::Semicolon {
@@ -876,7 +1423,7 @@ impl Parser {
                 break;
             } else {
                 // Regular statement in pattern body
-                body.push(self.statement()?);
+                self.block_statement(&mut body)?;
             }
         }
         
@@ -885,7 +1432,7 @@ impl Parser {
             GizmoError::ParseError("Pattern body must end with a return expression".to_string())
         })?;
         
-        self.skip_newlines(); // Allow flexible formatting before closing brace
+        self.line_break(); // Allow flexible formatting before closing brace
         
         // Expect closing brace
         if self.peek() != &Token::RightBrace {
@@ -895,33 +1442,30 @@ impl Parser {
         }
         self.advance(); // consume '}'
         
-        Ok(Expression::PatternGenerator {
-            width: Box::new(width),
-            height: Box::new(height),
-            body,
-            return_expr,
-        })
+        Ok((body, return_expr))
     }
     
     fn array_literal(&mut self) -> Result<Expression> {
         let mut elements = Vec::new();
         
-        self.skip_newlines(); // Skip newlines after opening bracket
+        let broken = self.line_break(); // Skip newlines after opening bracket
         
         if self.peek() != &Token::RightBracket {
-            elements.push(self.expression()?);
+            let element = self.expression()?;
+            elements.push(self.on_next_line(broken, element));
             
             while self.peek() == &Token::Comma {
                 self.advance();
-                self.skip_newlines(); // Skip newlines after comma
+                let broken = self.line_break(); // Skip newlines after comma
                 if self.peek() == &Token::RightBracket {
                     break; // Allow trailing comma
                 }
-                elements.push(self.expression()?);
+                let element = self.expression()?;
+                elements.push(self.on_next_line(broken, element));
             }
         }
         
-        self.skip_newlines(); // Skip newlines before closing bracket
+        self.line_break(); // Skip newlines before closing bracket
         
         if self.peek() != &Token::RightBracket {
             return Err(GizmoError::ParseError(format!(
@@ -936,26 +1480,42 @@ impl Parser {
     fn argument_list(&mut self) -> Result<Vec<Expression>> {
         let mut args = Vec::new();
         
-        self.skip_newlines(); // Skip newlines after opening paren
+        let broken = self.line_break(); // Skip newlines after opening paren
         
         if self.peek() != &Token::RightParen {
-            args.push(self.expression()?);
+            let arg = self.argument()?;
+            args.push(self.on_next_line(broken, arg));
             
             while self.peek() == &Token::Comma {
                 self.advance();
-                self.skip_newlines(); // Skip newlines after comma
+                let broken = self.line_break(); // Skip newlines after comma
                 if self.peek() == &Token::RightParen {
                     break; // Allow trailing comma
                 }
-                args.push(self.expression()?);
+                let arg = self.argument()?;
+                args.push(self.on_next_line(broken, arg));
             }
         }
         
-        self.skip_newlines(); // Skip newlines before closing paren
+        self.line_break(); // Skip newlines before closing paren
         
         Ok(args)
     }
     
+    /// Parses one call argument: either `expr` or a named `name = expr`.
+    fn argument(&mut self) -> Result<Expression> {
+        if let Token::Identifier(name) = self.peek().clone() {
+            if self.peek_ahead_is_assignment() {
+                self.advance(); // consume name
+                self.advance(); // consume '='
+                let value = self.expression()?;
+                return Ok(Expression::NamedArgument { name: self.intern(&name), value: Box::new(value) });
+            }
+        }
+        
+        self.expression()
+    }
+    
     /// Skips any newline tokens at the current position.
     ///
     /// Newlines are significant for statement separation but are often
@@ -970,12 +1530,84 @@ impl Parser {
     /// end
     /// ```
     /// Instead of requiring: `if condition then statement end`
+    ///
+    /// When keeping the layout, comments and blank lines are also skipped and
+    /// collected for [`take_trivia`](Self::take_trivia).
     fn skip_newlines(&mut self) {
-        while self.peek() == &Token::Newline {
-            self.advance();
+        while self.peek_is_line_end() {
+            let line_start = self.current == 0 || self.previous() == &Token::Newline;
+            match self.advance().clone() {
+                Token::Comment(text) => self.trivia.push(Trivia::Comment { text, trailing: !line_start }),
+                _ if self.keep_layout && line_start && self.trivia.last() != Some(&Trivia::BlankLine) => {
+                    self.trivia.push(Trivia::BlankLine);
+                }
+                _ => {}
+            }
+        }
+    }
+    
+    /// Skips newlines and comments inside an expression.
+    ///
+    /// Blank lines are dropped and comments are kept for the statement the
+    /// expression belongs to (see [`block_statement`](Self::block_statement)).
+    ///
+    /// # Returns
+    /// `true` if a newline was skipped, i.e. what follows is on another line
+    fn line_break(&mut self) -> bool {
+        let mut broken = false;
+        while self.peek_is_line_end() {
+            match self.advance().clone() {
+                Token::Comment(text) => self.expression_comments.push(text),
+                _ => broken = true,
+            }
+        }
+        broken
+    }
+    
+    /// Marks an operand found after [`line_break`](Self::line_break) as
+    /// being on its own line, when keeping the layout.
+    fn on_next_line(&self, broken: bool, operand: Expression) -> Expression {
+        if self.keep_layout && broken {
+            Expression::LineBreak(Box::new(operand))
+        } else {
+            operand
         }
     }
     
+    /// Returns `true` if the current token ends a line: a newline, or a
+    /// comment when keeping the layout.
+    fn peek_is_line_end(&self) -> bool {
+        matches!(self.peek(), Token::Newline | Token::Comment(_))
+    }
+    
+    /// Parses the next statement of a block into `body`.
+    ///
+    /// The comments and blank lines before it go first, followed by any
+    /// comments from inside its expressions on lines of their own.
+    fn block_statement(&mut self, body: &mut Vec<Statement>) -> Result<()> {
+        self.take_trivia(body);
+        let start = self.expression_comments.len();
+        let statement = self.statement()?;
+        body.extend(
+            self.expression_comments
+                .split_off(start)
+                .into_iter()
+                .map(|text| Self::trivia_statement(Trivia::Comment { text, trailing: false })),
+        );
+        body.push(statement);
+        Ok(())
+    }
+    
+    /// Moves the comments and blank lines skipped since the last statement into `body`.
+    fn take_trivia(&mut self, body: &mut Vec<Statement>) {
+        body.extend(self.trivia.drain(..).map(Self::trivia_statement));
+    }
+    
+    /// Makes a comment or blank line into a statement; layout has no position.
+    fn trivia_statement(trivia: Trivia) -> Statement {
+        Statement { kind: StatementKind::Trivia(trivia), position: None }
+    }
+    
     /// Checks if the parser has reached the end of the token stream.
     ///
     /// Returns true if either:
@@ -1016,6 +1648,12 @@ impl Parser {
         self.previous()
     }
     
+    /// Un-consumes the token just consumed, so the syntax error about it
+    /// is found at it.
+    fn step_back(&mut self) {
+        self.current = self.current.saturating_sub(1);
+    }
+    
     /// Returns the previously consumed token.
     ///
     /// Used by `advance()` to return the token that was just consumed.
@@ -1050,59 +1688,5 @@ impl Parser {
             false
         }
     }
-    
-    fn peek_ahead_for_return(&self) -> bool {
-        // Simple heuristic: if we see "return" keyword or if the next statement 
-        // looks like it's the last expression (followed by } or end of file)
-        // For now, we'll look for the pattern where there's no assignment
-        if matches!(self.peek(), Token::Return) {
-            return true;
-        }
-        
-        // Look ahead to see if this looks like a final expression
-        // (not an assignment or declaration)
-        let mut lookahead = self.current;
-        let mut depth = 0;
-        while lookahead < self.tokens.len() {
-            match &self.tokens[lookahead] {
-                Token::LeftBrace | Token::LeftParen | Token::LeftBracket => depth += 1,
-                Token::RightBrace => {
-                    if depth == 0 {
-                        return true; // Found closing brace, likely final expression
-                    }
-                    depth -= 1;
-                }
-                Token::RightParen | Token::RightBracket => {
-                    if depth > 0 {
-                        depth -= 1;
-                    }
-                }
-                Token::Equal => {
-                    if depth == 0 {
-                        return false; // Found assignment, not a return expression
-                    }
-                }
-                Token::Semicolon | Token::Newline => {
-                    if depth == 0 {
-                        // This suggests it's a statement, not the final expression
-                        // But we need to check if there are more statements after
-                        let mut next_lookahead = lookahead + 1;
-                        while next_lookahead < self.tokens.len() && 
-                              matches!(self.tokens[next_lookahead], Token::Newline) {
-                            next_lookahead += 1;
-                        }
-                        if next_lookahead < self.tokens.len() && 
-                           matches!(self.tokens[next_lookahead], Token::RightBrace) {
-                            return true; // Last statement before closing brace
-                        }
-                        return false;
-                    }
-                }
-                Token::Eof => return true,
-                _ => {}
-            }
-            lookahead += 1;
-        }
-        false
-    }
+
 }
\ No newline at end of file
@@ -0,0 +1,1013 @@
+//! Pattern Compilation
+//!
+//! `pattern` bodies run once per pixel, so a 64x64 pattern in a 48-frame loop
+//! runs its body almost 200,000 times. Walking the syntax tree for each of
+//! those means a hash lookup and a `Value` clone for every variable and a
+//! hash insert for every assignment. Before a pattern is rendered its body
+//! and return expression are therefore lowered to flat bytecode for a small
+//! stack machine:
+//!
+//! ```text
+//! d = sqrt(col * col + row * row);      Load col, Load col, Binary *,
+//! return d < 8;                         Load row, Load row, Binary *,
+//!                                       Binary +, Call sqrt, Store d,
+//!                                       Load d, Push 8, Binary <
+//! ```
+//!
+//! - `col`, `row` and the variables the body assigns live in numbered
+//!   registers instead of the environment
+//! - Other variables can't change while the pattern renders, so their values
+//!   are read once, at compile time, and become constants
+//! - Frames passed to `get_pixel()` and `count_neighbors()` (such as a
+//!   pipeline's `previous`) are sampled directly instead of being looked up
+//!   and passed through the built-in registry for every call
+//! - The math built-ins (`sin()`, `sqrt()`, `color()`, ...) run natively,
+//!   and each call to `sin()`, `cos()`, `sqrt()` or `atan2()` remembers its
+//!   recent results: `sin(col * 0.1)` computes the sine once per column, not
+//!   once per pixel
+//! - Work that doesn't depend on the pixel is done once, while compiling:
+//!   operations on constants are folded, and a variable assigned a constant
+//!   (`center_x = 32`, `phase = time * 0.1`) is a constant wherever that
+//!   value is certain to still hold. An `if` or `?:` whose condition folds
+//!   only compiles the branch it takes
+//!
+//! The compiled pattern behaves like the tree-walking interpreter, errors
+//! included: after rendering, the variables the body assigned and `col` /
+//! `row` are written back to the environment. Its frames can be kept in a
+//! [`PatternCache`] for later runs of the script. Bodies that use anything else
+//! (strings, arrays, nested patterns, loops, other functions) aren't
+//! compiled and are interpreted pixel by pixel as before.
+
+use crate::ast::{BinaryOperator, Expression, Frame, Name, Statement, StatementKind, Value};
+use crate::builtin::{self, BuiltinFunctions};
+use crate::cache::{CachedPattern, Fnv1a, PatternCache};
+use crate::error::{GizmoError, Result};
+use crate::interpreter::{apply_binary_operator, check_writable, Environment};
+use crate::lexer::Position;
+use crate::palette;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Register holding the pixel's column
+const COL: usize = 0;
+/// Register holding the pixel's row
+const ROW: usize = 1;
+/// Results a memoized call remembers
+const MEMO_SLOTS: usize = 128;
+
+/// A number or a palette index, the only values compiled code works with.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub(crate) enum Scalar {
+    Number(f64),
+    Color(u8),
+}
+
+impl Scalar {
+    /// Converts an environment value, if it is a number or a color.
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Number(n) => Some(Scalar::Number(*n)),
+            Value::Color(index) => Some(Scalar::Color(*index)),
+            _ => None,
+        }
+    }
+
+    /// Whether two values are identical, as seen by compiled code.
+    fn same(self, other: Self) -> bool {
+        match (self, other) {
+            (Scalar::Number(a), Scalar::Number(b)) => a.to_bits() == b.to_bits(),
+            (Scalar::Color(a), Scalar::Color(b)) => a == b,
+            _ => false,
+        }
+    }
+
+    fn to_value(self) -> Value {
+        match self {
+            Scalar::Number(n) => Value::Number(n),
+            Scalar::Color(index) => Value::Color(index),
+        }
+    }
+}
+
+/// Built-in functions compiled code calls natively.
+#[derive(Debug, Clone, Copy)]
+enum Function {
+    Floor,
+    Ceil,
+    Abs,
+    Sin,
+    Cos,
+    Sqrt,
+    Atan2,
+    Random,
+    Color,
+}
+
+impl Function {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "floor" => Function::Floor,
+            "ceil" => Function::Ceil,
+            "abs" => Function::Abs,
+            "sin" => Function::Sin,
+            "cos" => Function::Cos,
+            "sqrt" => Function::Sqrt,
+            "atan2" => Function::Atan2,
+            "random" => Function::Random,
+            "color" => Function::Color,
+            _ => return None,
+        })
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Function::Floor => "floor",
+            Function::Ceil => "ceil",
+            Function::Abs => "abs",
+            Function::Sin => "sin",
+            Function::Cos => "cos",
+            Function::Sqrt => "sqrt",
+            Function::Atan2 => "atan2",
+            Function::Random => "random",
+            Function::Color => "color",
+        }
+    }
+
+    /// Whether calls are worth memoizing: pure, and slower than a table lookup.
+    fn memoized(self) -> bool {
+        matches!(self, Function::Sin | Function::Cos | Function::Sqrt | Function::Atan2)
+    }
+
+    /// Computes the result for well-formed arguments.
+    ///
+    /// # Returns
+    /// * `Some(result)` - The function's result
+    /// * `None` - The arguments are wrong in some way; the built-in itself
+    ///   reports how
+    fn apply(self, args: &[Scalar]) -> Option<Scalar> {
+        if let Function::Random = self {
+            // random() ignores its arguments
+            return Some(Scalar::Number(builtin::random_number()));
+        }
+        let n = match args {
+            [Scalar::Number(n)] => *n,
+            [Scalar::Number(y), Scalar::Number(x)] if matches!(self, Function::Atan2) => {
+                return Some(Scalar::Number(y.atan2(*x)));
+            }
+            _ => return None,
+        };
+        let result = match self {
+            Function::Floor => n.floor(),
+            Function::Ceil => n.ceil(),
+            Function::Abs => n.abs(),
+            Function::Sin => n.sin(),
+            Function::Cos => n.cos(),
+            Function::Sqrt if n >= 0.0 => n.sqrt(),
+            Function::Color if (0.0..palette::RESERVED as f64).contains(&n) => return Some(Scalar::Color(n.floor() as u8)),
+            _ => return None,
+        };
+        Some(Scalar::Number(result))
+    }
+}
+
+/// Recent results of one memoized call, for one render. Each slot holds the
+/// last arguments whose bits hashed to it and the result for them.
+#[derive(Clone)]
+struct Memo {
+    slots: Vec<Option<([u64; 2], f64)>>,
+}
+
+impl Memo {
+    fn new() -> Self {
+        Self { slots: vec![None; MEMO_SLOTS] }
+    }
+
+    /// The arguments' bits, if they are all numbers.
+    fn key(args: &[Scalar]) -> Option<[u64; 2]> {
+        let mut key = [0; 2];
+        for (bits, arg) in key.iter_mut().zip(args) {
+            match arg {
+                Scalar::Number(n) => *bits = n.to_bits(),
+                Scalar::Color(_) => return None,
+            }
+        }
+        Some(key)
+    }
+
+    fn slot(key: [u64; 2]) -> usize {
+        let hash = (key[0] ^ key[1].rotate_left(32)).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+        (hash >> 32) as usize % MEMO_SLOTS
+    }
+
+    fn get(&self, key: [u64; 2]) -> Option<f64> {
+        match self.slots[Self::slot(key)] {
+            Some((stored, result)) if stored == key => Some(result),
+            _ => None,
+        }
+    }
+
+    fn insert(&mut self, key: [u64; 2], result: f64) {
+        self.slots[Self::slot(key)] = Some((key, result));
+    }
+}
+
+/// Built-ins that read a frame, compiled when the frame is a constant.
+#[derive(Debug, Clone, Copy)]
+enum Sampler {
+    GetPixel,
+    CountNeighbors,
+}
+
+impl Sampler {
+    fn name(self) -> &'static str {
+        match self {
+            Sampler::GetPixel => "get_pixel",
+            Sampler::CountNeighbors => "count_neighbors",
+        }
+    }
+}
+
+/// Which construct a conditional jump belongs to, for its error message.
+#[derive(Debug, Clone, Copy)]
+enum Condition {
+    If,
+    Ternary,
+}
+
+/// Where an instruction reads a value from.
+#[derive(Debug, Clone, Copy)]
+enum Operand {
+    Register(usize),
+    Constant(Scalar),
+}
+
+/// One instruction. Results go to a register, so `a = col * 2` is a single
+/// `Binary`.
+#[derive(Debug, Clone)]
+enum Op {
+    /// Copies a value into a register
+    Move { dst: usize, src: Operand },
+    /// Applies an operator to two numbers
+    Binary { operator: BinaryOperator, dst: usize, left: Operand, right: Operand },
+    /// Fails if a variable has no value yet; placed where the interpreter
+    /// would first read it, so errors come in the same order
+    Check(usize),
+    /// Jumps to the target if the condition is 0
+    JumpIfFalse { condition: Operand, target: usize, kind: Condition },
+    /// Jumps to the target
+    Jump(usize),
+    /// Calls a built-in with up to two arguments, looking the result up in
+    /// the given memo first
+    Call { function: Function, dst: usize, args: Vec<Operand>, memo: Option<usize> },
+    /// Samples one of the pattern's frames at (x, y)
+    Sample { sampler: Sampler, dst: usize, frame: usize, x: Operand, y: Operand },
+}
+
+/// A pattern body and return expression lowered to bytecode.
+pub struct CompiledPattern {
+    code: Vec<Op>,
+    /// Where each statement's code starts and the statement's position;
+    /// an op belongs to the last entry starting at or before it. Errors in
+    /// the return expression have none, like the interpreter's
+    positions: Vec<(usize, Option<Position>)>,
+    /// The pixel's palette index once the code has run
+    result: Operand,
+    /// Names of the variable registers; `col` and `row` first. Temporaries
+    /// come after them
+    variables: Vec<Name>,
+    /// Register contents before the first pixel: variables defined outside
+    /// the pattern have their values
+    initial: Vec<Option<Scalar>>,
+    /// Frames read by `Sample`
+    frames: Vec<Frame>,
+    /// Memos used by `Call`
+    memos: usize,
+}
+
+/// State of a compilation in progress.
+struct Compiler<'a> {
+    environment: &'a Environment,
+    builtins: &'a BuiltinFunctions,
+    code: Vec<Op>,
+    positions: Vec<(usize, Option<Position>)>,
+    /// Position of the statement being compiled, or the innermost
+    /// enclosing one that has a position
+    position: Option<Position>,
+    /// Register of every variable
+    registers: HashMap<Name, usize>,
+    /// Values of all registers before the first pixel
+    initial: Vec<Option<Scalar>>,
+    /// Registers certain to hold a value at this point of the code
+    assigned: Vec<bool>,
+    /// Registers whose value at this point of the code is known while compiling
+    known: Vec<Option<Scalar>>,
+    frames: Vec<Frame>,
+    memos: usize,
+}
+
+impl CompiledPattern {
+    /// Compiles a pattern body and return expression.
+    ///
+    /// # Arguments
+    /// * `body` - Statements run for every pixel
+    /// * `return_expr` - Expression giving the pixel's palette index
+    /// * `environment` - Variables defined outside the pattern, which become constants
+    /// * `builtins` - Functions the body may call
+    ///
+    /// # Returns
+    /// * `Some(pattern)` - The compiled pattern
+    /// * `None` - The body uses something compiled code can't do; interpret it instead
+    pub fn compile(
+        body: &[Statement],
+        return_expr: &Expression,
+        environment: &Environment,
+        builtins: &BuiltinFunctions,
+    ) -> Option<Self> {
+        let mut variables: Vec<Name> = vec!["col".into(), "row".into()];
+        for statement in body {
+            assigned_names(statement, &mut variables)?;
+        }
+        // Variables start with their values from outside the pattern, which
+        // have to fit in a register
+        let mut initial = Vec::with_capacity(variables.len());
+        for name in &variables {
+            initial.push(match environment.get(name) {
+                Ok(value) => Some(Scalar::from_value(&value)?),
+                Err(_) => None,
+            });
+        }
+
+        let mut compiler = Compiler {
+            environment,
+            builtins,
+            code: Vec::new(),
+            positions: Vec::new(),
+            position: None,
+            registers: variables.iter().enumerate().map(|(index, name)| (name.clone(), index)).collect(),
+            assigned: initial.iter().map(Option::is_some).collect(),
+            // A variable defined outside the pattern starts the next pixel
+            // with whatever the last one left in it
+            known: vec![None; initial.len()],
+            initial,
+            frames: Vec::new(),
+            memos: 0,
+        };
+        // Set for every pixel
+        compiler.assigned[COL] = true;
+        compiler.assigned[ROW] = true;
+        compiler.block(body)?;
+        let result = compiler.expression(return_expr)?;
+
+        // Variables read but never defined got registers while compiling
+        variables.resize(compiler.initial.len(), Name::from(""));
+        for (name, register) in compiler.registers {
+            variables[register] = name;
+        }
+        Some(Self {
+            code: compiler.code,
+            positions: compiler.positions,
+            result,
+            variables,
+            initial: compiler.initial,
+            frames: compiler.frames,
+            memos: compiler.memos,
+        })
+    }
+
+    /// Renders the pattern into a frame.
+    ///
+    /// # Arguments
+    /// * `w` - Frame width in pixels
+    /// * `h` - Frame height in pixels
+    /// * `environment` - Receives the final values of the variables the body assigned
+    /// * `builtins` - Reports errors in built-in calls
+    /// * `cache` - Frames of earlier renders to take the frame from, and
+    ///   to add it to
+    ///
+    /// # Returns
+    /// * `Ok(Frame)` - The rendered frame
+    /// * `Err` - The first pixel that failed, as the interpreter would report it
+    pub fn render(
+        &self,
+        w: usize,
+        h: usize,
+        environment: &mut Environment,
+        builtins: &BuiltinFunctions,
+        cache: Option<&mut PatternCache>,
+    ) -> Result<Frame> {
+        let Some((cache, key)) = cache.zip(self.cache_key(w, h)) else {
+            return self.render_uncached(w, h, environment, builtins);
+        };
+        if let Some(entry) = cache.get(key) {
+            self.write_back(environment, entry.registers.iter().copied());
+            return Ok(Frame::new(entry.pixels.clone()));
+        }
+
+        let mut registers = self.initial.clone();
+        let frame = self.render_pixels(w, h, &mut registers, builtins);
+        self.write_back(environment, registers.iter().copied());
+        let frame = frame?;
+        cache.insert(key, CachedPattern { pixels: frame.pixels.to_vec(), registers });
+        Ok(frame)
+    }
+
+    /// Renders the pattern without a cache, like `render()`.
+    fn render_uncached(
+        &self,
+        w: usize,
+        h: usize,
+        environment: &mut Environment,
+        builtins: &BuiltinFunctions,
+    ) -> Result<Frame> {
+        let mut registers = self.initial.clone();
+        let frame = self.render_pixels(w, h, &mut registers, builtins);
+        self.write_back(environment, registers);
+        frame
+    }
+
+    /// Hashes everything the frame and final registers depend on.
+    ///
+    /// # Returns
+    /// * `Some(key)` - The pattern's cache key at this size
+    /// * `None` - The pattern calls `random()`, so it can't be cached
+    fn cache_key(&self, w: usize, h: usize) -> Option<u64> {
+        let random = self
+            .code
+            .iter()
+            .any(|op| matches!(op, Op::Call { function: Function::Random, .. }));
+        if random {
+            return None;
+        }
+        // Debug output tells every value apart, including -0 and NaN
+        let mut hasher = Fnv1a::new();
+        hasher.write(env!("CARGO_PKG_VERSION").as_bytes());
+        hasher.write(format!("{:?} {:?} {:?} {:?}", self.code, self.result, self.variables, self.initial).as_bytes());
+        for frame in &self.frames {
+            hasher.write_u64(frame.height as u64);
+            for row in frame.pixels.iter() {
+                hasher.write(row);
+            }
+        }
+        hasher.write_u64(w as u64);
+        hasher.write_u64(h as u64);
+        Some(hasher.finish())
+    }
+
+    /// Leaves the variables as interpreting the body would have.
+    fn write_back(&self, environment: &mut Environment, registers: impl IntoIterator<Item = Option<Scalar>>) {
+        for (name, value) in self.variables.iter().zip(registers) {
+            if let Some(value) = value {
+                environment.define(name.clone(), value.to_value());
+            }
+        }
+    }
+
+    /// Runs the code for every pixel.
+    ///
+    /// # Arguments
+    /// * `registers` - Register contents before the first pixel; left as
+    ///   the last pixel run (the failing one on errors) left them
+    ///
+    /// # Returns
+    /// * `Ok(Frame)` - The rendered frame
+    /// * `Err` - The first pixel that failed
+    fn render_pixels(
+        &self,
+        w: usize,
+        h: usize,
+        registers: &mut [Option<Scalar>],
+        builtins: &BuiltinFunctions,
+    ) -> Result<Frame> {
+        let mut memos = vec![Memo::new(); self.memos];
+        let mut frame_data = vec![vec![0u8; w]; h];
+
+        for (row, pixels) in frame_data.iter_mut().enumerate() {
+            for (col, pixel) in pixels.iter_mut().enumerate() {
+                registers[COL] = Some(Scalar::Number(col as f64));
+                registers[ROW] = Some(Scalar::Number(row as f64));
+                *pixel = self.run(registers, &mut memos, builtins)?;
+            }
+        }
+        Ok(Frame::new(frame_data))
+    }
+
+    /// Runs the code for one pixel.
+    ///
+    /// # Returns
+    /// * `Ok(index)` - The pixel's palette index
+    /// * `Err` - A runtime error, at the statement it happened in
+    fn run(&self, registers: &mut [Option<Scalar>], memos: &mut [Memo], builtins: &BuiltinFunctions) -> Result<u8> {
+        let mut pc = 0;
+        if let Err(error) = self.execute(&mut pc, registers, memos, builtins) {
+            // `pc` is already past the failing op
+            return Err(match self.position(pc - 1) {
+                Some(position) => error.at(position),
+                None => error,
+            });
+        }
+
+        match self.read(registers, &self.result)? {
+            Scalar::Number(n) => Ok((n != 0.0) as u8), // 0.0 = off, non-zero = on (index 1)
+            Scalar::Color(index) => Ok(index),         // Explicit palette index from color(n)
+        }
+    }
+
+    /// Returns the position of the statement an op was compiled from.
+    fn position(&self, pc: usize) -> Option<Position> {
+        let entries = self.positions.partition_point(|(start, _)| *start <= pc);
+        entries.checked_sub(1).and_then(|entry| self.positions[entry].1)
+    }
+
+    /// Runs the code, leaving `pc` past the last op run.
+    fn execute(
+        &self,
+        pc: &mut usize,
+        registers: &mut [Option<Scalar>],
+        memos: &mut [Memo],
+        builtins: &BuiltinFunctions,
+    ) -> Result<()> {
+        while let Some(op) = self.code.get(*pc) {
+            *pc += 1;
+            match op {
+                Op::Move { dst, src } => registers[*dst] = Some(self.read(registers, src)?),
+                Op::Binary { operator, dst, left, right } => {
+                    let value = match (self.read(registers, left)?, self.read(registers, right)?) {
+                        (Scalar::Number(l), Scalar::Number(r)) => apply_binary_operator(operator, l, r)?,
+                        _ => {
+                            return Err(GizmoError::TypeError(
+                                "Binary operations only supported for numbers".to_string(),
+                            ))
+                        }
+                    };
+                    registers[*dst] = Some(Scalar::Number(value));
+                }
+                Op::Check(register) => {
+                    self.read(registers, &Operand::Register(*register))?;
+                }
+                Op::JumpIfFalse { condition, target, kind } => match (self.read(registers, condition)?, kind) {
+                    (Scalar::Number(n), _) => {
+                        if n == 0.0 {
+                            *pc = *target;
+                        }
+                    }
+                    (_, Condition::If) => {
+                        return Err(GizmoError::TypeError("if condition must be a number".to_string()))
+                    }
+                    (_, Condition::Ternary) => {
+                        return Err(GizmoError::TypeError("ternary condition must be a number".to_string()))
+                    }
+                },
+                Op::Jump(target) => *pc = *target,
+                Op::Call { function, dst, args, memo } => {
+                    let mut values = [Scalar::Number(0.0); 2];
+                    for (value, arg) in values.iter_mut().zip(args) {
+                        *value = self.read(registers, arg)?;
+                    }
+                    let values = &values[..args.len()];
+                    let memo = memo.zip(Memo::key(values));
+                    if let Some((memo, key)) = memo {
+                        if let Some(result) = memos[memo].get(key) {
+                            registers[*dst] = Some(Scalar::Number(result));
+                            continue;
+                        }
+                    }
+                    let result = match function.apply(values) {
+                        Some(result) => result,
+                        None => call_builtin(builtins, function.name(), values.iter().map(|arg| arg.to_value()).collect())?,
+                    };
+                    if let (Some((memo, key)), Scalar::Number(n)) = (memo, result) {
+                        memos[memo].insert(key, n);
+                    }
+                    registers[*dst] = Some(result);
+                }
+                Op::Sample { sampler, dst, frame, x, y } => {
+                    let frame = &self.frames[*frame];
+                    let (x, y) = (self.read(registers, x)?, self.read(registers, y)?);
+                    registers[*dst] = Some(match (sampler, x, y) {
+                        (Sampler::GetPixel, Scalar::Number(x), Scalar::Number(y)) => {
+                            Scalar::Number(builtin::pixel_at(frame, x, y))
+                        }
+                        (Sampler::CountNeighbors, Scalar::Number(x), Scalar::Number(y)) => {
+                            Scalar::Number(builtin::neighbor_count(frame, x, y))
+                        }
+                        _ => {
+                            let args = vec![Value::Frame(frame.clone()), x.to_value(), y.to_value()];
+                            call_builtin(builtins, sampler.name(), args)?
+                        }
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads an operand.
+    ///
+    /// # Returns
+    /// * `Ok(value)` - The operand's value
+    /// * `Err(GizmoError::UndefinedVariable)` - A variable without a value
+    #[inline]
+    fn read(&self, registers: &[Option<Scalar>], operand: &Operand) -> Result<Scalar> {
+        match *operand {
+            Operand::Constant(value) => Ok(value),
+            Operand::Register(register) => registers[register]
+                .ok_or_else(|| GizmoError::UndefinedVariable(self.variables[register].to_string())),
+        }
+    }
+}
+
+/// Calls a built-in through the registry, for the error it reports.
+fn call_builtin(builtins: &BuiltinFunctions, name: &str, args: Vec<Value>) -> Result<Scalar> {
+    let result = builtins.call(name, &args)?;
+    Scalar::from_value(&result).ok_or_else(|| GizmoError::TypeError(format!("{} returned {:?}", name, result)))
+}
+
+/// Adds the variables a statement assigns to `names`.
+///
+/// # Returns
+/// * `Some(())` - Every statement can be compiled
+/// * `None` - A statement can't be compiled
+fn assigned_names(statement: &Statement, names: &mut Vec<Name>) -> Option<()> {
+    match &statement.kind {
+        StatementKind::VariableDeclaration { name, .. } | StatementKind::Assignment { name, .. } => {
+            // Assigning a system variable is an error the interpreter reports
+            check_writable(name).ok()?;
+            if !names.contains(name) {
+                names.push(name.clone());
+            }
+        }
+        StatementKind::IfStatement { then_body, else_body, .. } => {
+            for statement in then_body.iter().chain(else_body.iter().flatten()) {
+                assigned_names(statement, names)?;
+            }
+        }
+        StatementKind::Trivia(_) => {}
+        _ => return None,
+    }
+    Some(())
+}
+
+impl Compiler<'_> {
+    /// Compiles the statements of a block.
+    fn block(&mut self, body: &[Statement]) -> Option<()> {
+        for statement in body {
+            self.statement(statement)?;
+        }
+        Some(())
+    }
+
+    /// Compiles a statement, recording its position for the ops it adds.
+    fn statement(&mut self, statement: &Statement) -> Option<()> {
+        let enclosing = self.position;
+        self.position = statement.position.or(enclosing);
+        self.positions.push((self.code.len(), self.position));
+        self.statement_kind(&statement.kind)?;
+        // Ops after a nested statement belong to the enclosing one again
+        self.position = enclosing;
+        self.positions.push((self.code.len(), enclosing));
+        Some(())
+    }
+
+    fn statement_kind(&mut self, kind: &StatementKind) -> Option<()> {
+        match kind {
+            StatementKind::VariableDeclaration { name, value, .. } | StatementKind::Assignment { name, value } => {
+                let register = self.registers[name];
+                // Still stored, for the variables written back after rendering
+                self.known[register] = self.expression_into(value, register)?;
+                self.assigned[register] = true;
+            }
+            StatementKind::IfStatement { condition, then_body, else_body } => {
+                let condition = self.expression(condition)?;
+                if let Operand::Constant(Scalar::Number(n)) = condition {
+                    return match (n != 0.0, else_body) {
+                        (true, _) => self.block(then_body),
+                        (false, Some(else_body)) => self.block(else_body),
+                        (false, None) => Some(()),
+                    };
+                }
+                let jump_to_else = self.placeholder();
+                let before = (self.assigned.clone(), self.known.clone());
+                self.block(then_body)?;
+                let (after_then, known_after_then) =
+                    (std::mem::replace(&mut self.assigned, before.0), std::mem::replace(&mut self.known, before.1));
+                match else_body {
+                    Some(else_body) => {
+                        let jump_to_end = self.placeholder();
+                        self.code[jump_to_else] = Op::JumpIfFalse { condition, target: self.code.len(), kind: Condition::If };
+                        self.block(else_body)?;
+                        self.code[jump_to_end] = Op::Jump(self.code.len());
+                    }
+                    None => {
+                        self.code[jump_to_else] = Op::JumpIfFalse { condition, target: self.code.len(), kind: Condition::If };
+                    }
+                }
+                // Only what both branches assign is certain afterwards
+                for (assigned, then_assigned) in self.assigned.iter_mut().zip(after_then) {
+                    *assigned &= then_assigned;
+                }
+                for (known, then_known) in self.known.iter_mut().zip(known_after_then) {
+                    if !matches!((*known, then_known), (Some(a), Some(b)) if a.same(b)) {
+                        *known = None;
+                    }
+                }
+            }
+            StatementKind::Trivia(_) => {}
+            _ => return None,
+        }
+        Some(())
+    }
+
+    /// Compiles an expression.
+    ///
+    /// # Returns
+    /// * `Some(operand)` - Where the value is once the code so far has run
+    /// * `None` - The expression can't be compiled
+    fn expression(&mut self, expression: &Expression) -> Option<Operand> {
+        match expression {
+            Expression::Number(n) => Some(Operand::Constant(Scalar::Number(*n))),
+            Expression::Identifier(name) => self.variable(name),
+            Expression::Grouping(inner) | Expression::LineBreak(inner) => self.expression(inner),
+            _ => {
+                let temporary = self.temporary();
+                match self.expression_into(expression, temporary)? {
+                    Some(value) => {
+                        // Folded: the only code is the move to the temporary
+                        self.code.pop();
+                        Some(Operand::Constant(value))
+                    }
+                    None => Some(Operand::Register(temporary)),
+                }
+            }
+        }
+    }
+
+    /// Compiles an expression whose value goes to a register.
+    ///
+    /// # Returns
+    /// * `Some(Some(value))` - The expression folded to a constant; the code
+    ///   is a single `Move` of it
+    /// * `Some(None)` - The value is only known when a pixel runs
+    /// * `None` - The expression can't be compiled
+    fn expression_into(&mut self, expression: &Expression, dst: usize) -> Option<Option<Scalar>> {
+        match expression {
+            Expression::BinaryOperation { left, operator, right } => {
+                let left = self.expression(left)?;
+                let right = self.expression(right)?;
+                if let (Operand::Constant(Scalar::Number(l)), Operand::Constant(Scalar::Number(r))) = (left, right) {
+                    // Errors such as dividing by zero are left for a pixel to report
+                    if let Ok(value) = apply_binary_operator(operator, l, r) {
+                        return Some(Some(self.constant(dst, Scalar::Number(value))));
+                    }
+                }
+                self.code.push(Op::Binary { operator: operator.clone(), dst, left, right });
+                Some(None)
+            }
+            Expression::TernaryOperation { condition, true_expr, false_expr } => {
+                let condition = self.expression(condition)?;
+                if let Operand::Constant(Scalar::Number(n)) = condition {
+                    return self.expression_into(if n != 0.0 { true_expr } else { false_expr }, dst);
+                }
+                let jump_to_false = self.placeholder();
+                self.expression_into(true_expr, dst)?;
+                let jump_to_end = self.placeholder();
+                self.code[jump_to_false] = Op::JumpIfFalse { condition, target: self.code.len(), kind: Condition::Ternary };
+                self.expression_into(false_expr, dst)?;
+                self.code[jump_to_end] = Op::Jump(self.code.len());
+                Some(None)
+            }
+            Expression::FunctionCall { name, args } => self.call(name, args, dst),
+            Expression::Grouping(inner) | Expression::LineBreak(inner) => self.expression_into(inner, dst),
+            Expression::Number(_) | Expression::Identifier(_) => {
+                let src = self.expression(expression)?;
+                self.code.push(Op::Move { dst, src });
+                match src {
+                    Operand::Constant(value) => Some(Some(value)),
+                    Operand::Register(_) => Some(None),
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Stores a folded value.
+    fn constant(&mut self, dst: usize, value: Scalar) -> Scalar {
+        self.code.push(Op::Move { dst, src: Operand::Constant(value) });
+        value
+    }
+
+    /// Compiles a read of a variable.
+    fn variable(&mut self, name: &str) -> Option<Operand> {
+        let register = match self.registers.get(name) {
+            Some(register) => *register,
+            None => match self.environment.get(name) {
+                // Can't change while the pattern renders
+                Ok(value) => return Some(Operand::Constant(Scalar::from_value(&value)?)),
+                // Only an error if a pixel gets here: give it a register that
+                // never gets a value
+                Err(_) => {
+                    let register = self.temporary();
+                    self.registers.insert(name.into(), register);
+                    register
+                }
+            },
+        };
+        if let Some(value) = self.known[register] {
+            return Some(Operand::Constant(value));
+        }
+        if !self.assigned[register] {
+            self.code.push(Op::Check(register));
+        }
+        Some(Operand::Register(register))
+    }
+
+    /// Compiles a built-in call, like `expression_into()`.
+    fn call(&mut self, name: &str, args: &[Expression], dst: usize) -> Option<Option<Scalar>> {
+        if !self.builtins.has_function(name) {
+            return None;
+        }
+        if let Some(function) = Function::from_name(name) {
+            if args.len() > 2 {
+                return None;
+            }
+            let args = args.iter().map(|arg| self.expression(arg)).collect::<Option<Vec<_>>>()?;
+            let values: Option<Vec<_>> = args
+                .iter()
+                .map(|arg| match arg {
+                    Operand::Constant(value) => Some(*value),
+                    Operand::Register(_) => None,
+                })
+                .collect();
+            // random() differs for every pixel
+            if let (Some(values), false) = (values, matches!(function, Function::Random)) {
+                if let Some(value) = function.apply(&values) {
+                    return Some(Some(self.constant(dst, value)));
+                }
+            }
+            let memo = function.memoized().then(|| {
+                self.memos += 1;
+                self.memos - 1
+            });
+            self.code.push(Op::Call { function, dst, args, memo });
+            return Some(None);
+        }
+
+        let sampler = match name {
+            "get_pixel" => Sampler::GetPixel,
+            "count_neighbors" => Sampler::CountNeighbors,
+            _ => return None,
+        };
+        // Only frames that are constants, such as `previous`
+        let [Expression::Identifier(frame), x, y] = args else {
+            return None;
+        };
+        if self.registers.contains_key(frame) {
+            return None;
+        }
+        let Ok(Value::Frame(frame)) = self.environment.get(frame) else {
+            return None;
+        };
+        let x = self.expression(x)?;
+        let y = self.expression(y)?;
+        if let (Operand::Constant(Scalar::Number(x)), Operand::Constant(Scalar::Number(y))) = (x, y) {
+            let value = match sampler {
+                Sampler::GetPixel => builtin::pixel_at(&frame, x, y),
+                Sampler::CountNeighbors => builtin::neighbor_count(&frame, x, y),
+            };
+            return Some(Some(self.constant(dst, Scalar::Number(value))));
+        }
+        self.frames.push(frame);
+        self.code.push(Op::Sample { sampler, dst, frame: self.frames.len() - 1, x, y });
+        Some(None)
+    }
+
+    /// Adds a register for an intermediate value.
+    fn temporary(&mut self) -> usize {
+        self.initial.push(None);
+        self.assigned.push(false);
+        self.known.push(None);
+        self.initial.len() - 1
+    }
+
+    /// Reserves a jump to be filled in once its target is known.
+    fn placeholder(&mut self) -> usize {
+        self.code.push(Op::Jump(usize::MAX));
+        self.code.len() - 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpreter::Interpreter;
+
+    const W: usize = 6;
+    const H: usize = 4;
+
+    /// The frame a render made (or its error) and the values it left in the
+    /// variables asked about.
+    type Outcome = (std::result::Result<Frame, String>, Vec<Option<Value>>);
+
+    /// Runs `setup`, then renders a pattern body by walking it, compiled,
+    /// and compiled again from the cache the first compiled render filled,
+    /// and checks all three leave the same frame and variables.
+    ///
+    /// # Arguments
+    /// * `setup` - Statements run before the pattern
+    /// * `body` - The pattern body, return included
+    /// * `variables` - Variables to compare afterwards, besides `col` and `row`
+    fn assert_same(setup: &str, body: &str, variables: &[&str]) {
+        let source = format!("{}\nframe under_test = pattern({}, {}) {{\n{}\n}};", setup, W, H, body);
+        let mut program = crate::parse(&source).unwrap();
+        let pattern = program.statements.pop().unwrap();
+        let StatementKind::VariableDeclaration {
+            value: Expression::PatternGenerator { body, return_expr, .. },
+            ..
+        } = pattern.kind
+        else {
+            panic!("not a pattern: {:?}", pattern);
+        };
+        let names: Vec<&str> = ["col", "row"].iter().chain(variables).copied().collect();
+        let outcome = |interpreter: &mut Interpreter, frame: Result<Frame>| -> Outcome {
+            let environment = interpreter.environment_mut();
+            (
+                frame.map_err(|e| e.to_string()),
+                names.iter().map(|name| environment.get(name).ok()).collect(),
+            )
+        };
+
+        let mut walked = Interpreter::new();
+        walked.execute(&program).unwrap();
+        let frame = walked.walk_pattern(W, H, &body, &return_expr);
+        let expected = outcome(&mut walked, frame);
+
+        let builtins = BuiltinFunctions::new();
+        let mut cache = PatternCache::new();
+        for render in ["compiled", "cached"] {
+            let mut interpreter = Interpreter::new();
+            interpreter.execute(&program).unwrap();
+            let pattern = CompiledPattern::compile(&body, &return_expr, interpreter.environment_mut(), &builtins)
+                .expect("the body should compile");
+            let frame = pattern.render(W, H, interpreter.environment_mut(), &builtins, Some(&mut cache));
+            assert_eq!(outcome(&mut interpreter, frame), expected, "{} render differs from the interpreter", render);
+        }
+    }
+
+    #[test]
+    fn if_statements() {
+        assert_same(
+            "",
+            "d = col - row;\nif d > 1 then\n    v = color(2);\nelse\n    v = d == 0;\nend;\nreturn v;",
+            &["d", "v"],
+        );
+    }
+
+    #[test]
+    fn ternaries_and_colors() {
+        assert_same("", "return col > 2 ? color(col % 3 + 1) : row == 1;", &[]);
+        assert_same("", "c = row > 1 ? color(5) : 0;\nreturn c;", &["c"]);
+    }
+
+    #[test]
+    fn sampling_previous() {
+        assert_same(
+            "frame previous = [[1, 0, 1, 0, 1, 0], [0, 1, 1, 0, 0, 1], [1, 1, 0, 0, 1, 1], [0, 0, 0, 1, 0, 1]];",
+            "left = get_pixel(previous, col - 1, row);\nreturn left + count_neighbors(previous, col, row) > 2;",
+            &["left"],
+        );
+    }
+
+    #[test]
+    fn folded_constants() {
+        assert_same(
+            "center = 3;\nscale = 2;",
+            "cx = center * scale;\nif scale > 1 then\n    k = 1;\nelse\n    k = 0;\nend;\nd = abs(col - cx / 2) + abs(row - center / 2) * k;\nreturn scale > 5 ? 0 : d < 3;",
+            &["cx", "k", "d"],
+        );
+    }
+
+    #[test]
+    fn memoized_calls() {
+        assert_same(
+            "",
+            "s = sin(col * 0.7) + cos(row * 1.3) + sqrt(col) + atan2(row, col + 1);\nreturn s > 0.5 ? color(2) : 0;",
+            &["s"],
+        );
+    }
+
+    #[test]
+    fn variables_from_outside_carry_over_between_pixels() {
+        assert_same("total = 0;", "total = total + col * row;\nreturn total % 2;", &["total"]);
+    }
+
+    #[test]
+    fn errors_stop_at_the_same_pixel() {
+        assert_same("", "last = col + row * 10;\nreturn 1 / (last - 13);", &["last"]);
+    }
+
+    #[test]
+    fn errors_are_at_the_statement_that_failed() {
+        assert_same("", "d = 1;\n  e = d / (col - 1);\nreturn e;", &["d", "e"]);
+        assert_same("", "if row > 1 then\n    d = 2;\n    e = d / (col - 3);\nend;\nreturn 1;", &["d", "e"]);
+        assert_same("", "d = row > 2 ? undefined_name : 1;\nreturn d;", &["d"]);
+    }
+}
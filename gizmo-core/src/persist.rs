@@ -0,0 +1,28 @@
+//! Persistent Variables
+//!
+//! The values `persist` variables carry from one session to the next. The
+//! interpreter only hands them over and takes them back
+//! ([`Interpreter::restore_persisted`](crate::interpreter::Interpreter::restore_persisted),
+//! [`Interpreter::persisted_values`](crate::interpreter::Interpreter::persisted_values));
+//! where they are stored is up to the program embedding it.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// A value a `persist` variable can hold between sessions.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Persisted {
+    Number(f64),
+    Text(String),
+}
+
+/// State saved by the script's previous session.
+#[derive(Debug, Default)]
+pub struct SavedState {
+    /// Persisted variables by name
+    pub values: HashMap<String, Persisted>,
+    /// How long ago the state was saved (zero for a new pet)
+    pub away: Duration,
+}
@@ -0,0 +1,98 @@
+//! Window Placement Rules
+//!
+//! Keeps the buddy window somewhere the user can see and grab it. While the
+//! window is dragged, every proposed position passes through [`constrain`],
+//! which:
+//!
+//! 1. Picks the monitor under the window's center (or the closest one when the
+//!    center is between monitors)
+//! 2. Clamps the window so it stays fully inside that monitor
+//! 3. Snaps it flush against an edge when it comes within [`SNAP_DISTANCE`]
+//!    pixels of it; snapping both axes at once lands it in a corner
+//!
+//! All coordinates are physical pixels, matching winit's outer positions.
+
+/// How close (in physical pixels) the window must get to an edge before it snaps.
+pub const SNAP_DISTANCE: i32 = 16;
+
+/// An axis-aligned rectangle in physical desktop coordinates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bounds {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl Bounds {
+    /// Creates bounds from a top-left corner and a size.
+    pub fn new(x: i32, y: i32, width: u32, height: u32) -> Self {
+        Self {
+            x,
+            y,
+            width: width as i32,
+            height: height as i32,
+        }
+    }
+
+    /// Squared distance from a point to the nearest point inside these bounds.
+    fn distance_squared(&self, px: i32, py: i32) -> i64 {
+        let dx = (self.x - px).max(0).max(px - (self.x + self.width)) as i64;
+        let dy = (self.y - py).max(0).max(py - (self.y + self.height)) as i64;
+        dx * dx + dy * dy
+    }
+}
+
+/// Adjusts a proposed window position so the window stays on a monitor.
+///
+/// # Arguments
+/// * `x`, `y` - Proposed top-left corner of the window
+/// * `width`, `height` - Outer size of the window
+/// * `monitors` - Bounds of every connected monitor
+///
+/// # Returns
+/// The clamped and snapped top-left corner. If no monitors are known the
+/// proposed position is returned unchanged.
+pub fn constrain(x: i32, y: i32, width: u32, height: u32, monitors: &[Bounds]) -> (i32, i32) {
+    let monitor = match monitor_for(x, y, width, height, monitors) {
+        Some(monitor) => monitor,
+        None => return (x, y),
+    };
+
+    let (width, height) = (width as i32, height as i32);
+    (
+        constrain_axis(x, width, monitor.x, monitor.width),
+        constrain_axis(y, height, monitor.y, monitor.height),
+    )
+}
+
+/// Finds the monitor a window belongs to: the one under its center, or the
+/// closest one when the center is off every monitor.
+///
+/// # Returns
+/// * `Some(&Bounds)` - The window's monitor
+/// * `None` - No monitors are known
+pub fn monitor_for(x: i32, y: i32, width: u32, height: u32, monitors: &[Bounds]) -> Option<&Bounds> {
+    let (center_x, center_y) = (x + width as i32 / 2, y + height as i32 / 2);
+    monitors
+        .iter()
+        .min_by_key(|monitor| monitor.distance_squared(center_x, center_y))
+}
+
+/// Clamps and snaps one axis of the window against one axis of a monitor.
+fn constrain_axis(position: i32, size: i32, start: i32, extent: i32) -> i32 {
+    let end = start + extent - size;
+    if end <= start {
+        // Window is larger than the monitor on this axis: pin it to the start
+        return start;
+    }
+
+    let position = position.clamp(start, end);
+    if position - start <= SNAP_DISTANCE {
+        start
+    } else if end - position <= SNAP_DISTANCE {
+        end
+    } else {
+        position
+    }
+}
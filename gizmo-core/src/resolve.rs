@@ -0,0 +1,363 @@
+//! Static Checks
+//!
+//! Running a script finds the errors in the code it runs, but an event
+//! handler or an `if` branch that doesn't run yet only fails once it does,
+//! maybe hours into the animation. [`resolve`] goes through the whole
+//! program without running it and finds the errors running it would run
+//! into:
+//!
+//! - **Undefined variables**: read where nothing can have assigned them yet
+//! - **Unknown functions**: calls to names that aren't built in
+//! - **Argument counts**: calls with more or fewer arguments than the
+//!   built-in function takes, or with `name = value` arguments it doesn't
+//! - **Argument kinds**: a string passed to `sin()`, a number to `play()`,
+//!   ... where the kind is plain from the code: a literal, a pattern, or what
+//!   a built-in function returns. Variables can hold anything, so arguments
+//!   read from variables aren't checked.
+//!
+//! ## Order
+//!
+//! The top level runs in order, so a variable has to be assigned above where
+//! it is read. Both branches of an `if` count as assigning, and so does a
+//! `repeat` body, though they might not run. Event handlers run after the
+//! top level, in any order, so they can read whatever the top level or any
+//! handler assigns.
+//!
+//! The runtime's own variables (`hour`, `cpu_percent`, ...) are always
+//! there; `time` is from the first `repeat` on, `col` and `row` from the
+//! first pattern body, and `previous` from a pipeline's second pass.
+
+use crate::ast::{Event, Expression, Program, Statement, StatementKind};
+use crate::builtin::{BuiltinFunctions, ValueKind};
+use crate::error::GizmoError;
+use crate::interpreter::SYSTEM_VARIABLES;
+use crate::lexer::Position;
+use std::collections::HashSet;
+
+/// Variables the runtime sets besides the system variables.
+const RUNTIME_VARIABLES: &[&str] = &["hour", "minute", "weekday", "drop_x", "drop_y"];
+
+/// Finds the errors a program would run into, without running it.
+///
+/// # Arguments
+/// * `program` - The parsed script, ideally with positions
+///   ([`parse`](crate::parse)) so errors say where they are
+///
+/// # Returns
+/// Every error found, in source order where positions are known; each
+/// undefined variable is reported where it is first read
+pub fn resolve(program: &Program) -> Vec<GizmoError> {
+    let mut assigned = HashSet::new();
+    assignments(&program.statements, &mut assigned);
+    let mut resolver = Resolver {
+        builtins: BuiltinFunctions::new(),
+        defined: SYSTEM_VARIABLES.iter().chain(RUNTIME_VARIABLES).copied().collect(),
+        assigned,
+        handlers: Vec::new(),
+        reported: HashSet::new(),
+        errors: Vec::new(),
+        position: None,
+    };
+    resolver.block(&program.statements);
+
+    // Each handler may run after any of the others
+    let handlers = std::mem::take(&mut resolver.handlers);
+    for body in &handlers {
+        assignments(body, &mut resolver.defined);
+    }
+    let defined = resolver.defined.clone();
+    resolver.handlers = handlers;
+    let mut next = 0;
+    while next < resolver.handlers.len() {
+        resolver.defined = defined.clone();
+        resolver.block(resolver.handlers[next]);
+        next += 1;
+    }
+
+    let mut errors = resolver.errors;
+    errors.sort_by_key(|error| error.position());
+    errors
+}
+
+/// Adds the variables a block assigns, its handlers and patterns included,
+/// to `names`.
+fn assignments<'a>(body: &'a [Statement], names: &mut HashSet<&'a str>) {
+    for statement in body {
+        match &statement.kind {
+            StatementKind::VariableDeclaration { name, value, .. }
+            | StatementKind::Assignment { name, value }
+            | StatementKind::PersistDeclaration { name, value } => {
+                names.insert(name);
+                expression_assignments(value, names);
+            }
+            StatementKind::ExpressionStatement(expression) => expression_assignments(expression, names),
+            StatementKind::RepeatLoop { count, body } => {
+                names.insert("time");
+                expression_assignments(count, names);
+                assignments(body, names);
+            }
+            StatementKind::IfStatement { condition, then_body, else_body } => {
+                expression_assignments(condition, names);
+                assignments(then_body, names);
+                assignments(else_body.as_deref().unwrap_or_default(), names);
+            }
+            StatementKind::WhenStatement { body, .. } => assignments(body, names),
+            StatementKind::Trivia(_) => {}
+        }
+    }
+}
+
+/// Adds the variables the pattern bodies in an expression assign to `names`.
+fn expression_assignments<'a>(expression: &'a Expression, names: &mut HashSet<&'a str>) {
+    match expression {
+        Expression::PatternGenerator { body, .. } => {
+            names.extend(["col", "row"]);
+            assignments(body, names);
+        }
+        Expression::Pipeline { passes, .. } => {
+            names.extend(["col", "row", "previous"]);
+            for pass in passes {
+                assignments(&pass.body, names);
+            }
+        }
+        Expression::Array(items) | Expression::FunctionCall { args: items, .. } => {
+            for item in items {
+                expression_assignments(item, names);
+            }
+        }
+        Expression::NamedArgument { value: inner, .. } | Expression::Grouping(inner) | Expression::LineBreak(inner) => {
+            expression_assignments(inner, names);
+        }
+        Expression::BinaryOperation { left, right, .. } => {
+            expression_assignments(left, names);
+            expression_assignments(right, names);
+        }
+        Expression::TernaryOperation { condition, true_expr, false_expr } => {
+            expression_assignments(condition, names);
+            expression_assignments(true_expr, names);
+            expression_assignments(false_expr, names);
+        }
+        Expression::Number(_) | Expression::String(_) | Expression::Identifier(_) => {}
+    }
+}
+
+/// What the walk through a program knows and has found so far.
+struct Resolver<'a> {
+    builtins: BuiltinFunctions,
+    /// Variables assigned at this point of the walk
+    defined: HashSet<&'a str>,
+    /// Variables assigned anywhere in the program
+    assigned: HashSet<&'a str>,
+    /// Bodies of the handlers met so far, walked after the top level
+    handlers: Vec<&'a [Statement]>,
+    /// Undefined variables already reported
+    reported: HashSet<&'a str>,
+    errors: Vec<GizmoError>,
+    /// Position of the statement being walked
+    position: Option<Position>,
+}
+
+impl<'a> Resolver<'a> {
+    fn error(&mut self, error: GizmoError) {
+        self.errors.push(match self.position {
+            Some(position) => error.at(position),
+            None => error,
+        });
+    }
+
+    fn block(&mut self, body: &'a [Statement]) {
+        for statement in body {
+            self.statement(statement);
+        }
+    }
+
+    fn statement(&mut self, statement: &'a Statement) {
+        self.position = statement.position;
+        match &statement.kind {
+            StatementKind::VariableDeclaration { name, value, .. }
+            | StatementKind::Assignment { name, value }
+            | StatementKind::PersistDeclaration { name, value } => {
+                self.expression(value);
+                self.defined.insert(name);
+            }
+            StatementKind::ExpressionStatement(expression) => self.expression(expression),
+            StatementKind::RepeatLoop { count, body } => {
+                self.expression(count);
+                self.defined.insert("time");
+                self.block(body);
+            }
+            StatementKind::IfStatement { condition, then_body, else_body } => {
+                self.expression(condition);
+                self.block(then_body);
+                if let Some(else_body) = else_body {
+                    self.block(else_body);
+                }
+            }
+            StatementKind::WhenStatement { event, body, priority, .. } => {
+                // The event's arguments and priority are read on registering
+                match event {
+                    Event::ClickCombo { times, within } => {
+                        self.expression(times);
+                        self.expression(within);
+                    }
+                    Event::Key(expression)
+                    | Event::Time(expression)
+                    | Event::Message(expression)
+                    | Event::Idle(expression)
+                    | Event::Every(expression)
+                    | Event::Battery(expression) => self.expression(expression),
+                    _ => {}
+                }
+                if let Some(priority) = priority {
+                    self.expression(priority);
+                }
+                self.handlers.push(body);
+            }
+            StatementKind::Trivia(_) => {}
+        }
+    }
+
+    fn expression(&mut self, expression: &'a Expression) {
+        match expression {
+            Expression::Number(_) | Expression::String(_) => {}
+            Expression::Identifier(name) => self.read(name),
+            Expression::Array(items) => {
+                for item in items {
+                    self.expression(item);
+                }
+            }
+            Expression::FunctionCall { name, args } => {
+                for arg in args {
+                    self.expression(arg);
+                }
+                self.call(name, args);
+            }
+            Expression::NamedArgument { value: inner, .. } | Expression::Grouping(inner) | Expression::LineBreak(inner) => {
+                self.expression(inner);
+            }
+            Expression::BinaryOperation { left, right, .. } => {
+                self.expression(left);
+                self.expression(right);
+            }
+            Expression::PatternGenerator { width, height, body, return_expr } => {
+                self.expression(width);
+                self.expression(height);
+                self.pattern(body, return_expr);
+            }
+            Expression::TernaryOperation { condition, true_expr, false_expr } => {
+                self.expression(condition);
+                self.expression(true_expr);
+                self.expression(false_expr);
+            }
+            Expression::Pipeline { width, height, passes } => {
+                self.expression(width);
+                self.expression(height);
+                for (index, pass) in passes.iter().enumerate() {
+                    if index > 0 {
+                        self.defined.insert("previous");
+                    }
+                    self.pattern(&pass.body, &pass.return_expr);
+                }
+            }
+        }
+    }
+
+    /// Walks a pattern body, whose statements belong to the enclosing one.
+    fn pattern(&mut self, body: &'a [Statement], return_expr: &'a Expression) {
+        let position = self.position;
+        self.defined.extend(["col", "row"]);
+        self.block(body);
+        self.position = position;
+        self.expression(return_expr);
+    }
+
+    /// Reports a variable read before anything can have assigned it.
+    fn read(&mut self, name: &'a str) {
+        if self.defined.contains(name) || !self.reported.insert(name) {
+            return;
+        }
+        let name = if self.assigned.contains(name) {
+            format!("{} (it is only assigned further on)", name)
+        } else {
+            name.to_string()
+        };
+        self.error(GizmoError::UndefinedVariable(name));
+    }
+
+    /// Checks a call against the built-in function it calls.
+    fn call(&mut self, name: &str, args: &[Expression]) {
+        if !self.builtins.has_function(name) {
+            self.error(GizmoError::UndefinedFunction(name.to_string()));
+            return;
+        }
+        if !self.builtins.accepts_named_arguments(name) {
+            if let Some(Expression::NamedArgument { name: arg_name, .. }) =
+                args.iter().find(|arg| matches!(arg, Expression::NamedArgument { .. }))
+            {
+                self.error(GizmoError::ArgumentError(format!(
+                    "{} does not take named arguments (got '{}')",
+                    name, arg_name
+                )));
+                return;
+            }
+        }
+
+        let Some(signature) = self.builtins.signature(name) else {
+            return;
+        };
+        if args.len() < signature.min_args || args.len() > signature.params.len() {
+            self.error(GizmoError::ArgumentError(format!(
+                "{} expects {}, got {}",
+                name,
+                signature.arity(),
+                args.len()
+            )));
+            return;
+        }
+        for (index, (arg, kinds)) in args.iter().zip(signature.params).enumerate() {
+            let Some(kind) = self.kind(arg) else {
+                continue;
+            };
+            if !kinds.contains(&kind) {
+                let expected: Vec<&str> = kinds.iter().map(ValueKind::describe).collect();
+                self.error(GizmoError::TypeError(format!(
+                    "{} argument {} must be {}, not {}",
+                    name,
+                    index + 1,
+                    expected.join(" or "),
+                    kind.describe()
+                )));
+            }
+        }
+    }
+
+    /// Returns the kind of value an expression has, if it is plain from the code.
+    fn kind(&self, expression: &Expression) -> Option<ValueKind> {
+        match expression {
+            Expression::Number(_) => Some(ValueKind::Number),
+            Expression::String(_) => Some(ValueKind::String),
+            Expression::Array(items) if !items.is_empty() => {
+                let kinds: Vec<Option<ValueKind>> = items.iter().map(|item| self.kind(item)).collect();
+                if kinds.iter().all(|kind| *kind == Some(ValueKind::String)) {
+                    Some(ValueKind::Strings)
+                } else if kinds.iter().all(|kind| *kind == Some(ValueKind::Number)) {
+                    // A row of pixels
+                    Some(ValueKind::Frame)
+                } else {
+                    None
+                }
+            }
+            Expression::FunctionCall { name, .. } => self.builtins.signature(name).map(|signature| signature.returns),
+            Expression::NamedArgument { value: inner, .. } | Expression::Grouping(inner) | Expression::LineBreak(inner) => {
+                self.kind(inner)
+            }
+            Expression::BinaryOperation { .. } => Some(ValueKind::Number),
+            Expression::PatternGenerator { .. } | Expression::Pipeline { .. } => Some(ValueKind::Frame),
+            Expression::TernaryOperation { true_expr, false_expr, .. } => {
+                let kind = self.kind(true_expr)?;
+                (self.kind(false_expr)? == kind).then_some(kind)
+            }
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,86 @@
+//! Time-of-Day Scheduling
+//!
+//! Fires `when time("22:00") do ... end` handlers when the local wall clock
+//! reaches the given time, so a buddy can go to sleep at night and wake up
+//! in the morning. The window loop checks the schedule on every wake-up and
+//! sleeps no longer than [`Schedule::until_next`].
+//!
+//! ## Missed Times
+//!
+//! Every time passed since the previous check fires once, in clock order -
+//! even if the computer was asleep through it - but at most one day is
+//! caught up. Setting the clock back doesn't replay anything.
+
+use chrono::{Duration as ChronoDuration, NaiveDateTime, NaiveTime, Timelike};
+use std::time::Duration;
+
+/// Parses a 24-hour `"HH:MM"` time of day.
+///
+/// # Returns
+/// * `Some(minute)` - Minutes since midnight (0 to 1439)
+/// * `None` - Not a valid time
+pub fn parse_time_of_day(text: &str) -> Option<u32> {
+    let time = NaiveTime::parse_from_str(text.trim(), "%H:%M").ok()?;
+    Some(time.hour() * 60 + time.minute())
+}
+
+/// Tracks which scheduled times of day have passed.
+#[derive(Debug, Clone)]
+pub struct Schedule {
+    /// Local time of the previous check
+    last_check: NaiveDateTime,
+}
+
+impl Schedule {
+    /// Creates a schedule; only times after `now` will fire.
+    pub fn new(now: NaiveDateTime) -> Self {
+        Self { last_check: now }
+    }
+
+    /// Returns the times passed since the last call, in the order they happened.
+    ///
+    /// # Arguments
+    /// * `now` - Current local time
+    /// * `times` - Handler times in minutes since midnight
+    pub fn due(&mut self, now: NaiveDateTime, times: &[u32]) -> Vec<u32> {
+        if now <= self.last_check {
+            // The clock was set back; start over from here
+            self.last_check = now;
+            return Vec::new();
+        }
+
+        let mut due: Vec<(NaiveDateTime, u32)> = times
+            .iter()
+            .map(|&minute| (next_occurrence(self.last_check, minute), minute))
+            .filter(|&(at, _)| at <= now)
+            .collect();
+        due.sort();
+        self.last_check = now;
+        due.into_iter().map(|(_, minute)| minute).collect()
+    }
+
+    /// Returns how long until the next scheduled time, or `None` without any.
+    ///
+    /// # Arguments
+    /// * `now` - Current local time
+    /// * `times` - Handler times in minutes since midnight
+    pub fn until_next(&self, now: NaiveDateTime, times: &[u32]) -> Option<Duration> {
+        times
+            .iter()
+            .map(|&minute| next_occurrence(now, minute) - now)
+            .min()
+            .and_then(|until| until.to_std().ok())
+    }
+}
+
+/// Returns the first moment strictly after `after` at which the wall clock
+/// shows `minute` (minutes since midnight).
+fn next_occurrence(after: NaiveDateTime, minute: u32) -> NaiveDateTime {
+    let time = NaiveTime::from_hms_opt(minute / 60, minute % 60, 0).unwrap_or(NaiveTime::MIN);
+    let today = after.date().and_time(time);
+    if today > after {
+        today
+    } else {
+        today + ChronoDuration::days(1)
+    }
+}
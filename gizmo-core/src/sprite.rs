@@ -0,0 +1,238 @@
+//! Sprite Import
+//!
+//! Turns sprites drawn in a pixel art editor into frames, so they can be
+//! animated with gizmo logic:
+//!
+//! - `load_image("cat.png", threshold)` loads a PNG or GIF as one frame
+//!   (`image` feature)
+//! - `load_aseprite("cat.aseprite", "walk")` loads an Aseprite file's frames,
+//!   all of them or those of one tag (`aseprite` feature)
+//!
+//! Builds without the feature report an error instead. Relative paths are
+//! relative to the script's directory.
+//!
+//! ## Conversion
+//!
+//! Frames are one palette index per pixel, so every image pixel becomes
+//! either lit (index 1) or off (index 0):
+//!
+//! | Image pixel                     | Frame pixel                               |
+//! |---------------------------------|-------------------------------------------|
+//! | Alpha below 50%                 | Off                                       |
+//! | Luminance at least `threshold`  | Lit                                       |
+//! | Darker                          | Off                                       |
+//!
+//! A threshold of `0` lights every opaque pixel, which suits sprites drawn on
+//! a transparent background. Instead of a number, `"dither"` spreads each
+//! pixel's luminance over a 4x4 ordered (Bayer) pattern, which keeps the
+//! shading of photos and gradients.
+//!
+//! Of an animated GIF only the first frame is loaded.
+//!
+//! ## Aseprite Files
+//!
+//! Each Aseprite frame is flattened (visible layers, as Aseprite exports
+//! them) and converted like an image. A tag is an animation sequence: its
+//! frames are returned in the tag's direction (forward, reverse or
+//! ping-pong).
+//!
+//! Aseprite gives every frame its own duration, while a gizmo sequence has
+//! one frame duration. Frames are therefore repeated so that each lasts a
+//! whole number of ticks of the durations' greatest common divisor, which
+//! `aseprite_speed()` returns for `loop_speed()`:
+//!
+//! ```text
+//! durations   100ms  200ms  100ms      tick 100ms
+//! frames      A      B  B   C
+//! ```
+//!
+//! Durations with no useful common divisor (say 100ms and 133ms) would need
+//! too many repeats; past [`MAX_EXPANDED_FRAMES`] the shortest duration is
+//! the tick instead and the others are rounded to it.
+
+use crate::ast::Frame;
+use crate::error::{GizmoError, Result};
+use std::path::Path;
+
+/// Threshold used when `load_image()` / `load_aseprite()` are called without one.
+pub const DEFAULT_THRESHOLD: f64 = 0.5;
+
+/// Most frames an Aseprite sequence is expanded to for its frame durations.
+#[cfg(feature = "aseprite")]
+pub const MAX_EXPANDED_FRAMES: usize = 1000;
+
+/// How image pixels are turned into lit and off frame pixels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Threshold {
+    /// Lit from this luminance on, from 0.0 (every opaque pixel) to 1.0 (only white)
+    Level(f64),
+    /// Ordered dithering of the luminance
+    Dither,
+}
+
+/// Frames loaded from an Aseprite file, ready for `loop_speed()`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sequence {
+    /// Frames in playback order, repeated to honor their durations
+    pub frames: Vec<Frame>,
+    /// How long each of `frames` is shown, in milliseconds
+    pub tick_ms: u64,
+}
+
+/// 4x4 Bayer matrix; each cell's threshold is `(value + 0.5) / 16`.
+#[cfg(any(feature = "image", feature = "aseprite"))]
+const BAYER: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// Converts RGBA pixels into a frame (see [Conversion](#conversion)).
+///
+/// # Arguments
+/// * `rows` - Rows of `[r, g, b, a]` pixels, top to bottom
+/// * `threshold` - How pixels are lit
+#[cfg(any(feature = "image", feature = "aseprite"))]
+fn to_frame<'a>(rows: impl Iterator<Item = impl Iterator<Item = [u8; 4]> + 'a>, threshold: Threshold) -> Frame {
+    let pixels = rows
+        .enumerate()
+        .map(|(y, row)| {
+            row.enumerate()
+                .map(|(x, [r, g, b, a])| {
+                    if a < 0x80 {
+                        return 0;
+                    }
+                    let luminance = (0.2126 * r as f64 + 0.7152 * g as f64 + 0.0722 * b as f64) / 255.0;
+                    let level = match threshold {
+                        Threshold::Level(level) => level,
+                        Threshold::Dither => (BAYER[y % 4][x % 4] as f64 + 0.5) / 16.0,
+                    };
+                    (luminance >= level) as u8
+                })
+                .collect()
+        })
+        .collect();
+    Frame::new(pixels)
+}
+
+/// Loads an image file as a frame.
+///
+/// # Arguments
+/// * `path` - PNG or GIF file
+/// * `threshold` - How pixels are lit (see [Conversion](#conversion))
+///
+/// # Returns
+/// * `Ok(Frame)` - One frame pixel per image pixel
+/// * `Err(GizmoError::IOError)` - The file couldn't be read
+/// * `Err(GizmoError::ArgumentError)` - It isn't a supported image, or this
+///   build has no image support
+#[cfg(feature = "image")]
+pub fn load_frame(path: &Path, threshold: Threshold) -> Result<Frame> {
+    let image = image::open(path)
+        .map_err(|e| match e {
+            image::ImageError::IoError(e) => GizmoError::IOError(format!("load_image can't read {}: {}", path.display(), e)),
+            e => GizmoError::ArgumentError(format!("load_image can't read {}: {}", path.display(), e)),
+        })?
+        .to_rgba8();
+
+    Ok(to_frame(image.rows().map(|row| row.map(|pixel| pixel.0)), threshold))
+}
+
+/// Loads an image file as a frame; this build has no image support.
+#[cfg(not(feature = "image"))]
+pub fn load_frame(path: &Path, _threshold: Threshold) -> Result<Frame> {
+    Err(GizmoError::ArgumentError(format!(
+        "load_image(\"{}\") needs a build with the `image` feature (cargo build --release --features image)",
+        path.display()
+    )))
+}
+
+/// Loads frames from an Aseprite file.
+///
+/// # Arguments
+/// * `path` - `.aseprite` / `.ase` file
+/// * `tag` - Only the frames of this tag, in its direction; every frame if `None`
+/// * `threshold` - How pixels are lit (see [Conversion](#conversion))
+///
+/// # Returns
+/// * `Ok(Sequence)` - The frames, repeated to honor their durations
+/// * `Err(GizmoError::IOError)` - The file couldn't be read
+/// * `Err(GizmoError::ArgumentError)` - It isn't an Aseprite file, has no
+///   such tag, or this build has no Aseprite support
+#[cfg(feature = "aseprite")]
+pub fn load_aseprite(path: &Path, tag: Option<&str>, threshold: Threshold) -> Result<Sequence> {
+    use asefile::{AnimationDirection, AsepriteFile, AsepriteParseError};
+
+    let file = AsepriteFile::read_file(path).map_err(|e| match e {
+        AsepriteParseError::IoError(e) => GizmoError::IOError(format!("load_aseprite can't read {}: {}", path.display(), e)),
+        e => GizmoError::ArgumentError(format!("load_aseprite can't read {}: {}", path.display(), e)),
+    })?;
+
+    let indices: Vec<u32> = match tag {
+        None => (0..file.num_frames()).collect(),
+        Some(name) => {
+            let Some(tag) = file.tag_by_name(name) else {
+                let names: Vec<String> = (0..file.num_tags()).map(|id| format!("\"{}\"", file.tag(id).name())).collect();
+                return Err(GizmoError::ArgumentError(format!(
+                    "{} has no tag \"{}\" (its tags: {})",
+                    path.display(),
+                    name,
+                    if names.is_empty() { "none".to_string() } else { names.join(", ") }
+                )));
+            };
+            let (from, to) = (tag.from_frame(), tag.to_frame());
+            match tag.animation_direction() {
+                AnimationDirection::Forward => (from..=to).collect(),
+                AnimationDirection::Reverse => (from..=to).rev().collect(),
+                // Back down without repeating either end
+                AnimationDirection::PingPong => (from..=to).chain((from + 1..to).rev()).collect(),
+            }
+        }
+    };
+
+    let frames: Vec<(Frame, u64)> = indices
+        .into_iter()
+        .map(|index| {
+            let frame = file.frame(index);
+            let image = frame.image();
+            (to_frame(image.rows().map(|row| row.map(|pixel| pixel.0)), threshold), frame.duration() as u64)
+        })
+        .collect();
+    Ok(expand(frames))
+}
+
+/// Loads frames from an Aseprite file; this build has no Aseprite support.
+#[cfg(not(feature = "aseprite"))]
+pub fn load_aseprite(path: &Path, _tag: Option<&str>, _threshold: Threshold) -> Result<Sequence> {
+    Err(GizmoError::ArgumentError(format!(
+        "load_aseprite(\"{}\") needs a build with the `aseprite` feature (cargo build --release --features aseprite)",
+        path.display()
+    )))
+}
+
+/// Repeats frames so that each lasts a whole number of common ticks (see
+/// [Aseprite Files](#aseprite-files)).
+///
+/// # Arguments
+/// * `frames` - Frames with their durations in milliseconds
+#[cfg(feature = "aseprite")]
+fn expand(frames: Vec<(Frame, u64)>) -> Sequence {
+    use crate::clock::TICK_MS_RANGE;
+
+    fn gcd(a: u64, b: u64) -> u64 {
+        if b == 0 { a } else { gcd(b, a % b) }
+    }
+
+    let durations = frames.iter().map(|&(_, ms)| ms.max(1));
+    let mut tick = durations.clone().fold(0, gcd);
+    if durations.clone().map(|ms| ms / tick.max(1)).sum::<u64>() > MAX_EXPANDED_FRAMES as u64 {
+        tick = durations.clone().min().unwrap_or(1);
+    }
+    let (min, max) = TICK_MS_RANGE;
+    let tick = tick.clamp(min, max);
+
+    let frames = frames
+        .into_iter()
+        .flat_map(|(frame, ms)| {
+            let repeats = ((ms as f64 / tick as f64).round() as usize).max(1);
+            std::iter::repeat_n(frame, repeats)
+        })
+        .collect();
+    Sequence { frames, tick_ms: tick }
+}
@@ -0,0 +1,149 @@
+//! System Statistics (`sysinfo` feature)
+//!
+//! Samples CPU load, memory use and battery state so scripts can react to
+//! the machine they run on - for example a buddy that runs faster when the
+//! CPU is busy or looks tired when the battery is low. The values reach
+//! scripts as read-only variables:
+//!
+//! | Variable          | Meaning                                          |
+//! |-------------------|--------------------------------------------------|
+//! | `cpu_percent`     | Average load over all cores, 0 to 100            |
+//! | `mem_percent`     | Memory in use, 0 to 100                          |
+//! | `battery_percent` | Charge, 0 to 100 (100 without a battery)         |
+//! | `on_battery`      | 1 while running on battery power, otherwise 0    |
+//!
+//! The window loop takes a new sample every [`SAMPLE_INTERVAL`]. CPU load is
+//! measured between two samples, so it reads 0 until the second one.
+//!
+//! ## Battery Sources
+//!
+//! - **Linux**: `/sys/class/power_supply/*` (all batteries averaged)
+//! - **Windows**: `GetSystemPowerStatus`
+//! - **macOS**: `pmset -g batt`
+
+use std::time::Duration;
+use sysinfo::System;
+
+/// How often the window loop refreshes the statistics.
+pub const SAMPLE_INTERVAL: Duration = Duration::from_secs(2);
+
+/// One sample of the system's state.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SystemStats {
+    /// Average CPU load over all cores, 0 to 100
+    pub cpu_percent: f64,
+    /// Memory in use, 0 to 100
+    pub mem_percent: f64,
+    /// Battery charge, 0 to 100 (100 when there is no battery)
+    pub battery_percent: f64,
+    /// Running on battery rather than external power
+    pub on_battery: bool,
+}
+
+impl SystemStats {
+    /// An idle machine on external power, as deterministic runs see it.
+    pub const IDLE: SystemStats = SystemStats {
+        cpu_percent: 0.0,
+        mem_percent: 0.0,
+        battery_percent: 100.0,
+        on_battery: false,
+    };
+}
+
+/// Reads [`SystemStats`], keeping the state CPU load is measured against.
+pub struct Sampler {
+    system: System,
+}
+
+impl Sampler {
+    /// Creates a sampler and takes the baseline for CPU load.
+    pub fn new() -> Self {
+        let mut system = System::new();
+        system.refresh_cpu_usage();
+        Self { system }
+    }
+
+    /// Takes a sample.
+    pub fn sample(&mut self) -> SystemStats {
+        self.system.refresh_cpu_usage();
+        self.system.refresh_memory();
+
+        let total = self.system.total_memory();
+        let mem_percent = if total == 0 {
+            0.0
+        } else {
+            self.system.used_memory() as f64 / total as f64 * 100.0
+        };
+        let (battery_percent, on_battery) = battery().unwrap_or((100.0, false));
+
+        SystemStats {
+            cpu_percent: self.system.global_cpu_usage() as f64,
+            mem_percent,
+            battery_percent,
+            on_battery,
+        }
+    }
+}
+
+impl Default for Sampler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Reads the battery charge and whether the system is running on it.
+///
+/// # Returns
+/// `None` when there is no battery or it can't be read
+fn battery() -> Option<(f64, bool)> {
+    #[cfg(target_os = "linux")]
+    {
+        let mut charges = Vec::new();
+        let mut discharging = false;
+        for entry in std::fs::read_dir("/sys/class/power_supply").ok()?.flatten() {
+            let path = entry.path();
+            let read = |name: &str| std::fs::read_to_string(path.join(name)).map(|text| text.trim().to_string());
+            if read("type").ok().as_deref() != Some("Battery") {
+                continue;
+            }
+            if let Some(capacity) = read("capacity").ok().and_then(|text| text.parse::<f64>().ok()) {
+                charges.push(capacity);
+            }
+            discharging |= read("status").ok().as_deref() == Some("Discharging");
+        }
+        if charges.is_empty() {
+            return None;
+        }
+        Some((charges.iter().sum::<f64>() / charges.len() as f64, discharging))
+    }
+
+    #[cfg(windows)]
+    {
+        use windows_sys::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+        // SAFETY: `status` is a plain struct the call fills in.
+        let mut status: SYSTEM_POWER_STATUS = unsafe { std::mem::zeroed() };
+        if unsafe { GetSystemPowerStatus(&mut status) } == 0 {
+            return None;
+        }
+        // 128 = no system battery, 255 = unknown status / charge
+        if status.BatteryFlag & 128 != 0 || status.BatteryFlag == 255 || status.BatteryLifePercent == 255 {
+            return None;
+        }
+        Some((status.BatteryLifePercent as f64, status.ACLineStatus == 0))
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        // e.g. "Now drawing from 'Battery Power'\n -InternalBattery-0 (id=1234)\t85%; discharging; ..."
+        let output = std::process::Command::new("pmset").args(["-g", "batt"]).output().ok()?;
+        let text = String::from_utf8_lossy(&output.stdout);
+        let percent = text
+            .split(|c: char| c.is_whitespace() || c == ';')
+            .find_map(|word| word.strip_suffix('%')?.parse::<f64>().ok())?;
+        Some((percent, text.contains("'Battery Power'")))
+    }
+
+    #[cfg(not(any(target_os = "linux", windows, target_os = "macos")))]
+    None
+}
@@ -0,0 +1,197 @@
+//! Animation Transitions
+//!
+//! This module generates the short in-between frames shown when playback
+//! switches from one animation sequence to another (for example from an idle
+//! loop to a clicked reaction). Instead of cutting hard from the last frame of
+//! the old sequence to the first frame of the new one, a few blended frames are
+//! inserted.
+//!
+//! ## Transition Kinds
+//!
+//! - **`none`**: Hard cut, no extra frames
+//! - **`fade`**: Ordered dissolve - pixels switch over following a 4x4 Bayer
+//!   matrix, which approximates a cross-fade on a 1-bit display
+//! - **`slide`**: The new frame pushes the old one out to the left
+//!
+//! ## Timing
+//!
+//! A transition lasts `duration_ms`, one frame per animation tick, and its
+//! last tick is the new sequence's first frame. So a 200ms fade at
+//! `loop_speed(..., 50)` spans 4 ticks and inserts 3 frames.
+//!
+//! ```gzmo
+//! transition("fade", 200);
+//! ```
+
+use crate::ast::Frame;
+use crate::error::{GizmoError, Result};
+
+/// 4x4 Bayer threshold matrix used for the ordered dissolve (values 0-15).
+const BAYER_4X4: [[u8; 4]; 4] = [
+    [0, 8, 2, 10],
+    [12, 4, 14, 6],
+    [3, 11, 1, 9],
+    [15, 7, 13, 5],
+];
+
+/// The visual style of a transition.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TransitionKind {
+    /// Hard cut between sequences
+    None,
+    /// Ordered dissolve from the old frame to the new one
+    Fade,
+    /// New frame slides in from the right, pushing the old one out
+    Slide,
+}
+
+/// Transition settings configured by `transition(kind, duration_ms)`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transition {
+    /// How the frames are blended
+    pub kind: TransitionKind,
+    /// Total transition length in milliseconds
+    pub duration_ms: u64,
+}
+
+impl Transition {
+    /// Builds a transition from its script-facing name.
+    ///
+    /// # Arguments
+    /// * `name` - One of `"none"`, `"fade"` or `"slide"`
+    /// * `duration_ms` - Transition length in milliseconds
+    ///
+    /// # Returns
+    /// * `Ok(Transition)` - Recognized transition kind
+    /// * `Err(GizmoError::ArgumentError)` - Unknown transition name
+    pub fn from_name(name: &str, duration_ms: u64) -> Result<Self> {
+        let kind = match name {
+            "none" => TransitionKind::None,
+            "fade" => TransitionKind::Fade,
+            "slide" => TransitionKind::Slide,
+            _ => {
+                return Err(GizmoError::ArgumentError(format!(
+                    "Unknown transition '{}' (expected \"none\", \"fade\" or \"slide\")",
+                    name
+                )))
+            }
+        };
+
+        Ok(Self { kind, duration_ms })
+    }
+
+    /// Generates the in-between frames from `from` to `to`.
+    ///
+    /// The returned frames exclude both endpoints, so they can be played
+    /// directly between the old sequence's last frame and the new sequence's
+    /// first frame. Frames take the size of `to`; pixels outside `from` count
+    /// as off.
+    ///
+    /// # Arguments
+    /// * `from` - Last frame of the outgoing sequence
+    /// * `to` - First frame of the incoming sequence
+    /// * `tick_ms` - Animation tick length, one generated frame per tick
+    ///
+    /// # Returns
+    /// Intermediate frames in playback order (empty for `none` or very short transitions)
+    pub fn frames_between(&self, from: &Frame, to: &Frame, tick_ms: u64) -> Vec<Frame> {
+        let steps = (self.duration_ms / tick_ms.max(1)) as usize;
+        if self.kind == TransitionKind::None || steps < 2 {
+            return Vec::new();
+        }
+
+        // The transition spans `steps` ticks; the final tick is the target frame itself
+        (1..steps)
+            .map(|step| {
+                let progress = step as f64 / steps as f64;
+                match self.kind {
+                    TransitionKind::Fade => dissolve(from, to, progress),
+                    TransitionKind::Slide => slide(from, to, progress),
+                    TransitionKind::None => unreachable!(),
+                }
+            })
+            .collect()
+    }
+}
+
+impl Default for Transition {
+    fn default() -> Self {
+        Self {
+            kind: TransitionKind::None,
+            duration_ms: 0,
+        }
+    }
+}
+
+/// Reads a pixel, treating coordinates outside the frame as off.
+fn pixel_at(frame: &Frame, x: usize, y: usize) -> u8 {
+    frame
+        .pixels
+        .get(y)
+        .and_then(|row| row.get(x))
+        .copied()
+        .unwrap_or(0)
+}
+
+/// Ordered dissolve: a pixel shows `to` once progress passes its Bayer threshold.
+fn dissolve(from: &Frame, to: &Frame, progress: f64) -> Frame {
+    let mut frame = Frame::new_blank(to.width, to.height);
+
+    for (y, row) in frame.pixels_mut().iter_mut().enumerate() {
+        for (x, pixel) in row.iter_mut().enumerate() {
+            let threshold = (BAYER_4X4[y % 4][x % 4] as f64 + 0.5) / 16.0;
+            *pixel = if progress > threshold {
+                pixel_at(to, x, y)
+            } else {
+                pixel_at(from, x, y)
+            };
+        }
+    }
+
+    frame
+}
+
+/// Push-left slide: `to` enters from the right edge as `from` leaves on the left.
+fn slide(from: &Frame, to: &Frame, progress: f64) -> Frame {
+    let mut frame = Frame::new_blank(to.width, to.height);
+    let offset = (to.width as f64 * progress).round() as usize;
+
+    for (y, row) in frame.pixels_mut().iter_mut().enumerate() {
+        for (x, pixel) in row.iter_mut().enumerate() {
+            let shifted = x + offset;
+            *pixel = if shifted < to.width {
+                pixel_at(from, shifted, y)
+            } else {
+                pixel_at(to, shifted - to.width, y)
+            };
+        }
+    }
+
+    frame
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transitions_insert_one_frame_less_than_their_ticks() {
+        let from = Frame::new_blank(4, 4);
+        let to = Frame::new(vec![vec![1; 4]; 4]);
+
+        let fade = Transition::from_name("fade", 200).unwrap();
+        assert_eq!(fade.frames_between(&from, &to, 50).len(), 3);
+
+        let slide = Transition::from_name("slide", 300).unwrap();
+        assert_eq!(slide.frames_between(&from, &to, 100).len(), 2);
+    }
+
+    #[test]
+    fn short_transitions_cut() {
+        let frame = Frame::new_blank(2, 2);
+
+        let fade = Transition::from_name("fade", 60).unwrap();
+        assert!(fade.frames_between(&frame, &frame, 50).is_empty());
+        assert!(Transition::default().frames_between(&frame, &frame, 50).is_empty());
+    }
+}
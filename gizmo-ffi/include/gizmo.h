@@ -0,0 +1,77 @@
+/* Gizmo C API - runs .gzmo animations. See gizmo-ffi/src/lib.rs. */
+
+#ifndef GIZMO_H
+#define GIZMO_H
+
+/* Generated by cbindgen from gizmo-ffi/src/lib.rs; don't edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// A compiled script, created by `gizmo_compile()` and released with `gizmo_free()`.
+typedef struct GizmoAnimation GizmoAnimation;
+
+// A frame returned by `gizmo_next_frame()`.
+typedef struct GizmoFrame {
+  // Width in pixels
+  uint32_t width;
+  // Height in pixels
+  uint32_t height;
+  // `width * height * 4` bytes of RGBA pixels, row by row; valid until the
+  // next `gizmo_next_frame()` or `gizmo_free()` on the same animation
+  const uint8_t *rgba;
+  // How long to show the frame, in milliseconds
+  uint32_t duration_ms;
+} GizmoFrame;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Compiles and runs a script.
+//
+// Returns NULL if the script has a syntax error or fails while running;
+// `gizmo_last_error()` then says why.
+//
+// # Arguments
+// * `source` - Contents of the .gzmo file, NUL-terminated UTF-8
+// * `script_dir` - Directory the files the script loads are relative to;
+//   NULL for the working directory
+//
+// # Safety
+// `source` must point to a NUL-terminated string, and `script_dir` must be
+// NULL or point to one.
+struct GizmoAnimation *gizmo_compile(const char *source, const char *script_dir);
+
+// Returns the animation's next frame, starting over after the last one.
+//
+// Returns false, leaving `frame` alone, if the script has no frames (it never
+// calls `loop_speed()`, `play()` or `loop()`).
+//
+// # Arguments
+// * `animation` - From `gizmo_compile()`
+// * `frame` - Filled in with the frame
+//
+// # Safety
+// `animation` must come from `gizmo_compile()` and not be freed yet, and
+// `frame` must point to a writable `GizmoFrame`.
+bool gizmo_next_frame(struct GizmoAnimation *animation, struct GizmoFrame *frame);
+
+// Releases an animation and its frames. NULL is ignored.
+//
+// # Safety
+// `animation` must be NULL or come from `gizmo_compile()`, and must not be
+// used afterwards.
+void gizmo_free(struct GizmoAnimation *animation);
+
+// Returns why the last failed call on this thread failed, or NULL if none
+// has. The text stays valid until the next call fails.
+const char *gizmo_last_error(void);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* GIZMO_H */
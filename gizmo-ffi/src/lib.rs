@@ -0,0 +1,207 @@
+//! Gizmo for C
+//!
+//! A small C API over `gizmo-core`, so desktop shells and plugins written in
+//! other languages can run .gzmo animations. Build it with
+//! `cargo build --release -p gizmo-ffi` (a shared and a static library) and
+//! include `gizmo-ffi/include/gizmo.h`:
+//!
+//! ```c
+//! #include "gizmo.h"
+//!
+//! GizmoAnimation *animation = gizmo_compile(source, "/path/to/scripts");
+//! if (!animation) {
+//!     fprintf(stderr, "%s\n", gizmo_last_error());
+//!     return 1;
+//! }
+//! GizmoFrame frame;
+//! while (gizmo_next_frame(animation, &frame)) {
+//!     draw_rgba(frame.rgba, frame.width, frame.height);
+//!     sleep_ms(frame.duration_ms);
+//! }
+//! gizmo_free(animation);
+//! ```
+//!
+//! `gizmo_next_frame()` hands out the script's frames in order and starts
+//! over after the last one, so the loop above plays the animation until it
+//! is stopped. Pixels are 4 bytes each, red, green, blue and straight alpha,
+//! row by row.
+//!
+//! The header is generated with cbindgen (see `gizmo-ffi/cbindgen.toml`).
+//! Every function may be called from any thread, but one animation must not
+//! be used by two threads at once. A panic never unwinds into the caller: the
+//! call fails as if the script had, and `gizmo_last_error()` says it panicked.
+
+use gizmo_core::Animation;
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::ptr;
+
+thread_local! {
+    /// Message of the last failed call on this thread
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// A compiled script, created by `gizmo_compile()` and released with `gizmo_free()`.
+pub struct GizmoAnimation {
+    animation: Animation,
+    /// Index of the frame `gizmo_next_frame()` returns next
+    next: usize,
+    /// RGBA pixels of the frame returned last
+    rgba: Vec<u8>,
+}
+
+/// A frame returned by `gizmo_next_frame()`.
+#[repr(C)]
+pub struct GizmoFrame {
+    /// Width in pixels
+    pub width: u32,
+    /// Height in pixels
+    pub height: u32,
+    /// `width * height * 4` bytes of RGBA pixels, row by row; valid until the
+    /// next `gizmo_next_frame()` or `gizmo_free()` on the same animation
+    pub rgba: *const u8,
+    /// How long to show the frame, in milliseconds
+    pub duration_ms: u32,
+}
+
+/// Remembers why a call failed, for `gizmo_last_error()`.
+fn set_error(message: String) {
+    let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+/// Runs the body of an exported function, turning a panic into a failed call
+/// instead of unwinding across the C boundary.
+///
+/// # Arguments
+/// * `function` - Name of the exported function, for the error message
+/// * `failed` - What the function returns when it fails
+/// * `body` - The function's work
+fn guard<T>(function: &str, failed: T, body: impl FnOnce() -> T) -> T {
+    panic::catch_unwind(AssertUnwindSafe(body)).unwrap_or_else(|payload| {
+        let reason = payload
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+            .unwrap_or("unknown reason");
+        set_error(format!("{}: panicked: {}", function, reason));
+        failed
+    })
+}
+
+/// Compiles and runs a script.
+///
+/// Returns NULL if the script has a syntax error or fails while running;
+/// `gizmo_last_error()` then says why.
+///
+/// # Arguments
+/// * `source` - Contents of the .gzmo file, NUL-terminated UTF-8
+/// * `script_dir` - Directory the files the script loads are relative to;
+///   NULL for the working directory
+///
+/// # Safety
+/// `source` must point to a NUL-terminated string, and `script_dir` must be
+/// NULL or point to one.
+#[no_mangle]
+pub unsafe extern "C" fn gizmo_compile(source: *const c_char, script_dir: *const c_char) -> *mut GizmoAnimation {
+    guard("gizmo_compile", ptr::null_mut(), || {
+        if source.is_null() {
+            set_error("gizmo_compile: source is NULL".to_string());
+            return ptr::null_mut();
+        }
+        let Ok(source) = CStr::from_ptr(source).to_str() else {
+            set_error("gizmo_compile: source isn't UTF-8".to_string());
+            return ptr::null_mut();
+        };
+        let result = if script_dir.is_null() {
+            gizmo_core::compile(source)
+        } else {
+            let dir = CStr::from_ptr(script_dir).to_string_lossy();
+            gizmo_core::compile_in(source, Path::new(dir.as_ref()))
+        };
+
+        match result {
+            Ok(animation) => Box::into_raw(Box::new(GizmoAnimation { animation, next: 0, rgba: Vec::new() })),
+            Err(e) => {
+                set_error(e.to_string());
+                ptr::null_mut()
+            }
+        }
+    })
+}
+
+/// Returns the animation's next frame, starting over after the last one.
+///
+/// Returns false, leaving `frame` alone, if the script has no frames (it never
+/// calls `loop_speed()`, `play()` or `loop()`).
+///
+/// # Arguments
+/// * `animation` - From `gizmo_compile()`
+/// * `frame` - Filled in with the frame
+///
+/// # Safety
+/// `animation` must come from `gizmo_compile()` and not be freed yet, and
+/// `frame` must point to a writable `GizmoFrame`.
+#[no_mangle]
+pub unsafe extern "C" fn gizmo_next_frame(animation: *mut GizmoAnimation, frame: *mut GizmoFrame) -> bool {
+    guard("gizmo_next_frame", false, || {
+        let (Some(animation), Some(out)) = (animation.as_mut(), frame.as_mut()) else {
+            set_error("gizmo_next_frame: animation or frame is NULL".to_string());
+            return false;
+        };
+        let frames = animation.animation.frames();
+        let Some(current) = frames.get(animation.next % frames.len().max(1)) else {
+            return false;
+        };
+        animation.next = (animation.next + 1) % frames.len();
+
+        let palette = animation.animation.palette();
+        animation.rgba.clear();
+        for &index in current.pixels.iter().flatten() {
+            let argb = palette.color(index);
+            animation.rgba.extend_from_slice(&[(argb >> 16) as u8, (argb >> 8) as u8, argb as u8, (argb >> 24) as u8]);
+        }
+        *out = GizmoFrame {
+            width: current.width as u32,
+            height: current.height as u32,
+            rgba: animation.rgba.as_ptr(),
+            duration_ms: animation.animation.frame_duration_ms() as u32,
+        };
+        true
+    })
+}
+
+/// Releases an animation and its frames. NULL is ignored.
+///
+/// # Safety
+/// `animation` must be NULL or come from `gizmo_compile()`, and must not be
+/// used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn gizmo_free(animation: *mut GizmoAnimation) {
+    if !animation.is_null() {
+        drop(Box::from_raw(animation));
+    }
+}
+
+/// Returns why the last failed call on this thread failed, or NULL if none
+/// has. The text stays valid until the next call fails.
+#[no_mangle]
+pub extern "C" fn gizmo_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ref().map_or(ptr::null(), |message| message.as_ptr()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn panics_become_failed_calls() {
+        let result = guard("gizmo_test", ptr::null_mut::<GizmoAnimation>(), || panic!("out of {}", "frames"));
+
+        assert!(result.is_null());
+        let error = unsafe { CStr::from_ptr(gizmo_last_error()) };
+        assert_eq!(error.to_str().unwrap(), "gizmo_test: panicked: out of frames");
+    }
+}
@@ -0,0 +1,63 @@
+//! Tests of the C API, called the way a C program calls it.
+
+use gizmo_ffi::{gizmo_compile, gizmo_free, gizmo_last_error, gizmo_next_frame, GizmoFrame};
+use std::ffi::{c_char, CStr, CString};
+use std::ptr;
+
+/// Returns the text of `gizmo_last_error()`.
+fn last_error() -> String {
+    let error = gizmo_last_error();
+    assert!(!error.is_null(), "no error was set");
+    unsafe { CStr::from_ptr(error) }.to_string_lossy().into_owned()
+}
+
+fn empty_frame() -> GizmoFrame {
+    GizmoFrame { width: 0, height: 0, rgba: ptr::null(), duration_ms: 0 }
+}
+
+#[test]
+fn frames_come_out_as_rgba_and_loop() {
+    let source = CString::new("frame a = [[1, 0], [1, 0]];\nframe b = [[0, 1], [0, 1]];\nframes both = [a, b];\nloop_speed(both, 40);").unwrap();
+    let animation = unsafe { gizmo_compile(source.as_ptr(), ptr::null()) };
+    assert!(!animation.is_null());
+
+    let mut frame = empty_frame();
+    let mut played = Vec::new();
+    for _ in 0..3 {
+        assert!(unsafe { gizmo_next_frame(animation, &mut frame) });
+        assert_eq!((frame.width, frame.height, frame.duration_ms), (2, 2, 40));
+        played.push(unsafe { std::slice::from_raw_parts(frame.rgba, 16) }.to_vec());
+    }
+    let (on, off) = (&played[0][..4], &played[0][4..8]);
+    assert_ne!(on, off);
+    assert_eq!(played[1], [off, on, off, on].concat());
+    assert_eq!(played[2], played[0]);
+
+    unsafe { gizmo_free(animation) };
+}
+
+#[test]
+fn failures_return_null_and_say_why() {
+    let source = CString::new("frame = 3;").unwrap();
+    assert!(unsafe { gizmo_compile(source.as_ptr(), ptr::null()) }.is_null());
+    assert!(last_error().contains("line 1"), "{}", last_error());
+
+    assert!(unsafe { gizmo_compile(ptr::null::<c_char>(), ptr::null()) }.is_null());
+    assert_eq!(last_error(), "gizmo_compile: source is NULL");
+
+    let mut frame = empty_frame();
+    assert!(!unsafe { gizmo_next_frame(ptr::null_mut(), &mut frame) });
+    assert_eq!(last_error(), "gizmo_next_frame: animation or frame is NULL");
+}
+
+#[test]
+fn scripts_without_frames_have_no_next_frame() {
+    let source = CString::new("x = 1;").unwrap();
+    let animation = unsafe { gizmo_compile(source.as_ptr(), ptr::null()) };
+    assert!(!animation.is_null());
+
+    let mut frame = empty_frame();
+    assert!(!unsafe { gizmo_next_frame(animation, &mut frame) });
+
+    unsafe { gizmo_free(animation) };
+}
@@ -0,0 +1,80 @@
+//! Gizmo for the Web
+//!
+//! Runs .gzmo scripts in a browser so a web playground can preview them.
+//! Build it with `wasm-pack build gizmo-wasm --target web` and call
+//! [`render_frames`] from JavaScript:
+//!
+//! ```text
+//! import init, { render_frames } from "./pkg/gizmo_wasm.js";
+//!
+//! await init();
+//! const bytes = render_frames(source);          // throws on script errors
+//! const view = new DataView(bytes.buffer, bytes.byteOffset);
+//! const count = view.getUint32(0, true);
+//! const durationMs = view.getUint32(4, true);
+//! const frames = [];
+//! let offset = 8;
+//! for (let i = 0; i < count; i++) {
+//!     const width = view.getUint32(offset, true);
+//!     const height = view.getUint32(offset + 4, true);
+//!     const rgba = new Uint8ClampedArray(bytes.buffer, bytes.byteOffset + offset + 8, width * height * 4);
+//!     frames.push(new ImageData(rgba, width, height));
+//!     offset += 8 + width * height * 4;
+//! }
+//! ```
+//!
+//! ## Byte Layout
+//!
+//! All numbers are little-endian `u32`s:
+//!
+//! ```text
+//! frame count | frame duration (ms) | frame 1 | frame 2 | ...
+//!
+//! frame:  width | height | width * height RGBA pixels, row by row
+//! ```
+//!
+//! Pixels are 4 bytes each with straight (not premultiplied) alpha, the
+//! layout `ImageData` expects. The default background is transparent.
+
+use gizmo_core::{Animation, Frame};
+use wasm_bindgen::prelude::*;
+
+/// Runs a script and returns its frames as RGBA pixels.
+///
+/// Scripts can't load files in a browser, so `load_image()` and
+/// `load_aseprite()` fail.
+///
+/// # Arguments
+/// * `source` - Contents of the .gzmo file
+///
+/// # Returns
+/// * `Ok(Vec<u8>)` - The frames in the layout above (a `Uint8Array` in JavaScript)
+/// * `Err(JsError)` - The script has a syntax error or failed while running;
+///   thrown as an `Error` with the message `gizmo check` would print
+#[wasm_bindgen]
+pub fn render_frames(source: &str) -> Result<Vec<u8>, JsError> {
+    let animation = gizmo_core::compile(source).map_err(|e| JsError::new(&e.to_string()))?;
+    Ok(encode(&animation))
+}
+
+/// Encodes an animation's frames in the byte layout of this module.
+fn encode(animation: &Animation) -> Vec<u8> {
+    let frames = animation.frames();
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(&(frames.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(&(animation.frame_duration_ms() as u32).to_le_bytes());
+    for frame in frames {
+        encode_frame(animation, frame, &mut bytes);
+    }
+    bytes
+}
+
+/// Appends one frame's size and RGBA pixels.
+fn encode_frame(animation: &Animation, frame: &Frame, bytes: &mut Vec<u8>) {
+    bytes.extend_from_slice(&(frame.width as u32).to_le_bytes());
+    bytes.extend_from_slice(&(frame.height as u32).to_le_bytes());
+    for &index in frame.pixels.iter().flatten() {
+        let argb = animation.palette().color(index);
+        bytes.extend_from_slice(&[(argb >> 16) as u8, (argb >> 8) as u8, argb as u8, (argb >> 24) as u8]);
+    }
+}
@@ -0,0 +1,262 @@
+//! Starting at Login
+//!
+//! `gizmo install-autostart` registers a login item that runs
+//! `gizmo start <file>` when the user logs in, so the buddy comes back after
+//! a reboot; `gizmo uninstall-autostart` removes it again.
+//!
+//! ## Login Items
+//!
+//! | Platform | Login item                                                           |
+//! |----------|----------------------------------------------------------------------|
+//! | macOS    | LaunchAgent `~/Library/LaunchAgents/com.gizmo.buddy.plist`            |
+//! | Linux    | XDG autostart entry `~/.config/autostart/gizmo.desktop`              |
+//! | Windows  | `Gizmo` value under `HKCU\Software\Microsoft\Windows\CurrentVersion\Run` |
+//!
+//! A named instance (`--name cat`) gets its own item (`com.gizmo.buddy.cat`,
+//! `gizmo-cat.desktop`, `Gizmo (cat)`) that starts it with the same name.
+//!
+//! The item names the script by its absolute path at install time; run
+//! `install-autostart` again after switching scripts to change it.
+
+use crate::daemon;
+use std::error::Error;
+use std::path::Path;
+
+/// Registers a login item that starts an instance with a script.
+///
+/// Replaces the instance's existing login item, if any.
+///
+/// # Arguments
+/// * `instance` - Instance to start at login
+/// * `gzmo_file` - Absolute path of the script to start
+///
+/// # Returns
+/// * `Ok(location)` - Where the login item was written, for the user
+/// * `Err` - The platform has no supported login items, or writing failed
+pub fn install(instance: &str, gzmo_file: &Path) -> Result<String, Box<dyn Error>> {
+    let exe = std::env::current_exe()?;
+    let mut command = vec![exe.to_string_lossy().into_owned()];
+    if instance != daemon::DEFAULT_INSTANCE {
+        command.extend(["--name".to_string(), instance.to_string()]);
+    }
+    command.extend(["start".to_string(), gzmo_file.to_string_lossy().into_owned()]);
+
+    #[cfg(any(target_os = "macos", target_os = "linux"))]
+    {
+        let path = item_path(instance)?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        #[cfg(target_os = "macos")]
+        let item = launch_agent(&label(instance), &command);
+        #[cfg(target_os = "linux")]
+        let item = desktop_entry(instance, &command);
+        std::fs::write(&path, item)?;
+        Ok(path.display().to_string())
+    }
+
+    #[cfg(windows)]
+    {
+        let command_line: Vec<String> = command.iter().map(|arg| format!("\"{}\"", arg)).collect();
+        windows_run::set(&value_name(instance), &command_line.join(" "))?;
+        Ok(format!(r"HKEY_CURRENT_USER\{}\{}", windows_run::KEY, value_name(instance)))
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "linux", windows)))]
+    {
+        let _ = command;
+        Err("Starting at login isn't supported on this platform".into())
+    }
+}
+
+/// Removes an instance's login item.
+///
+/// # Arguments
+/// * `instance` - Instance that should no longer start at login
+///
+/// # Returns
+/// * `Ok(true)` - The login item was removed
+/// * `Ok(false)` - There was none
+/// * `Err` - It couldn't be removed
+pub fn uninstall(instance: &str) -> Result<bool, Box<dyn Error>> {
+    #[cfg(any(target_os = "macos", target_os = "linux"))]
+    {
+        match std::fs::remove_file(item_path(instance)?) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    #[cfg(windows)]
+    {
+        windows_run::delete(&value_name(instance))
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "linux", windows)))]
+    {
+        let _ = instance;
+        Ok(false)
+    }
+}
+
+/// Returns where an instance's login item file lives.
+#[cfg(target_os = "macos")]
+fn item_path(instance: &str) -> Result<std::path::PathBuf, Box<dyn Error>> {
+    let home = dirs::home_dir().ok_or("Could not find home directory")?;
+    Ok(home.join("Library/LaunchAgents").join(format!("{}.plist", label(instance))))
+}
+
+/// Returns where an instance's login item file lives.
+#[cfg(target_os = "linux")]
+fn item_path(instance: &str) -> Result<std::path::PathBuf, Box<dyn Error>> {
+    let config_dir = dirs::config_dir().ok_or("Could not find config directory")?;
+    let name = if instance == daemon::DEFAULT_INSTANCE {
+        "gizmo.desktop".to_string()
+    } else {
+        format!("gizmo-{}.desktop", instance)
+    };
+    Ok(config_dir.join("autostart").join(name))
+}
+
+/// Returns the LaunchAgent label of an instance.
+#[cfg(target_os = "macos")]
+fn label(instance: &str) -> String {
+    if instance == daemon::DEFAULT_INSTANCE {
+        "com.gizmo.buddy".to_string()
+    } else {
+        format!("com.gizmo.buddy.{}", instance)
+    }
+}
+
+/// Writes a LaunchAgent that runs `command` once at login.
+///
+/// `start` exits as soon as the GUI is spawned; `AbandonProcessGroup` keeps
+/// launchd from cleaning up after it.
+#[cfg(target_os = "macos")]
+fn launch_agent(label: &str, command: &[String]) -> String {
+    let escape = |text: &str| text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;");
+    let arguments: String = command
+        .iter()
+        .map(|arg| format!("        <string>{}</string>\n", escape(arg)))
+        .collect();
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{}</string>
+    <key>ProgramArguments</key>
+    <array>
+{}    </array>
+    <key>RunAtLoad</key>
+    <true/>
+    <key>AbandonProcessGroup</key>
+    <true/>
+</dict>
+</plist>
+"#,
+        escape(label),
+        arguments
+    )
+}
+
+/// Writes an XDG autostart entry that runs `command` at login.
+#[cfg(target_os = "linux")]
+fn desktop_entry(instance: &str, command: &[String]) -> String {
+    let name = if instance == daemon::DEFAULT_INSTANCE {
+        "Gizmo".to_string()
+    } else {
+        format!("Gizmo ({})", instance)
+    };
+    let exec: Vec<String> = command.iter().map(|arg| exec_argument(arg)).collect();
+    format!(
+        "[Desktop Entry]\nType=Application\nName={}\nComment=Pixel art desktop buddy\nExec={}\nTerminal=false\nX-GNOME-Autostart-enabled=true\n",
+        name,
+        exec.join(" ")
+    )
+}
+
+/// Quotes one argument of a desktop entry's `Exec` key.
+///
+/// Inside the quotes `"`, `` ` ``, `$` and `\` are backslash-escaped and `%`
+/// is doubled, as the Desktop Entry Specification requires; the backslashes
+/// are then escaped once more because `Exec` is itself a string value.
+#[cfg(target_os = "linux")]
+fn exec_argument(arg: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in arg.chars() {
+        match c {
+            '"' | '`' | '$' | '\\' => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            '%' => quoted.push_str("%%"),
+            _ => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted.replace('\\', "\\\\")
+}
+
+/// Returns the Run key value name of an instance.
+#[cfg(windows)]
+fn value_name(instance: &str) -> String {
+    if instance == daemon::DEFAULT_INSTANCE {
+        "Gizmo".to_string()
+    } else {
+        format!("Gizmo ({})", instance)
+    }
+}
+
+/// The current user's `Run` registry key.
+#[cfg(windows)]
+mod windows_run {
+    use std::error::Error;
+    use windows_sys::Win32::Foundation::{ERROR_FILE_NOT_FOUND, ERROR_SUCCESS};
+    use windows_sys::Win32::System::Registry::{
+        RegDeleteKeyValueW, RegSetKeyValueW, HKEY_CURRENT_USER, REG_SZ,
+    };
+
+    /// Path of the key under `HKEY_CURRENT_USER`.
+    pub const KEY: &str = r"Software\Microsoft\Windows\CurrentVersion\Run";
+
+    /// Encodes a string as NUL-terminated UTF-16.
+    fn wide(text: &str) -> Vec<u16> {
+        text.encode_utf16().chain(std::iter::once(0)).collect()
+    }
+
+    /// Sets a value to a command line.
+    pub fn set(name: &str, command_line: &str) -> Result<(), Box<dyn Error>> {
+        let (key, name, data) = (wide(KEY), wide(name), wide(command_line));
+        // SAFETY: All strings are NUL-terminated UTF-16 that outlive the call;
+        // the data size is given in bytes, terminator included.
+        let status = unsafe {
+            RegSetKeyValueW(
+                HKEY_CURRENT_USER,
+                key.as_ptr(),
+                name.as_ptr(),
+                REG_SZ,
+                data.as_ptr().cast(),
+                (data.len() * 2) as u32,
+            )
+        };
+        if status != ERROR_SUCCESS {
+            return Err(std::io::Error::from_raw_os_error(status as i32).into());
+        }
+        Ok(())
+    }
+
+    /// Deletes a value; `Ok(false)` if there was none.
+    pub fn delete(name: &str) -> Result<bool, Box<dyn Error>> {
+        let (key, name) = (wide(KEY), wide(name));
+        // SAFETY: Both strings are NUL-terminated UTF-16 that outlive the call.
+        let status = unsafe { RegDeleteKeyValueW(HKEY_CURRENT_USER, key.as_ptr(), name.as_ptr()) };
+        match status {
+            ERROR_SUCCESS => Ok(true),
+            ERROR_FILE_NOT_FOUND => Ok(false),
+            _ => Err(std::io::Error::from_raw_os_error(status as i32).into()),
+        }
+    }
+}
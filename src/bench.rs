@@ -0,0 +1,131 @@
+//! Script Benchmarks
+//!
+//! `gizmo bench <file>` runs a script the way `gizmo start` does, several
+//! times over, and reports how long each phase took so a slow script (or a
+//! slower gizmo) shows up as numbers rather than a stutter:
+//!
+//! ```text
+//! examples/waves.gzmo: best of 5 runs
+//!   compile      0.31 ms  (lex 0.09 ms, parse 0.22 ms)
+//!   run         41.86 ms  32 frames
+//!   per frame    1.31 ms  764.5 frames/s
+//! ```
+//!
+//! Each phase is reported from its fastest run, which is the least disturbed
+//! by whatever else the machine was doing. Event handlers aren't run, and
+//! neither is the window: drawing frames on screen isn't measured.
+//!
+//! The library's own benchmarks (`cargo bench -p gizmo-core`) cover the same
+//! phases on fixed scripts, for comparing gizmo builds rather than scripts.
+
+use crate::error::GizmoError;
+use crate::interpreter::Interpreter;
+use crate::lexer::Lexer;
+use crate::parser::Parser;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Runs used when `--runs` isn't given
+pub const DEFAULT_RUNS: usize = 5;
+
+/// How long one run of a script took, phase by phase.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Timings {
+    /// Turning the source into tokens
+    pub lex: Duration,
+    /// Turning the tokens into a syntax tree
+    pub parse: Duration,
+    /// Running the script, which makes its frames
+    pub run: Duration,
+    /// Frames the script plays
+    pub frames: usize,
+}
+
+impl Timings {
+    /// Lexing and parsing together.
+    pub fn compile(&self) -> Duration {
+        self.lex + self.parse
+    }
+
+    /// Phase by phase, the fastest of several runs.
+    fn fastest(runs: &[Timings]) -> Option<Timings> {
+        runs.iter().copied().reduce(|best, run| Timings {
+            lex: best.lex.min(run.lex),
+            parse: best.parse.min(run.parse),
+            run: best.run.min(run.run),
+            frames: run.frames,
+        })
+    }
+}
+
+/// Times one run of a script.
+///
+/// # Arguments
+/// * `source` - Contents of the .gzmo file
+/// * `script_dir` - Directory the files the script loads are relative to
+///
+/// # Returns
+/// * `Ok(Timings)` - The script ran
+/// * `Err(GizmoError)` - The script has a syntax error or failed while running
+pub fn measure(source: &str, script_dir: &Path) -> Result<Timings, GizmoError> {
+    let started = Instant::now();
+    let tokens = Lexer::new(source).tokenize()?;
+    let lexed = Instant::now();
+    let program = Parser::new(tokens).parse()?;
+    let parsed = Instant::now();
+
+    let mut interpreter = Interpreter::new();
+    interpreter.set_script_dir(script_dir);
+    interpreter.execute(&program)?;
+    let ran = Instant::now();
+
+    Ok(Timings {
+        lex: lexed - started,
+        parse: parsed - lexed,
+        run: ran - parsed,
+        frames: interpreter.get_animation_frames().len(),
+    })
+}
+
+/// Benchmarks a script and describes the results.
+///
+/// # Arguments
+/// * `name` - How to refer to the script in the report
+/// * `source` - Contents of the .gzmo file
+/// * `script_dir` - Directory the files the script loads are relative to
+/// * `runs` - How many times to run it (at least 1)
+///
+/// # Returns
+/// * `Ok(report)` - The report, one line per phase
+/// * `Err(GizmoError)` - The script failed on its first run
+pub fn run(name: &str, source: &str, script_dir: &Path, runs: usize) -> Result<String, GizmoError> {
+    let timings = (0..runs.max(1))
+        .map(|_| measure(source, script_dir))
+        .collect::<Result<Vec<_>, _>>()?;
+    let best = Timings::fastest(&timings).expect("at least one run");
+
+    let mut report = format!(
+        "{}: best of {} run{}\n",
+        name,
+        timings.len(),
+        if timings.len() == 1 { "" } else { "s" }
+    );
+    report.push_str(&format!(
+        "  compile   {:>7} ms  (lex {} ms, parse {} ms)\n",
+        millis(best.compile()),
+        millis(best.lex),
+        millis(best.parse)
+    ));
+    report.push_str(&format!("  run       {:>7} ms  {} frames\n", millis(best.run), best.frames));
+    if best.frames > 0 {
+        let per_frame = best.run / best.frames as u32;
+        let per_second = best.frames as f64 / best.run.as_secs_f64().max(f64::EPSILON);
+        report.push_str(&format!("  per frame {:>7} ms  {:.1} frames/s\n", millis(per_frame), per_second));
+    }
+    Ok(report)
+}
+
+/// Formats a duration as milliseconds with two decimals.
+fn millis(duration: Duration) -> String {
+    format!("{:.2}", duration.as_secs_f64() * 1000.0)
+}
@@ -0,0 +1,287 @@
+//! Script Bundles
+//!
+//! A `.gzmopack` file holds a buddy's script together with everything it
+//! loads, so it can be shared as a single file and started, rendered or
+//! checked like a .gzmo script. `gizmo pack` makes one:
+//!
+//! ```text
+//! gizmo pack cat.gzmo                        # cat.gzmopack
+//! gizmo pack cat.gzmo -o cat.gzmopack sounds/meow.wav
+//! gizmo start cat.gzmopack
+//! ```
+//!
+//! ## Format
+//!
+//! A bundle is a zip archive with a `manifest.json` naming its main script.
+//! Files keep their paths relative to the script, so a script loads the same
+//! files from a bundle as from its directory:
+//!
+//! ```text
+//! cat.gzmopack
+//! ├── manifest.json        {"format": 1, "main": "cat.gzmo"}
+//! ├── cat.gzmo
+//! ├── sprites/cat-1.png
+//! └── sounds/meow.wav
+//! ```
+//!
+//! `gizmo pack` adds the files the script names in `load_image()`,
+//! `load_aseprite()` and `aseprite_speed()` calls with a literal path, and any
+//! others given on the command line (paths built at run time, included
+//! scripts, sounds). Every file has to be inside the script's directory.
+//!
+//! ## Loading
+//!
+//! A bundle is unpacked into `{cache_dir}/gizmo/bundles/` the first time it
+//! is loaded and run from there. The directory is named after the bundle's
+//! contents, so a changed bundle is unpacked anew and an unchanged one is
+//! reused. `persist` state is still keyed by the bundle's own name.
+
+use crate::ast::{Expression, Program, Statement, StatementKind};
+use crate::builtin::BuiltinFunctions;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::error::Error;
+use std::fs::{self, File};
+use std::hash::{Hash, Hasher};
+use std::io::{self, Read, Seek, Write};
+use std::path::{Component, Path, PathBuf};
+use zip::write::SimpleFileOptions;
+use zip::{ZipArchive, ZipWriter};
+
+/// File extension of bundles
+pub const EXTENSION: &str = "gzmopack";
+
+/// Name of the manifest inside a bundle
+const MANIFEST: &str = "manifest.json";
+
+/// Version of the bundle layout written by `gizmo pack`
+const FORMAT: u32 = 1;
+
+/// What a bundle contains, stored as its `manifest.json`.
+#[derive(Debug, Serialize, Deserialize)]
+struct Manifest {
+    /// Bundle layout version
+    format: u32,
+    /// Path of the script to run, inside the bundle
+    main: String,
+}
+
+/// Checks whether a file name is something gizmo can play: a .gzmo script
+/// or a bundle.
+pub fn is_playable(file: &str) -> bool {
+    file.ends_with(".gzmo") || is_bundle(Path::new(file))
+}
+
+/// Checks whether a file is a bundle, by its extension.
+pub fn is_bundle(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| extension == EXTENSION)
+}
+
+/// Returns the script to run for a playable file.
+///
+/// # Arguments
+/// * `file` - A .gzmo script or a bundle
+///
+/// # Returns
+/// * `Ok(PathBuf)` - The script itself, or the main script of the unpacked bundle
+/// * `Err` - The bundle couldn't be read or unpacked
+pub fn resolve(file: &Path) -> Result<PathBuf, Box<dyn Error>> {
+    if !is_bundle(file) {
+        return Ok(file.to_path_buf());
+    }
+
+    let bytes = fs::read(file).map_err(|e| format!("{}: {}", file.display(), e))?;
+    let mut hasher = DefaultHasher::new();
+    bytes.hash(&mut hasher);
+    let stem = file.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+    let cache = dirs::cache_dir().ok_or("Could not find cache directory")?.join("gizmo").join("bundles");
+    let dir = cache.join(format!("{}-{:016x}", stem, hasher.finish()));
+
+    let mut archive = ZipArchive::new(io::Cursor::new(bytes)).map_err(|e| format!("{} isn't a bundle: {}", file.display(), e))?;
+    let manifest = read_manifest(&mut archive)?;
+    if !dir.join(MANIFEST).exists() {
+        // Unpack next to the final directory and move it into place, so a
+        // half-unpacked bundle is never picked up
+        let partial = cache.join(format!(".partial-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&partial);
+        fs::create_dir_all(&partial)?;
+        archive.extract(&partial)?;
+        if fs::rename(&partial, &dir).is_err() {
+            // Another process unpacked it first
+            let _ = fs::remove_dir_all(&partial);
+        }
+    }
+    Ok(dir.join(manifest.main))
+}
+
+/// Reads and checks a bundle's manifest.
+fn read_manifest<R: Read + Seek>(archive: &mut ZipArchive<R>) -> Result<Manifest, Box<dyn Error>> {
+    let mut text = String::new();
+    archive
+        .by_name(MANIFEST)
+        .map_err(|_| format!("The bundle has no {}", MANIFEST))?
+        .read_to_string(&mut text)?;
+    let manifest: Manifest = serde_json::from_str(&text).map_err(|e| format!("The bundle's {} isn't valid: {}", MANIFEST, e))?;
+    if manifest.format > FORMAT {
+        return Err(format!("The bundle needs a newer gizmo (format {}, this one reads up to {})", manifest.format, FORMAT).into());
+    }
+    if archive.index_for_name(&manifest.main).is_none() {
+        return Err(format!("The bundle's main script {} is missing", manifest.main).into());
+    }
+    Ok(manifest)
+}
+
+/// Checks that bytes are a bundle whose main script parses, before
+/// `gizmo get` saves a download.
+///
+/// # Returns
+/// * `Ok(())` - A usable bundle
+/// * `Err` - Not a bundle, or its script has a syntax error
+#[cfg(feature = "get")]
+pub fn validate(bytes: &[u8]) -> Result<(), Box<dyn Error>> {
+    let mut archive = ZipArchive::new(io::Cursor::new(bytes)).map_err(|e| format!("not a bundle: {}", e))?;
+    let manifest = read_manifest(&mut archive)?;
+    let mut source = String::new();
+    archive.by_name(&manifest.main)?.read_to_string(&mut source)?;
+    parse(&source)?;
+    Ok(())
+}
+
+/// Packs a script and the files it loads into a bundle.
+///
+/// # Arguments
+/// * `script` - The .gzmo file to run
+/// * `extra` - More files to add, such as ones whose paths the script builds at run time
+/// * `output` - Where to write the bundle
+///
+/// # Returns
+/// * `Ok(entries)` - Paths of the files in the bundle, script first
+/// * `Err` - The script doesn't parse, or a file is missing or outside the script's directory
+pub fn pack(script: &Path, extra: &[PathBuf], output: &Path) -> Result<Vec<String>, Box<dyn Error>> {
+    let source = fs::read_to_string(script).map_err(|e| format!("{}: {}", script.display(), e))?;
+    let program = parse(&source)?;
+    let dir = script.parent().unwrap_or(Path::new(""));
+    let main = script.file_name().ok_or("The script has no file name")?.to_string_lossy().into_owned();
+
+    let mut entries = vec![main.clone()];
+    let mut add = |entry: String| {
+        if !entries.contains(&entry) {
+            entries.push(entry);
+        }
+    };
+    for path in loaded_files(&program) {
+        add(entry_name(Path::new(&path))?);
+    }
+    let script_dir = fs::canonicalize(if dir.as_os_str().is_empty() { Path::new(".") } else { dir })?;
+    for path in extra {
+        let path = fs::canonicalize(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let relative = path
+            .strip_prefix(&script_dir)
+            .map_err(|_| format!("{} isn't inside the script's directory", path.display()))?;
+        add(entry_name(relative)?);
+    }
+
+    let mut zip = ZipWriter::new(File::create(output)?);
+    let options = SimpleFileOptions::default();
+    zip.start_file(MANIFEST, options)?;
+    zip.write_all(serde_json::to_string(&Manifest { format: FORMAT, main })?.as_bytes())?;
+    for entry in &entries {
+        let path = dir.join(entry);
+        let bytes = fs::read(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        zip.start_file(entry.as_str(), options)?;
+        zip.write_all(&bytes)?;
+    }
+    zip.finish()?;
+    Ok(entries)
+}
+
+/// Parses a script to find out what it loads.
+fn parse(source: &str) -> Result<Program, Box<dyn Error>> {
+    Ok(gizmo_core::parse(source)?)
+}
+
+/// Turns a path relative to the script into a bundle entry name.
+///
+/// # Returns
+/// * `Ok(name)` - The path with `/` separators
+/// * `Err` - The path is absolute or leaves the script's directory
+fn entry_name(path: &Path) -> Result<String, Box<dyn Error>> {
+    let mut parts = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => parts.push(part.to_string_lossy().into_owned()),
+            Component::CurDir => {}
+            _ => return Err(format!("{} isn't inside the script's directory, so it can't be bundled", path.display()).into()),
+        }
+    }
+    Ok(parts.join("/"))
+}
+
+/// Returns the literal paths a program passes to functions that load files.
+fn loaded_files(program: &Program) -> Vec<String> {
+    let builtins = BuiltinFunctions::new();
+    let mut paths = Vec::new();
+    for statement in &program.statements {
+        statement_files(statement, &builtins, &mut paths);
+    }
+    paths
+}
+
+/// Collects the loaded file paths of the statements of a block.
+fn block_files(body: &[Statement], builtins: &BuiltinFunctions, paths: &mut Vec<String>) {
+    for statement in body {
+        statement_files(statement, builtins, paths);
+    }
+}
+
+/// Collects the loaded file paths of a statement and the blocks inside it.
+fn statement_files(statement: &Statement, builtins: &BuiltinFunctions, paths: &mut Vec<String>) {
+    match &statement.kind {
+        StatementKind::VariableDeclaration { value, .. }
+        | StatementKind::Assignment { value, .. }
+        | StatementKind::PersistDeclaration { value, .. }
+        | StatementKind::ExpressionStatement(value) => expression_files(value, builtins, paths),
+        StatementKind::RepeatLoop { count, body } => {
+            expression_files(count, builtins, paths);
+            block_files(body, builtins, paths);
+        }
+        StatementKind::IfStatement { condition, then_body, else_body } => {
+            expression_files(condition, builtins, paths);
+            block_files(then_body, builtins, paths);
+            block_files(else_body.as_deref().unwrap_or_default(), builtins, paths);
+        }
+        StatementKind::WhenStatement { body, .. } => block_files(body, builtins, paths),
+        StatementKind::Trivia(_) => {}
+    }
+}
+
+/// Collects the loaded file paths of an expression and the ones inside it.
+fn expression_files(expression: &Expression, builtins: &BuiltinFunctions, paths: &mut Vec<String>) {
+    let inner: Vec<&Expression> = match expression {
+        Expression::FunctionCall { name, args } => {
+            if let (true, Some(Expression::String(path))) = (builtins.takes_file_path(name), args.first()) {
+                paths.push(path.clone());
+            }
+            args.iter().collect()
+        }
+        Expression::Array(elements) => elements.iter().collect(),
+        Expression::NamedArgument { value, .. } => vec![value],
+        Expression::BinaryOperation { left, right, .. } => vec![left, right],
+        Expression::TernaryOperation { condition, true_expr, false_expr } => vec![condition, true_expr, false_expr],
+        Expression::PatternGenerator { body, return_expr, .. } => {
+            block_files(body, builtins, paths);
+            vec![return_expr]
+        }
+        Expression::Pipeline { passes, .. } => {
+            for pass in passes {
+                block_files(&pass.body, builtins, paths);
+            }
+            passes.iter().map(|pass| &pass.return_expr).collect()
+        }
+        Expression::Grouping(inner) | Expression::LineBreak(inner) => vec![inner],
+        Expression::Number(_) | Expression::String(_) | Expression::Identifier(_) => Vec::new(),
+    };
+    for expression in inner {
+        expression_files(expression, builtins, paths);
+    }
+}
@@ -0,0 +1,78 @@
+//! Pattern Cache Storage
+//!
+//! Keeps each script's [`PatternCache`] between starts, so starting a script
+//! that hasn't changed takes its pattern frames from disk instead of
+//! rendering them again. Caches live in `{config_dir}/cache/`, one per
+//! script path:
+//!
+//! ```text
+//! cache/waves-3f2a9c1e5b7d8046.json
+//! ```
+//!
+//! Only the frames the last start rendered are kept, so a cache never holds
+//! more than one run's worth of patterns. Deleting the directory is always
+//! safe; the next start renders everything again.
+
+use crate::daemon;
+use gizmo_core::cache::{Fnv1a, PatternCache};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Returns the cache file for a script.
+fn cache_path(script: &Path) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let script = fs::canonicalize(script)?;
+    let name = script
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .ok_or("Script file name is not valid UTF-8")?;
+    // The same script gets the same file from every build of gizmo
+    let mut hasher = Fnv1a::new();
+    hasher.write(script.as_os_str().as_encoded_bytes());
+
+    let dir = daemon::get_config_dir()?.join("cache");
+    fs::create_dir_all(&dir)?;
+    Ok(dir.join(format!("{}-{:016x}.json", name, hasher.finish())))
+}
+
+/// Reads the pattern cache the script's last start saved.
+///
+/// A missing or unreadable cache starts an empty one; an unreadable one is
+/// reported on stderr and replaced by the next save.
+///
+/// # Arguments
+/// * `script` - Path of the .gzmo file or .gzmopack bundle
+pub fn load(script: &Path) -> PatternCache {
+    let Ok(path) = cache_path(script) else {
+        return PatternCache::new();
+    };
+    let Ok(content) = fs::read_to_string(&path) else {
+        return PatternCache::new();
+    };
+    serde_json::from_str(&content).unwrap_or_else(|e| {
+        eprintln!("Ignoring unreadable pattern cache {}: {}", path.display(), e);
+        PatternCache::new()
+    })
+}
+
+/// Writes a script's pattern cache, keeping only what this start used.
+///
+/// # Arguments
+/// * `script` - Path of the .gzmo file or .gzmopack bundle
+/// * `cache` - The cache the script ran with
+///
+/// # Returns
+/// * `Ok(())` - Cache written, or unchanged and left as it was
+/// * `Err` - The config directory or cache file couldn't be written
+pub fn save(script: &Path, mut cache: PatternCache) -> Result<(), Box<dyn std::error::Error>> {
+    cache.prune();
+    if !cache.is_changed() {
+        return Ok(());
+    }
+    let path = cache_path(script)?;
+
+    // Write then rename so a crash mid-write can't leave half a cache
+    let temporary = path.with_extension("json.tmp");
+    fs::write(&temporary, serde_json::to_string(&cache)?)?;
+    fs::rename(&temporary, &path)?;
+    Ok(())
+}
@@ -0,0 +1,226 @@
+//! Script Checking
+//!
+//! `gizmo check <file>...` runs scripts through the same lexer, parser and
+//! interpreter as `gizmo start` without opening a window, and reports every
+//! problem it finds - not just the first - in the `file:line:column: message`
+//! form editors and CI logs turn into links:
+//!
+//! ```text
+//! pets/cat.gzmo:4:12: Lexical error: Unexpected character '@'
+//! pets/cat.gzmo:9:14: Parse error: Expected '=', found 'Newline'
+//! pets/dog.gzmo:7:5: Type error: Binary operations only supported for numbers
+//! ```
+//!
+//! ## Phases
+//!
+//! 1. **Lexing**: Bad characters and literals are reported and skipped
+//! 2. **Parsing**: A statement with a syntax error is reported and skipped up
+//!    to the end of the statement, so each broken statement is reported once
+//!    (and not at all on a line that already has a lexical error)
+//! 3. **Resolving**: A script without syntax errors is gone through without
+//!    running it (see the core's `resolve` module), to catch variables read
+//!    before they are assigned and calls built-in functions can't take, in
+//!    event handlers and untaken branches too
+//! 4. **Running**: A script that resolves is run once, as on start, to catch
+//!    the type, argument and frame errors left, reported at the innermost
+//!    statement that was running. Event handlers aren't run.
+//!
+//! ## Warnings
+//!
+//! [`warnings`] finds mistakes that don't stop a script (see the core's
+//! `lint` module), for `check` and `start` to print after the problems. They
+//! don't make `check` fail:
+//!
+//! ```text
+//! pets/cat.gzmo:3:1: warning: Variable 'speed' is never used
+//! ```
+//!
+//! ## Emitting
+//!
+//! `gizmo check <file> --emit tokens|ast|frames-json` prints what one phase
+//! made of a script instead of `ok`, for debugging the parser and for other
+//! tools: the token stream with positions, the pretty-printed syntax tree, or
+//! the frames the script plays as JSON. A script that doesn't get through the
+//! phase gets its problems reported as usual.
+
+use crate::ast::Frame;
+use crate::error::{GizmoDiagnostic, GizmoError};
+use crate::interpreter::Interpreter;
+use crate::lexer::{Lexer, Position};
+use crate::lint;
+use crate::palette::Palette;
+use crate::parser::Parser;
+use crate::resolve;
+use gizmo_core::Limits;
+use serde_json::json;
+use std::error::Error;
+use std::path::Path;
+
+/// One problem found in a script.
+#[derive(Debug, Clone)]
+pub struct Problem {
+    /// Where the problem is, if known
+    pub position: Option<Position>,
+    /// What is wrong
+    pub error: GizmoError,
+}
+
+/// Checks a script's source code.
+///
+/// # Arguments
+/// * `source` - Contents of the .gzmo file
+/// * `script_dir` - Directory of the .gzmo file, where the files it loads are
+///
+/// # Returns
+/// Every problem found, in source order; empty if the script is fine
+pub fn check(source: &str, script_dir: &Path) -> Vec<Problem> {
+    check_within(source, script_dir, Limits::default())
+}
+
+/// Checks a script's source code, running it within the given limits.
+///
+/// A run that goes past them is reported as a problem, like any other
+/// runtime error.
+///
+/// # Arguments
+/// * `source` - Contents of the .gzmo file
+/// * `script_dir` - Directory of the .gzmo file, where the files it loads are
+/// * `limits` - How long the run may take and how much it may make
+///
+/// # Returns
+/// Every problem found, in source order; empty if the script is fine
+pub fn check_within(source: &str, script_dir: &Path, limits: Limits) -> Vec<Problem> {
+    let (tokens, positions, lex_errors) = Lexer::new(source).tokenize_all();
+    let mut problems: Vec<Problem> = lex_errors
+        .into_iter()
+        .map(|(position, error)| Problem { position: Some(position), error })
+        .collect();
+
+    // A skipped bad character usually breaks its statement too; one report
+    // per line is enough
+    let lex_lines: Vec<usize> = problems.iter().filter_map(|problem| problem.position).map(|(line, _)| line).collect();
+    let (program, parse_errors) = Parser::with_positions(tokens, positions.clone()).parse_all();
+    problems.extend(
+        parse_errors
+            .into_iter()
+            .map(|(index, error)| Problem { position: positions.get(index).copied(), error })
+            .filter(|problem| problem.position.is_none_or(|(line, _)| !lex_lines.contains(&line))),
+    );
+    problems.sort_by_key(|problem| problem.position);
+
+    // Running half a program would only report what the syntax errors broke
+    if problems.is_empty() {
+        problems.extend(resolve::resolve(&program).into_iter().map(located));
+    }
+    if problems.is_empty() {
+        let mut interpreter = Interpreter::new();
+        interpreter.set_script_dir(script_dir);
+        interpreter.set_limits(limits);
+        if let Err(error) = interpreter.execute(&program) {
+            problems.push(located(error));
+        }
+    }
+    problems
+}
+
+/// Turns an error carrying its position into a problem.
+fn located(error: GizmoError) -> Problem {
+    Problem { position: error.position(), error: error.without_position().clone() }
+}
+
+/// Finds the warnings in a script.
+///
+/// # Arguments
+/// * `source` - Contents of the .gzmo file
+///
+/// # Returns
+/// Every warning, in source order; empty if the script has a syntax error,
+/// as a broken statement would make the rest look unused
+pub fn warnings(source: &str) -> Vec<GizmoDiagnostic> {
+    gizmo_core::parse(source).map(|program| lint::warnings(&program)).unwrap_or_default()
+}
+
+/// What `gizmo check --emit` prints.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Emit {
+    /// One token per line, after its `line:column`
+    Tokens,
+    /// The syntax tree, pretty-printed
+    Ast,
+    /// The frames, their timing and their colors as JSON
+    FramesJson,
+}
+
+impl Emit {
+    /// Parses an `--emit` value.
+    ///
+    /// # Arguments
+    /// * `name` - `"tokens"`, `"ast"` or `"frames-json"`
+    ///
+    /// # Returns
+    /// * `Ok(Emit)` - Known output
+    /// * `Err` - Unknown name
+    pub fn parse(name: &str) -> Result<Self, Box<dyn Error>> {
+        match name {
+            "tokens" => Ok(Emit::Tokens),
+            "ast" => Ok(Emit::Ast),
+            "frames-json" => Ok(Emit::FramesJson),
+            _ => Err(format!("Unknown output '{}' (expected tokens, ast or frames-json)", name).into()),
+        }
+    }
+}
+
+/// Runs a script up to the phase an output comes from and prints it.
+///
+/// # Arguments
+/// * `source` - Contents of the .gzmo file
+/// * `script_dir` - Directory of the .gzmo file, where the files it loads are
+/// * `emit` - What to print
+///
+/// # Returns
+/// * `Ok(String)` - The output, ending with a newline
+/// * `Err(problems)` - The script didn't get through the phase, for
+///   reporting like [`check`] does
+pub fn emit(source: &str, script_dir: &Path, emit: Emit) -> Result<String, Vec<Problem>> {
+    match emit {
+        Emit::Tokens => {
+            let (tokens, positions, errors) = Lexer::new(source).tokenize_all();
+            if !errors.is_empty() {
+                return Err(errors.into_iter().map(|(position, error)| Problem { position: Some(position), error }).collect());
+            }
+            let lines: Vec<String> = tokens
+                .iter()
+                .zip(&positions)
+                .map(|(token, (line, column))| format!("{}:{}\t{:?}\n", line, column, token))
+                .collect();
+            Ok(lines.concat())
+        }
+        Emit::Ast => {
+            let program = gizmo_core::parse(source).map_err(|_| check(source, script_dir))?;
+            Ok(format!("{:#?}\n", program))
+        }
+        Emit::FramesJson => {
+            let animation = gizmo_core::compile_in(source, script_dir).map_err(|_| check(source, script_dir))?;
+            Ok(format!("{}\n", frames_json(animation.frames(), animation.palette(), animation.frame_duration_ms())))
+        }
+    }
+}
+
+/// Describes frames as JSON: their timing, the `#RRGGBBAA` colors of the
+/// palette indices they use, and each frame's rows of indices.
+fn frames_json(frames: &[Frame], palette: &Palette, frame_ms: u64) -> String {
+    let highest = frames.iter().flat_map(|frame| frame.pixels.iter().flatten()).copied().max().unwrap_or(0).max(1);
+    let colors: Vec<String> = (0..=highest)
+        .map(|index| {
+            let argb = palette.color(index);
+            format!("#{:06x}{:02x}", argb & 0xFFFFFF, argb >> 24)
+        })
+        .collect();
+    let frames: Vec<_> = frames
+        .iter()
+        .map(|frame| json!({ "width": frame.width, "height": frame.height, "pixels": *frame.pixels }))
+        .collect();
+
+    let output = json!({ "frame_ms": frame_ms, "palette": colors, "frames": frames });
+    output.to_string()
+}
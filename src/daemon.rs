@@ -9,18 +9,54 @@
 //!
 //! Gizmo uses a dual-process model:
 //!
-//! 1. **CLI Process**: Handles user commands (`start`, `stop`, `restart`)
+//! 1. **CLI Process**: Handles user commands (`start`, `stop`, `restart`, `status`)
 //! 2. **GUI Process**: Runs the desktop window and animation loop
 //!
-//! The CLI process spawns the GUI process using `nohup` to detach it from the
-//! terminal, then exits. The GUI process continues running independently.
+//! `gizmo run` runs the GUI inside the CLI process instead, attached to the
+//! terminal; it doesn't touch the daemon state below.
+//!
+//! The CLI process spawns the GUI process detached from the terminal (in a
+//! new session on Unix, `DETACHED_PROCESS` on Windows), then exits. The GUI process
+//! continues running independently, writing its standard output and error
+//! (script errors, panics, pacing statistics) to the log file.
+//!
+//! ## Log Rotation
+//!
+//! Every start appends a `--- <time> Starting <file>` line and the new
+//! session's output to `gizmo.log`. A log that has grown past
+//! [`LOG_ROTATE_BYTES`] is first moved to `gizmo.log.1`, replacing the one
+//! before, so at most two sessions' worth of large logs are kept.
+//!
+//! ## Instances
+//!
+//! Several buddies can run at once, each under its own name (`--name work`).
+//! Every instance has its own state directory, so each has its own PID,
+//! script, position, log and control socket:
+//!
+//! - The `default` instance (no `--name`) keeps its state in the config
+//!   directory itself, where gizmo has always kept it
+//! - Other instances keep theirs in `{config_dir}/instances/<name>/`
+//!
+//! The GUI process is spawned with `--name <name>`, which is also how it is
+//! told apart from other instances' GUIs when its PID file is lost.
 //!
 //! ## State Management
 //!
-//! The daemon system maintains persistent state in the user's config directory:
+//! The daemon system maintains persistent state in each instance's state
+//! directory (see [Instances](#instances)):
 //!
 //! - **Current File** (`current.txt`): Path to the currently loaded .gzmo file
-//! - **Process ID** (`daemon.pid`): PID of the running GUI process
+//! - **Start Options** (`options.json`): The flags it was started with, for `restart`
+//! - **Process ID** (`daemon.pid`): PID of the running GUI process, and when
+//!   it started (see [Stale PIDs](#stale-pids))
+//! - **Window Position** (`position.txt`): Where the window was last dragged to
+//! - **GUI Status** (`status.json`): What the GUI process is playing, for `gizmo status`
+//! - **Log** (`gizmo.log`): The GUI process's output, for `gizmo logs`
+//! - **Crash Report** (`crash.json`): How the last GUI process died, if it
+//!   didn't exit cleanly (see [Crashes](#crashes))
+//! - **Control Socket** (`gizmo.sock`, Unix): Where the GUI process takes commands (see the `ipc` module)
+//! - **Pet State** (`pets/<script>.json`, config directory): `persist`
+//!   variables, shared by every instance (see the `persist` module)
 //!
 //! This state allows commands like `restart` to work without requiring the
 //! user to specify the file path again.
@@ -29,31 +65,89 @@
 //!
 //! ### Starting
 //! - Validates .gzmo file exists
-//! - Spawns detached GUI process with `nohup`
+//! - Spawns detached GUI process (see [`spawn_detached`])
 //! - Saves process PID and file path
 //!
 //! ### Stopping
-//! - Sends SIGTERM to GUI process
-//! - Falls back to `pkill` if PID-based termination fails
+//! - Checks the saved PID still belongs to a gizmo GUI process
+//! - Removes the PID file first, so a supervisor sees the exit as a stop
+//!   rather than a crash
+//! - Asks the GUI process to stop (SIGTERM on Unix, `WM_CLOSE` on Windows) so
+//!   it can play its farewell animation and fade out
+//! - Waits up to [`STOP_TIMEOUT`], then kills it (SIGKILL / `TerminateProcess`)
+//! - Falls back to finding the GUI by command line if PID-based termination
+//!   fails (Linux and macOS only)
 //! - Cleans up state files
 //!
+//! ### Crashes
+//! - A panicking GUI process writes a crash report before it dies
+//! - A GUI process that dies without cleaning up (panic, killed) leaves its
+//!   PID file behind; the next `start` notices the PID is no longer gizmo's,
+//!   reports the crash and starts anyway
+//! - With `start --respawn N` a supervisor process (see the `supervisor`
+//!   module) owns the GUI process and restarts it after up to N crashes
+//!
+//! ### Stale PIDs
+//! - PIDs are reused, so the PID file also records the process's start time
+//!   as the system reports it
+//! - A PID whose process is gone, or started at another time, is stale:
+//!   [`is_daemon_running`] removes the state and records a crash, so `start`
+//!   goes ahead instead of refusing because an unrelated process took the PID
+//! - PID files without a start time (written by older versions) are only
+//!   checked for liveness
+//!
 //! ### Restarting
 //! - Retrieves saved file path
 //! - Stops current process
 //! - Starts new process with same file
 //!
+//! ### Controlling
+//! - Everything short of stopping (pausing, moving, live status) goes to the
+//!   running GUI process over its control socket (see the `ipc` module)
+//!
 //! ## Platform Compatibility
 //!
-//! Currently designed for Unix-like systems (macOS, Linux) with:
-//! - `nohup` for process detachment
-//! - `kill` for process termination
-//! - `pkill` for fallback termination
+//! Unix-like systems (macOS, Linux) use:
+//! - `setsid()` in the spawned process for detachment
+//! - `kill()` for stop requests, forced termination and liveness checks
+//! - `/proc/<pid>/stat` (Linux) / `proc_pidinfo` (macOS) for process start times
+//! - The process's command line (`/proc` on Linux, `sysctl` on macOS) to
+//!   make sure a PID still belongs to gizmo, and to find the GUI when the
+//!   PID file is lost
 //!
-//! Future versions could extend support to Windows with equivalent mechanisms.
+//! Windows uses:
+//! - `DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP` creation flags for detachment,
+//!   plus `CREATE_BREAKAWAY_FROM_JOB` where the terminal's job object allows it
+//! - `OpenProcess` + `GetExitCodeProcess` for liveness checks
+//! - `GetProcessTimes` for process start times
+//! - `QueryFullProcessImageNameW` to make sure a PID still belongs to gizmo
+//! - `WM_CLOSE` to the process's visible windows for stop requests
+//! - `TerminateProcess` for forced termination
+//!
+//! The state files are identical on every platform.
+
+// Builds without the window only read the state the GUI process writes
+#![cfg_attr(not(feature = "gui"), allow(dead_code))]
+
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// How long `gizmo stop` waits for the GUI to finish its farewell before killing it.
+pub const STOP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Longest farewell animation the GUI plays, leaving time to fade out
+/// within [`STOP_TIMEOUT`].
+pub const FAREWELL_LIMIT: Duration = Duration::from_secs(3);
 
-use std::fs;
-use std::path::PathBuf;
-use dirs;
+/// Size past which the log is moved aside when the GUI starts.
+pub const LOG_ROTATE_BYTES: u64 = 1024 * 1024;
+
+/// Name of the instance started without `--name`.
+pub const DEFAULT_INSTANCE: &str = "default";
 
 /// Gets the Gizmo configuration directory, creating it if necessary.
 ///
@@ -68,11 +162,14 @@ use dirs;
 /// # Directory Location
 /// - **macOS**: `~/Library/Application Support/gizmo/`
 /// - **Linux**: `~/.config/gizmo/`
-/// - **Windows**: `%APPDATA%\gizmo\` (if supported)
+/// - **Windows**: `%APPDATA%\gizmo\`
 ///
 /// # Files Stored
-/// - `current.txt` - Path to currently loaded .gzmo file
-/// - `daemon.pid` - Process ID of running GUI instance
+/// - The default instance's state (see [`instance_dir`])
+/// - `instances/` - Other instances' state directories
+/// - `pets/` - Scripts' `persist` variables
+/// - `library/` - Scripts downloaded with `gizmo get`
+/// - `cache/` - Scripts' pattern frames from their last start
 pub fn get_config_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
     let mut config_dir = dirs::config_dir()
         .ok_or("Could not find config directory")?;
@@ -86,6 +183,80 @@ pub fn get_config_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
     Ok(config_dir)
 }
 
+/// Gets an instance's state directory, which doesn't exist until something
+/// is saved in it (see [`create_instance_dir`]), so looking up an instance
+/// that was never started leaves no trace.
+///
+/// # Arguments
+/// * `instance` - Instance name (see [`validate_instance_name`])
+///
+/// # Returns
+/// * `Ok(PathBuf)` - The config directory for [`DEFAULT_INSTANCE`],
+///   `{config_dir}/instances/<name>/` for any other
+/// * `Err` - If the config directory can't be found or created
+///
+/// # Files Stored
+/// - `current.txt` - Path to currently loaded .gzmo file
+/// - `options.json` - Flags the GUI process was started with
+/// - `daemon.pid` - Process ID of running GUI instance
+/// - `position.txt` - Last window position as `x,y`
+/// - `status.json` - What the running GUI instance is playing
+/// - `gizmo.log`, `gizmo.log.1` - Output of the current and earlier GUI instances
+/// - `gizmo.sock` - Control socket of the running GUI instance (Unix)
+pub fn instance_dir(instance: &str) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let config_dir = get_config_dir()?;
+    if instance == DEFAULT_INSTANCE {
+        return Ok(config_dir);
+    }
+
+    Ok(config_dir.join("instances").join(instance))
+}
+
+/// Gets an instance's state directory for saving in, creating it if necessary.
+///
+/// # Returns
+/// * `Ok(PathBuf)` - As for [`instance_dir`]
+/// * `Err` - If the directory can't be found or created
+pub fn create_instance_dir(instance: &str) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let dir = instance_dir(instance)?;
+    if !dir.exists() {
+        fs::create_dir_all(&dir)?;
+    }
+    Ok(dir)
+}
+
+/// Checks an instance name can be used as a directory and pipe name.
+///
+/// # Returns
+/// * `Ok(())` - The name is 1-32 ASCII letters, digits, `-` or `_`
+/// * `Err` - It isn't
+pub fn validate_instance_name(name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let valid_char = |c: char| c.is_ascii_alphanumeric() || c == '-' || c == '_';
+    if name.is_empty() || name.len() > 32 || !name.chars().all(valid_char) {
+        return Err(format!("Invalid name '{}': use up to 32 letters, digits, '-' or '_'", name).into());
+    }
+    Ok(())
+}
+
+/// Lists every instance that has been started at least once.
+///
+/// # Returns
+/// [`DEFAULT_INSTANCE`] followed by the other instances' names, sorted
+pub fn list_instances() -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let mut names: Vec<String> = match fs::read_dir(get_config_dir()?.join("instances")) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_dir())
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .filter(|name| validate_instance_name(name).is_ok() && name != DEFAULT_INSTANCE)
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    names.sort();
+    names.insert(0, DEFAULT_INSTANCE.to_string());
+    Ok(names)
+}
+
 /// Saves the current .gzmo file path for future restart operations.
 ///
 /// Stores the absolute path to the currently loaded .gzmo file so that
@@ -93,6 +264,7 @@ pub fn get_config_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
 /// user to specify it again.
 ///
 /// # Arguments
+/// * `instance` - Instance the file belongs to
 /// * `file_path` - Absolute path to the .gzmo file to save
 ///
 /// # Returns
@@ -100,9 +272,9 @@ pub fn get_config_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
 /// * `Err` - I/O error writing to config file
 ///
 /// # State File
-/// The path is stored in `{config_dir}/current.txt` as plain text.
-pub fn save_current_file(file_path: &str) -> Result<(), Box<dyn std::error::Error>> {
-    let config_dir = get_config_dir()?;
+/// The path is stored in `{instance_dir}/current.txt` as plain text.
+pub fn save_current_file(instance: &str, file_path: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let config_dir = create_instance_dir(instance)?;
     let current_file_path = config_dir.join("current.txt");
     fs::write(current_file_path, file_path)?;
     Ok(())
@@ -113,6 +285,9 @@ pub fn save_current_file(file_path: &str) -> Result<(), Box<dyn std::error::Erro
 /// Reads the file path that was saved by a previous `start` command,
 /// enabling the `restart` command to reload the same animation.
 ///
+/// # Arguments
+/// * `instance` - Instance to look up
+///
 /// # Returns
 /// * `Ok(String)` - Absolute path to the saved .gzmo file
 /// * `Err` - If no file is saved or I/O error reading config
@@ -121,8 +296,8 @@ pub fn save_current_file(file_path: &str) -> Result<(), Box<dyn std::error::Erro
 /// - No previous `start` command has been run
 /// - Config file is corrupted or unreadable
 /// - File system permissions prevent access
-pub fn get_current_file() -> Result<String, Box<dyn std::error::Error>> {
-    let config_dir = get_config_dir()?;
+pub fn get_current_file(instance: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let config_dir = instance_dir(instance)?;
     let current_file_path = config_dir.join("current.txt");
     
     if !current_file_path.exists() {
@@ -133,6 +308,33 @@ pub fn get_current_file() -> Result<String, Box<dyn std::error::Error>> {
     Ok(content.trim().to_string())
 }
 
+/// Saves the flags `start` launched the GUI process with, so `restart`
+/// brings it back the same way.
+///
+/// # Arguments
+/// * `instance` - Instance being started
+/// * `flags` - Renderer, colors, `--respawn` and the other start flags, as
+///   they would be typed
+///
+/// # State File
+/// The flags are stored in `{instance_dir}/options.json` as a JSON array.
+pub fn save_start_options(instance: &str, flags: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let config_dir = create_instance_dir(instance)?;
+    fs::write(config_dir.join("options.json"), serde_json::to_string(flags)?)?;
+    Ok(())
+}
+
+/// Retrieves the flags the last `start` used.
+///
+/// # Returns
+/// * `Some(flags)` - The saved flags
+/// * `None` - Nothing saved (started by an older gizmo), or the file is unreadable
+pub fn get_start_options(instance: &str) -> Option<Vec<String>> {
+    let config_dir = instance_dir(instance).ok()?;
+    let content = fs::read_to_string(config_dir.join("options.json")).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
 /// Saves the GUI process ID for future process management.
 ///
 /// Stores the PID of the detached GUI process so that `stop` and `restart`
@@ -140,6 +342,7 @@ pub fn get_current_file() -> Result<String, Box<dyn std::error::Error>> {
 /// successful process spawn.
 ///
 /// # Arguments
+/// * `instance` - Instance the process runs
 /// * `pid` - Process ID of the GUI process to track
 ///
 /// # Returns
@@ -147,11 +350,17 @@ pub fn get_current_file() -> Result<String, Box<dyn std::error::Error>> {
 /// * `Err` - I/O error writing to config file
 ///
 /// # State File
-/// The PID is stored in `{config_dir}/daemon.pid` as plain text.
-pub fn save_daemon_pid(pid: u32) -> Result<(), Box<dyn std::error::Error>> {
-    let config_dir = get_config_dir()?;
+/// The PID is stored in `{instance_dir}/daemon.pid` as plain text, followed
+/// on a second line by the process's start time where the platform reports
+/// one (see [`process_start_time`]).
+pub fn save_daemon_pid(instance: &str, pid: u32) -> Result<(), Box<dyn std::error::Error>> {
+    let config_dir = create_instance_dir(instance)?;
     let pid_file_path = config_dir.join("daemon.pid");
-    fs::write(pid_file_path, pid.to_string())?;
+    let content = match process_start_time(pid) {
+        Some(started) => format!("{}\n{}", pid, started),
+        None => pid.to_string(),
+    };
+    fs::write(pid_file_path, content)?;
     Ok(())
 }
 
@@ -160,6 +369,9 @@ pub fn save_daemon_pid(pid: u32) -> Result<(), Box<dyn std::error::Error>> {
 /// Reads the PID that was saved when the GUI process was started,
 /// enabling `stop` and `restart` commands to control the process.
 ///
+/// # Arguments
+/// * `instance` - Instance to look up
+///
 /// # Returns
 /// * `Ok(u32)` - Process ID of the running GUI process
 /// * `Err` - If no PID is saved or parsing fails
@@ -168,8 +380,13 @@ pub fn save_daemon_pid(pid: u32) -> Result<(), Box<dyn std::error::Error>> {
 /// - No daemon is currently tracked (no start command run)
 /// - PID file is corrupted or contains invalid data
 /// - File system permissions prevent access
-pub fn get_daemon_pid() -> Result<u32, Box<dyn std::error::Error>> {
-    let config_dir = get_config_dir()?;
+pub fn get_daemon_pid(instance: &str) -> Result<u32, Box<dyn std::error::Error>> {
+    Ok(read_pid_file(instance)?.0)
+}
+
+/// Reads the PID file: the PID, and the start time recorded with it, if any.
+fn read_pid_file(instance: &str) -> Result<(u32, Option<u64>), Box<dyn std::error::Error>> {
+    let config_dir = instance_dir(instance)?;
     let pid_file_path = config_dir.join("daemon.pid");
     
     if !pid_file_path.exists() {
@@ -177,8 +394,293 @@ pub fn get_daemon_pid() -> Result<u32, Box<dyn std::error::Error>> {
     }
     
     let content = fs::read_to_string(pid_file_path)?;
-    let pid: u32 = content.trim().parse()?;
-    Ok(pid)
+    let mut lines = content.lines().map(str::trim);
+    let pid: u32 = lines.next().unwrap_or_default().parse()?;
+    let started = lines.next().and_then(|line| line.parse().ok());
+    Ok((pid, started))
+}
+
+/// Saves the window's outer position so the next start can restore it.
+///
+/// Called by the GUI process whenever a drag ends, and by `start --position`
+/// to seed the position before the window opens.
+///
+/// # Arguments
+/// * `instance` - Instance whose window moved
+/// * `x` - Horizontal position of the window's top-left corner in physical pixels
+/// * `y` - Vertical position of the window's top-left corner in physical pixels
+///
+/// # State File
+/// The position is stored in `{instance_dir}/position.txt` as `x,y`.
+pub fn save_window_position(instance: &str, x: i32, y: i32) -> Result<(), Box<dyn std::error::Error>> {
+    let config_dir = create_instance_dir(instance)?;
+    let position_file_path = config_dir.join("position.txt");
+    fs::write(position_file_path, format!("{},{}", x, y))?;
+    Ok(())
+}
+
+/// Retrieves the last saved window position.
+///
+/// # Arguments
+/// * `instance` - Instance to look up
+///
+/// # Returns
+/// * `Some((x, y))` - Saved position in physical pixels
+/// * `None` - No position saved yet, or the file is unreadable
+pub fn get_window_position(instance: &str) -> Option<(i32, i32)> {
+    let config_dir = instance_dir(instance).ok()?;
+    let content = fs::read_to_string(config_dir.join("position.txt")).ok()?;
+    parse_position(&content).ok()
+}
+
+/// Parses a window position written as `x,y`.
+///
+/// # Arguments
+/// * `text` - Position text such as `"120,80"` (surrounding whitespace is ignored)
+///
+/// # Returns
+/// * `Ok((x, y))` - Parsed coordinates
+/// * `Err` - Missing comma or non-integer coordinates
+pub fn parse_position(text: &str) -> Result<(i32, i32), Box<dyn std::error::Error>> {
+    let (x, y) = text
+        .trim()
+        .split_once(',')
+        .ok_or_else(|| format!("Invalid position '{}', expected x,y", text.trim()))?;
+    
+    Ok((x.trim().parse()?, y.trim().parse()?))
+}
+
+/// What the GUI process reports about itself for `gizmo status`.
+///
+/// The GUI writes it when it starts and whenever the playing animation or
+/// its speed changes, so the file is never rewritten on every frame.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GuiStatus {
+    /// Process ID of the GUI process that wrote the status
+    pub pid: u32,
+    /// The .gzmo file being played (changes when a script is dropped on the buddy)
+    pub file: String,
+    /// Seconds since the Unix epoch when the GUI process started
+    pub started_at: u64,
+    /// Frames in the looping animation
+    pub frame_count: usize,
+    /// How long each frame is shown, in milliseconds
+    pub frame_ms: u64,
+}
+
+impl GuiStatus {
+    /// Creates the status of a GUI process starting now.
+    ///
+    /// # Arguments
+    /// * `file` - The .gzmo file being played
+    pub fn new(file: &str) -> Self {
+        Self {
+            pid: std::process::id(),
+            file: file.to_string(),
+            started_at: SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |since| since.as_secs()),
+            frame_count: 0,
+            frame_ms: 0,
+        }
+    }
+
+    /// Returns how long the GUI process has been running.
+    pub fn uptime(&self) -> Duration {
+        let started_at = UNIX_EPOCH + Duration::from_secs(self.started_at);
+        SystemTime::now().duration_since(started_at).unwrap_or_default()
+    }
+}
+
+/// Saves the GUI process's status.
+///
+/// # Arguments
+/// * `instance` - Instance the GUI process runs
+/// * `status` - The status to report
+///
+/// # Returns
+/// * `Ok(())` - Status saved successfully
+/// * `Err` - I/O error writing to config file
+///
+/// # State File
+/// The status is stored in `{instance_dir}/status.json`.
+pub fn save_gui_status(instance: &str, status: &GuiStatus) -> Result<(), Box<dyn std::error::Error>> {
+    let config_dir = create_instance_dir(instance)?;
+    fs::write(config_dir.join("status.json"), serde_json::to_string_pretty(status)?)?;
+    Ok(())
+}
+
+/// Retrieves the status the GUI process last saved.
+///
+/// # Arguments
+/// * `instance` - Instance to look up
+///
+/// # Returns
+/// * `Some(status)` - The saved status; check `pid` against the running
+///   daemon, as a GUI that crashed leaves its status behind
+/// * `None` - No status saved, or the file is unreadable
+pub fn get_gui_status(instance: &str) -> Option<GuiStatus> {
+    let config_dir = instance_dir(instance).ok()?;
+    let content = fs::read_to_string(config_dir.join("status.json")).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// How a GUI process that didn't exit cleanly died.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CrashReport {
+    /// Process ID of the GUI process that crashed
+    pub pid: u32,
+    /// The .gzmo file it was started with
+    pub file: String,
+    /// Seconds since the Unix epoch when it crashed (or when the crash was noticed)
+    pub time: u64,
+    /// The panic message, exit code or signal
+    pub reason: String,
+}
+
+impl CrashReport {
+    /// Creates a report of a crash happening now.
+    ///
+    /// # Arguments
+    /// * `pid` - Process ID of the GUI process
+    /// * `file` - The .gzmo file it was started with
+    /// * `reason` - What went wrong
+    pub fn new(pid: u32, file: &str, reason: String) -> Self {
+        Self {
+            pid,
+            file: file.to_string(),
+            time: SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |since| since.as_secs()),
+            reason,
+        }
+    }
+
+    /// Returns when the crash happened, in local time.
+    pub fn local_time(&self) -> String {
+        chrono::DateTime::from_timestamp(self.time as i64, 0)
+            .map(|time| time.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_default()
+    }
+}
+
+/// Saves a crash report, replacing the previous one.
+///
+/// # Arguments
+/// * `instance` - Instance whose GUI process crashed
+/// * `report` - What happened
+///
+/// # State File
+/// The report is stored in `{instance_dir}/crash.json`.
+pub fn save_crash_report(instance: &str, report: &CrashReport) -> Result<(), Box<dyn std::error::Error>> {
+    let config_dir = create_instance_dir(instance)?;
+    fs::write(config_dir.join("crash.json"), serde_json::to_string_pretty(report)?)?;
+    Ok(())
+}
+
+/// Retrieves the last crash report.
+///
+/// # Arguments
+/// * `instance` - Instance to look up
+///
+/// # Returns
+/// * `Some(report)` - The GUI process crashed since the last `start`
+/// * `None` - No crash reported, or the file is unreadable
+pub fn get_crash_report(instance: &str) -> Option<CrashReport> {
+    let config_dir = instance_dir(instance).ok()?;
+    let content = fs::read_to_string(config_dir.join("crash.json")).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Deletes the crash report once it has been shown by `start`.
+pub fn clear_crash_report(instance: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let path = instance_dir(instance)?.join("crash.json");
+    if path.exists() {
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+/// Makes the GUI process write a crash report when it panics.
+///
+/// The PID file is left in place: a supervisor needs it to tell the crash
+/// from a stop, and without one the next `start` notices it is stale.
+///
+/// # Arguments
+/// * `instance` - Instance the GUI process runs
+/// * `gzmo_file` - The script it was started with
+pub fn report_panics(instance: &str, gzmo_file: &str) {
+    let (instance, gzmo_file) = (instance.to_string(), gzmo_file.to_string());
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        default_hook(info);
+        let report = CrashReport::new(std::process::id(), &gzmo_file, format!("panic: {}", info));
+        let _ = save_crash_report(&instance, &report);
+    }));
+}
+
+/// Finds out whether the last GUI process died without cleaning up.
+///
+/// A PID file naming a process that is gone or no longer gizmo's is left
+/// behind by a crash. The stale state is removed, and a crash report is
+/// written if the process couldn't write one itself (it was killed).
+///
+/// # Arguments
+/// * `instance` - Instance about to start
+///
+/// # Returns
+/// * `Some(report)` - The last GUI process crashed; the report is removed
+/// * `None` - It exited cleanly (or never ran), or is still running
+pub fn take_crash(instance: &str) -> Result<Option<CrashReport>, Box<dyn std::error::Error>> {
+    if let Ok((pid, started)) = read_pid_file(instance) {
+        if is_gizmo_gui(pid) && is_same_process(pid, started) {
+            return Ok(None);
+        }
+        clean_up_stale(instance, pid)?;
+    }
+
+    let report = get_crash_report(instance);
+    clear_crash_report(instance)?;
+    Ok(report)
+}
+
+/// Removes the state of a GUI process that died without removing it itself,
+/// recording the crash unless the process reported it before dying.
+fn clean_up_stale(instance: &str, pid: u32) -> Result<(), Box<dyn std::error::Error>> {
+    cleanup_daemon_state(instance)?;
+    if get_crash_report(instance).is_none_or(|report| report.pid != pid) {
+        let file = get_current_file(instance).unwrap_or_default();
+        let reason = "exited without cleaning up (killed?)".to_string();
+        save_crash_report(instance, &CrashReport::new(pid, &file, reason))?;
+    }
+    Ok(())
+}
+
+/// Returns the path of an instance's log file (which may not exist yet).
+pub fn log_path(instance: &str) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    Ok(instance_dir(instance)?.join("gizmo.log"))
+}
+
+/// Opens the log for a GUI process about to start, rotating it first if it
+/// has grown too large.
+///
+/// # Arguments
+/// * `instance` - Instance being started
+/// * `gzmo_file` - The script being started, for the session's first line
+///
+/// # Returns
+/// * `Ok(File)` - The log, opened for appending
+/// * `Err` - The log couldn't be rotated, opened or written
+pub fn open_log(instance: &str, gzmo_file: &Path) -> Result<File, Box<dyn std::error::Error>> {
+    let path = create_instance_dir(instance)?.join("gizmo.log");
+    if fs::metadata(&path).is_ok_and(|metadata| metadata.len() >= LOG_ROTATE_BYTES) {
+        fs::rename(&path, path.with_extension("log.1"))?;
+    }
+
+    let mut log = OpenOptions::new().create(true).append(true).open(&path)?;
+    writeln!(
+        log,
+        "--- {} Starting {}",
+        chrono::Local::now().format("%Y-%m-%d %H:%M:%S"),
+        gzmo_file.display()
+    )?;
+    Ok(log)
 }
 
 /// Checks if a Gizmo daemon process is currently running.
@@ -187,83 +689,83 @@ pub fn get_daemon_pid() -> Result<u32, Box<dyn std::error::Error>> {
 /// This prevents starting multiple instances and provides accurate
 /// status information.
 ///
+/// # Arguments
+/// * `instance` - Instance to check
+///
 /// # Returns
 /// * `Ok(true)` - Daemon is running
 /// * `Ok(false)` - No daemon running or process is dead
 /// * `Err` - System error checking process status
 ///
 /// # Implementation
-/// Uses `kill -0 <pid>` which checks process existence without
-/// sending any signal. This is a standard Unix technique for
-/// testing process liveness.
-pub fn is_daemon_running() -> Result<bool, Box<dyn std::error::Error>> {
-    match get_daemon_pid() {
-        Ok(pid) => {
-            // Use kill -0 to test if process exists (doesn't send signal)
-            use std::process::Command;
-            let output = Command::new("kill")
-                .arg("-0")  // Test signal - checks existence without killing
-                .arg(pid.to_string())
-                .output()?;
-            Ok(output.status.success())
-        }
-        Err(_) => Ok(false),  // No PID file = no daemon running
+/// Delegates to [`is_process_alive`], which probes the PID without
+/// affecting the process, and compares the process's start time with the
+/// recorded one so a reused PID doesn't count. A stale PID file is cleaned up
+/// and recorded as a crash (see [Stale PIDs](#stale-pids)).
+pub fn is_daemon_running(instance: &str) -> Result<bool, Box<dyn std::error::Error>> {
+    let Ok((pid, started)) = read_pid_file(instance) else {
+        return Ok(false);  // No PID file = no daemon running
+    };
+    if is_process_alive(pid) && is_same_process(pid, started) {
+        return Ok(true);
     }
+    clean_up_stale(instance, pid)?;
+    Ok(false)
 }
 
 /// Stops the currently running Gizmo daemon process.
 ///
-/// Attempts to gracefully terminate the GUI process using SIGTERM,
-/// with fallback mechanisms for robust process cleanup.
+/// Asks the GUI process to stop and waits for it, with fallback mechanisms
+/// for robust process cleanup.
+///
+/// # Arguments
+/// * `instance` - Instance to stop; other instances keep running
 ///
 /// # Returns
 /// * `Ok(())` - Daemon stopped successfully
 /// * `Err` - No daemon running or termination failed
 ///
 /// # Termination Strategy
-/// 1. **Primary**: Send SIGTERM to saved PID for clean shutdown
-/// 2. **Fallback**: Use `pkill -f "gizmo --gui"` to kill by process name
-/// 3. **Cleanup**: Remove state files regardless of method used
+/// 1. **Primary**: Ask the saved PID to stop and wait up to [`STOP_TIMEOUT`] for
+///    its `when stopping` farewell and fade-out, if [`is_gizmo_gui`] confirms
+///    the PID still belongs to gizmo
+/// 2. **Forced**: Kill it if it is still running after the timeout
+/// 3. **Fallback**: Find the instance's GUI processes by command line (Linux
+///    and macOS only)
+/// 4. **Cleanup**: State files are removed first, whichever method is used
 ///
 /// # Process Signals
-/// - **SIGTERM (-TERM)**: Requests graceful termination, allows cleanup
-/// - **SIGKILL** (not used): Would force termination without cleanup
-///
-/// The graceful approach allows the GUI process to clean up resources
-/// like window handles and animation state before exiting.
-pub fn stop_daemon() -> Result<(), Box<dyn std::error::Error>> {
-    match get_daemon_pid() {
-        Ok(pid) => {
-            use std::process::Command;
-            // Try graceful termination with SIGTERM
-            let output = Command::new("kill")
-                .arg("-TERM")  // Graceful termination signal
-                .arg(pid.to_string())
-                .output()?;
-            
-            if output.status.success() {
-                cleanup_daemon_state()?;
+/// - **SIGTERM (-TERM)** / **`WM_CLOSE`**: Requests graceful termination, allows cleanup
+/// - **SIGKILL** / **`TerminateProcess`**: Only after the timeout
+pub fn stop_daemon(instance: &str) -> Result<(), Box<dyn std::error::Error>> {
+    // A PID that no longer belongs to gizmo was recycled: never signal it
+    let saved_pid = read_pid_file(instance)
+        .ok()
+        .filter(|&(pid, started)| is_gizmo_gui(pid) && is_same_process(pid, started))
+        .map(|(pid, _)| pid);
+
+    // A supervisor restarts a GUI process that exits while it is still
+    // registered; unregister it first so it stays stopped
+    cleanup_daemon_state(instance)?;
+
+    match saved_pid {
+        Some(pid) => {
+            // Ask nicely, give the farewell time to play, then insist
+            if request_stop(pid) {
+                if !wait_for_exit(pid, STOP_TIMEOUT) {
+                    kill_process(pid);
+                }
                 println!("Gizmo stopped (PID: {})", pid);
             } else {
                 // Fallback: kill by process name pattern
-                let _ = Command::new("pkill")
-                    .arg("-f")  // Match full command line
-                    .arg("gizmo --gui")
-                    .output();
-                cleanup_daemon_state()?;
+                kill_gui_by_name(instance);
                 println!("Gizmo stopped");
             }
         }
-        Err(_) => {
-            // No saved PID - try fallback method anyway
-            use std::process::Command;
-            let output = Command::new("pkill")
-                .arg("-f")
-                .arg("gizmo --gui")
-                .output()?;
-            
-            if output.status.success() {
-                cleanup_daemon_state()?;
+        None => {
+            // No saved PID (or a stale one) - try fallback method anyway
+            let stopped = kill_gui_by_name(instance);
+            if stopped {
                 println!("Gizmo stopped");
             } else {
                 return Err("Gizmo is not running".into());
@@ -279,28 +781,512 @@ pub fn stop_daemon() -> Result<(), Box<dyn std::error::Error>> {
 /// future daemon operations. Called automatically after successful
 /// process termination.
 ///
+/// # Arguments
+/// * `instance` - Instance whose process exited
+///
 /// # Returns
 /// * `Ok(())` - State cleaned up successfully
 /// * `Err` - I/O error removing state files
 ///
 /// # Files Cleaned
 /// - `daemon.pid` - Removed to indicate no process is running
+/// - `status.json` - Removed with the process it describes
+/// - `crash.json` - Preserved until the next `start` shows it
+/// - `gizmo.sock` - Removed with the process listening on it
 /// - `current.txt` - Preserved to allow restart with same file
 ///
 /// # Design Note
 /// The current file path is intentionally preserved so that `restart`
 /// can still work after a `stop` operation. Only the PID file is removed
 /// since it represents active process state.
-pub fn cleanup_daemon_state() -> Result<(), Box<dyn std::error::Error>> {
-    let config_dir = get_config_dir()?;
+pub fn cleanup_daemon_state(instance: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let config_dir = instance_dir(instance)?;
     let pid_file_path = config_dir.join("daemon.pid");
     
     // Remove PID file if it exists
     if pid_file_path.exists() {
         fs::remove_file(pid_file_path)?;
     }
+
+    let status_file_path = config_dir.join("status.json");
+    if status_file_path.exists() {
+        fs::remove_file(status_file_path)?;
+    }
+
+    let socket_path = config_dir.join("gizmo.sock");
+    if socket_path.exists() {
+        fs::remove_file(socket_path)?;
+    }
     
     // Note: current.txt is preserved for restart functionality
     
     Ok(())
+}
+
+/// Spawns the GUI process (or its supervisor) detached from the current terminal.
+///
+/// # Arguments
+/// * `exe` - Path to the gizmo executable
+/// * `args` - Its arguments: `--gui` or `--supervise`, the absolute path of
+///   the script, then the options forwarded to the GUI process (e.g. `--renderer gpu`)
+/// * `log` - Where the process's standard output and error go (see [`open_log`])
+///
+/// # Returns
+/// * `Ok(Child)` - Handle of the spawned process (only its PID is used)
+/// * `Err` - The process could not be spawned
+///
+/// # Platform Behavior
+/// - **Unix**: Calls `setsid()` in the child before it runs gizmo, so it
+///   leads a new session without a controlling terminal: closing the
+///   terminal doesn't send it SIGHUP, and Ctrl+C / job control don't reach it
+/// - **Windows**: Uses `DETACHED_PROCESS` so no console window is opened and
+///   `CREATE_NEW_PROCESS_GROUP` so Ctrl+C in the terminal doesn't reach it.
+///   `CREATE_BREAKAWAY_FROM_JOB` keeps it alive when a terminal that runs
+///   its shells in a job object closes; jobs that forbid breaking away
+///   refuse the flag, and the spawn is retried without it.
+pub fn spawn_detached(exe: &Path, args: &[String], log: File) -> std::io::Result<Child> {
+    let mut command = Command::new(exe);
+    command
+        .args(args)
+        .stdout(log.try_clone()?)
+        .stderr(log)
+        .stdin(Stdio::null());
+    
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        
+        // SAFETY: Runs in the forked child before exec; setsid is
+        // async-signal-safe and touches no memory of the parent.
+        unsafe {
+            command.pre_exec(|| {
+                if libc::setsid() == -1 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+        command.spawn()
+    }
+    
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        use windows_sys::Win32::System::Threading::{
+            CREATE_BREAKAWAY_FROM_JOB, CREATE_NEW_PROCESS_GROUP, DETACHED_PROCESS,
+        };
+        
+        let flags = DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP;
+        command.creation_flags(flags | CREATE_BREAKAWAY_FROM_JOB);
+        match command.spawn() {
+            Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => {
+                command.creation_flags(flags);
+                command.spawn()
+            }
+            result => result,
+        }
+    }
+}
+
+/// Checks whether a process with the given PID is still running.
+///
+/// # Platform Behavior
+/// - **Unix**: `kill(pid, 0)` tests existence without sending a signal
+/// - **Windows**: Opens the process and checks its exit code is still `STILL_ACTIVE`
+pub fn is_process_alive(pid: u32) -> bool {
+    #[cfg(unix)]
+    {
+        send_signal(pid, 0)
+    }
+    
+    #[cfg(windows)]
+    {
+        use windows_sys::Win32::Foundation::{CloseHandle, STILL_ACTIVE};
+        use windows_sys::Win32::System::Threading::{
+            GetExitCodeProcess, OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION,
+        };
+        
+        // SAFETY: OpenProcess returns null on failure, which is checked before
+        // use, and the handle is closed before returning.
+        unsafe {
+            let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+            if handle.is_null() {
+                return false;
+            }
+            
+            let mut exit_code = 0u32;
+            let queried = GetExitCodeProcess(handle, &mut exit_code) != 0;
+            CloseHandle(handle);
+            
+            queried && exit_code == STILL_ACTIVE as u32
+        }
+    }
+}
+
+/// Returns when a process started, as an opaque value that only needs to
+/// differ between processes that had the same PID.
+///
+/// # Platform Behavior
+/// - **Linux**: Clock ticks after boot (field 22 of `/proc/<pid>/stat`)
+/// - **macOS**: Microseconds since the epoch (`proc_pidinfo`)
+/// - **Windows**: The creation time (`GetProcessTimes`)
+/// - **Other Unix**: Not available
+///
+/// # Returns
+/// The start time, or `None` if the process is gone or it can't be read
+pub fn process_start_time(pid: u32) -> Option<u64> {
+    #[cfg(target_os = "linux")]
+    {
+        // The command name in parentheses may contain spaces; fields after it don't
+        let stat = fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+        let after_name = &stat[stat.rfind(')')? + 1..];
+        // State is field 3, start time field 22
+        after_name.split_whitespace().nth(19)?.parse().ok()
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let mut info = std::mem::MaybeUninit::<libc::proc_bsdinfo>::zeroed();
+        let size = std::mem::size_of::<libc::proc_bsdinfo>() as libc::c_int;
+        // SAFETY: The buffer is exactly one `proc_bsdinfo`, which the call
+        // fills completely when it returns its size.
+        let info = unsafe {
+            let filled = libc::proc_pidinfo(pid as libc::c_int, libc::PROC_PIDTBSDINFO, 0, info.as_mut_ptr().cast(), size);
+            if filled != size {
+                return None;
+            }
+            info.assume_init()
+        };
+        Some(info.pbi_start_tvsec * 1_000_000 + info.pbi_start_tvusec)
+    }
+
+    #[cfg(windows)]
+    {
+        use windows_sys::Win32::Foundation::{CloseHandle, FILETIME};
+        use windows_sys::Win32::System::Threading::{
+            GetProcessTimes, OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION,
+        };
+
+        let empty = FILETIME { dwLowDateTime: 0, dwHighDateTime: 0 };
+        let (mut created, mut exited, mut kernel, mut user) = (empty, empty, empty, empty);
+        // SAFETY: The handle is checked for null before use and closed
+        // afterwards; the FILETIMEs outlive the call.
+        let queried = unsafe {
+            let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+            if handle.is_null() {
+                return None;
+            }
+            let queried = GetProcessTimes(handle, &mut created, &mut exited, &mut kernel, &mut user) != 0;
+            CloseHandle(handle);
+            queried
+        };
+        queried.then_some(((created.dwHighDateTime as u64) << 32) | created.dwLowDateTime as u64)
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+    {
+        let _ = pid;
+        None
+    }
+}
+
+/// Checks that a live PID is still the process that started at `started`.
+///
+/// Without a recorded start time (older PID files, platforms that don't
+/// report one) any process passes.
+fn is_same_process(pid: u32, started: Option<u64>) -> bool {
+    started.is_none_or(|started| process_start_time(pid) == Some(started))
+}
+
+/// Checks that a PID belongs to a running gizmo GUI process.
+///
+/// PIDs are reused once a process exits, so a PID file left behind by a
+/// crashed GUI may name an unrelated process by now; this keeps `gizmo stop`
+/// from signalling it.
+///
+/// # Platform Behavior
+/// - **Linux / macOS**: The command line must be this executable's name
+///   followed by `--gui` (read from `/proc/<pid>/cmdline` / `KERN_PROCARGS2`)
+/// - **Windows**: The process image must have this executable's file name
+///   (the CLI process never outlives its command, so that is the GUI)
+/// - **Other Unix**: Only checks the process is alive
+pub fn is_gizmo_gui(pid: u32) -> bool {
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    {
+        process_args(pid).is_some_and(|args| is_gui_command(&args))
+    }
+    
+    #[cfg(windows)]
+    {
+        use windows_sys::Win32::Foundation::CloseHandle;
+        use windows_sys::Win32::System::Threading::{
+            OpenProcess, QueryFullProcessImageNameW, PROCESS_QUERY_LIMITED_INFORMATION,
+        };
+        
+        let mut buffer = [0u16; 1024];
+        let mut length = buffer.len() as u32;
+        // SAFETY: The handle is checked for null before use and closed
+        // afterwards; `length` holds the buffer's size in characters.
+        let queried = unsafe {
+            let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+            if handle.is_null() {
+                return false;
+            }
+            let queried = QueryFullProcessImageNameW(handle, 0, buffer.as_mut_ptr(), &mut length) != 0;
+            CloseHandle(handle);
+            queried
+        };
+        let image = String::from_utf16_lossy(&buffer[..length as usize]);
+        queried && is_own_executable(&image) && is_process_alive(pid)
+    }
+    
+    #[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+    {
+        is_process_alive(pid)
+    }
+}
+
+/// Checks whether a command line is this executable's `--gui` process.
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn is_gui_command(args: &[String]) -> bool {
+    matches!(args, [program, flag, ..] if flag == "--gui" && is_own_executable(program))
+}
+
+/// Checks whether a program path has the same file name as this executable.
+#[cfg(any(target_os = "linux", target_os = "macos", windows))]
+fn is_own_executable(program: &str) -> bool {
+    let own = std::env::current_exe().ok();
+    let own_name = own.as_deref().and_then(Path::file_name);
+    own_name.is_some() && Path::new(program).file_name() == own_name
+}
+
+/// Reads a process's command line.
+///
+/// # Returns
+/// The arguments including the program, or `None` if the process is gone or
+/// belongs to another user
+#[cfg(target_os = "linux")]
+fn process_args(pid: u32) -> Option<Vec<String>> {
+    let cmdline = fs::read(format!("/proc/{}/cmdline", pid)).ok()?;
+    Some(
+        cmdline
+            .split(|&byte| byte == 0)
+            .filter(|arg| !arg.is_empty())
+            .map(|arg| String::from_utf8_lossy(arg).into_owned())
+            .collect(),
+    )
+}
+
+/// Reads a process's command line.
+///
+/// # Returns
+/// The arguments including the program, or `None` if the process is gone or
+/// belongs to another user
+#[cfg(target_os = "macos")]
+fn process_args(pid: u32) -> Option<Vec<String>> {
+    let mut mib = [libc::CTL_KERN, libc::KERN_PROCARGS2, pid as libc::c_int];
+    let mut size: libc::size_t = 0;
+    // SAFETY: The first call only reports the size; the second fills a
+    // buffer of exactly that size.
+    let mut buffer = unsafe {
+        if libc::sysctl(mib.as_mut_ptr(), 3, std::ptr::null_mut(), &mut size, std::ptr::null_mut(), 0) != 0 {
+            return None;
+        }
+        let mut buffer = vec![0u8; size];
+        if libc::sysctl(mib.as_mut_ptr(), 3, buffer.as_mut_ptr().cast(), &mut size, std::ptr::null_mut(), 0) != 0 {
+            return None;
+        }
+        buffer
+    };
+    buffer.truncate(size);
+
+    // argc, then the executable path and padding, then argc arguments
+    let argc = i32::from_ne_bytes(buffer.get(..4)?.try_into().ok()?) as usize;
+    let mut strings = buffer[4..].split(|&byte| byte == 0).filter(|string| !string.is_empty());
+    strings.next()?; // executable path
+    Some(strings.take(argc).map(|arg| String::from_utf8_lossy(arg).into_owned()).collect())
+}
+
+/// Lists the PIDs of all processes.
+#[cfg(target_os = "linux")]
+fn all_pids() -> Vec<u32> {
+    fs::read_dir("/proc")
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse().ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Lists the PIDs of all processes.
+#[cfg(target_os = "macos")]
+fn all_pids() -> Vec<u32> {
+    // SAFETY: The first call reports how many processes there are; the
+    // second fills a buffer with room for a few more that may have started.
+    unsafe {
+        let count = libc::proc_listallpids(std::ptr::null_mut(), 0);
+        if count <= 0 {
+            return Vec::new();
+        }
+        let mut pids = vec![0 as libc::pid_t; count as usize + 16];
+        let size = (pids.len() * std::mem::size_of::<libc::pid_t>()) as libc::c_int;
+        let count = libc::proc_listallpids(pids.as_mut_ptr().cast(), size);
+        pids.truncate(count.max(0) as usize);
+        pids.into_iter().map(|pid| pid as u32).collect()
+    }
+}
+
+/// Sends a signal to a process.
+///
+/// # Returns
+/// `true` if the signal was delivered (for signal 0: the process exists and
+/// may be signalled)
+#[cfg(unix)]
+fn send_signal(pid: u32, signal: libc::c_int) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    // SAFETY: kill() has no memory-safety requirements; a positive PID only
+    // targets that one process.
+    pid > 0 && unsafe { libc::kill(pid, signal) } == 0
+}
+
+/// Asks a process to stop gracefully.
+///
+/// # Returns
+/// `true` if the request was delivered
+///
+/// # Platform Behavior
+/// - **Unix**: Sends SIGTERM, which the GUI turns into its farewell and fade-out
+/// - **Windows**: Posts `WM_CLOSE` to the process's visible windows (the buddy
+///   window), which the GUI handles the same way; falls back to
+///   `TerminateProcess` when there is no window to close
+fn request_stop(pid: u32) -> bool {
+    #[cfg(unix)]
+    {
+        send_signal(pid, libc::SIGTERM)
+    }
+    
+    #[cfg(windows)]
+    {
+        use windows_sys::Win32::Foundation::{BOOL, HWND, LPARAM};
+        use windows_sys::Win32::UI::WindowsAndMessaging::{
+            EnumWindows, GetWindowThreadProcessId, IsWindowVisible, PostMessageW, WM_CLOSE,
+        };
+        
+        /// Closes every visible top-level window of the process; counts them in `state.1`.
+        unsafe extern "system" fn close_window(hwnd: HWND, lparam: LPARAM) -> BOOL {
+            let state = &mut *(lparam as *mut (u32, u32));
+            let mut window_pid = 0u32;
+            GetWindowThreadProcessId(hwnd, &mut window_pid);
+            // Only visible windows: winit's hidden helper windows must not be destroyed
+            if window_pid == state.0 && IsWindowVisible(hwnd) != 0 && PostMessageW(hwnd, WM_CLOSE, 0, 0) != 0 {
+                state.1 += 1;
+            }
+            1 // Keep enumerating
+        }
+        
+        let mut state = (pid, 0u32);
+        // SAFETY: `state` outlives the synchronous enumeration, and the
+        // callback only reads and writes it through the passed pointer.
+        unsafe {
+            EnumWindows(Some(close_window), &mut state as *mut (u32, u32) as LPARAM);
+        }
+        state.1 > 0 || kill_process(pid)
+    }
+}
+
+/// Polls until a process exits.
+///
+/// # Returns
+/// `true` if the process exited within `timeout`
+fn wait_for_exit(pid: u32, timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    while is_process_alive(pid) {
+        if Instant::now() >= deadline {
+            return false;
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    true
+}
+
+/// Terminates a process immediately.
+///
+/// # Returns
+/// `true` if the process was killed
+///
+/// # Platform Behavior
+/// - **Unix**: Sends SIGKILL
+/// - **Windows**: Calls `TerminateProcess`; the CLI cleans up the state files
+fn kill_process(pid: u32) -> bool {
+    #[cfg(unix)]
+    {
+        send_signal(pid, libc::SIGKILL)
+    }
+    
+    #[cfg(windows)]
+    {
+        use windows_sys::Win32::Foundation::CloseHandle;
+        use windows_sys::Win32::System::Threading::{OpenProcess, TerminateProcess, PROCESS_TERMINATE};
+        
+        // SAFETY: The handle is checked for null before use and closed afterwards.
+        unsafe {
+            let handle = OpenProcess(PROCESS_TERMINATE, 0, pid);
+            if handle.is_null() {
+                return false;
+            }
+            
+            let terminated = TerminateProcess(handle, 0) != 0;
+            CloseHandle(handle);
+            terminated
+        }
+    }
+}
+
+/// Asks any GUI process of an instance found by its command line to stop,
+/// for when the PID file is lost or stale.
+///
+/// # Arguments
+/// * `instance` - Instance whose GUI processes to stop
+///
+/// # Returns
+/// `true` if a process was found and signalled
+///
+/// # Platform Behavior
+/// - **Linux / macOS**: Sends SIGTERM to every process passing [`is_gizmo_gui`]
+///   whose `--name` is `instance` (no `--name` for the default instance)
+/// - **Windows / other Unix**: Not available; matching by image name alone
+///   would also stop the CLI
+fn kill_gui_by_name(instance: &str) -> bool {
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    {
+        let own_pid = std::process::id();
+        let mut found = false;
+        for pid in all_pids() {
+            let Some(args) = process_args(pid).filter(|args| is_gui_command(args)) else {
+                continue;
+            };
+            if pid != own_pid && gui_instance(&args) == instance && request_stop(pid) {
+                found = true;
+            }
+        }
+        found
+    }
+    
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    {
+        let _ = instance;
+        false
+    }
+}
+
+/// Returns which instance a GUI process's command line runs.
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn gui_instance(args: &[String]) -> &str {
+    args.iter()
+        .position(|arg| arg == "--name")
+        .and_then(|index| args.get(index + 1))
+        .map_or(DEFAULT_INSTANCE, String::as_str)
 }
\ No newline at end of file
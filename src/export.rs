@@ -0,0 +1,584 @@
+//! Animation Export
+//!
+//! `gizmo render` runs a script without opening a window and writes its
+//! animation to a file that can be shared anywhere a buddy can't run.
+//!
+//! ## Formats
+//!
+//! | `--format`    | Output                                                        |
+//! |---------------|---------------------------------------------------------------|
+//! | `gif`         | Animated GIF (the default)                                    |
+//! | `apng`        | Animated PNG, with the script's exact colors and transparency |
+//! | `spritesheet` | All frames in a grid PNG, plus a JSON file describing it      |
+//! | `rust-array`  | 1-bit frame data as a Rust `static` byte array (`.rs`)        |
+//! | `c-array`     | 1-bit frame data as a C `const uint8_t` array (`.h`)          |
+//! | `svg`         | One frame as vector rectangles (`--frame N`, the first by default) |
+//!
+//! Frames already store palette indices, so every format writes the script's
+//! palette as the file's color table as is: no color quantization, and every
+//! frame encodes losslessly. Frames of different sizes are centered on a
+//! canvas (or sprite sheet cell) fitting the largest one, on the background
+//! color, like in the window.
+//!
+//! ## GIF
+//!
+//! | Gizmo                          | GIF                                           |
+//! |--------------------------------|-----------------------------------------------|
+//! | Color with alpha below 50%     | The transparent index (GIF has no partial alpha) |
+//! | `loop_speed()` duration        | Frame delay, rounded to 10ms (GIF's unit)     |
+//! | Looping animation              | Loops forever (NETSCAPE2.0 extension)         |
+//!
+//! Most players show delays below 20ms as 100ms, so faster animations are
+//! written at 20ms per frame.
+//!
+//! ## Repeated Frames
+//!
+//! GIFs and APNGs write a run of identical frames once, shown for the whole
+//! run, so animations that hold a picture for a while stay small. Where a
+//! frame repeats later, it is still written again: both formats can only
+//! show the frames in order.
+//!
+//! ## Encoding in Parallel
+//!
+//! Compressing frames is most of the work of writing a GIF or APNG, and each
+//! frame compresses on its own. Frames are encoded in batches of one per
+//! core, and each batch is written out in order before the next one starts,
+//! so long animations use every core and only a batch of frames is held in
+//! memory at a time.
+//!
+//! ## PNG
+//!
+//! APNGs and sprite sheets are indexed PNGs whose color table carries each
+//! color's alpha, so partially transparent colors survive. APNG frames last
+//! exactly the script's frame duration and loop forever.
+//!
+//! A sprite sheet `buddy.png` comes with `buddy.json`:
+//!
+//! ```json
+//! {"image":"buddy.png","frame_width":128,"frame_height":128,"columns":8,"rows":8,"frame_count":60,"frame_ms":40}
+//! ```
+//!
+//! Frames are laid out left to right, then top to bottom; frame `i` is at
+//! column `i % columns`, row `i / columns`.
+//!
+//! ## Byte Arrays
+//!
+//! For microcontrollers driving monochrome displays (SSD1306 OLEDs, LED
+//! matrices), the array formats keep one bit per pixel: set for any index
+//! but the background (0). The array starts with an 8-byte header of
+//! little-endian `u16`s, followed by every frame:
+//!
+//! ```text
+//! width | height | frame count | frame ms | frame 1 | frame 2 | ...
+//! ```
+//!
+//! Each frame is its rows top to bottom, each row padded to whole bytes with
+//! the leftmost pixel in the most significant bit - the layout of Adafruit
+//! GFX's and u8g2's `drawBitmap()`. The same numbers are also written as
+//! constants named after the output file (`buddy.h` → `BUDDY_WIDTH`,
+//! `BUDDY_FRAME_BYTES`, ...), so frame `i` starts at
+//! `8 + i * BUDDY_FRAME_BYTES`.
+//!
+//! ## SVG
+//!
+//! An SVG holds a single frame, for documentation images that stay crisp at
+//! any size or for cutting a stencil. Each run of same-colored pixels in a row
+//! becomes one `<rect>`, grouped by color. Only lit pixels are drawn: the
+//! background (index 0) and colors less than half opaque are left out, so
+//! the rest of the image is transparent. `--scale` sets the image's size;
+//! coordinates are always in frame pixels.
+
+use crate::ast::Frame;
+use crate::dedup::FrameRuns;
+use crate::palette::Palette;
+use serde::Serialize;
+use std::error::Error;
+use std::fs::File;
+use std::io::BufWriter;
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::thread;
+
+/// Largest `--scale` accepted by `gizmo render`.
+pub const MAX_SCALE: usize = 64;
+
+/// Shortest frame delay players honor, in GIF's hundredths of a second.
+const MIN_GIF_DELAY: u16 = 2;
+
+/// What `gizmo render` writes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    /// Animated GIF
+    Gif,
+    /// Animated PNG
+    Apng,
+    /// Grid PNG of all frames plus JSON metadata
+    Spritesheet,
+    /// Rust source with 1-bit frame data
+    RustArray,
+    /// C header with 1-bit frame data
+    CArray,
+    /// One frame as SVG rectangles
+    Svg,
+}
+
+impl Format {
+    /// Parses a `--format` value.
+    ///
+    /// # Arguments
+    /// * `name` - `"gif"`, `"apng"`, `"spritesheet"`, `"rust-array"`, `"c-array"` or `"svg"`
+    ///
+    /// # Returns
+    /// * `Ok(Format)` - Known format
+    /// * `Err` - Unknown name
+    pub fn parse(name: &str) -> Result<Self, Box<dyn Error>> {
+        match name {
+            "gif" => Ok(Format::Gif),
+            "apng" => Ok(Format::Apng),
+            "spritesheet" => Ok(Format::Spritesheet),
+            "rust-array" => Ok(Format::RustArray),
+            "c-array" => Ok(Format::CArray),
+            "svg" => Ok(Format::Svg),
+            _ => Err(format!("Unknown format '{}' (expected gif, apng, spritesheet, rust-array, c-array or svg)", name).into()),
+        }
+    }
+
+    /// Returns the file extension written when no output path is given.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Format::Gif => "gif",
+            Format::Apng | Format::Spritesheet => "png",
+            Format::RustArray => "rs",
+            Format::CArray => "h",
+            Format::Svg => "svg",
+        }
+    }
+
+    /// Returns `true` for formats that hold a single frame.
+    pub fn is_single_frame(&self) -> bool {
+        *self == Format::Svg
+    }
+}
+
+/// The frame metadata written next to a sprite sheet.
+#[derive(Debug, Clone, Serialize)]
+pub struct SpriteSheet {
+    /// File name of the sheet, relative to the metadata file
+    pub image: String,
+    /// Width of one cell in pixels
+    pub frame_width: usize,
+    /// Height of one cell in pixels
+    pub frame_height: usize,
+    /// Cells per row
+    pub columns: usize,
+    /// Rows of cells
+    pub rows: usize,
+    /// Frames in the sheet; the last row may not be full
+    pub frame_count: usize,
+    /// How long each frame is shown, in milliseconds
+    pub frame_ms: u64,
+}
+
+/// Writes frames in a format.
+///
+/// # Arguments
+/// * `format` - What to write
+/// * `path` - Where to write it (a sprite sheet's metadata goes next to it, as `.json`)
+/// * `frames` - Frames in playback order; only the first for single-frame formats
+/// * `palette` - Colors of the frames' palette indices
+/// * `frame_ms` - How long each frame is shown
+/// * `scale` - How many output pixels wide and high each frame pixel becomes
+///
+/// # Returns
+/// * `Ok(paths)` - The files written
+/// * `Err` - There are no frames, they are too large once scaled, or writing failed
+pub fn write(
+    format: Format,
+    path: &Path,
+    frames: &[Frame],
+    palette: &Palette,
+    frame_ms: u64,
+    scale: usize,
+) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    if frames.is_empty() {
+        return Err("The animation has no frames".into());
+    }
+    match format {
+        Format::Gif => write_gif(path, frames, palette, frame_ms, scale)?,
+        Format::Apng => write_apng(path, frames, palette, frame_ms, scale)?,
+        Format::RustArray | Format::CArray => write_array(format, path, frames, frame_ms, scale)?,
+        Format::Svg => write_svg(path, &frames[0], palette, scale)?,
+        Format::Spritesheet => {
+            let metadata = write_spritesheet(path, frames, palette, frame_ms, scale)?;
+            return Ok(vec![path.to_path_buf(), metadata]);
+        }
+    }
+    Ok(vec![path.to_path_buf()])
+}
+
+/// Writes frames to an animated, endlessly looping GIF.
+fn write_gif(path: &Path, frames: &[Frame], palette: &Palette, frame_ms: u64, scale: usize) -> Result<(), Box<dyn Error>> {
+    let (width, height) = canvas_size(frames, scale);
+    let (Ok(gif_width), Ok(gif_height)) = (u16::try_from(width), u16::try_from(height)) else {
+        return Err(format!("{}x{} is too large for a GIF (at most 65535 pixels a side)", width, height).into());
+    };
+
+    let colors = used_colors(frames, palette);
+    let is_transparent = |argb: u32| argb >> 24 < 0x80;
+    let transparent = colors.iter().position(|&argb| is_transparent(argb)).map(|index| index as u8);
+    let table: Vec<u8> = colors.iter().flat_map(|&argb| rgb(argb)).collect();
+
+    let mut encoder = gif::Encoder::new(BufWriter::new(File::create(path)?), gif_width, gif_height, &table)?;
+    encoder.set_repeat(gif::Repeat::Infinite)?;
+
+    let delay = ((frame_ms + 5) / 10).clamp(MIN_GIF_DELAY as u64, u16::MAX as u64) as u16;
+    let animation = FrameRuns::new(frames);
+    let encode = |&(index, length): &(usize, usize)| {
+        let mut buffer = vec![0; width * height];
+        draw(&mut buffer, width, &animation.unique[index], (0, 0), (width, height), scale);
+        // Every color GIF can't show as opaque becomes the one transparent index
+        if let Some(transparent) = transparent {
+            for index in &mut buffer {
+                if is_transparent(colors[*index as usize]) {
+                    *index = transparent;
+                }
+            }
+        }
+
+        let mut gif_frame = gif::Frame::from_indexed_pixels(gif_width, gif_height, buffer, transparent);
+        gif_frame.delay = delay.saturating_mul(u16::try_from(length).unwrap_or(u16::MAX));
+        // Each frame covers the whole canvas, transparent pixels included
+        gif_frame.dispose = gif::DisposalMethod::Background;
+        gif_frame.make_lzw_pre_encoded();
+        Ok(gif_frame)
+    };
+    encode_in_parallel(&animation.runs, encode, |gif_frame| Ok(encoder.write_lzw_pre_encoded_frame(&gif_frame)?))
+}
+
+/// Writes frames to an animated, endlessly looping PNG.
+fn write_apng(path: &Path, frames: &[Frame], palette: &Palette, frame_ms: u64, scale: usize) -> Result<(), Box<dyn Error>> {
+    let (width, height) = canvas_size(frames, scale);
+    let animation = FrameRuns::new(frames);
+    let colors = used_colors(frames, palette);
+    let (Ok(png_width), Ok(png_height)) = (u32::try_from(width), u32::try_from(height)) else {
+        return Err(format!("{}x{} is too large for a PNG", width, height).into());
+    };
+
+    // The png crate compresses frames one at a time as it writes them, so
+    // frames are compressed as standalone images instead and their data
+    // written in the animation chunks by hand
+    let mut writer = indexed_png(path, (width, height), frames, palette)?.write_header()?;
+    let mut animation_control = (animation.runs.len() as u32).to_be_bytes().to_vec();
+    animation_control.extend(0u32.to_be_bytes()); // Loop forever
+    writer.write_chunk(png::chunk::acTL, &animation_control)?;
+
+    let encode = |&(index, length): &(usize, usize)| {
+        let mut buffer = vec![0; width * height];
+        draw(&mut buffer, width, &animation.unique[index], (0, 0), (width, height), scale);
+        Ok((length, compressed_image_data((png_width, png_height), &colors, &buffer)?))
+    };
+    let mut sequence = 0u32;
+    encode_in_parallel(&animation.runs, encode, |(length, data)| {
+        // Milliseconds while they fit, which a single frame always does;
+        // long runs fall back to hundredths of a second
+        let ms = frame_ms * length as u64;
+        let (numerator, denominator) = match u16::try_from(ms) {
+            Ok(ms) => (ms, 1000u16),
+            Err(_) => ((ms / 10).min(u16::MAX as u64) as u16, 100),
+        };
+        let mut frame_control = Vec::with_capacity(26);
+        for field in [sequence, png_width, png_height, 0, 0] {
+            frame_control.extend(field.to_be_bytes());
+        }
+        frame_control.extend(numerator.to_be_bytes());
+        frame_control.extend(denominator.to_be_bytes());
+        frame_control.extend([0, 0]); // Dispose: none, blend: source
+        writer.write_chunk(png::chunk::fcTL, &frame_control)?;
+        sequence += 1;
+
+        // The first frame is also the image viewers without APNG show
+        if sequence == 1 {
+            writer.write_chunk(png::chunk::IDAT, &data)?;
+        } else {
+            let mut frame_data = sequence.to_be_bytes().to_vec();
+            frame_data.extend(&data);
+            writer.write_chunk(png::chunk::fdAT, &frame_data)?;
+            sequence += 1;
+        }
+        Ok(())
+    })?;
+    writer.finish()?;
+    Ok(())
+}
+
+/// Compresses one frame of palette indices like an indexed PNG's `IDAT`
+/// chunks hold it.
+fn compressed_image_data((width, height): (u32, u32), colors: &[u32], pixels: &[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+    let mut image = Vec::new();
+    let mut encoder = png::Encoder::new(&mut image, width, height);
+    encoder.set_color(png::ColorType::Indexed);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_palette(colors.iter().flat_map(|&argb| rgb(argb)).collect::<Vec<u8>>());
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(pixels)?;
+    writer.finish()?;
+
+    // Chunks follow the 8-byte signature as length, type, data and CRC
+    let mut data = Vec::new();
+    let mut rest = &image[8..];
+    while rest.len() >= 12 {
+        let length = u32::from_be_bytes([rest[0], rest[1], rest[2], rest[3]]) as usize;
+        if &rest[4..8] == b"IDAT" {
+            data.extend(&rest[8..8 + length]);
+        }
+        rest = &rest[12 + length..];
+    }
+    Ok(data)
+}
+
+/// Encodes items on every core and hands the results to `write` in order.
+///
+/// Items are encoded in batches of one per core; a batch is written before
+/// the next one is encoded, so only a batch of results is held at a time.
+///
+/// # Returns
+/// * `Ok(())` - Every item was encoded and written
+/// * `Err` - The first error encoding or writing; later items are skipped
+fn encode_in_parallel<T: Sync, R: Send>(
+    items: &[T],
+    encode: impl Fn(&T) -> Result<R, Box<dyn Error + Send + Sync>> + Sync,
+    mut write: impl FnMut(R) -> Result<(), Box<dyn Error>>,
+) -> Result<(), Box<dyn Error>> {
+    let threads = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+    for batch in items.chunks(threads) {
+        let encoded: Vec<_> = thread::scope(|scope| {
+            let workers: Vec<_> = batch.iter().map(|item| scope.spawn(|| encode(item))).collect();
+            workers.into_iter().map(|worker| worker.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic))).collect()
+        });
+        for result in encoded {
+            write(result.map_err(|e| e as Box<dyn Error>)?)?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes frames to a grid PNG, and the grid's [`SpriteSheet`] description
+/// next to it.
+///
+/// # Returns
+/// Where the description was written
+fn write_spritesheet(path: &Path, frames: &[Frame], palette: &Palette, frame_ms: u64, scale: usize) -> Result<PathBuf, Box<dyn Error>> {
+    let (cell_width, cell_height) = canvas_size(frames, scale);
+    // As square as possible, filled row by row
+    let columns = (1..).find(|columns| columns * columns >= frames.len()).unwrap_or(1);
+    let rows = frames.len().div_ceil(columns);
+    let (width, height) = (cell_width * columns, cell_height * rows);
+
+    let mut buffer = vec![0; width * height];
+    for (number, frame) in frames.iter().enumerate() {
+        let corner = ((number % columns) * cell_width, (number / columns) * cell_height);
+        draw(&mut buffer, width, frame, corner, (cell_width, cell_height), scale);
+    }
+    let mut writer = indexed_png(path, (width, height), frames, palette)?.write_header()?;
+    writer.write_image_data(&buffer)?;
+    writer.finish()?;
+
+    let sheet = SpriteSheet {
+        image: path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default(),
+        frame_width: cell_width,
+        frame_height: cell_height,
+        columns,
+        rows,
+        frame_count: frames.len(),
+        frame_ms,
+    };
+    let metadata = path.with_extension("json");
+    std::fs::write(&metadata, serde_json::to_string_pretty(&sheet)?)?;
+    Ok(metadata)
+}
+
+/// Writes frames as a 1-bit byte array in Rust or C source.
+fn write_array(format: Format, path: &Path, frames: &[Frame], frame_ms: u64, scale: usize) -> Result<(), Box<dyn Error>> {
+    let (width, height) = canvas_size(frames, scale);
+    let (Ok(array_width), Ok(array_height), Ok(count)) = (u16::try_from(width), u16::try_from(height), u16::try_from(frames.len())) else {
+        return Err(format!("{} frames of {}x{} are too many or too large for a byte array", frames.len(), width, height).into());
+    };
+    let frame_ms = u16::try_from(frame_ms).unwrap_or(u16::MAX);
+
+    let mut bytes: Vec<u8> = [array_width, array_height, count, frame_ms].iter().flat_map(|value| value.to_le_bytes()).collect();
+    let mut buffer = vec![0; width * height];
+    for frame in frames {
+        buffer.fill(0);
+        draw(&mut buffer, width, frame, (0, 0), (width, height), scale);
+        for row in buffer.chunks(width) {
+            for pixels in row.chunks(8) {
+                let byte = pixels.iter().enumerate().fold(0u8, |byte, (bit, &index)| byte | (((index != 0) as u8) << (7 - bit)));
+                bytes.push(byte);
+            }
+        }
+    }
+
+    let name = array_name(path);
+    let constants = [
+        ("WIDTH", width),
+        ("HEIGHT", height),
+        ("FRAME_COUNT", frames.len()),
+        ("FRAME_MS", frame_ms as usize),
+        ("FRAME_BYTES", width.div_ceil(8) * height),
+    ];
+    let data: Vec<String> = bytes
+        .chunks(16)
+        .map(|line| {
+            let line: Vec<String> = line.iter().map(|byte| format!("0x{:02x}", byte)).collect();
+            format!("    {},\n", line.join(", "))
+        })
+        .collect();
+
+    // Both languages share the `//` comment
+    let mut source = String::from(
+        "// Generated by gizmo render: an 8-byte header (width, height, frame count, frame ms as\n\
+         // little-endian u16), then each frame's rows, 1 bit per pixel, leftmost pixel in the top bit.\n\n",
+    );
+    if format == Format::RustArray {
+        for (constant, value) in constants {
+            source.push_str(&format!("pub const {}_{}: usize = {};\n", name, constant, value));
+        }
+        source.push_str(&format!("\npub static {}: [u8; {}] = [\n{}];\n", name, bytes.len(), data.concat()));
+    } else {
+        source.push_str("#pragma once\n#include <stdint.h>\n\n");
+        for (constant, value) in constants {
+            source.push_str(&format!("#define {}_{} {}\n", name, constant, value));
+        }
+        source.push_str(&format!("\nconst uint8_t {}[{}] = {{\n{}}};\n", name, bytes.len(), data.concat()));
+    }
+    std::fs::write(path, source)?;
+    Ok(())
+}
+
+/// Writes a frame as an SVG of rectangles, one per run of same-colored lit pixels.
+fn write_svg(path: &Path, frame: &Frame, palette: &Palette, scale: usize) -> Result<(), Box<dyn Error>> {
+    // Runs as (x, y, length), filed by palette index
+    let mut runs: Vec<Vec<(usize, usize, usize)>> = Vec::new();
+    for (y, row) in frame.pixels.iter().take(frame.height).enumerate() {
+        let row = &row[..frame.width.min(row.len())];
+        let mut x = 0;
+        while x < row.len() {
+            let index = row[x];
+            let length = row[x..].iter().take_while(|&&next| next == index).count();
+            if index != 0 && palette.color(index) >> 24 >= 0x80 {
+                let index = index as usize;
+                if runs.len() <= index {
+                    runs.resize(index + 1, Vec::new());
+                }
+                runs[index].push((x, y, length));
+            }
+            x += length;
+        }
+    }
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\" shape-rendering=\"crispEdges\">\n",
+        frame.width * scale,
+        frame.height * scale,
+        frame.width,
+        frame.height
+    );
+    for (index, rects) in runs.iter().enumerate().filter(|(_, rects)| !rects.is_empty()) {
+        let argb = palette.color(index as u8);
+        let [r, g, b] = rgb(argb);
+        let opacity = match argb >> 24 {
+            0xFF => String::new(),
+            alpha => format!(" fill-opacity=\"{:.3}\"", alpha as f64 / 255.0),
+        };
+        svg.push_str(&format!("  <g fill=\"#{:02x}{:02x}{:02x}\"{}>\n", r, g, b, opacity));
+        for (x, y, length) in rects {
+            svg.push_str(&format!("    <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"1\"/>\n", x, y, length));
+        }
+        svg.push_str("  </g>\n");
+    }
+    svg.push_str("</svg>\n");
+    std::fs::write(path, svg)?;
+    Ok(())
+}
+
+/// Derives the array's constant name from the output file: `my-buddy.h` → `MY_BUDDY`.
+fn array_name(path: &Path) -> String {
+    let stem = path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+    let name: String = stem.chars().map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' }).collect();
+    match name.chars().next() {
+        Some(first) if !first.is_ascii_digit() => name,
+        _ => format!("GIZMO_{}", name),
+    }
+}
+
+/// Sets up an 8-bit indexed PNG with the colors the frames use.
+fn indexed_png(
+    path: &Path,
+    (width, height): (usize, usize),
+    frames: &[Frame],
+    palette: &Palette,
+) -> Result<png::Encoder<'static, BufWriter<File>>, Box<dyn Error>> {
+    let (Ok(png_width), Ok(png_height)) = (u32::try_from(width), u32::try_from(height)) else {
+        return Err(format!("{}x{} is too large for a PNG", width, height).into());
+    };
+    let colors = used_colors(frames, palette);
+
+    let mut encoder = png::Encoder::new(BufWriter::new(File::create(path)?), png_width, png_height);
+    encoder.set_color(png::ColorType::Indexed);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_palette(colors.iter().flat_map(|&argb| rgb(argb)).collect::<Vec<u8>>());
+    encoder.set_trns(colors.iter().map(|&argb| (argb >> 24) as u8).collect::<Vec<u8>>());
+    Ok(encoder)
+}
+
+/// Returns the size of a canvas fitting the largest frame once scaled.
+fn canvas_size(frames: &[Frame], scale: usize) -> (usize, usize) {
+    let width = frames.iter().map(|frame| frame.width).max().unwrap_or(0);
+    let height = frames.iter().map(|frame| frame.height).max().unwrap_or(0);
+    (width * scale, height * scale)
+}
+
+/// Returns the colors of palette indices 0 up to the highest one the frames use.
+fn used_colors(frames: &[Frame], palette: &Palette) -> Vec<u32> {
+    let used = frames
+        .iter()
+        .flat_map(|frame| frame.pixels.iter().flatten())
+        .copied()
+        .max()
+        .unwrap_or(0);
+    (0..=used).map(|index| palette.color(index)).collect()
+}
+
+/// Splits a `0xAARRGGBB` color into its red, green and blue bytes.
+fn rgb(argb: u32) -> [u8; 3] {
+    [(argb >> 16) as u8, (argb >> 8) as u8, argb as u8]
+}
+
+/// Draws a scaled frame centered in a cell of an index buffer.
+///
+/// # Arguments
+/// * `buffer` - Row-major palette indices, `stride` wide
+/// * `stride` - Width of the whole buffer
+/// * `frame` - The frame to draw
+/// * `(left, top)` - The cell's top-left corner
+/// * `(width, height)` - The cell's size, at least the scaled frame's
+/// * `scale` - How many pixels wide and high each frame pixel becomes
+fn draw(
+    buffer: &mut [u8],
+    stride: usize,
+    frame: &Frame,
+    (left, top): (usize, usize),
+    (width, height): (usize, usize),
+    scale: usize,
+) {
+    let left = left + (width - frame.width * scale) / 2;
+    let top = top + (height - frame.height * scale) / 2;
+    for (row, pixels) in frame.pixels.iter().take(frame.height).enumerate() {
+        for (col, &index) in pixels.iter().take(frame.width).enumerate() {
+            for y in 0..scale {
+                let start = (top + row * scale + y) * stride + left + col * scale;
+                buffer[start..start + scale].fill(index);
+            }
+        }
+    }
+}
@@ -0,0 +1,132 @@
+//! Script Failures in the Window
+//!
+//! Once the buddy is on screen, a script that fails shouldn't take it down
+//! or go unnoticed. When an event handler fails, or a script dropped onto
+//! the window (or sent with `gizmo switch`) doesn't compile, the buddy keeps
+//! playing the last animation that worked, logs the error with the line it
+//! happened on (see `gizmo logs`), and shows a small error glyph in the
+//! sprite's top-right corner for [`SHOWN_FOR`]:
+//!
+//! ```text
+//! . . . . . # # #      # badge
+//! . . . . . # ! #      ! mark
+//! . . . . . # ! #
+//! . . . . . # # #
+//! . . . . . # ! #
+//! ```
+//!
+//! Like a speech bubble, the glyph is drawn as part of the frame: [`mark`]
+//! puts it on the frame being shown, in two of the palette indices scripts
+//! can't use ([`RESERVED`](crate::palette::RESERVED) and up), [`BADGE`] and
+//! [`MARK`], which [`with_glyph_colors`] adds to the palette.
+//! A handler that fails after `play()` doesn't switch the animation (the
+//! interpreter undoes it), so the glyph is all that changes.
+
+use crate::ast::Frame;
+use crate::bundle;
+use crate::error::GizmoError;
+use crate::palette::Palette;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Palette index of the glyph's fill.
+pub const BADGE: u8 = 252;
+
+/// Palette index of the exclamation mark on it.
+pub const MARK: u8 = 253;
+
+/// How long the glyph stays up after the latest failure.
+pub const SHOWN_FOR: Duration = Duration::from_secs(5);
+
+/// The glyph, one row per entry, leftmost pixel in bit 2: a mark where the
+/// bit is set, badge elsewhere.
+const GLYPH: [u8; 5] = [0b000, 0b010, 0b010, 0b000, 0b010];
+
+/// Reports the failures of the script being played.
+#[derive(Debug, Clone)]
+pub struct Faults {
+    /// Source of the script, for the line an error happened on
+    source: String,
+    /// When the glyph goes away, while it is shown
+    until: Option<Instant>,
+}
+
+impl Faults {
+    /// Starts reporting the failures of a script.
+    ///
+    /// # Arguments
+    /// * `script` - Path of the .gzmo file or .gzmopack bundle being played
+    pub fn new(script: &Path) -> Self {
+        Self { source: script_source(script), until: None }
+    }
+
+    /// Reports the failures of the script switched to instead; the glyph of
+    /// the last one goes away.
+    pub fn switch(&mut self, script: &Path) {
+        *self = Self::new(script);
+    }
+
+    /// Logs an event handler's error with the line it happened on, and shows
+    /// the glyph.
+    ///
+    /// # Arguments
+    /// * `handler` - Which handlers failed, e.g. `"click"`
+    /// * `error` - What went wrong
+    pub fn handler_failed(&mut self, handler: &str, error: &GizmoError) {
+        eprintln!("Error in {} handler: {}", handler, error.report(&self.source));
+        self.until = Some(Instant::now() + SHOWN_FOR);
+    }
+
+    /// Logs why switching to another script failed, and shows the glyph.
+    pub fn switch_failed(&mut self, message: &str) {
+        eprintln!("{}", message);
+        self.until = Some(Instant::now() + SHOWN_FOR);
+    }
+
+    /// Returns when the glyph goes away, while it is shown.
+    pub fn until(&self) -> Option<Instant> {
+        self.until
+    }
+
+    /// Takes the glyph down once its time is up.
+    ///
+    /// # Returns
+    /// `true` if it just went away, so the frame needs drawing without it
+    pub fn expire(&mut self, now: Instant) -> bool {
+        self.until.take_if(|until| now >= *until).is_some()
+    }
+}
+
+/// Draws the glyph in a frame's top-right corner, cut off where the frame is
+/// smaller than it.
+///
+/// # Arguments
+/// * `sprite` - The animation frame being shown
+pub fn mark(sprite: &Frame) -> Frame {
+    let mut pixels = (*sprite.pixels).clone();
+    let left = sprite.width.saturating_sub(3);
+    for (row, bits) in pixels.iter_mut().zip(GLYPH) {
+        for (column, pixel) in row.iter_mut().skip(left).enumerate() {
+            *pixel = if bits & (0b100 >> column) != 0 { MARK } else { BADGE };
+        }
+    }
+    Frame::new(pixels)
+}
+
+/// Returns a copy of a palette with the glyph's badge and mark colors added.
+pub fn with_glyph_colors(palette: &Palette) -> Palette {
+    let mut palette = palette.clone();
+    palette.set_argb(BADGE, 0xFFE0301E);
+    palette.set_argb(MARK, 0xFFFFFFFF);
+    palette
+}
+
+/// Reads the script a .gzmo file or bundle runs; empty if it can't be read,
+/// which only leaves the source line out of error reports.
+fn script_source(script: &Path) -> String {
+    bundle::resolve(script)
+        .ok()
+        .and_then(|script| fs::read_to_string(script).ok())
+        .unwrap_or_default()
+}
@@ -0,0 +1,362 @@
+//! Script Formatting
+//!
+//! `gizmo fmt <file>...` rewrites scripts in one canonical layout, and
+//! `gizmo fmt --check` only reports the ones that aren't in it yet:
+//!
+//! - Blocks are indented by four spaces
+//! - Operators, `=` and `? :` have a space on each side, commas one after
+//! - Every statement ends with `;`, including `end;` and the `};` of patterns
+//! - Comments stay where they were, with two spaces before a trailing `//`;
+//!   comments inside an expression move to their own line above its statement
+//! - Runs of blank lines become one, and blocks don't start or end with one
+//! - Line breaks inside expressions are kept, continuing one level deeper;
+//!   a list that starts on a new line gets its closing bracket on its own line
+//!
+//! The script is parsed with its comments, blank lines and parentheses kept
+//! ([`Parser::keeping_layout`]) and printed back from the AST. The result is
+//! parsed again and compared with the original, so formatting never changes
+//! what a script does.
+
+use crate::ast::*;
+use crate::error::{GizmoError, Result};
+use crate::lexer::Lexer;
+use crate::parser::Parser;
+
+/// Indentation of one block level
+const INDENT: &str = "    ";
+
+/// Formats a script's source code.
+///
+/// # Arguments
+/// * `source` - Contents of the .gzmo file
+///
+/// # Returns
+/// * `Ok(String)` - The script in the canonical layout
+/// * `Err(GizmoError)` - The script has a syntax error
+pub fn format(source: &str) -> Result<String> {
+    let tokens = Lexer::keeping_comments(source).tokenize()?;
+    let program = Parser::keeping_layout(tokens).parse()?;
+
+    let mut formatter = Formatter { out: String::new(), depth: 0 };
+    formatter.statements(&program.statements);
+    let formatted = formatter.out;
+
+    if parse(&formatted)? != parse(source)? {
+        return Err(GizmoError::RuntimeError(
+            "Formatting would change what the script does; it was left as it is".to_string(),
+        ));
+    }
+    Ok(formatted)
+}
+
+/// Parses a script the way running it does, for comparing before and after.
+fn parse(source: &str) -> Result<Program> {
+    Parser::new(Lexer::new(source).tokenize()?).parse()
+}
+
+/// Prints an AST parsed with its layout back to source code.
+struct Formatter {
+    /// Source code written so far
+    out: String,
+    /// Block nesting level of the current line
+    depth: usize,
+}
+
+impl Formatter {
+    /// Writes the statements of a block, one per line at the current depth.
+    ///
+    /// # Returns
+    /// `true` if the block ended with a blank line, which a pattern body
+    /// keeps before its `return`
+    fn statements(&mut self, statements: &[Statement]) -> bool {
+        let mut first = true;
+        let mut blank = false;
+
+        for statement in statements {
+            match &statement.kind {
+                StatementKind::Trivia(Trivia::BlankLine) => blank = !first,
+                StatementKind::Trivia(Trivia::Comment { text, trailing: true }) => self.trailing_comment(text),
+                _ => {
+                    if blank {
+                        self.out.push('\n');
+                        blank = false;
+                    }
+                    first = false;
+                    self.indent();
+                    self.statement(statement);
+                    self.out.push('\n');
+                }
+            }
+        }
+
+        blank
+    }
+
+    /// Writes one statement, without the indentation before it or the
+    /// newline after it.
+    fn statement(&mut self, statement: &Statement) {
+        match &statement.kind {
+            StatementKind::VariableDeclaration { var_type, name, value } => {
+                let keyword = match var_type {
+                    VariableType::Frame => "frame",
+                    VariableType::Frames => "frames",
+                };
+                self.out.push_str(&format!("{} {} =", keyword, name));
+                self.spaced(value);
+                self.out.push(';');
+            }
+            StatementKind::ExpressionStatement(expression) => {
+                self.expression(expression);
+                self.out.push(';');
+            }
+            StatementKind::Assignment { name, value } => {
+                self.out.push_str(&format!("{} =", name));
+                self.spaced(value);
+                self.out.push(';');
+            }
+            StatementKind::PersistDeclaration { name, value } => {
+                self.out.push_str(&format!("persist {} =", name));
+                self.spaced(value);
+                self.out.push(';');
+            }
+            StatementKind::RepeatLoop { count, body } => {
+                self.out.push_str("repeat");
+                self.spaced(count);
+                self.out.push_str(" times do");
+                self.block(body);
+                self.out.push_str("end;");
+            }
+            StatementKind::IfStatement { condition, then_body, else_body } => {
+                self.out.push_str("if");
+                self.spaced(condition);
+                self.out.push_str(" then");
+                self.block(then_body);
+                if let Some(else_body) = else_body {
+                    self.out.push_str("else");
+                    self.block(else_body);
+                }
+                self.out.push_str("end;");
+            }
+            StatementKind::WhenStatement { event, body, once, priority } => {
+                self.event(event);
+                if *once {
+                    self.out.push_str(" once");
+                }
+                if let Some(priority) = priority {
+                    self.out.push_str(" priority");
+                    self.spaced(priority);
+                }
+                self.out.push_str(" do");
+                self.block(body);
+                self.out.push_str("end;");
+            }
+            StatementKind::Trivia(Trivia::Comment { text, .. }) => self.out.push_str(&format!("//{}", text)),
+            StatementKind::Trivia(Trivia::BlankLine) => {}
+        }
+    }
+
+    /// Writes the header of a handler, up to its modifiers.
+    fn event(&mut self, event: &Event) {
+        match event {
+            Event::Every(interval) => {
+                self.out.push_str("every");
+                self.spaced(interval);
+            }
+            Event::ClickCombo { times, within } => {
+                self.out.push_str("when clicked");
+                self.spaced(times);
+                self.out.push_str(" times within");
+                self.spaced(within);
+            }
+            Event::Key(expression) | Event::Time(expression) | Event::Message(expression) => {
+                self.out.push_str(&format!("when {}(", event.name()));
+                self.expression(expression);
+                self.out.push(')');
+            }
+            Event::Idle(expression) => {
+                self.out.push_str("when idle >");
+                self.spaced(expression);
+            }
+            Event::Battery(Expression::Number(percent)) if *percent == 20.0 => self.out.push_str("when battery_low"),
+            Event::Battery(expression) => {
+                self.out.push_str("when battery <");
+                self.spaced(expression);
+            }
+            _ => self.out.push_str(&format!("when {}", event.name())),
+        }
+    }
+
+    /// Finishes a block's header line, writes its statements one level
+    /// deeper, and indents the line that closes it.
+    fn block(&mut self, body: &[Statement]) {
+        self.out.push('\n');
+        self.depth += 1;
+        self.statements(body);
+        self.depth -= 1;
+        self.indent();
+    }
+
+    /// Writes an expression.
+    fn expression(&mut self, expression: &Expression) {
+        match expression {
+            Expression::Number(n) => self.out.push_str(&n.to_string()),
+            Expression::String(s) => self.out.push_str(&format!("\"{}\"", s)),
+            Expression::Identifier(name) => self.out.push_str(name),
+            Expression::Array(elements) => {
+                self.out.push('[');
+                self.list(elements);
+                self.out.push(']');
+            }
+            Expression::FunctionCall { name, args } => {
+                self.out.push_str(name);
+                self.out.push('(');
+                self.list(args);
+                self.out.push(')');
+            }
+            Expression::NamedArgument { name, value } => {
+                self.out.push_str(&format!("{} =", name));
+                self.spaced(value);
+            }
+            Expression::BinaryOperation { left, operator, right } => {
+                self.expression(left);
+                self.out.push(' ');
+                self.out.push_str(operator_symbol(operator));
+                self.spaced(right);
+            }
+            Expression::PatternGenerator { width, height, body, return_expr } => {
+                self.out.push_str("pattern");
+                self.dimensions(width, height);
+                self.pattern_body(body, return_expr);
+            }
+            Expression::TernaryOperation { condition, true_expr, false_expr } => {
+                self.expression(condition);
+                self.out.push_str(" ?");
+                self.spaced(true_expr);
+                self.out.push_str(" :");
+                self.spaced(false_expr);
+            }
+            Expression::Pipeline { width, height, passes } => {
+                self.out.push_str("pipeline");
+                self.dimensions(width, height);
+                self.out.push_str(" {\n");
+                self.depth += 1;
+                for pass in passes {
+                    self.indent();
+                    self.out.push_str("pass");
+                    self.pattern_body(&pass.body, &pass.return_expr);
+                    self.out.push('\n');
+                }
+                self.depth -= 1;
+                self.indent();
+                self.out.push('}');
+            }
+            Expression::Grouping(inner) => {
+                self.out.push('(');
+                self.expression(inner);
+                self.out.push(')');
+            }
+            Expression::LineBreak(inner) => {
+                self.depth += 1;
+                self.out.push('\n');
+                self.indent();
+                self.expression(inner);
+                self.depth -= 1;
+            }
+        }
+    }
+
+    /// Writes an operand after a word or operator: on the same line after a
+    /// space, or on the next line if it was written there.
+    fn spaced(&mut self, expression: &Expression) {
+        if !starts_on_next_line(expression) {
+            self.out.push(' ');
+        }
+        self.expression(expression);
+    }
+
+    /// Writes the comma-separated items between a pair of brackets. If the
+    /// first item is on a new line, so is the closing bracket.
+    fn list<'e>(&mut self, items: impl IntoIterator<Item = &'e Expression>) {
+        let mut broken = false;
+        for (i, item) in items.into_iter().enumerate() {
+            if i > 0 {
+                self.out.push(',');
+                self.spaced(item);
+            } else {
+                broken = starts_on_next_line(item);
+                self.expression(item);
+            }
+        }
+        if broken {
+            self.out.push('\n');
+            self.indent();
+        }
+    }
+
+    /// Writes the `(width, height)` of a pattern or pipeline.
+    fn dimensions(&mut self, width: &Expression, height: &Expression) {
+        self.out.push('(');
+        self.list([width, height]);
+        self.out.push(')');
+    }
+
+    /// Writes ` { ... return expr; }` after `pattern(w, h)` or `pass`.
+    fn pattern_body(&mut self, body: &[Statement], return_expr: &Expression) {
+        self.out.push_str(" {\n");
+        self.depth += 1;
+        if self.statements(body) {
+            self.out.push('\n');
+        }
+        self.indent();
+        self.out.push_str("return");
+        self.spaced(return_expr);
+        self.out.push_str(";\n");
+        self.depth -= 1;
+        self.indent();
+        self.out.push('}');
+    }
+
+    /// Appends `// text` to the last line written.
+    fn trailing_comment(&mut self, text: &str) {
+        if self.out.ends_with('\n') {
+            self.out.pop();
+        }
+        self.out.push_str(&format!("  //{}\n", text));
+    }
+
+    /// Writes the indentation of the current depth.
+    fn indent(&mut self) {
+        for _ in 0..self.depth {
+            self.out.push_str(INDENT);
+        }
+    }
+}
+
+/// Returns `true` if an expression's first operand was written on the next line.
+fn starts_on_next_line(expression: &Expression) -> bool {
+    match expression {
+        Expression::LineBreak(_) => true,
+        Expression::BinaryOperation { left, .. } => starts_on_next_line(left),
+        Expression::TernaryOperation { condition, .. } => starts_on_next_line(condition),
+        _ => false,
+    }
+}
+
+/// Returns how a binary operator is written.
+fn operator_symbol(operator: &BinaryOperator) -> &'static str {
+    match operator {
+        BinaryOperator::Add => "+",
+        BinaryOperator::Subtract => "-",
+        BinaryOperator::Multiply => "*",
+        BinaryOperator::Divide => "/",
+        BinaryOperator::Modulo => "%",
+        BinaryOperator::Equal => "==",
+        BinaryOperator::NotEqual => "!=",
+        BinaryOperator::Greater => ">",
+        BinaryOperator::Less => "<",
+        BinaryOperator::GreaterEqual => ">=",
+        BinaryOperator::LessEqual => "<=",
+        BinaryOperator::And => "and",
+        BinaryOperator::Or => "or",
+    }
+}
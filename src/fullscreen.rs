@@ -0,0 +1,162 @@
+//! Full-Screen App Detection
+//!
+//! Tells scripts whether a full-screen application - a presentation, a game,
+//! a video - is in front, through the read-only `screen_busy` variable (1 or
+//! 0), so a buddy can hide or shrink instead of floating over the slides:
+//!
+//! ```gizmo
+//! every 1000 do
+//!     opacity(screen_busy ? 0 : 1);
+//! end;
+//! ```
+//!
+//! The window loop checks at most once per [`CHECK_INTERVAL`], before it
+//! runs any handlers.
+//!
+//! ## Detection
+//!
+//! - **macOS**: `NSApplication.currentSystemPresentationOptions`, which
+//!   reflects the frontmost app's full-screen / hidden-dock presentation mode
+//! - **Windows**: `SHQueryUserNotificationState` (full-screen apps, Direct3D
+//!   exclusive mode and presentation mode)
+//! - **X11**: The `_NET_WM_STATE_FULLSCREEN` state of `_NET_ACTIVE_WINDOW`
+//! - **Wayland / others**: Unavailable; `screen_busy` stays 0
+
+use std::time::Duration;
+use winit::window::Window;
+
+/// How often the window loop checks for a full-screen app.
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Our own X11 connection with the atoms the check needs.
+#[cfg(target_os = "linux")]
+struct X11Check {
+    connection: x11rb::rust_connection::RustConnection,
+    root: u32,
+    net_active_window: u32,
+    net_wm_state: u32,
+    net_wm_state_fullscreen: u32,
+}
+
+/// Checks whether a full-screen application is frontmost.
+pub struct FullscreenDetector {
+    /// Set up when on X11
+    #[cfg(target_os = "linux")]
+    x11: Option<X11Check>,
+}
+
+impl FullscreenDetector {
+    /// Prepares full-screen checks for the display the window is on.
+    ///
+    /// On X11 this opens a separate connection to the X server; if that fails
+    /// (or on Wayland) [`is_busy`](Self::is_busy) always returns `false`.
+    pub fn new(window: &Window) -> Self {
+        #[cfg(target_os = "linux")]
+        let x11 = {
+            use raw_window_handle::{HasWindowHandle, RawWindowHandle};
+
+            let on_x11 = matches!(
+                window.window_handle().map(|handle| handle.as_raw()),
+                Ok(RawWindowHandle::Xlib(_) | RawWindowHandle::Xcb(_))
+            );
+            on_x11.then(X11Check::connect).flatten()
+        };
+        #[cfg(not(target_os = "linux"))]
+        let _ = window;
+
+        Self {
+            #[cfg(target_os = "linux")]
+            x11,
+        }
+    }
+
+    /// Returns `true` while a full-screen app is in front.
+    pub fn is_busy(&self) -> bool {
+        #[cfg(target_os = "macos")]
+        {
+            use objc::runtime::Object;
+            use objc::*;
+
+            // NSApplicationPresentationHideDock | HideMenuBar | DisableProcessSwitching | FullScreen
+            const BUSY_OPTIONS: u64 = (1 << 1) | (1 << 3) | (1 << 5) | (1 << 10);
+
+            // SAFETY: sharedApplication and currentSystemPresentationOptions
+            // are standard NSApplication methods; winit has already created
+            // the shared application on this (main) thread.
+            let options: u64 = unsafe {
+                let app: *mut Object = msg_send![class!(NSApplication), sharedApplication];
+                msg_send![app, currentSystemPresentationOptions]
+            };
+            options & BUSY_OPTIONS != 0
+        }
+
+        #[cfg(windows)]
+        {
+            use windows_sys::Win32::UI::Shell::{
+                SHQueryUserNotificationState, QUNS_BUSY, QUNS_PRESENTATION_MODE, QUNS_RUNNING_D3D_FULL_SCREEN,
+            };
+
+            let mut state = 0;
+            // SAFETY: `state` is a plain integer the call fills in.
+            if unsafe { SHQueryUserNotificationState(&mut state) } != 0 {
+                return false;
+            }
+            matches!(state, QUNS_BUSY | QUNS_RUNNING_D3D_FULL_SCREEN | QUNS_PRESENTATION_MODE)
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            self.x11.as_ref().is_some_and(X11Check::is_busy)
+        }
+
+        #[cfg(not(any(target_os = "macos", windows, target_os = "linux")))]
+        false
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl X11Check {
+    /// Connects to the X server and looks up the EWMH atoms.
+    fn connect() -> Option<Self> {
+        use x11rb::connection::Connection;
+        use x11rb::protocol::xproto::ConnectionExt;
+
+        let (connection, screen) = x11rb::connect(None).ok()?;
+        let root = connection.setup().roots[screen].root;
+        let atom = |name: &str| Some(connection.intern_atom(false, name.as_bytes()).ok()?.reply().ok()?.atom);
+        let net_active_window = atom("_NET_ACTIVE_WINDOW")?;
+        let net_wm_state = atom("_NET_WM_STATE")?;
+        let net_wm_state_fullscreen = atom("_NET_WM_STATE_FULLSCREEN")?;
+
+        Some(Self {
+            connection,
+            root,
+            net_active_window,
+            net_wm_state,
+            net_wm_state_fullscreen,
+        })
+    }
+
+    /// Asks the window manager whether the active window is full screen.
+    fn is_busy(&self) -> bool {
+        use x11rb::protocol::xproto::{AtomEnum, ConnectionExt};
+
+        let property = |window: u32, name: u32, kind: AtomEnum, length: u32| {
+            self.connection
+                .get_property(false, window, name, kind, 0, length)
+                .ok()?
+                .reply()
+                .ok()
+        };
+
+        let Some(active) = property(self.root, self.net_active_window, AtomEnum::WINDOW, 1)
+            .and_then(|reply| reply.value32()?.next())
+            .filter(|&window| window != 0)
+        else {
+            return false;
+        };
+        property(active, self.net_wm_state, AtomEnum::ATOM, 32)
+            .and_then(|reply| Some(reply.value32()?.any(|state| state == self.net_wm_state_fullscreen)))
+            .unwrap_or(false)
+    }
+}
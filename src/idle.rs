@@ -0,0 +1,185 @@
+//! Idle Detection
+//!
+//! Measures how long the buddy has been ignored so `when idle > N do ... end`
+//! handlers can fire. By default idle time counts from the last interaction
+//! with the buddy window itself (click, drag, hover, key or scroll). With
+//! `--system-idle` it counts from the last keyboard or mouse input anywhere
+//! on the system instead, so the buddy only dozes off when the whole
+//! computer is left alone.
+//!
+//! ## Firing
+//!
+//! Each threshold fires once per idle period, in ascending order. Any
+//! interaction starts a new period, and the same thresholds fire again the
+//! next time the buddy is ignored long enough:
+//!
+//! ```text
+//! thresholds  2000          5000
+//! idle time   0 ──────────── 2000 ──────────── 5000 ── (input) 0 ─── 2000 ...
+//! fires                       ▲ idle_2000        ▲ idle_5000          ▲ idle_2000
+//! ```
+//!
+//! ## System-Wide Idle Time
+//!
+//! - **X11**: The MIT-SCREEN-SAVER extension's time since the last input
+//! - **Windows**: `GetLastInputInfo`
+//! - **macOS**: `CGEventSourceSecondsSinceLastEventType`
+//! - **Wayland / others**: Unavailable; the window's own idle time is used
+
+use std::time::{Duration, Instant};
+use winit::window::Window;
+
+/// Tracks idle time and which `when idle > N` thresholds have fired.
+#[derive(Debug, Clone)]
+pub struct IdleTimer {
+    /// Last interaction with the buddy window
+    last_activity: Instant,
+    /// Largest threshold fired during the current idle period
+    fired: Option<u64>,
+}
+
+impl IdleTimer {
+    /// Creates a timer whose first idle period starts at `now`.
+    pub fn new(now: Instant) -> Self {
+        Self {
+            last_activity: now,
+            fired: None,
+        }
+    }
+
+    /// Records an interaction with the buddy window.
+    pub fn activity(&mut self, now: Instant) {
+        self.last_activity = now;
+    }
+
+    /// Returns how long the buddy window has gone without interaction.
+    pub fn window_idle(&self, now: Instant) -> Duration {
+        now.saturating_duration_since(self.last_activity)
+    }
+
+    /// Returns the thresholds passed since the last call, smallest first.
+    ///
+    /// An idle time below an already fired threshold means there was input
+    /// in between, which starts a new idle period.
+    ///
+    /// # Arguments
+    /// * `idle` - Current idle time (window or system-wide)
+    /// * `thresholds` - Handler thresholds in milliseconds, ascending
+    pub fn due(&mut self, idle: Duration, thresholds: &[u64]) -> Vec<u64> {
+        let idle_ms = idle.as_millis() as u64;
+        if self.fired.is_some_and(|fired| idle_ms < fired) {
+            self.fired = None;
+        }
+
+        let due: Vec<u64> = thresholds
+            .iter()
+            .copied()
+            .filter(|&threshold| threshold <= idle_ms && self.fired.is_none_or(|fired| threshold > fired))
+            .collect();
+        if let Some(&last) = due.last() {
+            self.fired = Some(last);
+        }
+        due
+    }
+
+    /// Returns how long until the next threshold would fire without input.
+    ///
+    /// # Arguments
+    /// * `idle` - Current idle time (window or system-wide)
+    /// * `thresholds` - Handler thresholds in milliseconds, ascending
+    ///
+    /// # Returns
+    /// `None` when every threshold has already fired in this idle period
+    pub fn until_next(&self, idle: Duration, thresholds: &[u64]) -> Option<Duration> {
+        let next = thresholds
+            .iter()
+            .copied()
+            .find(|&threshold| self.fired.is_none_or(|fired| threshold > fired))?;
+        Some(Duration::from_millis(next).saturating_sub(idle))
+    }
+}
+
+/// Reads the time since the last keyboard or mouse input anywhere on the system.
+pub struct SystemIdle {
+    /// Our own X11 connection and the root window, when on X11
+    #[cfg(target_os = "linux")]
+    x11: Option<(x11rb::rust_connection::RustConnection, u32)>,
+}
+
+impl SystemIdle {
+    /// Prepares system idle queries for the display the window is on.
+    ///
+    /// On X11 this opens a separate connection to the X server; if that fails
+    /// (or on Wayland) [`idle_time`](Self::idle_time) returns `None`.
+    pub fn new(window: &Window) -> Self {
+        #[cfg(target_os = "linux")]
+        let x11 = {
+            use raw_window_handle::{HasWindowHandle, RawWindowHandle};
+
+            let on_x11 = matches!(
+                window.window_handle().map(|handle| handle.as_raw()),
+                Ok(RawWindowHandle::Xlib(_) | RawWindowHandle::Xcb(_))
+            );
+            on_x11
+                .then(|| x11rb::connect(None).ok())
+                .flatten()
+                .map(|(connection, screen)| {
+                    use x11rb::connection::Connection;
+                    let root = connection.setup().roots[screen].root;
+                    (connection, root)
+                })
+        };
+        #[cfg(not(target_os = "linux"))]
+        let _ = window;
+
+        Self {
+            #[cfg(target_os = "linux")]
+            x11,
+        }
+    }
+
+    /// Returns the time since the last input anywhere, if the platform reports it.
+    pub fn idle_time(&self) -> Option<Duration> {
+        #[cfg(target_os = "linux")]
+        {
+            use x11rb::protocol::screensaver::ConnectionExt;
+
+            let (connection, root) = self.x11.as_ref()?;
+            let info = connection.screensaver_query_info(*root).ok()?.reply().ok()?;
+            Some(Duration::from_millis(info.ms_since_user_input as u64))
+        }
+
+        #[cfg(windows)]
+        {
+            use windows_sys::Win32::System::SystemInformation::GetTickCount;
+            use windows_sys::Win32::UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO};
+
+            let mut info = LASTINPUTINFO {
+                cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32,
+                dwTime: 0,
+            };
+            // SAFETY: `info` is a properly sized LASTINPUTINFO that outlives the call.
+            if unsafe { GetLastInputInfo(&mut info) } == 0 {
+                return None;
+            }
+            // Both are 32-bit millisecond tick counts that wrap after ~49 days
+            let now = unsafe { GetTickCount() };
+            Some(Duration::from_millis(now.wrapping_sub(info.dwTime) as u64))
+        }
+
+        #[cfg(target_os = "macos")]
+        {
+            #[link(name = "CoreGraphics", kind = "framework")]
+            extern "C" {
+                fn CGEventSourceSecondsSinceLastEventType(state: i32, event_type: u32) -> f64;
+            }
+            // kCGEventSourceStateCombinedSessionState, kCGAnyInputEventType
+            // SAFETY: A pure query with constant arguments.
+            let seconds = unsafe { CGEventSourceSecondsSinceLastEventType(0, u32::MAX) };
+            Duration::try_from_secs_f64(seconds).ok()
+        }
+
+        #[cfg(not(any(target_os = "linux", windows, target_os = "macos")))]
+        None
+    }
+}
@@ -0,0 +1,300 @@
+//! Pointer Input: Clicks, Combos, Drags and Shakes
+//!
+//! Turns raw left-button presses, cursor moves and releases into the gestures
+//! the buddy cares about. A press only becomes a drag once the cursor has
+//! moved more than [`DRAG_THRESHOLD`] pixels, so a slightly shaky click is
+//! still a click.
+//!
+//! ## Gestures
+//!
+//! ```text
+//! press ── move < threshold ── release   →  Clicked (DoubleClicked if quick)
+//! press ── move ≥ threshold ─┬─ move     →  DragStarted, then Dragged ...
+//!                            ├─ wiggle   →  Shaken (still moving the window)
+//!                            └─ release  →  Dropped
+//! ```
+//!
+//! ## Click Combos
+//!
+//! Every click (double-clicks included) is kept in a short history, which
+//! [`Pointer::combos`] checks for `when clicked N times within MS` handlers.
+//! A combo fires as soon as the last `N` clicks fit in `MS` milliseconds;
+//! those clicks are then used up for that combo, so clicking on fires it
+//! again after another `N` clicks. Different combos don't use up each
+//! other's clicks, so 5 quick clicks fire both a 3-click and a 5-click combo.
+//!
+//! ## Shaking
+//!
+//! While dragging, each axis is watched for direction changes. A change only
+//! counts once the cursor has come back [`SHAKE_DISTANCE`] pixels from the
+//! farthest point of the previous stroke, so the jitter of a slow drag never
+//! adds up. [`SHAKE_REVERSALS`] changes within [`SHAKE_WINDOW`] make a shake;
+//! shaking on fires again after another full set of changes.
+//!
+//! ## Screen Coordinates
+//!
+//! Cursor positions are tracked in screen space (window position + cursor
+//! position inside the window). Window-relative positions shift every time the
+//! window itself moves, which made the window lag behind or latch away from
+//! the cursor during fast drags.
+
+use std::time::{Duration, Instant};
+
+/// How far (in physical pixels) the cursor must move before a press becomes a drag.
+pub const DRAG_THRESHOLD: f64 = 4.0;
+
+/// Longest gap between two clicks that still counts as a double-click.
+pub const DOUBLE_CLICK_INTERVAL: Duration = Duration::from_millis(400);
+
+/// How far (in physical pixels) the cursor must travel back for a direction change to count.
+pub const SHAKE_DISTANCE: f64 = 30.0;
+
+/// Direction changes that make a shake.
+pub const SHAKE_REVERSALS: usize = 4;
+
+/// Time the direction changes of one shake must fall within.
+pub const SHAKE_WINDOW: Duration = Duration::from_millis(1000);
+
+/// Most clicks remembered for click combos.
+const CLICK_HISTORY: usize = 100;
+
+/// A gesture recognized from pointer input.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PointerEvent {
+    /// The cursor moved past the threshold; move the window to (x, y)
+    DragStarted { x: i32, y: i32 },
+    /// The drag continues; move the window to (x, y)
+    Dragged { x: i32, y: i32 },
+    /// The drag continued with a shake; move the window to (x, y)
+    Shaken { x: i32, y: i32 },
+    /// The button was released (or input lost) at the end of a drag
+    Dropped,
+    /// Press and release without dragging
+    Clicked,
+    /// A click shortly after the previous one
+    DoubleClicked,
+}
+
+/// Where a press started.
+#[derive(Debug, Clone, Copy)]
+struct Press {
+    /// Cursor position in screen coordinates
+    cursor: (f64, f64),
+    /// Window's top-left corner when the button went down
+    window: (i32, i32),
+}
+
+/// Direction changes of the cursor along one axis during a drag.
+#[derive(Debug, Default)]
+struct Strokes {
+    /// Farthest position reached by the current stroke
+    extreme: f64,
+    /// -1 or 1 once a stroke has covered `SHAKE_DISTANCE`, 0 before
+    direction: f64,
+    /// When each recent direction change happened, oldest first
+    reversals: Vec<Instant>,
+}
+
+impl Strokes {
+    /// Starts watching from the cursor position where the drag began.
+    fn new(start: f64) -> Self {
+        Self {
+            extreme: start,
+            ..Self::default()
+        }
+    }
+
+    /// Follows the cursor to `position`.
+    ///
+    /// # Returns
+    /// `true` once `SHAKE_REVERSALS` direction changes happened within `SHAKE_WINDOW`
+    fn moved(&mut self, position: f64, now: Instant) -> bool {
+        let travel = position - self.extreme;
+        if self.direction == 0.0 {
+            if travel.abs() >= SHAKE_DISTANCE {
+                self.direction = travel.signum();
+                self.extreme = position;
+            }
+            return false;
+        }
+
+        if travel * self.direction > 0.0 {
+            // Still going the same way
+            self.extreme = position;
+            return false;
+        }
+        if travel.abs() < SHAKE_DISTANCE {
+            return false;
+        }
+
+        self.direction = -self.direction;
+        self.extreme = position;
+        self.reversals.retain(|&at| now.saturating_duration_since(at) <= SHAKE_WINDOW);
+        self.reversals.push(now);
+        if self.reversals.len() < SHAKE_REVERSALS {
+            return false;
+        }
+        self.reversals.clear();
+        true
+    }
+}
+
+/// Recognizes clicks and drags from the left mouse button.
+#[derive(Debug, Default)]
+pub struct Pointer {
+    /// Button currently held down
+    press: Option<Press>,
+    /// Whether the current press has turned into a drag
+    dragging: bool,
+    /// Horizontal and vertical strokes of the current drag
+    strokes: Option<(Strokes, Strokes)>,
+    /// When the last click (not drag) finished
+    last_click: Option<Instant>,
+    /// When recent clicks finished, oldest first
+    clicks: Vec<Instant>,
+    /// Last time each click combo fired; earlier clicks don't count toward it
+    combos_fired: Vec<((u32, u64), Instant)>,
+}
+
+impl Pointer {
+    /// Creates a tracker with no button held.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns `true` while the window is being dragged.
+    pub fn is_dragging(&self) -> bool {
+        self.dragging
+    }
+
+    /// Records a left-button press.
+    ///
+    /// # Arguments
+    /// * `cursor` - Cursor position in screen coordinates
+    /// * `window` - Window's current top-left corner
+    pub fn press(&mut self, cursor: (f64, f64), window: (i32, i32)) {
+        self.press = Some(Press { cursor, window });
+        self.dragging = false;
+        self.strokes = None;
+    }
+
+    /// Records cursor movement.
+    ///
+    /// # Arguments
+    /// * `cursor` - Cursor position in screen coordinates
+    /// * `now` - Time of the move, for shake detection
+    ///
+    /// # Returns
+    /// `DragStarted` / `Dragged` / `Shaken` with the window position that
+    /// keeps the grab point under the cursor, or `None` when no drag is
+    /// happening
+    pub fn moved(&mut self, cursor: (f64, f64), now: Instant) -> Option<PointerEvent> {
+        let press = self.press?;
+        let (dx, dy) = (cursor.0 - press.cursor.0, cursor.1 - press.cursor.1);
+
+        let started = !self.dragging;
+        if started {
+            if dx.hypot(dy) < DRAG_THRESHOLD {
+                return None;
+            }
+            self.dragging = true;
+        }
+
+        let (horizontal, vertical) = self
+            .strokes
+            .get_or_insert_with(|| (Strokes::new(press.cursor.0), Strokes::new(press.cursor.1)));
+        // Both axes always follow the cursor, even if one of them shook
+        let shaken_x = horizontal.moved(cursor.0, now);
+        let shaken_y = vertical.moved(cursor.1, now);
+
+        let (x, y) = (press.window.0 + dx.round() as i32, press.window.1 + dy.round() as i32);
+        Some(if started {
+            PointerEvent::DragStarted { x, y }
+        } else if shaken_x || shaken_y {
+            PointerEvent::Shaken { x, y }
+        } else {
+            PointerEvent::Dragged { x, y }
+        })
+    }
+
+    /// Records the left button going up.
+    ///
+    /// # Arguments
+    /// * `now` - Time of the release, for double-click detection
+    ///
+    /// # Returns
+    /// `Dropped` after a drag, `Clicked` / `DoubleClicked` otherwise, or
+    /// `None` if no press was seen
+    pub fn release(&mut self, now: Instant) -> Option<PointerEvent> {
+        self.press.take()?;
+
+        if std::mem::take(&mut self.dragging) {
+            return Some(PointerEvent::Dropped);
+        }
+
+        if self.clicks.len() == CLICK_HISTORY {
+            self.clicks.remove(0);
+        }
+        self.clicks.push(now);
+
+        let double = self
+            .last_click
+            .is_some_and(|last| now.saturating_duration_since(last) <= DOUBLE_CLICK_INTERVAL);
+        // A double-click consumes both clicks so a third starts over
+        self.last_click = if double { None } else { Some(now) };
+        Some(if double {
+            PointerEvent::DoubleClicked
+        } else {
+            PointerEvent::Clicked
+        })
+    }
+
+    /// Returns the click combos completed by the latest click.
+    ///
+    /// Call after each `Clicked` / `DoubleClicked`.
+    ///
+    /// # Arguments
+    /// * `combos` - (clicks, window in milliseconds) of the script's combo handlers
+    pub fn combos(&mut self, combos: &[(u32, u64)]) -> Vec<(u32, u64)> {
+        let Some(&now) = self.clicks.last() else {
+            return Vec::new();
+        };
+
+        let mut completed = Vec::new();
+        for &combo @ (times, within_ms) in combos {
+            let window_start = now.checked_sub(Duration::from_millis(within_ms));
+            let fired = self
+                .combos_fired
+                .iter()
+                .find(|(fired, _)| *fired == combo)
+                .map(|&(_, at)| at);
+            let counted = self
+                .clicks
+                .iter()
+                .rev()
+                .take_while(|&&click| window_start.is_none_or(|start| click >= start))
+                .take_while(|&&click| fired.is_none_or(|at| click > at))
+                .count();
+            if counted < times as usize {
+                continue;
+            }
+
+            completed.push(combo);
+            match self.combos_fired.iter_mut().find(|(fired, _)| *fired == combo) {
+                Some((_, at)) => *at = now,
+                None => self.combos_fired.push((combo, now)),
+            }
+        }
+        completed
+    }
+
+    /// Abandons the current press, e.g. when the window loses focus before
+    /// the release arrives.
+    ///
+    /// # Returns
+    /// `Dropped` if a drag was in progress
+    pub fn cancel(&mut self) -> Option<PointerEvent> {
+        self.press.take()?;
+        std::mem::take(&mut self.dragging).then_some(PointerEvent::Dropped)
+    }
+}
@@ -0,0 +1,344 @@
+//! Control Channel
+//!
+//! Lets CLI commands talk to the running GUI process instead of only being
+//! able to stop it. The GUI listens on a local socket; each command is one
+//! connection carrying one line of JSON, answered with one line of JSON:
+//!
+//! | Command                                    | Reply                     |
+//! |--------------------------------------------|---------------------------|
+//! | `{"command":"pause"}`                      | `{"reply":"ok"}`          |
+//! | `{"command":"resume"}`                     | `{"reply":"ok"}`          |
+//! | `{"command":"status"}`                     | `{"reply":"status",...}`  |
+//! | `{"command":"position","to":null}`         | `{"reply":"position",...}`|
+//! | `{"command":"position","to":[120,80]}`     | `{"reply":"position",...}`|
+//! | `{"command":"speed","to":null}`            | `{"reply":"speed",...}`   |
+//! | `{"command":"speed","to":{"scale":2.0}}`   | `{"reply":"speed",...}`   |
+//! | `{"command":"switch","file":"/abs/a.gzmo"}`| `{"reply":"ok"}`          |
+//!
+//! Any command can instead be answered with
+//! `{"reply":"error","message":"..."}`.
+//!
+//! ## Transport
+//!
+//! - **Unix**: A Unix domain socket, `{instance_dir}/gizmo.sock`, only
+//!   reachable by the user (the config directory is theirs)
+//! - **Windows**: The named pipe `\\.\pipe\gizmo-<user name>` (with
+//!   `-<instance>` appended for named instances), which rejects remote clients
+//!
+//! The listener runs on its own thread and hands each command to the window
+//! loop as a [`GuiEvent::Control`](crate::GuiEvent::Control), then waits for
+//! the loop's answer. Builds without the `gui` feature only have the CLI
+//! side.
+
+use crate::clock::Speed;
+use crate::daemon::{self, GuiStatus};
+#[cfg(feature = "gui")]
+use crate::GuiEvent;
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+#[cfg(feature = "gui")]
+use std::sync::mpsc;
+#[cfg(feature = "gui")]
+use std::time::Duration;
+#[cfg(feature = "gui")]
+use winit::event_loop::EventLoopProxy;
+
+/// How long the listener waits for the window loop to answer a command.
+#[cfg(feature = "gui")]
+const ANSWER_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A command the CLI sends to the running GUI.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum Command {
+    /// Freeze the animation
+    Pause,
+    /// Continue a frozen animation
+    Resume,
+    /// Report what is playing and how
+    Status,
+    /// Report the window position, after moving the window to `to` if given
+    Position {
+        /// Where to move the window's top-left corner, in physical pixels
+        to: Option<(i32, i32)>,
+    },
+    /// Report the playback speed, after overriding it with `to` if given
+    Speed {
+        /// The new override; [`Speed::Script`] goes back to the script's speed
+        to: Option<Speed>,
+    },
+    /// Play another script in the same window, as if it was dropped onto it
+    Switch {
+        /// Absolute path of the .gzmo file
+        file: PathBuf,
+    },
+}
+
+/// The GUI's answer to a [`Command`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "reply", rename_all = "snake_case")]
+pub enum Reply {
+    /// The command was carried out
+    Ok,
+    /// Answer to [`Command::Status`]
+    Status(LiveStatus),
+    /// Answer to [`Command::Position`]: where the window's top-left corner is
+    Position {
+        /// Horizontal position in physical pixels
+        x: i32,
+        /// Vertical position in physical pixels
+        y: i32,
+    },
+    /// Answer to [`Command::Speed`]
+    Speed {
+        /// The override in effect
+        speed: Speed,
+        /// How long each frame is shown now, in milliseconds
+        frame_ms: u64,
+    },
+    /// The command couldn't be carried out
+    Error {
+        /// What went wrong
+        message: String,
+    },
+}
+
+/// What the running GUI reports about itself, beyond what it saves to
+/// `status.json`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LiveStatus {
+    /// The same status `gizmo status` reads when the GUI can't be reached
+    #[serde(flatten)]
+    pub status: GuiStatus,
+    /// Whether the animation is frozen (`gizmo pause`)
+    pub paused: bool,
+    /// Override of the script's speed (`gizmo speed`)
+    pub speed: Speed,
+    /// Where the window's top-left corner is, in physical pixels
+    pub position: Option<(i32, i32)>,
+    /// Frames shown per second lately; `None` while there's no measurement
+    /// (or for GUIs that predate it)
+    pub fps: Option<f64>,
+}
+
+/// A command received by the GUI, waiting for the window loop's answer.
+#[cfg(feature = "gui")]
+#[derive(Debug)]
+pub struct Request {
+    /// What the CLI asked for
+    pub command: Command,
+    /// Where the answer goes back to the listener thread
+    reply: mpsc::Sender<Reply>,
+}
+
+#[cfg(feature = "gui")]
+impl Request {
+    /// Answers the CLI.
+    pub fn answer(self, reply: Reply) {
+        // The listener may have given up waiting; nothing to do then
+        let _ = self.reply.send(reply);
+    }
+}
+
+/// Starts listening for CLI commands on a background thread.
+///
+/// # Arguments
+/// * `instance` - Instance the GUI process runs
+/// * `proxy` - Event loop the commands are forwarded to
+///
+/// # Returns
+/// * `Ok(())` - Listening
+/// * `Err` - The socket / pipe couldn't be created
+#[cfg(feature = "gui")]
+pub fn listen(instance: &str, proxy: EventLoopProxy<GuiEvent>) -> Result<(), Box<dyn std::error::Error>> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::net::UnixListener;
+
+        // A GUI that crashed leaves its socket behind
+        daemon::create_instance_dir(instance)?;
+        let path = socket_path(instance)?;
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path)?;
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let reader = BufReader::new(match stream.try_clone() {
+                    Ok(reader) => reader,
+                    Err(_) => continue,
+                });
+                serve(reader, stream, &proxy);
+            }
+        });
+        Ok(())
+    }
+
+    #[cfg(windows)]
+    {
+        let name = pipe_name(instance);
+        // Fail now rather than on the thread if the pipe can't be created
+        let first = create_pipe(&name)?;
+        std::thread::spawn(move || {
+            let mut pipe = Some(first);
+            loop {
+                let pipe = match pipe.take().map_or_else(|| create_pipe(&name), Ok) {
+                    Ok(pipe) => pipe,
+                    Err(e) => {
+                        eprintln!("Control channel closed: {}", e);
+                        return;
+                    }
+                };
+                if let Some(file) = accept_pipe(pipe) {
+                    let Ok(reader) = file.try_clone() else {
+                        continue;
+                    };
+                    serve(BufReader::new(reader), &file, &proxy);
+                    // Wait for the CLI to read the answer before closing the pipe
+                    let _ = file.sync_all();
+                }
+            }
+        });
+        Ok(())
+    }
+}
+
+/// Reads one command from a connection, forwards it and writes the answer.
+#[cfg(feature = "gui")]
+fn serve(mut reader: impl BufRead, mut writer: impl Write, proxy: &EventLoopProxy<GuiEvent>) {
+    let mut line = String::new();
+    if reader.read_line(&mut line).is_err() {
+        return;
+    }
+
+    let reply = match serde_json::from_str::<Command>(&line) {
+        Ok(command) => {
+            let (sender, receiver) = mpsc::channel();
+            let request = Request { command, reply: sender };
+            if proxy.send_event(GuiEvent::Control(request)).is_err() {
+                error("Gizmo is shutting down")
+            } else {
+                receiver.recv_timeout(ANSWER_TIMEOUT).unwrap_or_else(|e| match e {
+                    mpsc::RecvTimeoutError::Timeout => error("Gizmo didn't answer"),
+                    // Dropped unanswered, e.g. a switch while playing the farewell
+                    mpsc::RecvTimeoutError::Disconnected => error("Gizmo is shutting down"),
+                })
+            }
+        }
+        // A newer CLI talking to an older GUI ends up here
+        Err(e) => error(&format!("Unknown command: {}", e)),
+    };
+
+    if let Ok(mut reply) = serde_json::to_string(&reply) {
+        reply.push('\n');
+        let _ = writer.write_all(reply.as_bytes());
+    }
+}
+
+/// Builds an error reply.
+#[cfg(feature = "gui")]
+fn error(message: &str) -> Reply {
+    Reply::Error { message: message.to_string() }
+}
+
+/// Sends a command to the running GUI and waits for its answer.
+///
+/// # Arguments
+/// * `instance` - Instance to talk to
+/// * `command` - What to ask for
+///
+/// # Returns
+/// * `Ok(reply)` - The GUI carried it out; never [`Reply::Error`]
+/// * `Err` - Gizmo isn't running, or the GUI answered with an error
+pub fn send(instance: &str, command: &Command) -> Result<Reply, Box<dyn std::error::Error>> {
+    if !daemon::is_daemon_running(instance)? {
+        return Err("Gizmo is not running".into());
+    }
+
+    #[cfg(unix)]
+    let stream = std::os::unix::net::UnixStream::connect(socket_path(instance)?);
+    #[cfg(windows)]
+    let stream = std::fs::OpenOptions::new().read(true).write(true).open(pipe_name(instance));
+    let mut stream = stream.map_err(|e| format!("Can't reach the running gizmo: {}", e))?;
+
+    let mut line = serde_json::to_string(command)?;
+    line.push('\n');
+    stream.write_all(line.as_bytes())?;
+    let mut answer = String::new();
+    BufReader::new(&mut stream).read_line(&mut answer)?;
+
+    let reply: Reply =
+        serde_json::from_str(&answer).map_err(|_| format!("Unexpected answer from gizmo: '{}'", answer.trim()))?;
+    match reply {
+        Reply::Error { message } => Err(message.into()),
+        reply => Ok(reply),
+    }
+}
+
+/// Returns where an instance's control socket lives.
+#[cfg(unix)]
+fn socket_path(instance: &str) -> Result<std::path::PathBuf, Box<dyn std::error::Error>> {
+    Ok(daemon::instance_dir(instance)?.join("gizmo.sock"))
+}
+
+/// Returns the name of an instance's control pipe; one per user and instance.
+#[cfg(windows)]
+fn pipe_name(instance: &str) -> String {
+    let user = std::env::var("USERNAME").unwrap_or_default();
+    if instance == daemon::DEFAULT_INSTANCE {
+        format!(r"\\.\pipe\gizmo-{}", user)
+    } else {
+        format!(r"\\.\pipe\gizmo-{}-{}", user, instance)
+    }
+}
+
+/// Creates a new instance of the control pipe.
+#[cfg(all(windows, feature = "gui"))]
+fn create_pipe(name: &str) -> std::io::Result<std::fs::File> {
+    use std::os::windows::io::FromRawHandle;
+    use windows_sys::Win32::Foundation::INVALID_HANDLE_VALUE;
+    use windows_sys::Win32::Storage::FileSystem::PIPE_ACCESS_DUPLEX;
+    use windows_sys::Win32::System::Pipes::{
+        CreateNamedPipeW, PIPE_REJECT_REMOTE_CLIENTS, PIPE_TYPE_BYTE, PIPE_UNLIMITED_INSTANCES, PIPE_WAIT,
+    };
+
+    let wide: Vec<u16> = name.encode_utf16().chain(std::iter::once(0)).collect();
+    // SAFETY: `wide` is a NUL-terminated UTF-16 string that outlives the call;
+    // null security attributes give the default (current user) access.
+    let handle = unsafe {
+        CreateNamedPipeW(
+            wide.as_ptr(),
+            PIPE_ACCESS_DUPLEX,
+            PIPE_TYPE_BYTE | PIPE_WAIT | PIPE_REJECT_REMOTE_CLIENTS,
+            PIPE_UNLIMITED_INSTANCES,
+            512,
+            512,
+            0,
+            std::ptr::null(),
+        )
+    };
+    if handle == INVALID_HANDLE_VALUE {
+        return Err(std::io::Error::last_os_error());
+    }
+    // SAFETY: `handle` is a valid pipe instance nobody else owns
+    Ok(unsafe { std::fs::File::from_raw_handle(handle as _) })
+}
+
+/// Waits for a CLI to connect to a pipe instance.
+///
+/// # Returns
+/// The connected pipe, or `None` if connecting failed (the pipe is closed)
+#[cfg(all(windows, feature = "gui"))]
+fn accept_pipe(pipe: std::fs::File) -> Option<std::fs::File> {
+    use std::os::windows::io::AsRawHandle;
+    use windows_sys::Win32::Foundation::{GetLastError, ERROR_PIPE_CONNECTED};
+    use windows_sys::Win32::System::Pipes::ConnectNamedPipe;
+
+    // SAFETY: `pipe` is an open pipe instance for the whole call; without an
+    // OVERLAPPED the call blocks until a client connects
+    let connected = unsafe {
+        // A client that connected between creating and waiting is fine too
+        ConnectNamedPipe(pipe.as_raw_handle() as _, std::ptr::null_mut()) != 0
+            || GetLastError() == ERROR_PIPE_CONNECTED
+    };
+    connected.then_some(pipe)
+}
@@ -0,0 +1,149 @@
+//! Keyboard Shortcuts
+//!
+//! Key presses reach scripts through `when key("p") do ... end` handlers.
+//! While the buddy window has focus, winit's keyboard input is turned into a
+//! key name with [`key_name`]; for a window this small, focus is easy to
+//! lose, so `--hotkey` additionally registers system-wide shortcuts.
+//!
+//! ## Key Names
+//!
+//! Character keys are named by the (lowercased) character they type: `"p"`,
+//! `"+"`, `"1"`. Other keys use winit's names in lowercase: `"space"`,
+//! `"enter"`, `"escape"`, `"arrowleft"`, `"f1"`, ...
+//!
+//! ## Global Hotkeys
+//!
+//! `--hotkey <combo>=<action>` binds a shortcut such as `ctrl+alt+p` that
+//! works even when another application has focus. The action `pause`
+//! toggles the animation like a double-click; any other action is a key
+//! name, and pressing the shortcut runs that key's `when key(...)` handlers
+//! as if it had been pressed on the buddy:
+//!
+//! ```text
+//! gizmo start --hotkey ctrl+alt+p=pause --hotkey ctrl+alt+n=n buddy.gzmo
+//! ```
+
+use crate::GuiEvent;
+use global_hotkey::hotkey::HotKey;
+use global_hotkey::{GlobalHotKeyEvent, GlobalHotKeyManager, HotKeyState};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Mutex;
+use winit::event_loop::EventLoopProxy;
+use winit::keyboard::Key;
+
+/// Returns the name scripts use for a key, or `None` for keys without one
+/// (dead keys, unidentified keys).
+pub fn key_name(key: &Key) -> Option<String> {
+    match key {
+        Key::Character(text) => Some(text.to_lowercase()),
+        Key::Named(named) => Some(format!("{:?}", named).to_lowercase()),
+        _ => None,
+    }
+}
+
+/// What a global hotkey does.
+#[derive(Debug, Clone, PartialEq)]
+pub enum HotkeyAction {
+    /// Toggle the animation like a double-click
+    Pause,
+    /// Run the script's `when key(name)` handlers
+    Key(String),
+}
+
+/// A `--hotkey <combo>=<action>` option.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HotkeyBinding {
+    /// Shortcut as written on the command line, e.g. `ctrl+alt+p`
+    combo: String,
+    /// What pressing it does
+    action: HotkeyAction,
+}
+
+impl FromStr for HotkeyBinding {
+    type Err = String;
+
+    /// Parses `<combo>=<action>`, checking that the combo is a valid shortcut.
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let (combo, action) = text
+            .rsplit_once('=')
+            .filter(|(combo, action)| !combo.is_empty() && !action.is_empty())
+            .ok_or_else(|| format!("Invalid hotkey '{}': expected <combo>=<action>, e.g. ctrl+alt+p=pause", text))?;
+        HotKey::from_str(combo).map_err(|e| format!("Invalid hotkey '{}': {}", combo, e))?;
+
+        let action = match action.to_lowercase().as_str() {
+            "pause" => HotkeyAction::Pause,
+            key => HotkeyAction::Key(key.to_string()),
+        };
+        Ok(Self {
+            combo: combo.to_string(),
+            action,
+        })
+    }
+}
+
+impl fmt::Display for HotkeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.action {
+            HotkeyAction::Pause => write!(f, "{}=pause", self.combo),
+            HotkeyAction::Key(key) => write!(f, "{}={}", self.combo, key),
+        }
+    }
+}
+
+/// Registered system-wide shortcuts; they stay active until this is dropped.
+pub struct GlobalHotkeys {
+    /// Keeps the registrations alive
+    _manager: GlobalHotKeyManager,
+    /// Hotkey ids and their actions
+    actions: Vec<(u32, HotkeyAction)>,
+}
+
+impl GlobalHotkeys {
+    /// Registers the shortcuts and forwards presses to the window loop as
+    /// [`GuiEvent::Hotkey`].
+    ///
+    /// # Arguments
+    /// * `bindings` - Parsed `--hotkey` options
+    /// * `proxy` - Event loop to notify
+    ///
+    /// # Returns
+    /// * `Ok(GlobalHotkeys)` - Every shortcut was registered
+    /// * `Err` - The platform has no global hotkeys (e.g. Wayland) or a
+    ///   shortcut is already taken by another application
+    pub fn register(
+        bindings: &[HotkeyBinding],
+        proxy: EventLoopProxy<GuiEvent>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let manager = GlobalHotKeyManager::new()?;
+        let mut actions = Vec::new();
+        for binding in bindings {
+            let hotkey = HotKey::from_str(&binding.combo)?;
+            manager.register(hotkey)?;
+            actions.push((hotkey.id(), binding.action.clone()));
+        }
+
+        // The handler runs on the hotkey backend's thread
+        let proxy = Mutex::new(proxy);
+        GlobalHotKeyEvent::set_event_handler(Some(move |event: GlobalHotKeyEvent| {
+            if event.state() == HotKeyState::Pressed {
+                if let Ok(proxy) = proxy.lock() {
+                    let _ = proxy.send_event(GuiEvent::Hotkey(event.id()));
+                }
+            }
+        }));
+
+        Ok(Self {
+            _manager: manager,
+            actions,
+        })
+    }
+
+    /// Returns the action bound to a hotkey id from [`GuiEvent::Hotkey`].
+    pub fn action(&self, id: u32) -> Option<&HotkeyAction> {
+        self.actions
+            .iter()
+            .find(|(hotkey_id, _)| *hotkey_id == id)
+            .map(|(_, action)| action)
+    }
+}
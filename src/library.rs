@@ -0,0 +1,188 @@
+//! Script Library
+//!
+//! `gizmo get` downloads scripts into a local library so they can be started
+//! by name instead of by path:
+//!
+//! ```text
+//! gizmo get https://example.com/pets/cat.gzmo   # saved as "cat"
+//! gizmo get spinner                              # looked up in the gallery index
+//! gizmo library list
+//! gizmo start --from-library cat
+//! ```
+//!
+//! The library is the `library/` directory of the config directory (see
+//! [`daemon::get_config_dir`]), one `<name>.gzmo` per script, or
+//! `<name>.gzmopack` for a [`bundle`](crate::bundle) with its sprites.
+//! Scripts copied there by hand work the same.
+//!
+//! ## Gallery Index
+//!
+//! A name that isn't a URL is looked up in a curated index, the gallery's
+//! `index.json` unless `--index <url>` names another one:
+//!
+//! ```json
+//! {"scripts": [{"name": "spinner", "url": "https://.../spinner.gzmo", "description": "Loading spinner"}]}
+//! ```
+//!
+//! Downloaded scripts (and bundles' main scripts) must parse before they are
+//! saved, so a server's error page never ends up in the library.
+//! Downloading needs a build with the `get` feature; listing and starting
+//! library scripts doesn't.
+
+use crate::bundle;
+use crate::daemon;
+#[cfg(feature = "get")]
+use serde::Deserialize;
+use std::error::Error;
+use std::fs;
+#[cfg(feature = "get")]
+use std::io::Read;
+#[cfg(feature = "get")]
+use std::path::Path;
+use std::path::PathBuf;
+
+/// Largest script or bundle `gizmo get` downloads, in bytes
+#[cfg(feature = "get")]
+const MAX_DOWNLOAD: u64 = 64 * 1024 * 1024;
+
+/// Gallery index used when `gizmo get` isn't given `--index`
+pub const DEFAULT_INDEX: &str = "https://raw.githubusercontent.com/coldielb/gizmo/main/gallery/index.json";
+
+/// Returns the library directory, creating it if necessary.
+fn library_dir() -> Result<PathBuf, Box<dyn Error>> {
+    let dir = daemon::get_config_dir()?.join("library");
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// Checks that a script name is usable as a library file name.
+///
+/// # Arguments
+/// * `name` - Letters, digits, `-` and `_`
+#[cfg(any(feature = "gui", feature = "get"))]
+fn validate_name(name: &str) -> Result<(), Box<dyn Error>> {
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(format!("Invalid script name '{}' (use letters, digits, '-' and '_')", name).into());
+    }
+    Ok(())
+}
+
+/// Returns the path of a library script or bundle.
+///
+/// # Returns
+/// * `Ok(PathBuf)` - The script's file
+/// * `Err` - The name is invalid or there is no such script
+#[cfg(feature = "gui")]
+pub fn path(name: &str) -> Result<PathBuf, Box<dyn Error>> {
+    validate_name(name)?;
+    let dir = library_dir()?;
+    ["gzmo", bundle::EXTENSION]
+        .iter()
+        .map(|extension| dir.join(format!("{}.{}", name, extension)))
+        .find(|path| path.exists())
+        .ok_or_else(|| format!("No script named '{}' in the library (see 'gizmo library list')", name).into())
+}
+
+/// Lists the names of the library's scripts and bundles, sorted.
+pub fn list() -> Result<Vec<String>, Box<dyn Error>> {
+    let mut names: Vec<String> = fs::read_dir(library_dir()?)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.to_str().is_some_and(bundle::is_playable))
+        .filter_map(|path| path.file_stem().map(|stem| stem.to_string_lossy().into_owned()))
+        .collect();
+    names.sort();
+    names.dedup();
+    Ok(names)
+}
+
+/// A curated list of scripts to download by name.
+#[cfg(feature = "get")]
+#[derive(Debug, Deserialize)]
+struct Index {
+    scripts: Vec<IndexEntry>,
+}
+
+/// One script of the gallery index.
+#[cfg(feature = "get")]
+#[derive(Debug, Deserialize)]
+struct IndexEntry {
+    /// Name to `gizmo get` it by, and its library name
+    name: String,
+    /// Where the .gzmo file is
+    url: String,
+}
+
+/// Downloads a script or bundle into the library.
+///
+/// # Arguments
+/// * `source` - URL of a .gzmo or .gzmopack file, or the name of a script in the index
+/// * `index_url` - Gallery index to look names up in
+/// * `name` - Library name to save it as; the file or index name if `None`
+///
+/// # Returns
+/// * `Ok((name, replaced))` - The library name, and whether a script of
+///   that name was replaced
+/// * `Err` - The download failed, or it isn't a script
+#[cfg(feature = "get")]
+pub fn get(source: &str, index_url: &str, name: Option<&str>) -> Result<(String, bool), Box<dyn Error>> {
+    let (url, default_name) = if source.starts_with("http://") || source.starts_with("https://") {
+        let file = source.rsplit('/').next().unwrap_or_default();
+        let file = file.split(['?', '#']).next().unwrap_or_default();
+        let stem = Path::new(file).file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+        (source.to_string(), stem)
+    } else {
+        let index: Index = serde_json::from_slice(&download(index_url)?)
+            .map_err(|e| format!("The index at {} isn't valid: {}", index_url, e))?;
+        let entry = index
+            .scripts
+            .into_iter()
+            .find(|entry| entry.name == source)
+            .ok_or_else(|| format!("No script named '{}' in the index at {}", source, index_url))?;
+        (entry.url, entry.name)
+    };
+    let name = name.map_or(default_name, str::to_string);
+    if name.is_empty() {
+        return Err(format!("Can't tell a name from {}; give it one with --as", url).into());
+    }
+    validate_name(&name)?;
+
+    let bytes = download(&url)?;
+    let path_part = url.split(['?', '#']).next().unwrap_or_default();
+    let extension = if bundle::is_bundle(Path::new(path_part)) {
+        bundle::validate(&bytes).map_err(|e| format!("{} isn't a valid bundle: {}", url, e))?;
+        bundle::EXTENSION
+    } else {
+        let script = std::str::from_utf8(&bytes).map_err(|_| format!("{} isn't a .gzmo script", url))?;
+        gizmo_core::parse(script).map_err(|e| format!("{} isn't a valid .gzmo script: {}", url, e))?;
+        "gzmo"
+    };
+
+    // A script replaces a bundle of the same name and the other way around
+    let dir = library_dir()?;
+    let mut replaced = false;
+    for old in ["gzmo", bundle::EXTENSION] {
+        let old = dir.join(format!("{}.{}", name, old));
+        if old.exists() {
+            fs::remove_file(old)?;
+            replaced = true;
+        }
+    }
+    fs::write(dir.join(format!("{}.{}", name, extension)), bytes)?;
+    Ok((name, replaced))
+}
+
+/// Downloads a file.
+#[cfg(feature = "get")]
+fn download(url: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    let response = ureq::get(url).call().map_err(|e| format!("Couldn't download {}", e))?;
+    let mut bytes = Vec::new();
+    response.into_reader().take(MAX_DOWNLOAD).read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Downloads a script into the library; this build can't download.
+#[cfg(not(feature = "get"))]
+pub fn get(_source: &str, _index_url: &str, _name: Option<&str>) -> Result<(String, bool), Box<dyn Error>> {
+    Err("gizmo get needs a build with the `get` feature (cargo build --release --features get)".into())
+}
@@ -0,0 +1,383 @@
+//! Language Server
+//!
+//! `gizmo lsp` speaks the Language Server Protocol over standard input and
+//! output, so any LSP-capable editor (VS Code, Neovim, Helix, ...) gets live
+//! feedback while editing .gzmo files:
+//!
+//! - **Diagnostics**: Every change is run through the same checks as
+//!   `gizmo check`, and its problems are underlined. The run gets a second
+//!   and a million statements rather than `gizmo check`'s limits, so a
+//!   runaway loop doesn't hold up the editor; going past them is reported
+//! - **Hover**: Built-in functions and variables show what they do
+//! - **Completion**: Keywords, event names, built-in functions and variables,
+//!   and the variables the script itself declares
+//!
+//! Documents are synced in full on every change; .gzmo files are small
+//! enough that incremental sync isn't worth it. Positions are exchanged in
+//! the UTF-16 code units LSP counts columns in, and converted to and from the
+//! characters the lexer counts.
+//!
+//! ## Editor Setup
+//!
+//! Neovim:
+//! ```text
+//! vim.lsp.start({ name = "gizmo", cmd = { "gizmo", "lsp" }, filetypes = { "gizmo" } })
+//! ```
+
+use crate::builtin::BuiltinFunctions;
+use crate::check::{check_within, Problem};
+use crate::lexer::{Lexer, Token};
+use gizmo_core::Limits;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Words with a fixed meaning, offered by completion
+const KEYWORDS: &[&str] = &[
+    "frame", "frames", "persist", "pattern", "pipeline", "pass", "return", "if", "then", "else", "repeat",
+    "times", "do", "end", "when", "every", "once", "priority", "within", "and", "or",
+];
+
+/// Events that can follow `when`, offered by completion
+const EVENTS: &[&str] = &[
+    "launched", "stopping", "clicked", "double_clicked", "hovered", "unhovered", "dragged", "dropped",
+    "shaken", "key", "time", "message", "idle", "unplugged", "plugged_in", "battery", "battery_low",
+];
+
+/// Variables the interpreter provides, with what they hold
+const VARIABLES: &[(&str, &str)] = &[
+    ("row", "Current pixel row inside a pattern (0 to height-1)"),
+    ("col", "Current pixel column inside a pattern (0 to width-1)"),
+    ("time", "Current iteration of the enclosing `repeat` loop"),
+    ("previous", "Output of the prior pass, inside `pipeline` passes"),
+    ("hour", "Hour of the day (0 to 23)"),
+    ("minute", "Minute of the hour (0 to 59)"),
+    ("weekday", "Day of the week, 0 (Sunday) to 6 (Saturday)"),
+    ("screen_busy", "1 while a full-screen app is frontmost, otherwise 0"),
+    ("cpu_percent", "Average CPU load over all cores, 0 to 100 (`sysinfo` feature)"),
+    ("mem_percent", "Memory in use, 0 to 100 (`sysinfo` feature)"),
+    ("battery_percent", "Battery charge, 0 to 100 (`sysinfo` feature)"),
+    ("on_battery", "1 while running on battery power, otherwise 0 (`sysinfo` feature)"),
+    ("mic_level", "Smoothed microphone loudness, 0 to 100 (`mic` feature with `--mic`)"),
+    ("away_seconds", "Seconds since `persist` variables were last saved"),
+    ("drop_x", "Window x position after the last drop"),
+    ("drop_y", "Window y position after the last drop"),
+];
+
+/// Statements a diagnostics run may take; far fewer than `gizmo check`
+/// allows, as one runs on every keystroke
+const MAX_STATEMENTS: u64 = 1_000_000;
+
+/// How long a diagnostics run may take
+const TIMEOUT: Duration = Duration::from_secs(1);
+
+/// LSP `CompletionItemKind` values
+const KIND_FUNCTION: u8 = 3;
+const KIND_VARIABLE: u8 = 6;
+const KIND_KEYWORD: u8 = 14;
+const KIND_EVENT: u8 = 23;
+
+/// Serves one editor session over standard input and output.
+///
+/// # Returns
+/// * `Ok(())` - The editor sent `exit` or closed standard input
+/// * `Err` - Reading or writing a message failed
+pub fn run() -> io::Result<()> {
+    let mut input = io::stdin().lock();
+    let mut server = Server { out: io::stdout().lock(), documents: HashMap::new(), builtins: BuiltinFunctions::new() };
+
+    while let Some(message) = read_message(&mut input)? {
+        if message["method"] == "exit" {
+            break;
+        }
+        server.handle(&message)?;
+    }
+    Ok(())
+}
+
+/// Reads one `Content-Length`-framed JSON-RPC message.
+///
+/// # Returns
+/// * `Ok(Some(message))` - The next message; `Null` if it wasn't valid JSON
+/// * `Ok(None)` - Standard input ended
+fn read_message(input: &mut impl BufRead) -> io::Result<Option<Value>> {
+    let mut length = None;
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some(value) = header.strip_prefix("Content-Length:") {
+            length = value.trim().parse::<usize>().ok();
+        }
+    }
+
+    let Some(length) = length else {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "LSP message without a Content-Length"));
+    };
+    let mut body = vec![0; length];
+    input.read_exact(&mut body)?;
+    Ok(Some(serde_json::from_slice(&body).unwrap_or(Value::Null)))
+}
+
+/// The state of an editor session.
+struct Server<W: Write> {
+    /// Where messages to the editor go
+    out: W,
+    /// Text of the open documents by URI
+    documents: HashMap<String, String>,
+    /// Registry the hover and completion docs come from
+    builtins: BuiltinFunctions,
+}
+
+impl<W: Write> Server<W> {
+    /// Answers a request or acts on a notification.
+    fn handle(&mut self, message: &Value) -> io::Result<()> {
+        let params = &message["params"];
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or_default().to_string();
+
+        let result = match message["method"].as_str().unwrap_or_default() {
+            "initialize" => json!({
+                "capabilities": {
+                    "textDocumentSync": 1,
+                    "hoverProvider": true,
+                    "completionProvider": {},
+                },
+                "serverInfo": { "name": "gizmo" },
+            }),
+            "shutdown" => Value::Null,
+            "textDocument/didOpen" => {
+                let text = params["textDocument"]["text"].as_str().unwrap_or_default();
+                return self.update(uri, text.to_string());
+            }
+            "textDocument/didChange" => {
+                // Full sync: the last change holds the whole text
+                let changes = params["contentChanges"].as_array();
+                let Some(text) = changes.and_then(|changes| changes.last()).and_then(|change| change["text"].as_str()) else {
+                    return Ok(());
+                };
+                return self.update(uri, text.to_string());
+            }
+            "textDocument/didClose" => {
+                self.documents.remove(&uri);
+                return self.notify("textDocument/publishDiagnostics", json!({ "uri": uri, "diagnostics": [] }));
+            }
+            "textDocument/hover" => self.hover(&uri, &params["position"]),
+            "textDocument/completion" => self.completion(&uri),
+            method => {
+                // Notifications we don't act on need no answer
+                if message.get("id").is_some() {
+                    return self.send(json!({
+                        "jsonrpc": "2.0",
+                        "id": message["id"],
+                        "error": { "code": -32601, "message": format!("Unsupported method {}", method) },
+                    }));
+                }
+                return Ok(());
+            }
+        };
+
+        self.send(json!({ "jsonrpc": "2.0", "id": message["id"], "result": result }))
+    }
+
+    /// Stores a document's new text and publishes its problems.
+    fn update(&mut self, uri: String, text: String) -> io::Result<()> {
+        let limits = Limits { max_statements: Some(MAX_STATEMENTS), timeout: Some(TIMEOUT), ..Limits::default() };
+        let diagnostics: Vec<Value> =
+            check_within(&text, &script_dir(&uri), limits).iter().map(|problem| diagnostic(&text, problem)).collect();
+        self.documents.insert(uri.clone(), text);
+        self.notify("textDocument/publishDiagnostics", json!({ "uri": uri, "diagnostics": diagnostics }))
+    }
+
+    /// Describes the built-in function or variable under the cursor.
+    fn hover(&self, uri: &str, position: &Value) -> Value {
+        let Some(text) = self.documents.get(uri) else {
+            return Value::Null;
+        };
+        let line = position["line"].as_u64().unwrap_or_default() as usize;
+        let character = position["character"].as_u64().unwrap_or_default() as usize;
+        let Some(text) = text.lines().nth(line) else {
+            return Value::Null;
+        };
+        let Some((word, start, end)) = word_at(text, char_column(text, character)) else {
+            return Value::Null;
+        };
+
+        let docs = if let Some((signature, description)) = self.builtins.documentation(&word) {
+            format!("```gizmo\n{}\n```\n{}", signature, description)
+        } else if let Some((_, description)) = VARIABLES.iter().find(|(name, _)| *name == word) {
+            format!("```gizmo\n{}\n```\n{}", word, description)
+        } else {
+            return Value::Null;
+        };
+
+        json!({
+            "contents": { "kind": "markdown", "value": docs },
+            "range": range((line, utf16_column(text, start)), (line, utf16_column(text, end))),
+        })
+    }
+
+    /// Lists everything that can be typed, whatever the cursor position.
+    fn completion(&self, uri: &str) -> Value {
+        let item = |label: &str, kind: u8| json!({ "label": label, "kind": kind });
+        let mut items: Vec<Value> = KEYWORDS.iter().map(|keyword| item(keyword, KIND_KEYWORD)).collect();
+        items.extend(EVENTS.iter().map(|event| item(event, KIND_EVENT)));
+        items.extend(self.builtins.names().into_iter().map(|name| {
+            let (signature, description) = self.builtins.documentation(name).unwrap_or((name, ""));
+            json!({ "label": name, "kind": KIND_FUNCTION, "detail": signature, "documentation": description })
+        }));
+        items.extend(VARIABLES.iter().map(|(name, description)| {
+            json!({ "label": name, "kind": KIND_VARIABLE, "documentation": description })
+        }));
+        if let Some(text) = self.documents.get(uri) {
+            items.extend(declared_variables(text).iter().map(|name| item(name, KIND_VARIABLE)));
+        }
+        Value::Array(items)
+    }
+
+    /// Sends a notification to the editor.
+    fn notify(&mut self, method: &str, params: Value) -> io::Result<()> {
+        self.send(json!({ "jsonrpc": "2.0", "method": method, "params": params }))
+    }
+
+    /// Writes one message with its `Content-Length` header.
+    fn send(&mut self, message: Value) -> io::Result<()> {
+        let body = message.to_string();
+        write!(self.out, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+        self.out.flush()
+    }
+}
+
+/// Converts a problem from `check` to an LSP diagnostic.
+///
+/// Problems without a position are put on the first line.
+/// Otherwise the diagnostic covers the word at the position, or one character.
+fn diagnostic(text: &str, problem: &Problem) -> Value {
+    let (line, start, end) = match problem.position {
+        Some((line, column)) => {
+            let (line, start) = (line - 1, column - 1);
+            let text = text.lines().nth(line).unwrap_or_default();
+            let end = word_at(text, start)
+                .filter(|&(_, word_start, _)| word_start == start)
+                .map_or(start + 1, |(_, _, end)| end);
+            (line, utf16_column(text, start), utf16_column(text, end))
+        }
+        None => (0, 0, text.lines().next().map_or(0, |line| line.encode_utf16().count())),
+    };
+
+    json!({
+        "range": range((line, start), (line, end)),
+        "severity": 1,
+        "source": "gizmo",
+        "message": problem.error.to_string(),
+    })
+}
+
+/// Builds an LSP range from zero-based `(line, character)` pairs.
+fn range(start: (usize, usize), end: (usize, usize)) -> Value {
+    json!({
+        "start": { "line": start.0, "character": start.1 },
+        "end": { "line": end.0, "character": end.1 },
+    })
+}
+
+/// Converts a zero-based character column to the UTF-16 code units before it,
+/// as LSP counts columns.
+fn utf16_column(line: &str, column: usize) -> usize {
+    line.chars().take(column).map(char::len_utf16).sum::<usize>() + column.saturating_sub(line.chars().count())
+}
+
+/// Converts a zero-based UTF-16 column from the editor to a character column.
+fn char_column(line: &str, utf16: usize) -> usize {
+    let mut units = 0;
+    for (column, c) in line.chars().enumerate() {
+        if units >= utf16 {
+            return column;
+        }
+        units += c.len_utf16();
+    }
+    line.chars().count() + utf16.saturating_sub(units)
+}
+
+/// Finds the identifier touching a zero-based character column.
+///
+/// # Returns
+/// The identifier and its start and end columns, or `None` if the column
+/// isn't on or right after one
+fn word_at(line: &str, column: usize) -> Option<(String, usize, usize)> {
+    let chars: Vec<char> = line.chars().collect();
+    let is_word = |i: usize| chars.get(i).is_some_and(|&c| c.is_ascii_alphanumeric() || c == '_');
+
+    let mut start = column.min(chars.len());
+    if !is_word(start) && start > 0 && is_word(start - 1) {
+        start -= 1;
+    }
+    if !is_word(start) {
+        return None;
+    }
+    while start > 0 && is_word(start - 1) {
+        start -= 1;
+    }
+    let mut end = start;
+    while is_word(end) {
+        end += 1;
+    }
+    Some((chars[start..end].iter().collect(), start, end))
+}
+
+/// Returns the names a script declares with `frame`, `frames` or `persist`,
+/// even while it doesn't parse.
+fn declared_variables(text: &str) -> Vec<String> {
+    let (tokens, _, _) = Lexer::new(text).tokenize_all();
+    let mut names: Vec<String> = tokens
+        .windows(2)
+        .filter_map(|pair| match pair {
+            [Token::Frame | Token::Frames, Token::Identifier(name)] => Some(name.clone()),
+            [Token::Identifier(keyword), Token::Identifier(name)] if keyword == "persist" => Some(name.clone()),
+            _ => None,
+        })
+        .collect();
+    names.sort_unstable();
+    names.dedup();
+    names
+}
+
+/// Returns the directory of a `file://` document, where the files it loads are.
+fn script_dir(uri: &str) -> PathBuf {
+    let mut path = uri.strip_prefix("file://").map(percent_decode).unwrap_or_default();
+    // `file:///C:/pets/cat.gzmo` on Windows
+    if path.as_bytes().get(2) == Some(&b':') {
+        path.remove(0);
+    }
+    Path::new(&path).parent().map_or_else(|| PathBuf::from("."), Path::to_path_buf)
+}
+
+/// Decodes the `%XX` escapes of a URI path.
+fn percent_decode(path: &str) -> String {
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| path.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
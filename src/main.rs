@@ -11,7 +11,8 @@
 //!
 //! ## Architecture Overview
 //!
-//! The application consists of several key modules:
+//! The language itself is the `gizmo-core` crate, which other programs can
+//! embed without the window:
 //! - **lexer**: Tokenizes .gzmo script files into lexical tokens
 //! - **parser**: Parses tokens into an Abstract Syntax Tree using operator precedence
 //! - **ast**: Defines the data structures for the language's syntax tree
@@ -19,6 +20,8 @@
 //! - **builtin**: Implements built-in mathematical and animation functions
 //! - **frame**: Handles frame rendering utilities
 //! - **error**: Provides comprehensive error handling across all modules
+//!
+//! This crate adds the CLI and the desktop window around it:
 //! - **daemon**: Manages background process lifecycle and state persistence
 //!
 //! ## Process Architecture
@@ -28,37 +31,122 @@
 //! 2. **GUI Process**: Runs the desktop window and animation loop in the background
 //!
 //! This separation allows the desktop buddy to persist even after the terminal is closed.
+//! With `start --respawn N` a supervisor process sits in between and restarts
+//! the GUI process when it crashes.
 
-mod lexer;
-mod parser;
-mod ast;
-mod interpreter;
-mod builtin;
-mod frame;
-mod error;
 mod daemon;
+#[cfg(feature = "gui")]
+mod renderer;
+#[cfg(feature = "gui")]
+mod opacity;
+#[cfg(feature = "gui")]
+mod shape;
+#[cfg(feature = "gui")]
+mod visibility;
+#[cfg(feature = "gui")]
+mod pacing;
+#[cfg(feature = "gui")]
+mod throttle;
+#[cfg(feature = "gui")]
+mod input;
+#[cfg(feature = "gui")]
+mod zoom;
+#[cfg(feature = "gui")]
+mod screensaver;
+mod tty;
+mod sequence;
+#[cfg(feature = "gui")]
+mod idle;
+#[cfg(feature = "gui")]
+mod keyboard;
+mod timer;
+#[cfg(feature = "gui")]
+mod fullscreen;
+#[cfg(feature = "gui")]
+mod workarea;
+#[cfg(feature = "gui")]
+mod notification;
+mod check;
+mod bench;
+mod format;
+mod repl;
+mod lsp;
+mod library;
+mod bundle;
+#[cfg(feature = "gui")]
+mod remote;
+#[cfg(feature = "gui")]
+mod output;
+mod ipc;
+mod export;
+mod autostart;
+#[cfg(feature = "gui")]
+mod supervisor;
+#[cfg(feature = "gui")]
+mod speech;
+#[cfg(feature = "gui")]
+mod fault;
+mod persist;
+mod cache;
+#[cfg(all(feature = "sysinfo", feature = "gui"))]
+mod power;
+
+// The language lives in gizmo-core; importing its modules here keeps them at
+// `crate::` paths for the GUI modules
+use gizmo_core::{ast, builtin, clock, dedup, error, frame, interpreter, lexer, lint, palette, parser, resolve, schedule};
+#[cfg(feature = "gui")]
+use gizmo_core::{motion, placement};
+#[cfg(feature = "sysinfo")]
+use gizmo_core::stats;
+#[cfg(feature = "mic")]
+use gizmo_core::mic;
 
-use std::{env, fs, path::Path, process, time::Duration, thread, rc::Rc};
+use std::{env, fs, io::Write, path::{Path, PathBuf}, process, time::Duration, thread};
+#[cfg(feature = "gui")]
+use std::rc::Rc;
+#[cfg(feature = "gui")]
 use winit::{
     event::{Event, WindowEvent},
-    event_loop::{ControlFlow, EventLoop},
-    window::WindowBuilder,
+    event_loop::{ControlFlow, EventLoopBuilder},
+    window::{WindowBuilder, WindowLevel},
 };
-use softbuffer::{Context, Surface};
 use ast::Frame;
 
 /// Main entry point for the Gizmo application.
 ///
 /// Handles command-line argument parsing and dispatches to appropriate handlers:
-/// - `--gui <file>`: Internal flag to run the desktop window (used by daemon)
-/// - `start <file>`: Start Gizmo with specified .gzmo animation file
-/// - `stop`: Stop the currently running Gizmo instance
+/// - `--gui <file> [--renderer name] [--fg color] [--bg color] [--system-idle] [--hotkey combo=action] [--subscribe url] [--stream target]`: Internal flag to run the desktop window (used by daemon)
+/// - `start [--position x,y] [--respawn N] [--renderer name] [--fg color] [--bg color] [--system-idle] [--hotkey combo=action] [--subscribe url] [--stream target] <file | --from-library name>`: Start Gizmo with specified .gzmo animation file
+/// - `screensaver [--renderer name] [--fg color] [--bg color] <file>`: Play a .gzmo animation full-screen until input
+/// - `tty <file>`: Play a .gzmo animation in the terminal in color
+/// - `stop [--all]`: Stop the currently running Gizmo instance (or all of them)
+/// - `list`: List the running instances
+/// - `install-autostart [file]`, `uninstall-autostart`: Add or remove the login item starting Gizmo
 /// - `restart`: Restart Gizmo with the last used animation file
+/// - `render [--format gif|apng|spritesheet|rust-array|c-array|svg] [-o output] [--scale N] [--frames N | --frame N] <file>`: Export the animation without a window
+/// - `check <file>...`, `check <file> --emit tokens|ast|frames-json`: Report every problem in scripts, or print what one phase made of a script
+/// - `bench [--runs N] <file>`: Time compiling and running a script
+/// - `fmt [--check] <file>...`: Rewrite scripts in the canonical layout (or only report the ones that aren't)
+/// - `repl`: Run statements typed in the terminal one at a time, printing their values
+/// - `pack [-o output] <file> [extra-file]...`: Bundle a script and the files it loads into a `.gzmopack`
+/// - `get [--as name] [--index url] <url-or-name>`, `library list`: Download scripts into the library, and list it
+/// - `lsp`: Serve the Language Server Protocol on stdin/stdout for editors
+/// - `pause`, `resume`, `switch <file>`, `speed [2x|50ms|normal]`, `position [x,y]`: Control the running instance over its control channel
+///
+/// `--name <name>` anywhere on the command line selects the instance a
+/// command is about (the `default` instance without it).
 ///
 /// The main function implements the CLI interface while delegating the actual
 /// GUI functionality to a separate background process for persistence.
 fn main() {
-    let args: Vec<String> = env::args().collect();
+    let mut args: Vec<String> = env::args().collect();
+
+    // `--name` picks which buddy any command is about
+    let instance = take_flag_value(&mut args, "--name").unwrap_or_else(|| daemon::DEFAULT_INSTANCE.to_string());
+    if let Err(e) = daemon::validate_instance_name(&instance) {
+        eprintln!("Error: {}", e);
+        process::exit(1);
+    }
     
     if args.len() < 2 {
         print_usage();
@@ -66,43 +154,444 @@ fn main() {
     }
 
     match args[1].as_str() {
+        #[cfg(feature = "gui")]
         "--gui" => {
             // This is the GUI process - run the desktop window directly
-            if args.len() < 3 {
+            let mut gui_args = args[2..].to_vec();
+            let mut options = GuiOptions::take_from(&mut gui_args).unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                process::exit(1);
+            });
+            options.instance = instance;
+            
+            if gui_args.is_empty() {
                 eprintln!("Internal error: gui missing gzmo file argument");
                 process::exit(1);
             }
-            let gzmo_file = &args[2];
-            if let Err(e) = run_desktop_window(gzmo_file) {
+            let gzmo_file = &gui_args[0];
+            daemon::report_panics(&options.instance, gzmo_file);
+            if let Err(e) = run_desktop_window(gzmo_file, &options) {
                 eprintln!("Error running gizmo window: {}", e);
-                // Clean up daemon state on exit
-                let _ = daemon::cleanup_daemon_state();
+                // Clean up daemon state on exit, leaving `start` the reason
+                let _ = daemon::cleanup_daemon_state(&options.instance);
+                let report = daemon::CrashReport::new(process::id(), gzmo_file, e.to_string());
+                let _ = daemon::save_crash_report(&options.instance, &report);
                 process::exit(1);
             }
         }
+        #[cfg(feature = "gui")]
+        "--supervise" => {
+            // The supervisor of a `start --respawn` GUI process
+            let mut supervise_args = args[2..].to_vec();
+            let mut options = GuiOptions::take_from(&mut supervise_args).unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                process::exit(1);
+            });
+            options.instance = instance;
+
+            let respawns = supervise_args.first().and_then(|text| text.parse::<u32>().ok());
+            let (Some(respawns), Some(gzmo_file)) = (respawns, supervise_args.get(1)) else {
+                eprintln!("Internal error: supervisor missing respawn count or gzmo file argument");
+                process::exit(1);
+            };
+            if let Err(e) = supervisor::supervise(&options.instance, Path::new(gzmo_file), &options.to_args(), respawns) {
+                eprintln!("Error supervising gizmo: {}", e);
+                let _ = daemon::cleanup_daemon_state(&options.instance);
+                process::exit(1);
+            }
+        }
+        #[cfg(feature = "gui")]
         "start" => {
-            if args.len() < 3 {
-                eprintln!("Usage: gizmo start <path-to-gzmo-file>");
+            let mut start_args = args[2..].to_vec();
+            let position = match take_flag_value(&mut start_args, "--position") {
+                Some(text) => match daemon::parse_position(&text) {
+                    Ok(position) => Some(position),
+                    Err(e) => {
+                        eprintln!("Error: {}", e);
+                        process::exit(1);
+                    }
+                },
+                None => None,
+            };
+            let respawns = match take_flag_value(&mut start_args, "--respawn").map(|text| text.parse::<u32>()) {
+                Some(Ok(respawns)) if respawns <= supervisor::MAX_RESPAWNS => respawns,
+                Some(_) => {
+                    eprintln!("Error: --respawn expects a number of restarts up to {}", supervisor::MAX_RESPAWNS);
+                    process::exit(1);
+                }
+                None => 0,
+            };
+            let mut options = GuiOptions::take_from(&mut start_args).unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
                 process::exit(1);
+            });
+            options.instance = instance;
+            if let Some(name) = take_flag_value(&mut start_args, "--from-library") {
+                match library::path(&name) {
+                    Ok(path) => start_args.insert(0, path.display().to_string()),
+                    Err(e) => {
+                        eprintln!("Error: {}", e);
+                        process::exit(1);
+                    }
+                }
             }
-            let gzmo_file = &args[2];
-            if let Err(e) = start_gizmo(gzmo_file) {
+            
+            if start_args.is_empty() {
+                eprintln!("Usage: gizmo start [--name name] [--position x,y] [--respawn N] [--renderer software|gpu] [--fg color] [--bg color] [--system-idle] [--hotkey combo=action] [--subscribe url] [--stream target] [--throttle policy] <path-to-gzmo-file | --from-library name>");
+                process::exit(1);
+            }
+            let gzmo_file = &start_args[0];
+            if let Err(e) = start_gizmo(gzmo_file, position, respawns, &options) {
                 eprintln!("Error starting gizmo: {}", e);
                 process::exit(1);
             }
         }
+        #[cfg(feature = "gui")]
+        "run" => {
+            let mut run_args = args[2..].to_vec();
+            let mut options = GuiOptions::take_from(&mut run_args).unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                process::exit(1);
+            });
+            options.attached = true;
+            
+            if run_args.is_empty() {
+                eprintln!("Usage: gizmo run [--renderer software|gpu] [--fg color] [--bg color] [--system-idle] [--hotkey combo=action] [--subscribe url] [--stream target] [--throttle policy] <path-to-gzmo-file>");
+                process::exit(1);
+            }
+            if let Err(e) = run_gizmo(&run_args[0], &options) {
+                eprintln!("Error running gizmo: {}", e);
+                process::exit(1);
+            }
+        }
+        #[cfg(feature = "gui")]
+        "screensaver" => {
+            let mut screensaver_args = args[2..].to_vec();
+            let options = GuiOptions::take_from(&mut screensaver_args).unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                process::exit(1);
+            });
+            
+            if screensaver_args.is_empty() {
+                eprintln!("Usage: gizmo screensaver [--renderer software|gpu] [--fg color] [--bg color] <path-to-gzmo-file>");
+                process::exit(1);
+            }
+            if let Err(e) = run_screensaver(&screensaver_args[0], &options) {
+                eprintln!("Error running screensaver: {}", e);
+                process::exit(1);
+            }
+        }
+        #[cfg(not(feature = "gui"))]
+        "--gui" | "--supervise" | "start" | "run" | "screensaver" | "restart" => {
+            eprintln!("Error: this build has no desktop window; rebuild with the `gui` feature (on by default)");
+            process::exit(1);
+        }
+        "tty" => {
+            if args.len() < 3 {
+                eprintln!("Usage: gizmo tty <path-to-gzmo-file>");
+                process::exit(1);
+            }
+            if let Err(e) = tty_gizmo(&args[2]) {
+                eprintln!("Error playing gizmo: {}", e);
+                process::exit(1);
+            }
+        }
+        "preview" => {
+            let mut preview_args = args[2..].to_vec();
+            let looping = preview_args.iter().any(|arg| arg == "--loop");
+            preview_args.retain(|arg| arg != "--loop");
+            let deterministic = take_deterministic(&mut preview_args).unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                process::exit(1);
+            });
+            let frame = match take_flag_value(&mut preview_args, "--frame").map(|text| text.parse::<usize>()) {
+                Some(Ok(frame)) if frame > 0 => Some(frame),
+                Some(_) => {
+                    eprintln!("Error: --frame expects a frame number starting at 1");
+                    process::exit(1);
+                }
+                None => None,
+            };
+
+            if preview_args.is_empty() {
+                eprintln!("Usage: gizmo preview [--frame N] [--loop] [--deterministic] [--seed N] <path-to-gzmo-file>");
+                process::exit(1);
+            }
+            if let Err(e) = preview_gizmo(&preview_args[0], frame, looping, deterministic) {
+                eprintln!("Error previewing gizmo: {}", e);
+                process::exit(1);
+            }
+        }
+        "render" => {
+            let mut render_args = args[2..].to_vec();
+            let output = take_flag_value(&mut render_args, "-o").or_else(|| take_flag_value(&mut render_args, "--output"));
+            let deterministic = take_deterministic(&mut render_args).unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                process::exit(1);
+            });
+            let format = match take_flag_value(&mut render_args, "--format").map(|name| export::Format::parse(&name)) {
+                Some(Ok(format)) => format,
+                Some(Err(e)) => {
+                    eprintln!("Error: {}", e);
+                    process::exit(1);
+                }
+                None => export::Format::Gif,
+            };
+            let scale = match take_flag_value(&mut render_args, "--scale").map(|text| text.parse::<usize>()) {
+                Some(Ok(scale)) if (1..=export::MAX_SCALE).contains(&scale) => scale,
+                Some(_) => {
+                    eprintln!("Error: --scale expects a whole number from 1 to {}", export::MAX_SCALE);
+                    process::exit(1);
+                }
+                None => 1,
+            };
+            let frames = match take_flag_value(&mut render_args, "--frames").map(|text| text.parse::<usize>()) {
+                Some(Ok(frames)) if frames > 0 => Some(frames),
+                Some(_) => {
+                    eprintln!("Error: --frames expects a number of frames of at least 1");
+                    process::exit(1);
+                }
+                None => None,
+            };
+            let frame = match take_flag_value(&mut render_args, "--frame").map(|text| text.parse::<usize>()) {
+                Some(Ok(frame)) if frame > 0 && frames.is_none() => Some(frame),
+                Some(Ok(_)) if frames.is_some() => {
+                    eprintln!("Error: --frame and --frames can't be used together");
+                    process::exit(1);
+                }
+                Some(_) => {
+                    eprintln!("Error: --frame expects a frame number starting at 1");
+                    process::exit(1);
+                }
+                None => None,
+            };
+
+            if render_args.is_empty() {
+                eprintln!("Usage: gizmo render [--format gif|apng|spritesheet|rust-array|c-array|svg] [-o output] [--scale N] [--frames N | --frame N] [--deterministic] [--seed N] <path-to-gzmo-file>");
+                process::exit(1);
+            }
+            if let Err(e) = render_gizmo(&render_args[0], format, output.as_deref(), scale, frames, frame, deterministic) {
+                eprintln!("Error rendering gizmo: {}", e);
+                process::exit(1);
+            }
+        }
+        "check" => {
+            let mut files = args[2..].to_vec();
+            let emit = match take_flag_value(&mut files, "--emit").map(|name| check::Emit::parse(&name)) {
+                Some(Ok(emit)) => Some(emit),
+                Some(Err(e)) => {
+                    eprintln!("Error: {}", e);
+                    process::exit(1);
+                }
+                None => None,
+            };
+            if files.is_empty() || (emit.is_some() && files.len() > 1) {
+                eprintln!("Usage: gizmo check <path-to-gzmo-file>...");
+                eprintln!("       gizmo check <path-to-gzmo-file> --emit tokens|ast|frames-json");
+                process::exit(1);
+            }
+            match check_gizmo(&files, emit) {
+                Ok(true) => {}
+                Ok(false) => process::exit(1),
+                Err(e) => {
+                    eprintln!("Error checking gizmo: {}", e);
+                    process::exit(1);
+                }
+            }
+        }
+        "bench" => {
+            let mut bench_args = args[2..].to_vec();
+            let runs = match take_flag_value(&mut bench_args, "--runs").map(|text| text.parse::<usize>()) {
+                Some(Ok(runs)) if runs > 0 => runs,
+                Some(_) => {
+                    eprintln!("Error: --runs expects a number of runs of at least 1");
+                    process::exit(1);
+                }
+                None => bench::DEFAULT_RUNS,
+            };
+            if bench_args.len() != 1 {
+                eprintln!("Usage: gizmo bench [--runs N] <path-to-gzmo-file>");
+                process::exit(1);
+            }
+            if let Err(e) = bench_gizmo(&bench_args[0], runs) {
+                eprintln!("Error benchmarking gizmo: {}", e);
+                process::exit(1);
+            }
+        }
+        "fmt" => {
+            let mut files = args[2..].to_vec();
+            let check_only = files.iter().any(|arg| arg == "--check");
+            files.retain(|arg| arg != "--check");
+            if files.is_empty() {
+                eprintln!("Usage: gizmo fmt [--check] <path-to-gzmo-file>...");
+                process::exit(1);
+            }
+            match format_gizmo(&files, check_only) {
+                Ok(true) => {}
+                Ok(false) => process::exit(1),
+                Err(e) => {
+                    eprintln!("Error formatting gizmo: {}", e);
+                    process::exit(1);
+                }
+            }
+        }
+        "repl" => {
+            if let Err(e) = repl::run() {
+                eprintln!("Error: {}", e);
+                process::exit(1);
+            }
+        }
+        "pack" => {
+            let mut pack_args = args[2..].to_vec();
+            let output = take_flag_value(&mut pack_args, "-o").or_else(|| take_flag_value(&mut pack_args, "--output"));
+            if pack_args.is_empty() {
+                eprintln!("Usage: gizmo pack [-o output.gzmopack] <path-to-gzmo-file> [extra-file]...");
+                process::exit(1);
+            }
+            if let Err(e) = pack_gizmo(&pack_args[0], &pack_args[1..], output.as_deref()) {
+                eprintln!("Error packing gizmo: {}", e);
+                process::exit(1);
+            }
+        }
+        "get" => {
+            let mut get_args = args[2..].to_vec();
+            let name = take_flag_value(&mut get_args, "--as");
+            let index = take_flag_value(&mut get_args, "--index").unwrap_or_else(|| library::DEFAULT_INDEX.to_string());
+            if get_args.len() != 1 {
+                eprintln!("Usage: gizmo get [--as name] [--index url] <url-or-gallery-name>");
+                process::exit(1);
+            }
+            match library::get(&get_args[0], &index, name.as_deref()) {
+                Ok((name, replaced)) => {
+                    println!("{} {} in the library; start it with 'gizmo start --from-library {}'", if replaced { "Updated" } else { "Saved" }, name, name);
+                }
+                Err(e) => {
+                    eprintln!("Error getting script: {}", e);
+                    process::exit(1);
+                }
+            }
+        }
+        "library" => {
+            if args.get(2).map(String::as_str) != Some("list") {
+                eprintln!("Usage: gizmo library list");
+                process::exit(1);
+            }
+            match library::list() {
+                Ok(names) if names.is_empty() => println!("The library is empty; add scripts with 'gizmo get'"),
+                Ok(names) => {
+                    for name in names {
+                        println!("{}", name);
+                    }
+                }
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    process::exit(1);
+                }
+            }
+        }
+        "lsp" => {
+            if let Err(e) = lsp::run() {
+                eprintln!("Error: {}", e);
+                process::exit(1);
+            }
+        }
         "stop" => {
-            if let Err(e) = stop_gizmo() {
+            let stopped = if args[2..].iter().any(|arg| arg == "--all") {
+                stop_all_gizmos()
+            } else {
+                stop_gizmo(&instance)
+            };
+            if let Err(e) = stopped {
                 eprintln!("Error stopping gizmo: {}", e);
                 process::exit(1);
             }
         }
+        #[cfg(feature = "gui")]
         "restart" => {
-            if let Err(e) = restart_gizmo() {
+            if let Err(e) = restart_gizmo(&instance) {
                 eprintln!("Error restarting gizmo: {}", e);
                 process::exit(1);
             }
         }
+        "logs" => {
+            let follow = args[2..].iter().any(|arg| arg == "--follow" || arg == "-f");
+            if let Err(e) = show_logs(&instance, follow) {
+                eprintln!("Error reading gizmo logs: {}", e);
+                process::exit(1);
+            }
+        }
+        "pause" | "resume" => {
+            if let Err(e) = pause_gizmo(&instance, args[1] == "pause") {
+                eprintln!("Error: {}", e);
+                process::exit(1);
+            }
+        }
+        "switch" => {
+            if args.len() < 3 {
+                eprintln!("Usage: gizmo switch <path-to-gzmo-file>");
+                process::exit(1);
+            }
+            if let Err(e) = switch_gizmo(&instance, &args[2]) {
+                eprintln!("Error: {}", e);
+                process::exit(1);
+            }
+        }
+        "speed" => {
+            let to = match args.get(2).map(|text| text.parse::<clock::Speed>()) {
+                Some(Ok(speed)) => Some(speed),
+                Some(Err(e)) => {
+                    eprintln!("{}", e);
+                    eprintln!("Usage: gizmo speed [2x|50ms|normal]");
+                    process::exit(1);
+                }
+                None => None,
+            };
+            if let Err(e) = speed_gizmo(&instance, to) {
+                eprintln!("Error: {}", e);
+                process::exit(1);
+            }
+        }
+        "position" => {
+            let to = match args.get(2).map(|text| daemon::parse_position(text)) {
+                Some(Ok(position)) => Some(position),
+                Some(Err(e)) => {
+                    eprintln!("Invalid position: {}", e);
+                    eprintln!("Usage: gizmo position [x,y]");
+                    process::exit(1);
+                }
+                None => None,
+            };
+            if let Err(e) = position_gizmo(&instance, to) {
+                eprintln!("Error: {}", e);
+                process::exit(1);
+            }
+        }
+        "status" => {
+            if let Err(e) = show_status(&instance) {
+                eprintln!("Error reading gizmo status: {}", e);
+                process::exit(1);
+            }
+        }
+        "install-autostart" => {
+            if let Err(e) = install_autostart(&instance, args.get(2).map(String::as_str)) {
+                eprintln!("Error installing autostart: {}", e);
+                process::exit(1);
+            }
+        }
+        "uninstall-autostart" => match autostart::uninstall(&instance) {
+            Ok(true) => println!("Gizmo will no longer start at login"),
+            Ok(false) => println!("Gizmo wasn't set to start at login"),
+            Err(e) => {
+                eprintln!("Error uninstalling autostart: {}", e);
+                process::exit(1);
+            }
+        },
+        "list" => {
+            if let Err(e) = list_gizmos() {
+                eprintln!("Error listing gizmos: {}", e);
+                process::exit(1);
+            }
+        }
         _ => {
             print_usage();
             process::exit(1);
@@ -120,7 +609,233 @@ fn print_usage() {
     println!("Usage:");
     println!("  gizmo start <path-to-gzmo-file>  Start gizmo with specified animation file");
     println!("  gizmo restart                    Restart current gizmo animation");
-    println!("  gizmo stop                       Stop gizmo");
+    println!("  gizmo run <path-to-gzmo-file>    Run gizmo in this terminal, showing its errors");
+    println!("  gizmo screensaver <file>         Play an animation full-screen until input");
+    println!("  gizmo tty <file>                 Play an animation in the terminal (over SSH, without a compositor)");
+    println!("  gizmo preview <file>             Print an animation's frames as text");
+    println!("  gizmo render <file> [-o out.gif] Export an animation to a GIF (--format apng|spritesheet|rust-array|c-array|svg, --scale N, --frames N, --frame N)");
+    println!("  gizmo check <file>...            Report every error in scripts without running them");
+    println!("  gizmo check <file> --emit tokens|ast|frames-json  Print the tokens, syntax tree or frames (JSON) instead");
+    println!("  gizmo bench [--runs N] <file>    Time compiling and running a script (frames/s, cost per frame)");
+    println!("  gizmo fmt [--check] <file>...    Rewrite scripts in the canonical layout (--check: only list the ones that aren't)");
+    println!("  gizmo repl                       Try statements one at a time, printing values and frames");
+    println!("  gizmo lsp                        Language server for editors (diagnostics, hover, completion)");
+    println!("  gizmo pack <file> [extra-file]...  Bundle a script and the files it loads into a .gzmopack");
+    println!("  gizmo get [--as name] <url|name> Download a script into the library (a URL or a gallery name)");
+    println!("  gizmo library list               List the library's scripts (start one with --from-library name)");
+    println!("  gizmo stop [--all]               Stop gizmo (or every running buddy)");
+    println!("  gizmo pause                      Freeze the animation on its current frame");
+    println!("  gizmo resume                     Continue a paused animation");
+    println!("  gizmo switch <path-to-gzmo-file> Switch the running gizmo to another animation");
+    println!("  gizmo speed [2x|50ms|normal]     Show or override the animation's speed");
+    println!("  gizmo position [x,y]             Show or move the buddy's window position");
+    println!("  gizmo status                     Show whether gizmo is running and what it plays");
+    println!("  gizmo logs [--follow]            Show the background process's output");
+    println!("  gizmo list                       List the running buddies");
+    println!("  gizmo install-autostart [file]   Start gizmo at login with the file (default: the last one)");
+    println!("  gizmo uninstall-autostart        Stop starting gizmo at login");
+    println!();
+    println!("Add --respawn N to start to restart gizmo after up to N crashes.");
+    println!("Add --throttle battery|lag|auto to start or run to show every other frame on battery or when frames fall behind.");
+    println!("Add --deterministic (or --seed N) to render or preview for the same frames on every run.");
+    println!("Add --name <name> to any command to run or control another buddy next to the default one.");
+}
+
+/// Removes `--deterministic` and `--seed N` from `args`.
+///
+/// # Returns
+/// * `Ok(Some(seed))` - Either was given; `--deterministic` alone seeds with 0
+/// * `Ok(None)` - Neither was given
+/// * `Err` - The seed isn't a whole number
+fn take_deterministic(args: &mut Vec<String>) -> Result<Option<u64>, String> {
+    let deterministic = args.iter().any(|arg| arg == "--deterministic");
+    args.retain(|arg| arg != "--deterministic");
+    match take_flag_value(args, "--seed").map(|text| text.parse::<u64>()) {
+        Some(Ok(seed)) => Ok(Some(seed)),
+        Some(Err(_)) => Err("--seed expects a whole number".to_string()),
+        None => Ok(deterministic.then_some(0)),
+    }
+}
+
+/// Removes `flag` and the value after it from `args`.
+///
+/// # Returns
+/// * `Some(value)` - The flag was present with a value
+/// * `None` - The flag was absent (or had no value)
+fn take_flag_value(args: &mut Vec<String>, flag: &str) -> Option<String> {
+    let index = args.iter().position(|arg| arg == flag)?;
+    if index + 1 >= args.len() {
+        args.remove(index);
+        return None;
+    }
+    
+    let value = args.remove(index + 1);
+    args.remove(index);
+    Some(value)
+}
+
+/// Window options shared by `start` and the internal `--gui` process.
+#[cfg(feature = "gui")]
+#[derive(Debug, Clone, PartialEq)]
+struct GuiOptions {
+    /// Presentation backend (`--renderer`)
+    renderer_kind: renderer::RendererKind,
+    /// Foreground color overriding the script's palette index 1 (`--fg`)
+    foreground: Option<String>,
+    /// Background color overriding the script's palette index 0 (`--bg`)
+    background: Option<String>,
+    /// Count idle time from system-wide input rather than the window's (`--system-idle`)
+    system_idle: bool,
+    /// System-wide shortcuts (`--hotkey <combo>=<action>`, repeatable)
+    hotkeys: Vec<keyboard::HotkeyBinding>,
+    /// Listen to the microphone for `mic_level` (`--mic`)
+    mic: bool,
+    /// MQTT topics and WebSockets feeding `when message(...)` handlers
+    /// (`--subscribe <url>`, repeatable)
+    subscriptions: Vec<remote::Subscription>,
+    /// External displays every drawn frame is sent to (`--stream <target>`, repeatable)
+    streams: Vec<output::Target>,
+    /// Running attached to the terminal (`gizmo run`) rather than as the
+    /// daemon, so the daemon's state files are left alone
+    attached: bool,
+    /// Which buddy this is (`--name`); each has its own daemon state
+    instance: String,
+    /// Slow the animation down when frames can't keep up (`--adapt-speed`)
+    adapt_speed: bool,
+    /// When to show every other frame (`--throttle`)
+    throttle: throttle::Policy,
+}
+
+#[cfg(feature = "gui")]
+impl Default for GuiOptions {
+    fn default() -> Self {
+        Self {
+            renderer_kind: renderer::RendererKind::Software,
+            foreground: None,
+            background: None,
+            system_idle: false,
+            hotkeys: Vec::new(),
+            mic: false,
+            subscriptions: Vec::new(),
+            streams: Vec::new(),
+            attached: false,
+            instance: daemon::DEFAULT_INSTANCE.to_string(),
+            adapt_speed: false,
+            throttle: throttle::Policy::Off,
+        }
+    }
+}
+
+#[cfg(feature = "gui")]
+impl GuiOptions {
+    /// Removes `--renderer`, `--fg`, `--bg`, `--hotkey`, `--subscribe`,
+    /// `--stream`, `--throttle` (with their values), `--system-idle`, `--mic`
+    /// and `--adapt-speed` from `args`.
+    ///
+    /// # Returns
+    /// * `Ok(GuiOptions)` - Parsed options; absent flags keep their defaults
+    /// * `Err` - Unknown renderer or throttle policy, malformed color, invalid
+    ///   hotkey, URL or target, or `--mic` / `--subscribe` / `--throttle battery`
+    ///   in a build without the `mic` / `remote` / `sysinfo` feature
+    fn take_from(args: &mut Vec<String>) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut options = Self::default();
+        if let Some(name) = take_flag_value(args, "--renderer") {
+            options.renderer_kind = renderer::RendererKind::parse(&name)?;
+        }
+        
+        for (flag, color) in [("--fg", &mut options.foreground), ("--bg", &mut options.background)] {
+            if let Some(text) = take_flag_value(args, flag) {
+                palette::parse_hex_color(&text)?;
+                *color = Some(text);
+            }
+        }
+        
+        if let Some(index) = args.iter().position(|arg| arg == "--system-idle") {
+            args.remove(index);
+            options.system_idle = true;
+        }
+        
+        while let Some(text) = take_flag_value(args, "--hotkey") {
+            options.hotkeys.push(text.parse::<keyboard::HotkeyBinding>()?);
+        }
+        
+        if let Some(index) = args.iter().position(|arg| arg == "--mic") {
+            if !cfg!(feature = "mic") {
+                return Err("This build can't use the microphone; rebuild with `cargo build --release --features mic`".into());
+            }
+            args.remove(index);
+            options.mic = true;
+        }
+        
+        while let Some(url) = take_flag_value(args, "--subscribe") {
+            if !cfg!(feature = "remote") {
+                return Err("This build can't subscribe to messages; rebuild with `cargo build --release --features remote`".into());
+            }
+            options.subscriptions.push(url.parse::<remote::Subscription>()?);
+        }
+        
+        while let Some(target) = take_flag_value(args, "--stream") {
+            options.streams.push(target.parse::<output::Target>()?);
+        }
+        
+        if let Some(index) = args.iter().position(|arg| arg == "--adapt-speed") {
+            args.remove(index);
+            options.adapt_speed = true;
+        }
+        
+        if let Some(name) = take_flag_value(args, "--throttle") {
+            options.throttle = throttle::Policy::parse(&name)?;
+        }
+        
+        Ok(options)
+    }
+    
+    /// Applies `--fg` / `--bg` on top of the colors the script chose.
+    fn apply_colors(&self, interpreter: &mut interpreter::Interpreter) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(color) = &self.foreground {
+            interpreter.palette_mut().set_color(1, color)?;
+        }
+        if let Some(color) = &self.background {
+            interpreter.palette_mut().set_color(0, color)?;
+        }
+        Ok(())
+    }
+    
+    /// Turns the options back into flags for the spawned GUI process.
+    fn to_args(&self) -> Vec<String> {
+        let mut args = vec!["--renderer".to_string(), self.renderer_kind.name().to_string()];
+        if let Some(color) = &self.foreground {
+            args.extend(["--fg".to_string(), color.clone()]);
+        }
+        if let Some(color) = &self.background {
+            args.extend(["--bg".to_string(), color.clone()]);
+        }
+        if self.system_idle {
+            args.push("--system-idle".to_string());
+        }
+        for binding in &self.hotkeys {
+            args.extend(["--hotkey".to_string(), binding.to_string()]);
+        }
+        if self.mic {
+            args.push("--mic".to_string());
+        }
+        for subscription in &self.subscriptions {
+            args.extend(["--subscribe".to_string(), subscription.to_string()]);
+        }
+        for target in &self.streams {
+            args.extend(["--stream".to_string(), target.to_string()]);
+        }
+        if self.instance != daemon::DEFAULT_INSTANCE {
+            args.extend(["--name".to_string(), self.instance.clone()]);
+        }
+        if self.adapt_speed {
+            args.push("--adapt-speed".to_string());
+        }
+        if self.throttle != throttle::Policy::Off {
+            args.extend(["--throttle".to_string(), self.throttle.name().to_string()]);
+        }
+        args
+    }
 }
 
 /// Starts a new Gizmo instance with the specified .gzmo animation file.
@@ -128,76 +843,320 @@ fn print_usage() {
 /// This function:
 /// 1. Validates the input file exists and has the correct extension
 /// 2. Saves the file path for future restart operations
-/// 3. Checks that no Gizmo instance is already running
-/// 4. Spawns a detached GUI process using nohup for background execution
-/// 5. Saves the process ID for future stop/restart operations
+/// 3. Reports a crash of the previous GUI process, if any
+/// 4. Checks that no Gizmo instance is already running
+/// 5. Spawns a detached GUI process (or its supervisor) for background execution
+/// 6. Saves the process ID for future stop/restart operations
+/// 7. Waits until the window is up, reporting why if it fails to come up
 ///
 /// # Arguments
 /// * `gzmo_file` - Path to the .gzmo script file to execute
+/// * `position` - Optional window position overriding the remembered one
+/// * `respawns` - How many crashes to restart the GUI process after (0 runs it unsupervised)
+/// * `options` - Renderer and color overrides forwarded to the GUI process
 ///
 /// # Returns
 /// * `Ok(())` if the Gizmo instance started successfully
-/// * `Err` if file validation fails, daemon is already running, or process spawn fails
+/// * `Err` if file validation fails, daemon is already running, process spawn
+///   fails, or the GUI process fails to start (a script error, say)
 ///
 /// # Process Management
-/// Uses nohup to detach the GUI process from the terminal, allowing it to persist
-/// even after the terminal is closed. The process ID is saved for later management.
-fn start_gizmo(gzmo_file: &str) -> Result<(), Box<dyn std::error::Error>> {
+/// Detaches the GUI process from the terminal (a new session on Unix, detached
+/// process flags on Windows), allowing it to persist even after the terminal is closed.
+/// The process ID is saved for later management.
+#[cfg(feature = "gui")]
+fn start_gizmo(
+    gzmo_file: &str,
+    position: Option<(i32, i32)>,
+    respawns: u32,
+    options: &GuiOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
     // Validate file exists and has .gzmo extension
     let path = Path::new(gzmo_file);
     if !path.exists() {
         return Err(format!("File not found: {}", gzmo_file).into());
     }
     
-    if !gzmo_file.ends_with(".gzmo") {
-        return Err("File must have .gzmo extension".into());
+    if !bundle::is_playable(gzmo_file) {
+        return Err("File must have .gzmo or .gzmopack extension".into());
     }
 
-    // Save current gzmo file for restart command
-    daemon::save_current_file(gzmo_file)?;
+    let instance = &options.instance;
+
+    // A crashed GUI process leaves its PID file behind; clear it up and say so
+    if let Some(crash) = daemon::take_crash(instance)? {
+        eprintln!(
+            "Note: Gizmo didn't exit cleanly last time (PID {}, {}): {}. See 'gizmo logs'.",
+            crash.pid,
+            crash.local_time(),
+            crash.reason
+        );
+    }
 
     // Check if daemon is already running
-    if daemon::is_daemon_running()? {
-        return Err("Gizmo is already running. Use 'gizmo stop' first.".into());
+    if daemon::is_daemon_running(instance)? {
+        if instance == daemon::DEFAULT_INSTANCE {
+            return Err("Gizmo is already running. Use 'gizmo stop' first, or --name to start another buddy.".into());
+        }
+        return Err(format!("Gizmo '{}' is already running. Use 'gizmo stop --name {}' first.", instance, instance).into());
+    }
+
+    // Save current gzmo file and options for restart command
+    daemon::save_current_file(instance, gzmo_file)?;
+    let mut flags = options.to_args();
+    if respawns > 0 {
+        flags.extend(["--respawn".to_string(), respawns.to_string()]);
+    }
+    daemon::save_start_options(instance, &flags)?;
+
+    // An explicit position replaces the remembered one before the window opens
+    if let Some((x, y)) = position {
+        daemon::save_window_position(instance, x, y)?;
     }
 
     println!("Starting Gizmo with: {}", gzmo_file);
     
-    // Use nohup to detach the GUI process from the terminal
+    // The GUI process only logs problems; warnings are worth seeing up front
+    if let Ok(source) = bundle::resolve(path).and_then(|script| Ok(fs::read_to_string(script)?)) {
+        for warning in check::warnings(&source) {
+            eprintln!("{}", warning_line(gzmo_file, &warning));
+        }
+    }
+    
+    // Detach the GUI process from the terminal
     let current_exe = std::env::current_exe()?;
     let absolute_gzmo_path = std::fs::canonicalize(gzmo_file)?;
     
-    let child = process::Command::new("nohup")
-        .arg(&current_exe)
-        .arg("--gui")
-        .arg(&absolute_gzmo_path)
-        .stdout(process::Stdio::null())
-        .stderr(process::Stdio::null())
-        .stdin(process::Stdio::null())
-        .spawn()?;
+    let mut args = if respawns > 0 {
+        vec!["--supervise".to_string(), respawns.to_string()]
+    } else {
+        vec!["--gui".to_string()]
+    };
+    args.push(absolute_gzmo_path.to_string_lossy().into_owned());
+    args.extend(options.to_args());
+
+    let log = daemon::open_log(instance, &absolute_gzmo_path)?;
+    let mut child = daemon::spawn_detached(&current_exe, &args, log)?;
     
-    // Save the child PID directly
+    // Save the child PID directly; a supervisor saves its GUI process's instead
     let pid = child.id();
-    daemon::save_daemon_pid(pid)?;
+    if respawns == 0 {
+        daemon::save_daemon_pid(instance, pid)?;
+    }
     
-    // Give it a moment to start
-    thread::sleep(Duration::from_millis(500));
+    // Wait until the window is up, or report why it didn't come up
+    let ready = match wait_until_ready(instance, &mut child) {
+        Ok(ready) => ready,
+        Err(reason) if respawns > 0 => {
+            return Err(format!("Gizmo failed to start: {} (retrying up to {} times; see 'gizmo logs')", reason, respawns).into());
+        }
+        Err(reason) => {
+            daemon::clear_crash_report(instance)?;
+            return Err(format!("Gizmo failed to start: {} (see 'gizmo logs')", reason).into());
+        }
+    };
     
-    println!("Gizmo started in background (PID: {})", pid);
+    let pid = daemon::get_daemon_pid(instance).unwrap_or(pid);
+    if !ready {
+        println!("Gizmo is still starting in background (PID: {}); see 'gizmo status'", pid);
+    } else if respawns > 0 {
+        println!("Gizmo started in background (PID: {}, restarted up to {} times if it crashes)", pid, respawns);
+    } else {
+        println!("Gizmo started in background (PID: {})", pid);
+    }
     
     Ok(())
 }
 
+/// Longest `gizmo start` waits for the window to come up; a script that
+/// renders many frames can take a while.
+#[cfg(feature = "gui")]
+const START_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Waits until a just-started GUI process answers on its control channel,
+/// which it opens once the script has run and the window is up.
+///
+/// # Arguments
+/// * `instance` - Instance being started
+/// * `child` - The GUI process, or its supervisor
+///
+/// # Returns
+/// * `Ok(true)` - The window is up
+/// * `Ok(false)` - Still starting after [`START_TIMEOUT`]
+/// * `Err` - It failed to start; the error says why
+#[cfg(feature = "gui")]
+fn wait_until_ready(instance: &str, child: &mut process::Child) -> Result<bool, Box<dyn std::error::Error>> {
+    let deadline = std::time::Instant::now() + START_TIMEOUT;
+    while std::time::Instant::now() < deadline {
+        if ipc::send(instance, &ipc::Command::Status).is_ok() {
+            return Ok(true);
+        }
+        // A GUI process that fails to start leaves a crash report saying why
+        if let Some(crash) = daemon::get_crash_report(instance) {
+            return Err(crash.reason.into());
+        }
+        if let Some(status) = child.try_wait()? {
+            return Err(format!("the window process exited ({})", status).into());
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+    Ok(false)
+}
+
+/// Runs Gizmo in the foreground, attached to the terminal.
+///
+/// Unlike `start`, nothing is detached and output isn't discarded: script
+/// errors, handler errors and pacing statistics all appear in the terminal.
+/// Ctrl+C plays the farewell and exits like `gizmo stop` (press it again to
+/// exit at once). The daemon's state is left alone, so a buddy started with
+/// `start` keeps running next to it.
+///
+/// # Arguments
+/// * `gzmo_file` - Path to the .gzmo script file to run
+/// * `options` - Window options, with `attached` set
+///
+/// # Returns
+/// * `Ok(())` once the window closes
+/// * `Err` if the file is missing or the script fails to load
+#[cfg(feature = "gui")]
+fn run_gizmo(gzmo_file: &str, options: &GuiOptions) -> Result<(), Box<dyn std::error::Error>> {
+    if !Path::new(gzmo_file).exists() {
+        return Err(format!("File not found: {}", gzmo_file).into());
+    }
+    if !bundle::is_playable(gzmo_file) {
+        return Err("File must have .gzmo or .gzmopack extension".into());
+    }
+
+    println!("Running {} (Ctrl+C to stop)", gzmo_file);
+    run_desktop_window(gzmo_file, options)
+}
+
 /// Stops the currently running Gizmo instance.
 ///
 /// Delegates to the daemon module to terminate the background GUI process
 /// and clean up associated state files.
 ///
+/// # Arguments
+/// * `instance` - Instance to stop
+///
 /// # Returns
 /// * `Ok(())` if the daemon was stopped successfully
 /// * `Err` if no daemon is running or termination fails
-fn stop_gizmo() -> Result<(), Box<dyn std::error::Error>> {
-    daemon::stop_daemon()?;
+fn stop_gizmo(instance: &str) -> Result<(), Box<dyn std::error::Error>> {
+    daemon::stop_daemon(instance)?;
+    Ok(())
+}
+
+/// Stops every running Gizmo instance (`gizmo stop --all`).
+///
+/// # Returns
+/// * `Ok(())` if at least one instance was running and all of them stopped
+/// * `Err` if none was running, or one failed to stop (the others are still stopped)
+fn stop_all_gizmos() -> Result<(), Box<dyn std::error::Error>> {
+    let mut running = 0;
+    let mut failed = Vec::new();
+    for instance in daemon::list_instances()? {
+        if !daemon::is_daemon_running(&instance)? {
+            continue;
+        }
+        running += 1;
+        print!("{}: ", instance);
+        if let Err(e) = daemon::stop_daemon(&instance) {
+            println!("{}", e);
+            failed.push(instance);
+        }
+    }
+
+    match (running, failed.is_empty()) {
+        (0, _) => Err("Gizmo is not running".into()),
+        (_, true) => Ok(()),
+        (_, false) => Err(format!("Couldn't stop {}", failed.join(", ")).into()),
+    }
+}
+
+/// Freezes or resumes the running Gizmo's animation.
+///
+/// # Arguments
+/// * `instance` - Instance to control
+/// * `pause` - `true` to freeze, `false` to resume
+///
+/// # Returns
+/// * `Ok(())` if the running Gizmo carried it out
+/// * `Err` if Gizmo isn't running or can't be reached
+fn pause_gizmo(instance: &str, pause: bool) -> Result<(), Box<dyn std::error::Error>> {
+    if pause {
+        ipc::send(instance, &ipc::Command::Pause)?;
+        println!("Gizmo paused");
+    } else {
+        ipc::send(instance, &ipc::Command::Resume)?;
+        println!("Gizmo resumed");
+    }
+    Ok(())
+}
+
+/// Switches the running Gizmo to another animation file, keeping its window.
+///
+/// The running GUI compiles the file itself and blends into it, as when the
+/// file is dropped onto the buddy; `gizmo restart` then uses the new file.
+///
+/// # Arguments
+/// * `instance` - Instance to switch
+/// * `gzmo_file` - Path to the .gzmo file to play
+///
+/// # Returns
+/// * `Ok(())` if the running Gizmo switched
+/// * `Err` if the file is missing, Gizmo isn't running, or the script doesn't compile
+fn switch_gizmo(instance: &str, gzmo_file: &str) -> Result<(), Box<dyn std::error::Error>> {
+    if !bundle::is_playable(gzmo_file) {
+        return Err("File must have .gzmo or .gzmopack extension".into());
+    }
+    // The GUI process runs in another directory
+    let file = std::fs::canonicalize(gzmo_file).map_err(|_| format!("File not found: {}", gzmo_file))?;
+    ipc::send(instance, &ipc::Command::Switch { file })?;
+    println!("Switched to {}", gzmo_file);
+    Ok(())
+}
+
+/// Prints the running Gizmo's playback speed, overriding it first if asked.
+///
+/// # Arguments
+/// * `instance` - Instance to control
+/// * `to` - The new override (`2x`, `50ms`, or `normal` for the script's speed)
+///
+/// # Returns
+/// * `Ok(())` if the running Gizmo answered
+/// * `Err` if Gizmo isn't running or can't be reached
+fn speed_gizmo(instance: &str, to: Option<clock::Speed>) -> Result<(), Box<dyn std::error::Error>> {
+    match ipc::send(instance, &ipc::Command::Speed { to })? {
+        ipc::Reply::Speed { speed, frame_ms } => println!("{}ms per frame ({})", frame_ms, describe_speed(speed)),
+        reply => return Err(format!("Unexpected answer from gizmo: {:?}", reply).into()),
+    }
+    Ok(())
+}
+
+/// Describes a speed override for the CLI.
+fn describe_speed(speed: clock::Speed) -> String {
+    match speed {
+        clock::Speed::Script => "the script's own speed".to_string(),
+        clock::Speed::Scale(factor) => format!("{}x the script's speed", factor),
+        clock::Speed::FixedMs(_) => "fixed, ignoring the script".to_string(),
+    }
+}
+
+/// Prints the running Gizmo's window position, moving it first if asked.
+///
+/// # Arguments
+/// * `instance` - Instance to control
+/// * `to` - Where to move the window's top-left corner, in physical pixels
+///
+/// # Returns
+/// * `Ok(())` if the running Gizmo answered
+/// * `Err` if Gizmo isn't running or can't be reached
+fn position_gizmo(instance: &str, to: Option<(i32, i32)>) -> Result<(), Box<dyn std::error::Error>> {
+    match ipc::send(instance, &ipc::Command::Position { to })? {
+        ipc::Reply::Position { x, y } => println!("{},{}", x, y),
+        reply => return Err(format!("Unexpected answer from gizmo: {:?}", reply).into()),
+    }
     Ok(())
 }
 
@@ -207,7 +1166,10 @@ fn stop_gizmo() -> Result<(), Box<dyn std::error::Error>> {
 /// 1. Retrieves the last used .gzmo file path from daemon state
 /// 2. Stops the current Gizmo instance if running
 /// 3. Waits briefly for clean shutdown
-/// 4. Starts a new instance with the saved file
+/// 4. Starts a new instance with the saved file and the flags it was started with
+///
+/// # Arguments
+/// * `instance` - Instance to restart
 ///
 /// # Returns
 /// * `Ok(())` if restart completed successfully
@@ -215,26 +1177,498 @@ fn stop_gizmo() -> Result<(), Box<dyn std::error::Error>> {
 ///
 /// # Timing
 /// Includes a 500ms delay between stop and start to ensure clean process termination.
-fn restart_gizmo() -> Result<(), Box<dyn std::error::Error>> {
-    let current_file = daemon::get_current_file()?;
-    stop_gizmo()?;
+#[cfg(feature = "gui")]
+fn restart_gizmo(instance: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let current_file = daemon::get_current_file(instance)?;
+    stop_gizmo(instance)?;
     thread::sleep(Duration::from_millis(500)); // Give it time to stop
-    start_gizmo(&current_file)
+    // Start the way the last start did, flags and all
+    let mut flags = daemon::get_start_options(instance).unwrap_or_default();
+    let respawns = take_flag_value(&mut flags, "--respawn").and_then(|count| count.parse().ok()).unwrap_or(0);
+    let options = GuiOptions { instance: instance.to_string(), ..GuiOptions::take_from(&mut flags)? };
+    start_gizmo(&current_file, None, respawns, &options)
+}
+
+/// Prints a script's frames as ASCII art in the terminal, without opening a window.
+///
+/// Runs the same compile and interpret steps as `start`, so script errors
+/// show up exactly as they would there.
+///
+/// # Arguments
+/// * `gzmo_file` - Path to the .gzmo script file to preview
+/// * `frame` - Print only this frame (numbered from 1)
+/// * `looping` - Play the animation in place at its own speed until Ctrl+C
+/// * `deterministic` - Seed for a reproducible run (`--deterministic`), if given
+///
+/// # Returns
+/// * `Ok(())` once the frames are printed
+/// * `Err` if the script fails to load or the frame doesn't exist
+fn preview_gizmo(
+    gzmo_file: &str,
+    frame: Option<usize>,
+    looping: bool,
+    deterministic: Option<u64>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (frames, interpreter) = load_gizmo_animation(gzmo_file, deterministic)?;
+    let frame_ms = interpreter.get_frame_duration_ms();
+    let total = frames.len();
+    let frames = match frame {
+        Some(number) if number > frames.len() => {
+            return Err(format!("Frame {} doesn't exist; the animation has {} frames", number, total).into());
+        }
+        Some(number) => &frames[number - 1..number],
+        None => &frames[..],
+    };
+    let first_number = frame.unwrap_or(1);
+    let render = |frame: &Frame| frame::FrameRenderer::new(frame.width, frame.height).render_ascii(frame);
+    // Written rather than printed, so piping into `head` reports the closed pipe instead of panicking
+    let mut out = std::io::stdout().lock();
+
+    if !looping {
+        for (index, frame) in frames.iter().enumerate() {
+            writeln!(out, "Frame {}/{} ({}x{}, {}ms)", first_number + index, total, frame.width, frame.height, frame_ms)?;
+            writeln!(out, "{}", render(frame))?;
+        }
+        return Ok(());
+    }
+
+    // Redraw in place: clear the screen and home the cursor before each frame
+    for (index, frame) in frames.iter().enumerate().cycle() {
+        write!(out, "\x1b[2J\x1b[H{}", render(frame))?;
+        writeln!(out, "Frame {}/{} - {}ms per frame - Ctrl+C to stop", first_number + index, total, frame_ms)?;
+        out.flush()?;
+        thread::sleep(Duration::from_millis(frame_ms));
+    }
+    Ok(())
+}
+
+/// Renders a script's animation to a file without opening a window.
+///
+/// # Arguments
+/// * `gzmo_file` - Path to the .gzmo script file to render
+/// * `format` - What to write: a GIF, an APNG, a sprite sheet, ...
+/// * `output` - Where to write it; the script's path with the format's
+///   extension if `None`
+/// * `scale` - How many output pixels wide and high each frame pixel becomes
+/// * `frames` - Only render this many frames from the start, if given
+/// * `frame` - Only render this frame (numbered from 1), if given; single-frame
+///   formats such as SVG render the first one otherwise
+/// * `deterministic` - Seed for a reproducible run (`--deterministic`), if given
+///
+/// # Returns
+/// * `Ok(())` - The file was written
+/// * `Err` - The script failed, the frame doesn't exist, or the file couldn't be written
+fn render_gizmo(
+    gzmo_file: &str,
+    format: export::Format,
+    output: Option<&str>,
+    scale: usize,
+    frames: Option<usize>,
+    frame: Option<usize>,
+    deterministic: Option<u64>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if !Path::new(gzmo_file).exists() {
+        return Err(format!("File not found: {}", gzmo_file).into());
+    }
+    let (mut all_frames, interpreter) = load_gizmo_animation(gzmo_file, deterministic)?;
+    let total = all_frames.len();
+    if let Some(count) = frames {
+        all_frames.truncate(count);
+    }
+    let frame = frame.or(format.is_single_frame().then_some(1));
+    if let Some(number) = frame {
+        if number > total {
+            return Err(format!("Frame {} doesn't exist; the animation has {} frames", number, total).into());
+        }
+        all_frames = vec![all_frames.swap_remove(number - 1)];
+    }
+    let output = output.map_or_else(|| Path::new(gzmo_file).with_extension(format.extension()), PathBuf::from);
+    let frame_ms = interpreter.get_frame_duration_ms();
+
+    let written = export::write(format, &output, &all_frames, interpreter.palette(), frame_ms, scale)?;
+    let written: Vec<String> = written.iter().map(|path| path.display().to_string()).collect();
+    match frame {
+        Some(number) => println!("Rendered frame {}/{} to {}", number, total, written.join(" and ")),
+        None => {
+            let unique = dedup::FrameRuns::new(&all_frames).unique.len();
+            let repeats = match all_frames.len() - unique {
+                0 => String::new(),
+                _ => format!(", {} distinct", unique),
+            };
+            println!("Rendered {} frames ({}ms per frame{}) to {}", all_frames.len(), frame_ms, repeats, written.join(" and "));
+        }
+    }
+    Ok(())
+}
+
+/// Packs a script and the files it loads into a `.gzmopack` bundle.
+///
+/// # Arguments
+/// * `gzmo_file` - Path to the .gzmo script
+/// * `extra` - More files to bundle, relative to the working directory
+/// * `output` - Where to write the bundle; next to the script if `None`
+///
+/// # Returns
+/// * `Ok(())` - The bundle was written
+/// * `Err` - The script doesn't parse, or a file is missing or outside the script's directory
+fn pack_gizmo(gzmo_file: &str, extra: &[String], output: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    if !gzmo_file.ends_with(".gzmo") {
+        return Err("File must have .gzmo extension".into());
+    }
+    let output = output.map_or_else(|| Path::new(gzmo_file).with_extension(bundle::EXTENSION), PathBuf::from);
+    let extra: Vec<PathBuf> = extra.iter().map(PathBuf::from).collect();
+    let entries = bundle::pack(Path::new(gzmo_file), &extra, &output)?;
+    println!("Packed {} into {}", entries.join(", "), output.display());
+    Ok(())
+}
+
+/// Checks scripts for errors without showing them, reporting every problem
+/// and warning as `file:line:column: message` on standard output.
+///
+/// # Arguments
+/// * `files` - Paths of the .gzmo scripts to check
+/// * `emit` - Print this output of each script instead of `ok`
+///
+/// # Returns
+/// * `Ok(true)` - Every script is fine
+/// * `Ok(false)` - At least one script has problems
+/// * `Err` - A script couldn't be read
+fn check_gizmo(files: &[String], emit: Option<check::Emit>) -> Result<bool, Box<dyn std::error::Error>> {
+    let mut clean = true;
+    for file in files {
+        let script = bundle::resolve(Path::new(file)).map_err(|e| format!("{}: {}", file, e))?;
+        let source = fs::read_to_string(&script).map_err(|e| format!("{}: {}", file, e))?;
+        let script_dir = script.parent().unwrap_or(Path::new(""));
+        let problems = match emit {
+            Some(emit) => match check::emit(&source, script_dir, emit) {
+                Ok(output) => {
+                    print!("{}", output);
+                    Vec::new()
+                }
+                Err(problems) => problems,
+            },
+            None => {
+                let problems = check::check(&source, script_dir);
+                if problems.is_empty() {
+                    println!("{}: ok", file);
+                }
+                problems
+            }
+        };
+        for problem in &problems {
+            match problem.position {
+                Some((line, column)) => println!("{}:{}:{}: {}", file, line, column, problem.error),
+                None => println!("{}: {}", file, problem.error),
+            }
+        }
+        // Warnings don't make a script fail its check
+        if emit.is_none() {
+            for warning in check::warnings(&source) {
+                println!("{}", warning_line(file, &warning));
+            }
+        }
+        clean &= problems.is_empty();
+    }
+    Ok(clean)
+}
+
+/// Formats a warning as `file:line:column: warning: message`.
+fn warning_line(file: &str, warning: &error::GizmoDiagnostic) -> String {
+    match warning.position {
+        Some((line, column)) => format!("{}:{}:{}: {}", file, line, column, warning),
+        None => format!("{}: {}", file, warning),
+    }
+}
+
+/// Times compiling and running a script and prints the results.
+///
+/// # Arguments
+/// * `gzmo_file` - Path to the .gzmo script or .gzmopack bundle
+/// * `runs` - How many times to run it
+///
+/// # Returns
+/// * `Ok(())` - The report was printed
+/// * `Err` - The script couldn't be read or failed
+fn bench_gizmo(gzmo_file: &str, runs: usize) -> Result<(), Box<dyn std::error::Error>> {
+    let script = bundle::resolve(Path::new(gzmo_file))?;
+    let source = fs::read_to_string(&script)?;
+    let script_dir = script.parent().unwrap_or(Path::new(""));
+    print!("{}", bench::run(gzmo_file, &source, script_dir, runs)?);
+    Ok(())
+}
+
+/// Rewrites scripts in the canonical layout, or with `check_only` lists the
+/// ones that aren't in it on standard output without changing them.
+///
+/// # Arguments
+/// * `files` - Paths of the .gzmo scripts to format
+/// * `check_only` - Report instead of rewriting
+///
+/// # Returns
+/// * `Ok(true)` - Every script was formatted (or already was)
+/// * `Ok(false)` - With `check_only`, at least one script needs formatting
+/// * `Err` - A script couldn't be read, parsed or written
+fn format_gizmo(files: &[String], check_only: bool) -> Result<bool, Box<dyn std::error::Error>> {
+    let mut formatted = true;
+    for file in files {
+        let source = fs::read_to_string(file).map_err(|e| format!("{}: {}", file, e))?;
+        let canonical = format::format(&source).map_err(|e| format!("{}: {}", file, e))?;
+        if canonical == source {
+            continue;
+        }
+        if check_only {
+            println!("{}: needs formatting", file);
+            formatted = false;
+        } else {
+            fs::write(file, canonical).map_err(|e| format!("{}: {}", file, e))?;
+            println!("Formatted {}", file);
+        }
+    }
+    Ok(formatted)
+}
+
+/// Reports whether the daemon is running and what it is playing.
+///
+/// The PID comes from the daemon state; the file, uptime and animation come
+/// from the status file the GUI process keeps up to date. A GUI that is
+/// still starting (or was started by an older version) hasn't written one
+/// yet, so only the PID and the saved file are shown.
+///
+/// # Arguments
+/// * `instance` - Instance to report on
+///
+/// # Returns
+/// * `Ok(())` - Status printed, whether or not gizmo is running
+/// * `Err` - The daemon state couldn't be read
+fn show_status(instance: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let pid = match daemon::get_daemon_pid(instance) {
+        Ok(pid) if daemon::is_daemon_running(instance)? => pid,
+        _ => {
+            println!("Gizmo is not running");
+            if let Ok(file) = daemon::get_current_file(instance) {
+                println!("Last file: {}", file);
+            }
+            if let Some(crash) = daemon::get_crash_report(instance) {
+                println!("Last run crashed at {}: {}", crash.local_time(), crash.reason);
+            }
+            return Ok(());
+        }
+    };
+
+    println!("Gizmo is running");
+    if instance != daemon::DEFAULT_INSTANCE {
+        println!("  Name:   {}", instance);
+    }
+    println!("  PID:    {}", pid);
+
+    // Ask the GUI first; the status file covers a GUI that can't be reached
+    let live = match ipc::send(instance, &ipc::Command::Status) {
+        Ok(ipc::Reply::Status(live)) => Some(live),
+        _ => None,
+    };
+    let saved = live.as_ref().map(|live| live.status.clone()).or_else(|| daemon::get_gui_status(instance));
+    match saved.filter(|status| status.pid == pid) {
+        Some(status) => {
+            println!("  File:   {}", status.file);
+            println!("  Uptime: {}", format_uptime(status.uptime()));
+            println!("  Frames: {}", status.frame_count);
+            println!("  Speed:  {}ms per frame", status.frame_ms);
+        }
+        None => println!("  File:   {}", daemon::get_current_file(instance)?),
+    }
+    if let Some(live) = live {
+        println!("  State:  {}", if live.paused { "paused" } else { "playing" });
+        if live.speed != clock::Speed::Script {
+            println!("  Speed override: {}", describe_speed(live.speed));
+        }
+        if let Some((x, y)) = live.position {
+            println!("  Window: {},{}", x, y);
+        }
+        if let Some(fps) = live.fps {
+            println!("  Rate:   {:.1} frames/s shown", fps);
+        }
+    }
+    Ok(())
+}
+
+/// Makes Gizmo start at login with a script.
+///
+/// # Arguments
+/// * `instance` - Instance to start at login
+/// * `gzmo_file` - Script to start; the instance's last used file if `None`
+///
+/// # Returns
+/// * `Ok(())` if the login item was installed
+/// * `Err` if there is no script to start, or the login item couldn't be written
+fn install_autostart(instance: &str, gzmo_file: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    let file = match gzmo_file {
+        Some(file) => file.to_string(),
+        None => daemon::get_current_file(instance)?,
+    };
+    if !bundle::is_playable(&file) {
+        return Err("File must have .gzmo or .gzmopack extension".into());
+    }
+    // Login items don't run in this directory
+    let file = std::fs::canonicalize(&file).map_err(|_| format!("File not found: {}", file))?;
+
+    let location = autostart::install(instance, &file)?;
+    println!("Gizmo will start at login with {}", file.display());
+    println!("  Login item: {}", location);
+    Ok(())
+}
+
+/// Prints a table of the running Gizmo instances (`gizmo list`).
+///
+/// The file and uptime come from each instance's status file, so a GUI that
+/// is still starting shows its saved file and no uptime yet.
+///
+/// # Returns
+/// * `Ok(())` - Table printed, or a note that nothing is running
+/// * `Err` - The instances' state couldn't be read
+fn list_gizmos() -> Result<(), Box<dyn std::error::Error>> {
+    let mut rows = Vec::new();
+    for instance in daemon::list_instances()? {
+        let pid = match daemon::get_daemon_pid(&instance) {
+            Ok(pid) if daemon::is_daemon_running(&instance)? => pid,
+            _ => continue,
+        };
+        let (file, uptime) = match daemon::get_gui_status(&instance).filter(|status| status.pid == pid) {
+            Some(status) => (status.file.clone(), format_uptime(status.uptime())),
+            None => (daemon::get_current_file(&instance).unwrap_or_default(), "-".to_string()),
+        };
+        rows.push([instance, pid.to_string(), uptime, file]);
+    }
+
+    if rows.is_empty() {
+        println!("Gizmo is not running");
+        return Ok(());
+    }
+    let name_width = rows.iter().map(|row| row[0].len()).max().unwrap_or(0).max(4);
+    println!("{:<name_width$}  {:>7}  {:>11}  FILE", "NAME", "PID", "UPTIME");
+    for [name, pid, uptime, file] in rows {
+        println!("{:<name_width$}  {:>7}  {:>11}  {}", name, pid, uptime, file);
+    }
+    Ok(())
+}
+
+/// Prints the GUI process's log.
+///
+/// # Arguments
+/// * `instance` - Instance whose log to print
+/// * `follow` - Keep printing new output as it is written, like `tail -f`,
+///   until Ctrl+C; a restart's rotated log is picked up from the start
+///
+/// # Returns
+/// * `Ok(())` once the log is printed
+/// * `Err` if there is no log yet or it can't be read
+fn show_logs(instance: &str, follow: bool) -> Result<(), Box<dyn std::error::Error>> {
+    use std::io::Seek;
+
+    let path = daemon::log_path(instance)?;
+    let mut log = fs::File::open(&path).map_err(|_| "No log yet. Use 'gizmo start <file>' first.")?;
+    let mut out = std::io::stdout().lock();
+    std::io::copy(&mut log, &mut out)?;
+    if !follow {
+        return Ok(());
+    }
+
+    loop {
+        out.flush()?;
+        thread::sleep(Duration::from_millis(250));
+
+        // A shorter file means the log was rotated (or removed and recreated)
+        let position = log.stream_position()?;
+        if fs::metadata(&path).is_ok_and(|metadata| metadata.len() < position) {
+            log = fs::File::open(&path)?;
+        }
+        std::io::copy(&mut log, &mut out)?;
+    }
+}
+
+/// Formats an uptime as hours, minutes and seconds, e.g. `2h 05m 09s`.
+fn format_uptime(uptime: Duration) -> String {
+    let seconds = uptime.as_secs();
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours > 0 {
+        format!("{}h {:02}m {:02}s", hours, minutes, seconds)
+    } else if minutes > 0 {
+        format!("{}m {:02}s", minutes, seconds)
+    } else {
+        format!("{}s", seconds)
+    }
+}
+
+/// Plays a .gzmo animation full-screen on every monitor in the foreground.
+///
+/// # Arguments
+/// * `gzmo_file` - Path to the .gzmo script file to play
+/// * `options` - Renderer and color overrides
+///
+/// # Returns
+/// * `Ok(())` once any input ends the screensaver
+/// * `Err` if the script fails to load or the windows can't be created
+#[cfg(feature = "gui")]
+fn run_screensaver(gzmo_file: &str, options: &GuiOptions) -> Result<(), Box<dyn std::error::Error>> {
+    let (animation_frames, mut interpreter) = load_gizmo_animation(gzmo_file, None)?;
+    options.apply_colors(&mut interpreter)?;
+    screensaver::run(animation_frames, interpreter, options.renderer_kind)
+}
+
+/// Plays an animation in the terminal until Ctrl+C.
+///
+/// # Arguments
+/// * `gzmo_file` - Path to the .gzmo script file (or bundle) to play
+fn tty_gizmo(gzmo_file: &str) -> Result<(), Box<dyn std::error::Error>> {
+    if !bundle::is_playable(gzmo_file) {
+        return Err("File must have .gzmo or .gzmopack extension".into());
+    }
+    if !Path::new(gzmo_file).exists() {
+        return Err(format!("File not found: {}", gzmo_file).into());
+    }
+    let (animation_frames, interpreter) = load_gizmo_animation(gzmo_file, None)?;
+    tty::run(Path::new(gzmo_file), animation_frames, interpreter)
 }
 
 /// Runs the desktop window GUI process for displaying Gizmo animations.
 ///
 /// This is the core GUI function that:
 /// 1. Loads and parses the .gzmo script file into animation frames
-/// 2. Creates a borderless, draggable window positioned at screen center
-/// 3. Sets up platform-specific always-on-top behavior (macOS implementation included)
-/// 4. Implements an optimized animation loop with two timing modes:
-///    - **Polling mode**: For fast animations (<20ms) - continuous redraw requests
-///    - **Wait mode**: For slower animations (≥20ms) - efficient sleep-based timing
-/// 5. Handles mouse input for window dragging functionality
+/// 2. Creates a borderless, draggable window at its remembered position (or screen center)
+/// 3. Sets up always-on-top behavior (winit window level plus a macOS-specific level)
+/// 4. Implements an animation loop that sleeps until each frame is due, aligned
+///    to the display refresh when known, skipping frames whose deadline was missed
+/// 5. Handles mouse input: dragging past a small threshold moves the window (clamped
+///    to the screen with edge snapping), double-clicking pauses the animation (unless the script handles it), and
+///    `+` / `-` or the scroll wheel zoom in whole sprite-scale steps
+/// 6. Plays back script-driven window motion (`move_to()`, `wander()`, `fall()`, `bounce()`)
+/// 7. Runs the script's `when ...` and `every N` handlers, switching to any frames
+///    they `play()` through the configured transition:
+///    - pointer events (`clicked`, `double_clicked`, `clicked N times within MS`,
+///      `hovered`, `unhovered`, `dragged`, `shaken`, `dropped`) as the user
+///      interacts with the buddy
+///    - `key(...)` on key presses and `--hotkey` shortcuts
+///    - `time("HH:MM")` when the local clock reaches it
+///    - `message(...)` as messages arrive on `--subscribe` sources (`remote` feature)
+///    - `idle > N` once the buddy (or, with `--system-idle`, the whole system)
+///      has gone without input long enough
+///    - `every N` each time its interval elapses
+///    - `unplugged`, `plugged_in` and `battery < N` as the power state changes
+///      (`sysinfo` feature)
+/// 8. Plays `when launched` frames once before the script's animation, and on
+///    `gizmo stop` (or a close request) plays `when stopping` frames once before
+///    fading out
+/// 9. Switches to a .gzmo file dropped onto the window, recompiling it and
+///    remembering it for `gizmo restart`
+/// 10. Saves the script's `persist` variables whenever a handler changes them
+///     and on exit
+/// 11. Keeps playing the last animation that worked when a handler fails or a
+///     dropped script doesn't compile, logging the error and showing an error
+///     glyph for a few seconds (see the `fault` module)
 ///
 /// # Arguments
 /// * `gzmo_file` - Path to the .gzmo script file to execute and display
+/// * `options` - Presentation backend (software, or GPU when built with the `gpu` feature)
+///   and `--fg` / `--bg` colors, which take precedence over the script's
 ///
 /// # Returns
 /// * `Ok(())` if the window ran and closed successfully
@@ -242,17 +1676,29 @@ fn restart_gizmo() -> Result<(), Box<dyn std::error::Error>> {
 ///
 /// # Platform Notes
 /// - **macOS**: Uses Objective-C runtime to set window level for always-on-top behavior
+/// - **X11 / Windows**: Always-on-top via winit's `WindowLevel::AlwaysOnTop`
+/// - **Wayland**: Stacking is up to the compositor; the level request is a no-op there
 /// - **Cross-platform**: Window dragging implemented using winit mouse events
+/// - **Transparency**: The window is created transparent; off pixels are drawn with
+///   alpha 0 so only the sprite is visible where the desktop composites windows,
+///   and they fall back to black where it does not
 ///
 /// # Performance Optimization
-/// The animation timing system automatically switches between polling and wait modes
-/// based on frame duration to balance responsiveness with CPU efficiency.
-fn run_desktop_window(gzmo_file: &str) -> Result<(), Box<dyn std::error::Error>> {
+/// The loop sleeps until the next frame is due instead of polling, even for fast
+/// animations; wake-ups are aligned to the display's refresh when the monitor
+/// reports its rate (see the `pacing` module). Frames are only re-rendered when the
+/// visible frame index or window size changes, or after a drag. Pacing statistics
+/// (measured FPS, dropped frames) are logged to stderr every minute and on exit.
+#[cfg(feature = "gui")]
+fn run_desktop_window(gzmo_file: &str, options: &GuiOptions) -> Result<(), Box<dyn std::error::Error>> {
     // Load and parse the gizmo file
-    let (animation_frames, frame_duration_ms) = load_gizmo_animation(gzmo_file)?;
+    let (animation_frames, mut interpreter) = load_gizmo_animation(gzmo_file, None)?;
+    
+    // Command-line colors win over whatever the script set
+    options.apply_colors(&mut interpreter)?;
     
     // Create window
-    let event_loop = EventLoop::new()?;
+    let event_loop = EventLoopBuilder::<GuiEvent>::with_user_event().build()?;
     
     let window_size = 128;
     
@@ -261,19 +1707,35 @@ fn run_desktop_window(gzmo_file: &str) -> Result<(), Box<dyn std::error::Error>>
         .with_inner_size(winit::dpi::LogicalSize::new(window_size, window_size))
         .with_resizable(false)
         .with_decorations(false) // Remove window borders and bars
+        .with_transparent(true) // Let transparent palette colors show the desktop through
+        .with_window_level(WindowLevel::AlwaysOnTop) // Keep the buddy above other windows
         .with_visible(true)
         .build(&event_loop)?);
 
-    // Back to exact center position that worked before
-    let primary_monitor = event_loop.primary_monitor().unwrap();
-    let screen_size = primary_monitor.size();
+    // Restore the last dragged position if it is still on a connected monitor,
+    // otherwise center on the primary monitor
+    let saved_position = daemon::get_window_position(&options.instance).filter(|&(x, y)| {
+        event_loop.available_monitors().any(|monitor| {
+            let (origin, size) = (monitor.position(), monitor.size());
+            x >= origin.x && y >= origin.y
+                && x < origin.x + size.width as i32 && y < origin.y + size.height as i32
+        })
+    });
     
-    let center_x = screen_size.width as i32 / 2 - window_size / 2;
-    let center_y = screen_size.height as i32 / 2 - window_size / 2;
-    
-    window.set_outer_position(winit::dpi::LogicalPosition::new(center_x, center_y));
+    if let Some((x, y)) = saved_position {
+        window.set_outer_position(winit::dpi::PhysicalPosition::new(x, y));
+    } else {
+        let primary_monitor = event_loop.primary_monitor().unwrap();
+        let screen_size = primary_monitor.size();
+        
+        let center_x = screen_size.width as i32 / 2 - window_size / 2;
+        let center_y = screen_size.height as i32 / 2 - window_size / 2;
+        
+        window.set_outer_position(winit::dpi::LogicalPosition::new(center_x, center_y));
+    }
 
-    // Set window to always be on top using platform-specific code
+    // winit's AlwaysOnTop level covers X11 and Windows; macOS additionally gets
+    // an explicit floating level through the Objective-C runtime below
     #[cfg(target_os = "macos")]
     {
         use raw_window_handle::{HasWindowHandle, RawWindowHandle};
@@ -301,126 +1763,910 @@ fn run_desktop_window(gzmo_file: &str) -> Result<(), Box<dyn std::error::Error>>
     window.set_visible(true);
     window.focus_window();
     
-    // Initialize softbuffer
-    let context = Context::new(window.as_ref())?;
-    let mut surface = Surface::new(&context, window.as_ref())?;
+    // Initialize the frame presentation backend
+    let mut renderer = renderer::Renderer::new(options.renderer_kind, &window)?;
+
+    // Only the sprite's visible pixels catch the mouse
+    let mut input_shape = shape::InputShape::new(&window);
+
+    // Whether a full-screen app is in front, for the `screen_busy` variable
+    let fullscreen_detector = fullscreen::FullscreenDetector::new(&window);
+    interpreter.set_screen_busy(fullscreen_detector.is_busy());
+    let mut last_fullscreen_check = std::time::Instant::now();
 
+    // Playback is driven by the interpreter's fixed-step clock: real time is fed
+    // in on every wake-up and the visible frame is derived from whole ticks, so
+    // stalled or throttled redraws skip frames instead of slowing the animation.
+    // Event handlers may switch to new frames later (see the `sequence` module).
+    // Frames played by `when launched` handlers run once before the script's own.
+    // Failures from here on show the error glyph and keep the last animation
+    // that worked (see the `fault` module)
+    let mut faults = fault::Faults::new(Path::new(gzmo_file));
+    let mut sequence = sequence::Sequence::new(Vec::new());
+    let launch_frames = run_launch_handlers(&mut interpreter, |e| faults.handler_failed("launch", e));
+    sequence.switch(launch_frames, animation_frames, interpreter.clock().ticks());
+    let mut sequence_version = interpreter.sequence_version();
     let mut frame_index = 0;
-    let mut last_frame_time = std::time::Instant::now();
-    let frame_duration = Duration::from_millis(frame_duration_ms);
+    let mut last_clock_update = std::time::Instant::now();
+
+    // What is currently on screen: (sequence position, width, height). Redraw requests
+    // that wouldn't change any of these are skipped; resizes and drags clear it
+    // to force the next redraw through.
+    let mut presented: Option<(usize, u32, u32)> = None;
+
+    // Present timing: dropped-frame statistics and refresh-aligned wake-ups
+    let mut pacer = pacing::FramePacer::new(window.current_monitor().and_then(|monitor| monitor.refresh_rate_millihertz()));
+    let mut last_pacing_log = std::time::Instant::now();
+    // Whether the last lag window was too slow, so the warning is given once
+    let mut falling_behind = false;
+    // Ticks between shown frames: 1, or every other frame under --throttle
+    let mut throttle = throttle::Throttle::new(options.throttle);
+    let mut stride = 1;
+
+    // Clicks and drags, plus the last cursor position inside the window
+    // (button events don't carry one)
+    let mut pointer = input::Pointer::new();
+    let mut cursor_in_window = winit::dpi::PhysicalPosition::new(0.0, 0.0);
+
+    // Double-clicking the buddy freezes and unfreezes the animation, unless
+    // the script has its own `when double_clicked` handlers
+    let mut paused = false;
+
+    // Set when pointer input or idle time ran the script's event handlers
+    let mut handlers_ran = false;
+
+    // Time since the buddy was last touched, for `when idle > N` handlers
+    let mut idle_timer = idle::IdleTimer::new(std::time::Instant::now());
+    let system_idle = options.system_idle.then(|| idle::SystemIdle::new(&window));
+
+    // Wall-clock times for `when time("HH:MM")` handlers
+    let mut schedule = schedule::Schedule::new(chrono::Local::now().naive_local());
+
+    // Deadlines of `every N` handlers, kept apart from the animation clock
+    let mut timers = timer::Timers::new();
+
+    // The script whose `persist` variables are saved, and what they were last
+    let mut pet_script = std::path::PathBuf::from(gzmo_file);
+    let mut persisted = interpreter.persisted_values();
+
+    // What `gizmo status` reports, saved whenever the animation changes
+    // (`gizmo run` isn't the daemon and has nothing to report)
+    let mut status = (!options.attached).then(|| daemon::GuiStatus::new(gzmo_file));
+    report_status(&mut status, &options.instance, &pet_script, &sequence, &interpreter);
+
+    // System load for the `cpu_percent` etc. variables, refreshed now and then
+    #[cfg(feature = "sysinfo")]
+    let (mut stats_sampler, mut last_stats_sample) = (stats::Sampler::new(), std::time::Instant::now());
+    #[cfg(feature = "sysinfo")]
+    let mut power_watch = power::PowerWatch::new();
+
+    // Microphone loudness for `mic_level`, only when the user opted in with `--mic`
+    #[cfg(feature = "mic")]
+    let microphone = if options.mic {
+        match mic::MicMonitor::start() {
+            Ok(microphone) => {
+                interpreter.set_microphone(microphone.level());
+                Some(microphone)
+            }
+            Err(e) => {
+                eprintln!("Microphone unavailable: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    // Touchpad scrolling not yet added up to a whole zoom step
+    let mut scroll_remainder = 0.0;
+
+    // Script-driven window motion, clamped to the monitors known at startup
+    // (refreshed whenever a drag starts); falls land on the work areas' bottom
+    let mut motion = motion::Motion::new(interpreter.take_motion_commands());
+    let mut monitors = monitor_bounds(&window);
+    let mut work_areas = workarea::work_areas(&window);
+
+    // Notifications the script sent while loading
+    notification::show_all(interpreter.take_notifications());
+
+    // The `say()` bubble above the sprite, growing the window while shown
+    let mut speech = interpreter.take_speech().and_then(|(text, ms)| {
+        let until = std::time::Instant::now() + Duration::from_millis(ms);
+        speech::say(&window, None, &text, until, sequence.frame(0))
+    });
 
-    // Variables for dragging
-    let mut is_dragging = false;
-    let mut drag_start_pos: Option<winit::dpi::PhysicalPosition<f64>> = None;
-    let mut window_start_pos: Option<winit::dpi::PhysicalPosition<i32>> = None;
+    // Fade in from invisible to the script's opacity; a stop request plays the
+    // `when stopping` farewell (until `farewell_until`), then fades back out
+    // before exiting
+    let mut target_opacity = interpreter.opacity();
+    let mut current_opacity = 0.0;
+    let mut fade = Some(opacity::Fade::new(0.0, target_opacity, std::time::Instant::now()));
+    let mut native_opacity = opacity::set_native_opacity(&window, current_opacity);
+    let mut stopping: Option<std::time::Instant> = None;
+    let mut farewell_until: Option<std::time::Instant> = None;
+
+    // `gizmo stop` sends SIGTERM and Ctrl+C in `gizmo run` SIGINT; turn them
+    // into a farewell and fade-out instead of dying mid-frame. Asking twice
+    // exits at once.
+    #[cfg(unix)]
+    {
+        use signal_hook::consts::{SIGINT, SIGTERM};
+        let proxy = event_loop.create_proxy();
+        let mut signals = signal_hook::iterator::Signals::new([SIGTERM, SIGINT])?;
+        thread::spawn(move || {
+            for (count, _) in signals.forever().enumerate() {
+                if count > 0 {
+                    process::exit(1);
+                }
+                let _ = proxy.send_event(GuiEvent::Stop);
+            }
+        });
+    }
+
+    // Stop animating while the buddy is hidden (screen locked, display asleep,
+    // fully covered) so a busy poll loop doesn't keep running overnight
+    let mut hidden = false;
+    visibility::watch_display_state(&window, event_loop.create_proxy());
+
+    // `--hotkey` shortcuts that work while other applications have focus
+    let hotkeys = if options.hotkeys.is_empty() {
+        None
+    } else {
+        match keyboard::GlobalHotkeys::register(&options.hotkeys, event_loop.create_proxy()) {
+            Ok(hotkeys) => Some(hotkeys),
+            Err(e) => {
+                eprintln!("Global hotkeys unavailable: {}", e);
+                None
+            }
+        }
+    };
+
+    // `when message(...)` handlers fed by MQTT topics and WebSockets
+    #[cfg(feature = "remote")]
+    for subscription in &options.subscriptions {
+        remote::subscribe(subscription.clone(), event_loop.create_proxy());
+    }
+
+    // Every drawn frame also goes to the `--stream` displays
+    let mut streams = Vec::new();
+    for target in &options.streams {
+        match target.open() {
+            Ok(display) => streams.push(output::Stream::start(target.to_string(), display)),
+            Err(e) => eprintln!("Can't stream to {}: {}", target, e),
+        }
+    }
+
+    // `gizmo pause` and friends; `gizmo run` leaves the channel to the daemon
+    if !options.attached {
+        if let Err(e) = ipc::listen(&options.instance, event_loop.create_proxy()) {
+            eprintln!("Control channel unavailable: {}", e);
+        }
+    }
+
+    // Window close requests take the same farewell path as `gizmo stop`
+    let stop_proxy = event_loop.create_proxy();
 
     let window_clone = window.clone();
     event_loop.run(move |event, elwt| {
         elwt.set_control_flow(ControlFlow::Wait);
 
+        // `gizmo switch` swaps scripts exactly like dropping the file onto the buddy
+        let (event, mut switch_request) = match event {
+            Event::UserEvent(GuiEvent::Control(request)) => match &request.command {
+                ipc::Command::Switch { file } => (
+                    Event::WindowEvent { window_id: window_clone.id(), event: WindowEvent::DroppedFile(file.clone()) },
+                    Some(request),
+                ),
+                _ => (Event::UserEvent(GuiEvent::Control(request)), None),
+            },
+            event => (event, None),
+        };
+
         match event {
+            // Size or DPI changes invalidate whatever was presented
+            Event::WindowEvent {
+                event: WindowEvent::Resized(_) | WindowEvent::ScaleFactorChanged { .. },
+                ..
+            } => {
+                presented = None;
+                window_clone.request_redraw();
+            }
             Event::WindowEvent { event: WindowEvent::CloseRequested, .. } => {
-                // Clean up daemon state when window is closed
-                let _ = daemon::cleanup_daemon_state();
+                // Clean up daemon state when window is closed, then say goodbye
+                // like for `gizmo stop` (which is how Windows delivers it)
+                if !options.attached {
+                    let _ = daemon::cleanup_daemon_state(&options.instance);
+                }
+                let _ = stop_proxy.send_event(GuiEvent::Stop);
+            }
+            Event::UserEvent(GuiEvent::Stop) if hidden => {
+                // Nobody would see a fade-out
                 elwt.exit();
             }
-            // Handle mouse input for window dragging functionality
-            Event::WindowEvent { event: WindowEvent::MouseInput { state, button, .. }, .. } => {
-                if button == winit::event::MouseButton::Left {
-                    match state {
-                        winit::event::ElementState::Pressed => {
-                            // Start dragging: prepare to track mouse movement
-                            is_dragging = true;
-                            drag_start_pos = None; // Will be set on first mouse move
-                            if let Ok(pos) = window_clone.outer_position() {
-                                window_start_pos = Some(pos);
-                            }
-                        }
-                        winit::event::ElementState::Released => {
-                            // End dragging: reset tracking state
-                            is_dragging = false;
-                            drag_start_pos = None;
-                            window_start_pos = None;
+            Event::UserEvent(GuiEvent::Hotkey(id)) => {
+                idle_timer.activity(std::time::Instant::now());
+                match hotkeys.as_ref().and_then(|hotkeys| hotkeys.action(id)) {
+                    Some(keyboard::HotkeyAction::Pause) => {
+                        paused = !paused;
+                        pacer.restart();
+                    }
+                    Some(keyboard::HotkeyAction::Key(name)) => {
+                        if let Err(e) = interpreter.handle_key_event(name) {
+                            faults.handler_failed("key", &e);
                         }
+                        handlers_ran = true;
                     }
+                    None => {}
+                }
+            }
+            Event::UserEvent(GuiEvent::Message(text)) => {
+                if let Err(e) = interpreter.handle_message_event(&text) {
+                    faults.handler_failed("message", &e);
                 }
+                handlers_ran = true;
             }
-            // Handle cursor movement for window dragging
-            Event::WindowEvent { event: WindowEvent::CursorMoved { position, .. }, .. } => {
-                if is_dragging {
-                    // Initialize drag reference point on first movement
-                    if drag_start_pos.is_none() {
-                        drag_start_pos = Some(position);
+            Event::UserEvent(GuiEvent::Control(request)) => {
+                let reply = match &request.command {
+                    ipc::Command::Pause | ipc::Command::Resume => {
+                        paused = request.command == ipc::Command::Pause;
+                        pacer.restart();
+                        window_clone.request_redraw();
+                        ipc::Reply::Ok
+                    }
+                    ipc::Command::Status => match &status {
+                        Some(status) => ipc::Reply::Status(ipc::LiveStatus {
+                            status: status.clone(),
+                            paused,
+                            speed: interpreter.clock().speed(),
+                            position: window_clone.outer_position().ok().map(|pos| (pos.x, pos.y)),
+                            fps: pacer.recent().and_then(pacing::PacingStats::fps),
+                        }),
+                        None => ipc::Reply::Error { message: "Not running as the daemon".to_string() },
+                    },
+                    ipc::Command::Position { to } => {
+                        if let Some((x, y)) = *to {
+                            // Same rules as a drop: stay on a monitor and be
+                            // remembered for the next start
+                            let size = window_clone.outer_size();
+                            let (x, y) = placement::constrain(x, y, size.width, size.height, &monitor_bounds(&window_clone));
+                            window_clone.set_outer_position(winit::dpi::PhysicalPosition::new(x, y));
+                            let _ = daemon::save_window_position(&options.instance, x, y);
+                            motion.interrupt();
+                        }
+                        match window_clone.outer_position() {
+                            Ok(pos) => ipc::Reply::Position { x: pos.x, y: pos.y },
+                            Err(e) => ipc::Reply::Error { message: e.to_string() },
+                        }
                     }
-                    
-                    // Calculate and apply window position delta
-                    if let (Some(drag_start), Some(window_start)) = (drag_start_pos, window_start_pos) {
-                        let delta_x = position.x - drag_start.x;
-                        let delta_y = position.y - drag_start.y;
-                        
-                        let new_x = window_start.x + delta_x as i32;
-                        let new_y = window_start.y + delta_y as i32;
-                        
-                        // Move window to new position (ignore errors - non-critical)
-                        let _ = window_clone.set_outer_position(winit::dpi::PhysicalPosition::new(new_x, new_y));
+                    ipc::Command::Speed { to } => {
+                        if let Some(speed) = *to {
+                            interpreter.clock_mut().set_speed(speed);
+                            pacer.restart();
+                            window_clone.request_redraw();
+                            report_status(&mut status, &options.instance, &pet_script, &sequence, &interpreter);
+                        }
+                        let clock = interpreter.clock();
+                        ipc::Reply::Speed { speed: clock.speed(), frame_ms: clock.tick_ms() }
                     }
+                    ipc::Command::Switch { .. } => unreachable!("switches arrive as dropped files"),
+                };
+                request.answer(reply);
+            }
+            Event::UserEvent(GuiEvent::Stop) if stopping.is_none() => {
+                // The CLI already cleaned up daemon state; play the farewell,
+                // then fade out and exit
+                let now = std::time::Instant::now();
+                stopping = Some(now);
+                farewell_until = Some(now);
+                if let Err(e) = interpreter.handle_stopping_event() {
+                    faults.handler_failed("stopping", &e);
+                }
+                handlers_ran = true;
+            }
+            Event::WindowEvent { event: WindowEvent::Occluded(now_hidden), .. }
+            | Event::UserEvent(GuiEvent::Hidden(now_hidden)) => {
+                if hidden && !now_hidden {
+                    // Resume where playback stopped instead of catching up on
+                    // the time spent hidden
+                    last_clock_update = std::time::Instant::now();
+                    pacer.restart();
+                    presented = None;
+                    window_clone.request_redraw();
                 }
+                hidden = now_hidden;
             }
-            Event::WindowEvent { event: WindowEvent::RedrawRequested, window_id } => {
-                if window_id == window_clone.id() {
-                    // Update animation frame
-                    if last_frame_time.elapsed() >= frame_duration && !animation_frames.is_empty() {
-                        frame_index = (frame_index + 1) % animation_frames.len();
-                        last_frame_time = std::time::Instant::now();
+            // Left button, cursor movement and focus loss drive clicks and drags
+            Event::WindowEvent {
+                event:
+                    event @ (WindowEvent::MouseInput { button: winit::event::MouseButton::Left, .. }
+                    | WindowEvent::CursorMoved { .. }
+                    | WindowEvent::Focused(false)),
+                ..
+            } => {
+                // Track the cursor in screen space so moving the window doesn't
+                // shift the reference point mid-drag
+                if let WindowEvent::CursorMoved { position, .. } = event {
+                    cursor_in_window = position;
+                }
+                if !matches!(event, WindowEvent::Focused(_)) {
+                    idle_timer.activity(std::time::Instant::now());
+                }
+                let origin = window_clone.inner_position().unwrap_or_default();
+                let cursor = (origin.x as f64 + cursor_in_window.x, origin.y as f64 + cursor_in_window.y);
+
+                let pointer_event = match event {
+                    WindowEvent::MouseInput { state: winit::event::ElementState::Pressed, .. } => {
+                        if let Ok(pos) = window_clone.outer_position() {
+                            pointer.press(cursor, (pos.x, pos.y));
+                        }
+                        None
                     }
+                    WindowEvent::MouseInput { .. } => pointer.release(std::time::Instant::now()),
+                    WindowEvent::CursorMoved { .. } => pointer.moved(cursor, std::time::Instant::now()),
+                    // The release may never arrive once focus is gone
+                    _ => pointer.cancel(),
+                };
 
-                    // Render current frame
-                    let (width, height) = {
-                        let size = window_clone.inner_size();
-                        (size.width, size.height)
-                    };
+                match pointer_event {
+                    Some(
+                        input::PointerEvent::DragStarted { x, y }
+                        | input::PointerEvent::Dragged { x, y }
+                        | input::PointerEvent::Shaken { x, y },
+                    ) => {
+                        if matches!(pointer_event, Some(input::PointerEvent::DragStarted { .. })) {
+                            // Snapshot monitor bounds once per drag for clamping and snapping
+                            monitors = monitor_bounds(&window_clone);
+                            work_areas = workarea::work_areas(&window_clone);
+
+                            // The user has the window now; scripted motion resumes after the drop
+                            motion.interrupt();
+
+                            if let Err(e) = interpreter.handle_drag_event() {
+                                faults.handler_failed("drag", &e);
+                            }
+                            handlers_ran = true;
+                        }
+                        if matches!(pointer_event, Some(input::PointerEvent::Shaken { .. })) {
+                            if let Err(e) = interpreter.handle_shake_event() {
+                                faults.handler_failed("shake", &e);
+                            }
+                            handlers_ran = true;
+                        }
 
-                    surface.resize(width.try_into().unwrap(), height.try_into().unwrap()).unwrap();
-                    let mut buffer = surface.buffer_mut().unwrap();
+                        // Keep the window on a monitor and snap it to nearby edges
+                        let size = window_clone.outer_size();
+                        let (new_x, new_y) = placement::constrain(x, y, size.width, size.height, &monitors);
+                        window_clone.set_outer_position(winit::dpi::PhysicalPosition::new(new_x, new_y));
+                    }
+                    Some(input::PointerEvent::Dropped) => {
+                        // Remember where the window was dropped for the next start
+                        if let Ok(pos) = window_clone.outer_position() {
+                            let _ = daemon::save_window_position(&options.instance, pos.x, pos.y);
 
-                    // Clear buffer to black
-                    buffer.fill(0x000000);
+                            if let Err(e) = interpreter.handle_drop_event(pos.x, pos.y) {
+                                faults.handler_failed("drop", &e);
+                            }
+                            handlers_ran = true;
+                        }
 
-                    // Draw current animation frame if available
-                    if !animation_frames.is_empty() {
-                        let current_frame = &animation_frames[frame_index];
-                        draw_frame_to_buffer(&mut buffer, current_frame, width as usize, height as usize);
+                        // Repaint once after a drag in case the platform dropped contents
+                        presented = None;
+                        window_clone.request_redraw();
+                    }
+                    Some(input::PointerEvent::DoubleClicked) if interpreter.handles_double_click() => {
+                        if let Err(e) = interpreter.handle_double_click_event() {
+                            faults.handler_failed("double-click", &e);
+                        }
+                        handlers_ran = true;
+                    }
+                    // Without a script handler, double-clicking pauses
+                    Some(input::PointerEvent::DoubleClicked) => {
+                        paused = !paused;
+                        // Time spent frozen isn't a pacing problem
+                        pacer.restart();
                     }
+                    Some(input::PointerEvent::Clicked) => {
+                        // Let the script react; its effects are applied on the next wake-up
+                        if let Err(e) = interpreter.handle_click_event() {
+                            faults.handler_failed("click", &e);
+                        }
+                        handlers_ran = true;
+                    }
+                    None => {}
+                }
 
-                    buffer.present().unwrap();
+                // Clicks and double-clicks both count toward `when clicked N times` combos
+                if matches!(pointer_event, Some(input::PointerEvent::Clicked | input::PointerEvent::DoubleClicked)) {
+                    for (times, within_ms) in pointer.combos(interpreter.click_combos()) {
+                        if let Err(e) = interpreter.handle_click_combo_event(times, within_ms) {
+                            faults.handler_failed("click combo", &e);
+                        }
+                        handlers_ran = true;
+                    }
                 }
             }
-            Event::AboutToWait => {
-                // Adaptive timing strategy based on animation speed:
-                // Fast animations need continuous polling for smooth playback,
-                // while slower animations can use efficient wait-based timing.
-                
-                if frame_duration_ms < 20 {
-                    // POLLING MODE: For high-speed animations (>50 FPS)
-                    // Continuously check for frame updates to ensure smooth playback.
-                    // This trades CPU efficiency for animation smoothness.
-                    elwt.set_control_flow(ControlFlow::Poll);
-                    if last_frame_time.elapsed() >= frame_duration {
+            // Dropping a .gzmo file onto the buddy switches to that script
+            Event::WindowEvent { event: WindowEvent::DroppedFile(path), .. } if stopping.is_none() => {
+                let now = std::time::Instant::now();
+                idle_timer.activity(now);
+                let (new_frames, mut new_interpreter) = match load_dropped_script(&path, options) {
+                    Ok(loaded) => loaded,
+                    Err(e) => {
+                        let message = format!("Can't switch to {}: {}", path.display(), e);
+                        faults.switch_failed(&message);
+                        if let Some(request) = switch_request.take() {
+                            request.answer(ipc::Reply::Error { message });
+                        }
+                        presented = None;
                         window_clone.request_redraw();
+                        return;
+                    }
+                };
+
+                // Reconnect the runtime-provided variables
+                new_interpreter.set_screen_busy(fullscreen_detector.is_busy());
+                #[cfg(feature = "sysinfo")]
+                new_interpreter.set_system_stats(&stats_sampler.sample());
+                #[cfg(feature = "mic")]
+                if let Some(microphone) = &microphone {
+                    new_interpreter.set_microphone(microphone.level());
+                }
+
+                // Put the old pet away before the new one takes over
+                save_pet_state(&pet_script, &interpreter);
+                pet_script = path.clone();
+                faults.switch(&path);
+
+                // Blend from the frame on screen into the new script's intro
+                // and animation
+                let current = sequence.frame(frame_index).cloned();
+                interpreter = new_interpreter;
+                let launch_frames = run_launch_handlers(&mut interpreter, |e| faults.handler_failed("launch", e));
+                let first_frames = if launch_frames.is_empty() { &new_frames } else { &launch_frames };
+                let mut intro = match current {
+                    Some(current) => interpreter.transition_frames(std::slice::from_ref(&current), first_frames),
+                    None => Vec::new(),
+                };
+                intro.extend(launch_frames);
+                sequence.switch(intro, new_frames, interpreter.clock().ticks());
+                sequence_version = interpreter.sequence_version();
+                report_status(&mut status, &options.instance, &pet_script, &sequence, &interpreter);
+
+                // Nothing of the old script keeps running
+                motion = motion::Motion::new(interpreter.take_motion_commands());
+                notification::show_all(interpreter.take_notifications());
+                let (text, ms) = interpreter.take_speech().unwrap_or_default();
+                speech = speech::say(
+                    &window_clone,
+                    speech.take(),
+                    &text,
+                    now + Duration::from_millis(ms),
+                    sequence.frame(0),
+                );
+                timers = timer::Timers::new();
+                idle_timer = idle::IdleTimer::new(now);
+                #[cfg(feature = "sysinfo")]
+                {
+                    power_watch = power::PowerWatch::new();
+                }
+                if interpreter.opacity() != target_opacity {
+                    fade = Some(opacity::Fade::new(current_opacity, interpreter.opacity(), now));
+                }
+                target_opacity = interpreter.opacity();
+                persisted = interpreter.persisted_values();
+                paused = false;
+                last_clock_update = now;
+                pacer.restart();
+                presented = None;
+                window_clone.request_redraw();
+
+                // `gizmo restart` brings back the dropped script
+                if options.attached {
+                    // Not the daemon's script
+                } else if let Err(e) = daemon::save_current_file(&options.instance, &path.to_string_lossy()) {
+                    eprintln!("Failed to remember {}: {}", path.display(), e);
+                }
+                if let Some(request) = switch_request.take() {
+                    request.answer(ipc::Reply::Ok);
+                }
+            }
+            // The cursor moving onto or off the sprite (the input shape limits
+            // both to its visible pixels)
+            Event::WindowEvent {
+                event: event @ (WindowEvent::CursorEntered { .. } | WindowEvent::CursorLeft { .. }),
+                ..
+            } => {
+                idle_timer.activity(std::time::Instant::now());
+                let hovered = matches!(event, WindowEvent::CursorEntered { .. });
+                if let Err(e) = interpreter.handle_hover_event(hovered) {
+                    faults.handler_failed("hover", &e);
+                }
+                handlers_ran = true;
+            }
+            // Keys go to the script's `when key(...)` handlers; +/- keys the
+            // script doesn't handle and the scroll wheel zoom in whole
+            // sprite-scale steps
+            Event::WindowEvent {
+                event: event @ (WindowEvent::KeyboardInput { .. } | WindowEvent::MouseWheel { .. }),
+                ..
+            } => {
+                idle_timer.activity(std::time::Instant::now());
+                if let WindowEvent::KeyboardInput { event: key_event, .. } = &event {
+                    let handled = keyboard::key_name(&key_event.logical_key).filter(|name| interpreter.handles_key(name));
+                    if let Some(name) = handled {
+                        if key_event.state == winit::event::ElementState::Pressed && !key_event.repeat {
+                            if let Err(e) = interpreter.handle_key_event(&name) {
+                                faults.handler_failed("key", &e);
+                            }
+                            handlers_ran = true;
+                        }
+                        return;
+                    }
+                }
+                let steps = match event {
+                    WindowEvent::KeyboardInput {
+                        event: winit::event::KeyEvent {
+                            logical_key: winit::keyboard::Key::Character(text),
+                            state: winit::event::ElementState::Pressed,
+                            ..
+                        },
+                        ..
+                    } => match text.as_str() {
+                        "+" | "=" => 1,
+                        "-" => -1,
+                        _ => 0,
+                    },
+                    WindowEvent::MouseWheel { delta: winit::event::MouseScrollDelta::LineDelta(_, y), .. } => {
+                        if y > 0.0 { 1 } else if y < 0.0 { -1 } else { 0 }
+                    }
+                    WindowEvent::MouseWheel { delta: winit::event::MouseScrollDelta::PixelDelta(offset), .. } => {
+                        scroll_remainder += offset.y;
+                        let steps = (scroll_remainder / zoom::PIXELS_PER_STEP).trunc();
+                        scroll_remainder -= steps * zoom::PIXELS_PER_STEP;
+                        steps as i32
+                    }
+                    _ => 0,
+                };
+
+                if let (true, Some(frame)) = (steps != 0, sequence.frame(frame_index)) {
+                    // Zooming ends a speech bubble, so zoom from the size without it
+                    let size = match speech.take() {
+                        Some(current) => current.hide(&window_clone).into(),
+                        None => window_clone.inner_size(),
+                    };
+                    presented = None;
+                    let limit = window_clone
+                        .current_monitor()
+                        .map_or((u32::MAX, u32::MAX), |monitor| (monitor.size().width, monitor.size().height));
+
+                    if let Some((width, height)) = zoom::zoomed_size(
+                        (frame.width, frame.height),
+                        (size.width, size.height),
+                        steps,
+                        limit,
+                    ) {
+                        // Grow or shrink around the window's center, staying on screen
+                        if let Ok(pos) = window_clone.outer_position() {
+                            let x = pos.x + (size.width as i32 - width as i32) / 2;
+                            let y = pos.y + (size.height as i32 - height as i32) / 2;
+                            let (x, y) = placement::constrain(x, y, width, height, &monitor_bounds(&window_clone));
+                            window_clone.set_outer_position(winit::dpi::PhysicalPosition::new(x, y));
+                        }
+                        let _ = window_clone.request_inner_size(winit::dpi::PhysicalSize::new(width, height));
                     }
+                }
+            }
+            Event::WindowEvent { event: WindowEvent::RedrawRequested, window_id } if window_id == window_clone.id() => {
+                // Show whichever frame the clock says is current
+                frame_index = sequence.position(throttle::shown_tick(interpreter.clock().ticks(), stride));
+
+                // Render current frame
+                let (width, height) = {
+                    let size = window_clone.inner_size();
+                    (size.width, size.height)
+                };
+
+                // Nothing visible changed since the last present: skip the work
+                if presented == Some((frame_index, width, height)) {
+                    return;
+                }
+
+                // The error glyph goes in the sprite's corner after a failure
+                let sprite = match (faults.until(), sequence.frame(frame_index)) {
+                    (Some(_), Some(frame)) => Some(std::borrow::Cow::Owned(fault::mark(frame))),
+                    (_, frame) => frame.map(std::borrow::Cow::Borrowed),
+                };
+
+                // A speech bubble is drawn as part of the frame, above the sprite
+                let (frame, bubble_palette) = match (&speech, sprite) {
+                    (Some(current), Some(sprite)) => (
+                        Some(std::borrow::Cow::Owned(speech::compose(&current.bubble, &sprite))),
+                        Some(speech::with_bubble_colors(interpreter.palette())),
+                    ),
+                    (_, sprite) => (sprite, None),
+                };
+                let glyph_palette = faults.until().map(|_| fault::with_glyph_colors(bubble_palette.as_ref().unwrap_or(interpreter.palette())));
+                let base_palette = glyph_palette.as_ref().or(bubble_palette.as_ref()).unwrap_or(interpreter.palette());
+
+                // Without native window alpha, opacity is baked into every pixel
+                let faded_palette;
+                let palette = if native_opacity || current_opacity >= 1.0 {
+                    base_palette
                 } else {
-                    // WAIT MODE: For normal-speed animations (≤50 FPS)
-                    // Use event loop sleeping to reduce CPU usage while maintaining accuracy.
-                    if last_frame_time.elapsed() >= frame_duration {
+                    faded_palette = base_palette.with_opacity(current_opacity);
+                    &faded_palette
+                };
+
+                match renderer.draw(frame.as_deref(), palette, width, height) {
+                    Ok(()) => {
+                        presented = Some((frame_index, width, height));
+                        // Throttled presents count in strides, so skipped frames aren't dropped ones
+                        let tick = interpreter.clock().ticks();
+                        let tick = (!sequence.is_still(tick)).then_some(tick / stride);
+                        pacer.record_present(std::time::Instant::now(), tick);
+                        if let Some(frame) = &frame {
+                            input_shape.update(&window_clone, shape::silhouette(frame, base_palette, width, height));
+                        }
+                        // The sprite without the speech bubble, which wouldn't fit a fixed-size display
+                        if let Some(frame) = sequence.frame(frame_index) {
+                            for stream in &streams {
+                                stream.send(frame, interpreter.palette());
+                            }
+                        }
+                    }
+                    Err(e) => eprintln!("Error drawing frame: {}", e),
+                }
+            }
+            Event::AboutToWait if hidden => {
+                // Sleep until the buddy is visible again; the Wait set above
+                // replaces any pending WaitUntil
+            }
+            Event::AboutToWait => {
+                // Advance the animation clock by the real time since the last wake-up
+                let now = std::time::Instant::now();
+                if !paused {
+                    interpreter.clock_mut().advance(now - last_clock_update);
+                }
+                last_clock_update = now;
+                // Frame rate halved or restored under --throttle
+                let new_stride = throttle.stride(now);
+                if new_stride != stride {
+                    stride = new_stride;
+                    pacer.restart();
+                    match stride {
+                        1 => eprintln!("Showing every frame again"),
+                        _ => eprintln!("Showing every other frame (--throttle {})", options.throttle.name()),
+                    }
+                }
+                let shown_tick = throttle::shown_tick(interpreter.clock().ticks(), stride);
+                let frame_due = sequence.position(shown_tick) != frame_index;
+
+                // Take down a speech bubble whose time is up
+                if let Some(expired) = speech.take_if(|current| now >= current.until) {
+                    expired.hide(&window_clone);
+                    presented = None;
+                    window_clone.request_redraw();
+                }
+
+                // Take down the error glyph whose time is up
+                if faults.expire(now) {
+                    presented = None;
+                    window_clone.request_redraw();
+                }
+
+                // Keep the system variables fresh for the next handler
+                #[cfg(feature = "sysinfo")]
+                if now - last_stats_sample >= stats::SAMPLE_INTERVAL {
+                    let sample = stats_sampler.sample();
+                    interpreter.set_system_stats(&sample);
+                    throttle.set_on_battery(sample.on_battery);
+                    last_stats_sample = now;
+
+                    // Fire `when unplugged` / `plugged_in` / `battery < N` handlers
+                    for change in power_watch.changes(&sample, interpreter.battery_thresholds()) {
+                        let result = match change {
+                            power::PowerChange::Unplugged => interpreter.handle_power_event(true),
+                            power::PowerChange::PluggedIn => interpreter.handle_power_event(false),
+                            power::PowerChange::BatteryBelow(percent) => interpreter.handle_battery_event(percent),
+                        };
+                        if let Err(e) = result {
+                            faults.handler_failed("power", &e);
+                        }
+                        handlers_ran = true;
+                    }
+                }
+                if now - last_fullscreen_check >= fullscreen::CHECK_INTERVAL {
+                    interpreter.set_screen_busy(fullscreen_detector.is_busy());
+                    last_fullscreen_check = now;
+                }
+
+                // Fire `when idle > N` handlers whose threshold was just passed
+                let idle_time = system_idle
+                    .as_ref()
+                    .and_then(idle::SystemIdle::idle_time)
+                    .unwrap_or_else(|| idle_timer.window_idle(now));
+                for threshold in idle_timer.due(idle_time, interpreter.idle_thresholds()) {
+                    if let Err(e) = interpreter.handle_idle_event(threshold) {
+                        faults.handler_failed("idle", &e);
+                    }
+                    handlers_ran = true;
+                }
+
+                // Fire `when time("HH:MM")` handlers the local clock has reached
+                let local_now = chrono::Local::now().naive_local();
+                for minute in schedule.due(local_now, interpreter.time_triggers()) {
+                    if let Err(e) = interpreter.handle_time_event(minute) {
+                        faults.handler_failed("time", &e);
+                    }
+                    handlers_ran = true;
+                }
+
+                // Fire `every N` handlers whose interval has elapsed
+                for interval in timers.due(now, interpreter.timer_intervals()) {
+                    if let Err(e) = interpreter.handle_timer_event(interval) {
+                        faults.handler_failed("timer", &e);
+                    }
+                    handlers_ran = true;
+                }
+
+                // Apply what the script's event handlers changed
+                if std::mem::take(&mut handlers_ran) {
+                    let new_version = interpreter.sequence_version();
+                    let new_frames = interpreter.get_animation_frames();
+                    if new_version != sequence_version && !new_frames.is_empty() {
+                        // Blend from the frame on screen into the new animation
+                        let intro = match sequence.frame(frame_index) {
+                            Some(current) => interpreter.transition_frames(std::slice::from_ref(current), &new_frames),
+                            None => Vec::new(),
+                        };
+                        sequence.switch(intro, new_frames, interpreter.clock().ticks());
+
+                        // A farewell plays through once, within the stop timeout
+                        if let (Some(requested), Some(until)) = (stopping, farewell_until) {
+                            let ticks = interpreter.get_frame_duration_ms() * sequence.cycle_len() as u64;
+                            let played = now + std::time::Duration::from_millis(ticks);
+                            farewell_until = Some(until.max(played).min(requested + daemon::FAREWELL_LIMIT));
+                        }
+                    }
+                    sequence_version = new_version;
+                    interpreter.end_handler_batch();
+                    report_status(&mut status, &options.instance, &pet_script, &sequence, &interpreter);
+
+                    let values = interpreter.persisted_values();
+                    if values != persisted {
+                        if let Err(e) = persist::save(&pet_script, &values) {
+                            eprintln!("Failed to save pet state: {}", e);
+                        }
+                        persisted = values;
+                    }
+
+                    motion.queue(interpreter.take_motion_commands());
+                    notification::show_all(interpreter.take_notifications());
+                    if let Some((text, ms)) = interpreter.take_speech() {
+                        let until = now + Duration::from_millis(ms);
+                        speech = speech::say(&window_clone, speech.take(), &text, until, sequence.frame(frame_index));
+                    }
+
+                    if interpreter.opacity() != target_opacity && stopping.is_none() {
+                        fade = Some(opacity::Fade::new(current_opacity, interpreter.opacity(), now));
+                    }
+                    target_opacity = interpreter.opacity();
+
+                    presented = None;
+                    window_clone.request_redraw();
+                }
+
+                // The farewell is over: fade out (and exit once faded)
+                if farewell_until.is_some_and(|until| now >= until) {
+                    farewell_until = None;
+                    fade = Some(opacity::Fade::new(current_opacity, 0.0, now));
+                }
+
+                // Apply script-driven window motion (paused while the user drags)
+                if !pointer.is_dragging() && !motion.is_idle() {
+                    if let Ok(pos) = window_clone.outer_position() {
+                        let size = window_clone.outer_size();
+                        let (position, size) = ((pos.x, pos.y), (size.width, size.height));
+                        if let Some((x, y)) = motion.update(now, position, size, &monitors, &work_areas) {
+                            window_clone.set_outer_position(winit::dpi::PhysicalPosition::new(x, y));
+                        }
+                    }
+                }
+
+                // Step any fade in progress
+                if let Some(active) = &fade {
+                    current_opacity = active.value(now);
+                    native_opacity = opacity::set_native_opacity(&window_clone, current_opacity);
+                    if !native_opacity {
+                        presented = None;
                         window_clone.request_redraw();
-                    } else {
-                        // Sleep until the next frame is due, minimizing CPU usage
-                        let sleep_duration = frame_duration - last_frame_time.elapsed();
-                        elwt.set_control_flow(ControlFlow::WaitUntil(
-                            std::time::Instant::now() + sleep_duration
-                        ));
                     }
+                    if active.is_finished(now) {
+                        fade = None;
+                        if stopping.is_some() && farewell_until.is_none() {
+                            elwt.exit();
+                            return;
+                        }
+                    }
+                }
+
+                // Log pacing statistics now and then (see `gizmo logs`)
+                if now - last_pacing_log >= pacing::LOG_INTERVAL {
+                    eprintln!("Frame pacing: {}", pacer.stats());
+                    last_pacing_log = now;
+                }
+
+                // Warn when frames stop keeping up instead of silently skipping
+                // them; --throttle then shows every other frame, and
+                // --adapt-speed slows the animation to the rate it reaches
+                let frame_ms = interpreter.clock().tick_ms();
+                match pacer.falling_behind(Duration::from_millis(frame_ms * stride)) {
+                    Some(interval) if !falling_behind => {
+                        falling_behind = true;
+                        eprintln!(
+                            "Warning: frames are shown every {:.1}ms on average, slower than the animation's {}ms, so frames are skipped",
+                            interval.as_secs_f64() * 1000.0,
+                            frame_ms * stride
+                        );
+                        // Throttling comes first; still behind once throttled, slow down
+                        if !throttle.fell_behind(now) && options.adapt_speed && interpreter.clock().speed() == clock::Speed::Script {
+                            let adapted = interval.as_millis() as u64 + 1;
+                            interpreter.clock_mut().set_speed(clock::Speed::FixedMs(adapted));
+                            pacer.restart();
+                            report_status(&mut status, &options.instance, &pet_script, &sequence, &interpreter);
+                            eprintln!("Slowed the animation to {}ms per frame (--adapt-speed)", adapted);
+                        }
+                    }
+                    Some(_) => {
+                        throttle.fell_behind(now);
+                    }
+                    None => falling_behind = false,
+                }
+
+                // Timing strategy:
+                // Sleep until the exact moment the next tick is due, rounded up
+                // to the next display refresh when the refresh rate is known.
+                // Fast animations are scheduled the same way instead of
+                // polling; if a wake-up comes late, the fixed-step clock skips
+                // the ticks that were missed rather than falling behind.
+                if frame_due {
+                    window_clone.request_redraw();
                 }
+                // Wake often enough to keep window motion and fades smooth
+                // (a paused animation has no next frame to wait for)
+                let mut wake_at = (!paused).then(|| pacer.align(now + interpreter.clock().until_tick(shown_tick + stride)));
+                if !motion.is_idle() || fade.is_some() {
+                    let update_at = now + motion::UPDATE_INTERVAL;
+                    wake_at = Some(wake_at.map_or(update_at, |wake_at| wake_at.min(update_at)));
+                }
+                // ...and in time for the next idle, time-of-day, timer or
+                // power handler, or the end of a farewell
+                #[cfg(feature = "sysinfo")]
+                let until_sample = interpreter
+                    .handles_power_events()
+                    .then(|| stats::SAMPLE_INTERVAL.saturating_sub(now - last_stats_sample));
+                #[cfg(not(feature = "sysinfo"))]
+                let until_sample = None;
+                let until_handler = [
+                    idle_timer.until_next(idle_time, interpreter.idle_thresholds()),
+                    schedule.until_next(local_now, interpreter.time_triggers()),
+                    timers.until_next(now),
+                    until_sample,
+                    farewell_until.map(|until| until.saturating_duration_since(now)),
+                    speech.as_ref().map(|current| current.until.saturating_duration_since(now)),
+                    faults.until().map(|until| until.saturating_duration_since(now)),
+                ];
+                if let Some(until_handler) = until_handler.into_iter().flatten().min() {
+                    let handler_at = now + until_handler;
+                    wake_at = Some(wake_at.map_or(handler_at, |wake_at| wake_at.min(handler_at)));
+                }
+                if let Some(wake_at) = wake_at {
+                    elwt.set_control_flow(ControlFlow::WaitUntil(wake_at));
+                }
+            }
+            Event::LoopExiting => {
+                save_pet_state(&pet_script, &interpreter);
+                eprintln!("Frame pacing: {}", pacer.stats());
             }
             _ => {}
         }
@@ -429,20 +2675,152 @@ fn run_desktop_window(gzmo_file: &str) -> Result<(), Box<dyn std::error::Error>>
     Ok(())
 }
 
+/// Events sent to the window loop from outside winit.
+#[cfg(feature = "gui")]
+#[derive(Debug)]
+pub enum GuiEvent {
+    /// Play the farewell, fade out and exit (sent when `gizmo stop` signals
+    /// the process, or the window is asked to close).
+    Stop,
+    /// The session was locked or the display turned off (`true`), or the
+    /// buddy can be seen again (`false`). Only Windows sends this; other
+    /// platforms report `WindowEvent::Occluded` instead.
+    #[cfg_attr(not(windows), allow(dead_code))]
+    Hidden(bool),
+    /// A `--hotkey` shortcut was pressed; the id comes from `keyboard::GlobalHotkeys`.
+    Hotkey(u32),
+    /// A CLI command arrived on the control channel (see the `ipc` module).
+    Control(ipc::Request),
+    /// A message arrived on a `--subscribe` source (see the `remote` module).
+    #[cfg_attr(not(feature = "remote"), allow(dead_code))]
+    Message(String),
+}
+
+/// Runs the script's `when launched` handlers.
+///
+/// # Arguments
+/// * `interpreter` - The script's interpreter
+/// * `failed` - Reports a handler's error
+///
+/// # Returns
+/// The frames the handlers played, to show once before the script's own
+/// animation; empty if they didn't play anything
+fn run_launch_handlers(interpreter: &mut interpreter::Interpreter, failed: impl FnOnce(&error::GizmoError)) -> Vec<Frame> {
+    let script_version = interpreter.sequence_version();
+    if let Err(e) = interpreter.handle_launch_event() {
+        failed(&e);
+    }
+    interpreter.end_handler_batch();
+    if interpreter.sequence_version() != script_version {
+        interpreter.get_animation_frames()
+    } else {
+        Vec::new()
+    }
+}
+
+/// Saves the status `gizmo status` reports, if the playing script or
+/// animation changed since it was last saved.
+///
+/// # Arguments
+/// * `status` - The status saved last; `None` when not running as the daemon
+/// * `instance` - Instance the GUI process runs
+/// * `script` - The .gzmo file being played
+/// * `sequence` - The frames on screen
+/// * `interpreter` - The script's interpreter, for the frame duration
+#[cfg(feature = "gui")]
+fn report_status(
+    status: &mut Option<daemon::GuiStatus>,
+    instance: &str,
+    script: &Path,
+    sequence: &sequence::Sequence,
+    interpreter: &interpreter::Interpreter,
+) {
+    let Some(status) = status else {
+        return;
+    };
+    let file = script.to_string_lossy();
+    let (frame_count, frame_ms) = (sequence.frame_count(), interpreter.get_frame_duration_ms());
+    if status.file == file && status.frame_count == frame_count && status.frame_ms == frame_ms {
+        return;
+    }
+    status.file = file.into_owned();
+    status.frame_count = frame_count;
+    status.frame_ms = frame_ms;
+    if let Err(e) = daemon::save_gui_status(instance, status) {
+        eprintln!("Failed to save status: {}", e);
+    }
+}
+
+/// Saves a script's `persist` variables, if it declares any.
+///
+/// Saving also stamps the time, which the next session's `away_seconds` counts from.
+///
+/// # Arguments
+/// * `script` - The .gzmo file the state belongs to
+/// * `interpreter` - The script's interpreter
+fn save_pet_state(script: &Path, interpreter: &interpreter::Interpreter) {
+    let values = interpreter.persisted_values();
+    if values.is_empty() {
+        return;
+    }
+    if let Err(e) = persist::save(script, &values) {
+        eprintln!("Failed to save pet state: {}", e);
+    }
+}
+
+/// Compiles a .gzmo file dropped onto the buddy.
+///
+/// # Arguments
+/// * `path` - The dropped file
+/// * `options` - Window options; `--fg` / `--bg` apply to the new script too
+///
+/// # Returns
+/// * `Ok((frames, interpreter))` - The new script, ready to take over
+/// * `Err` - Not a .gzmo file, or it failed to compile or run
+#[cfg(feature = "gui")]
+fn load_dropped_script(
+    path: &Path,
+    options: &GuiOptions,
+) -> Result<(Vec<Frame>, interpreter::Interpreter), Box<dyn std::error::Error>> {
+    let file = path.to_str().ok_or("File path is not valid UTF-8")?;
+    if !bundle::is_playable(file) {
+        return Err("File must have .gzmo or .gzmopack extension".into());
+    }
+    let (frames, mut interpreter) = load_gizmo_animation(file, None)?;
+    options.apply_colors(&mut interpreter)?;
+    Ok((frames, interpreter))
+}
+
+/// Collects the bounds of every connected monitor in physical pixels.
+#[cfg(feature = "gui")]
+fn monitor_bounds(window: &winit::window::Window) -> Vec<placement::Bounds> {
+    window
+        .available_monitors()
+        .map(|monitor| {
+            let (origin, size) = (monitor.position(), monitor.size());
+            placement::Bounds::new(origin.x, origin.y, size.width, size.height)
+        })
+        .collect()
+}
+
 /// Loads and processes a .gzmo script file into executable animation frames.
 ///
 /// This function orchestrates the complete compilation pipeline:
-/// 1. **File Loading**: Reads the .gzmo script file from disk
+/// 1. **File Loading**: Reads the .gzmo script file from disk (a bundle's
+///    main script, unpacked by [`bundle::resolve`])
 /// 2. **Lexical Analysis**: Tokenizes the source code into language tokens
 /// 3. **Parsing**: Builds an Abstract Syntax Tree using operator precedence parsing
 /// 4. **Interpretation**: Executes the script to generate animation frames
-/// 5. **Frame Extraction**: Retrieves the final frames and timing information
+/// 5. **Frame Extraction**: Retrieves the final frames; the interpreter is returned
+///    as well since its clock drives playback timing
 ///
 /// # Arguments
-/// * `gzmo_file` - Path to the .gzmo script file to process
+/// * `gzmo_file` - Path to the .gzmo script or .gzmopack bundle to process
+/// * `deterministic` - Seed for a reproducible run: `random()` is seeded, the
+///   time and system variables are fixed and persisted state is left out
 ///
 /// # Returns
-/// * `Ok((frames, duration_ms))` - Animation frames and timing on success
+/// * `Ok((frames, interpreter))` - Animation frames and the interpreter owning the playback clock
 /// * `Err` - Compilation or execution error with descriptive message
 ///
 /// # Error Handling
@@ -455,55 +2833,83 @@ fn run_desktop_window(gzmo_file: &str) -> Result<(), Box<dyn std::error::Error>>
 /// If the script produces no animation frames, the function will:
 /// 1. Try to use the interpreter's current frame state
 /// 2. Fall back to a default smiley face pattern if nothing else is available
-fn load_gizmo_animation(gzmo_file: &str) -> Result<(Vec<Frame>, u64), Box<dyn std::error::Error>> {
-    let content = fs::read_to_string(gzmo_file)?;
+fn load_gizmo_animation(gzmo_file: &str, deterministic: Option<u64>) -> Result<(Vec<Frame>, interpreter::Interpreter), Box<dyn std::error::Error>> {
+    // A bundle runs its main script, unpacked next to the files it loads
+    let script = bundle::resolve(Path::new(gzmo_file))?;
+    let content = fs::read_to_string(&script)?;
     
     // LEXICAL ANALYSIS PHASE
     // Convert source code into a stream of tokens for parsing
     let mut lexer = lexer::Lexer::new(&content);
-    let tokens = match lexer.tokenize() {
+    let (tokens, positions) = match lexer.tokenize_with_positions() {
         Ok(tokens) => tokens,
         Err(e) => {
             eprintln!("Lexical analysis error: {}", e);
-            return Err(format!("Script parsing failed: {}", e).into());
+            return Err(format!("Script parsing failed: {}", e.report(&content)).into());
         }
     };
     
     // PARSING PHASE
     // Build Abstract Syntax Tree using operator precedence parsing
-    let mut parser = parser::Parser::new(tokens);
+    let mut parser = parser::Parser::with_positions(tokens, positions);
     let ast = match parser.parse() {
         Ok(ast) => ast,
         Err(e) => {
             eprintln!("Parse error: {}", e);
-            return Err(format!("Script parsing failed: {}", e).into());
+            return Err(format!("Script parsing failed: {}", e.report(&content)).into());
         }
     };
     
     // INTERPRETATION PHASE
     // Execute the AST to generate animation frames and extract timing
     let mut interpreter = interpreter::Interpreter::new();
+    if let Some(dir) = script.parent() {
+        interpreter.set_script_dir(dir);
+    }
+    
+    // `persist` variables pick up where the last session left off, unless
+    // every run has to start the same
+    match deterministic {
+        Some(seed) => interpreter.set_deterministic(seed),
+        None => interpreter.restore_persisted(persist::load(Path::new(gzmo_file))),
+    }
+    
+    // Patterns the last start rendered are taken from its cache
+    interpreter.set_pattern_cache(cache::load(Path::new(gzmo_file)));
+    
+    // Scripts can read system load from the start
+    #[cfg(feature = "sysinfo")]
+    interpreter.set_system_stats(&match deterministic {
+        Some(_) => stats::SystemStats::IDLE,
+        None => stats::Sampler::new().sample(),
+    });
     
     if let Err(e) = interpreter.execute(&ast) {
         eprintln!("Execution error: {}", e);
-        return Err(format!("Script execution failed: {}", e).into());
+        return Err(format!("Script execution failed: {}", e.report(&content)).into());
+    }
+    
+    // Handlers render without it, so it doesn't grow while the buddy runs
+    if let Some(cache) = interpreter.take_pattern_cache() {
+        if let Err(e) = cache::save(Path::new(gzmo_file), cache) {
+            eprintln!("Failed to save pattern cache: {}", e);
+        }
     }
     
-    // Extract animation frames and timing from interpreter
+    // Extract animation frames from interpreter
     let frames = interpreter.get_animation_frames();
-    let frame_duration_ms = interpreter.get_frame_duration_ms();
     
     if frames.is_empty() {
         // If no animation, create a single frame from current state
         if let Some(current_frame) = interpreter.get_current_frame() {
-            return Ok((vec![current_frame], frame_duration_ms));
+            return Ok((vec![current_frame], interpreter));
         } else {
             // Create a default smiley face if nothing else
-            return Ok((vec![create_default_smiley()], frame_duration_ms));
+            return Ok((vec![create_default_smiley()], interpreter));
         }
     }
     
-    Ok((frames, frame_duration_ms))
+    Ok((frames, interpreter))
 }
 
 /// Creates a default smiley face animation frame as a fallback.
@@ -525,97 +2931,30 @@ fn load_gizmo_animation(gzmo_file: &str) -> Result<(Vec<Frame>, u64), Box<dyn st
 /// - Smile curve from (55-73, 75) with connecting diagonal lines
 fn create_default_smiley() -> Frame {
     // Create a simple smiley face pattern
-    let mut data = vec![vec![false; 128]; 128];
+    let mut data = vec![vec![0u8; 128]; 128];
     
     // Simple smiley in the center
     let _center_x = 64;
     let _center_y = 64;
     
     // Eyes
-    for x in 50..=58 {
-        for y in 50..=58 {
-            data[y][x] = true;
-        }
-    }
-    for x in 70..=78 {
-        for y in 50..=58 {
-            data[y][x] = true;
-        }
+    for row in &mut data[50..=58] {
+        row[50..=58].fill(1);
+        row[70..=78].fill(1);
     }
     
     // Smile
-    for x in 55..=73 {
-        data[75][x] = true;
-        data[80][x] = true;
-    }
-    data[76][55] = true;
-    data[77][56] = true;
-    data[78][57] = true;
-    data[79][58] = true;
+    data[75][55..=73].fill(1);
+    data[80][55..=73].fill(1);
+    data[76][55] = 1;
+    data[77][56] = 1;
+    data[78][57] = 1;
+    data[79][58] = 1;
     
-    data[76][73] = true;
-    data[77][72] = true;
-    data[78][71] = true;
-    data[79][70] = true;
+    data[76][73] = 1;
+    data[77][72] = 1;
+    data[78][71] = 1;
+    data[79][70] = 1;
     
     Frame::new(data)
 }
-
-/// Renders a Gizmo frame to a pixel buffer for display.
-///
-/// This function handles the conversion from Gizmo's boolean pixel format
-/// to the 32-bit ARGB format expected by the graphics system. It includes
-/// automatic scaling to fit the frame content to the window size.
-///
-/// # Arguments
-/// * `buffer` - Mutable slice of 32-bit pixels to write to (ARGB format)
-/// * `frame` - The Gizmo frame containing boolean pixel data
-/// * `width` - Target buffer width in pixels
-/// * `height` - Target buffer height in pixels
-///
-/// # Scaling Behavior
-/// - Automatically scales frame content to fit the window dimensions
-/// - Maintains aspect ratio by using the same scaling factor for both axes
-/// - Uses nearest-neighbor sampling for pixel-perfect scaling
-///
-/// # Color Mapping
-/// - `true` pixels (on) → `0xFFFFFF` (white)
-/// - `false` pixels (off) → `0x000000` (black)
-///
-/// # Safety
-/// Uses bounds checking when writing to the buffer to prevent crashes
-/// from mismatched buffer sizes.
-fn draw_frame_to_buffer(buffer: &mut [u32], frame: &Frame, width: usize, height: usize) {
-    let frame_data = frame.get_data();
-    let frame_height = frame_data.len();
-    let frame_width = if frame_height > 0 { frame_data[0].len() } else { 0 };
-    
-    // Calculate scaling factors to fit frame to window
-    // Uses floating-point arithmetic for smooth scaling
-    let scale_x = width as f32 / frame_width as f32;
-    let scale_y = height as f32 / frame_height as f32;
-    
-    // Render each window pixel by sampling from the frame
-    for y in 0..height {
-        for x in 0..width {
-            // Map window coordinates back to frame coordinates
-            // Using nearest-neighbor sampling for pixel-perfect results
-            let frame_x = (x as f32 / scale_x) as usize;
-            let frame_y = (y as f32 / scale_y) as usize;
-            
-            if frame_y < frame_height && frame_x < frame_width {
-                // Convert boolean pixel to 32-bit ARGB color
-                let pixel = if frame_data[frame_y][frame_x] {
-                    0xFFFFFF // White for "on" pixels
-                } else {
-                    0x000000 // Black for "off" pixels
-                };
-                
-                // Safely write to buffer with bounds checking
-                if let Some(buf_pixel) = buffer.get_mut(y * width + x) {
-                    *buf_pixel = pixel;
-                }
-            }
-        }
-    }
-}
\ No newline at end of file
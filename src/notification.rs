@@ -0,0 +1,60 @@
+//! Desktop Notifications (`notify` feature)
+//!
+//! Shows the native notifications scripts request with `notify("title",
+//! "body")` - for example a pomodoro buddy announcing that a break is due.
+//! The interpreter only queues them; the window loop hands each batch to
+//! [`show_all`] after the script loads and after every event handler.
+//!
+//! ## Backends
+//!
+//! With the `notify` feature, notifications go through `notify-rust`:
+//!
+//! - **Linux / BSD**: The freedesktop notification service over D-Bus
+//! - **macOS**: Notification Center (via `mac-notification-sys`)
+//! - **Windows**: Toast notifications
+//!
+//! Without the feature, or when the backend fails, the notification is
+//! written to standard error instead so the script keeps running.
+
+use std::thread;
+
+/// Shows notifications in order on a background thread.
+///
+/// Talking to the notification service can block (D-Bus round trips, a
+/// slow Notification Center), so this never delays the next frame.
+///
+/// # Arguments
+/// * `notifications` - (title, body) pairs from `Interpreter::take_notifications`
+pub fn show_all(notifications: Vec<(String, String)>) {
+    if notifications.is_empty() {
+        return;
+    }
+    let spawned = thread::Builder::new()
+        .name("gizmo-notify".to_string())
+        .spawn(move || {
+            for (title, body) in notifications {
+                show(&title, &body);
+            }
+        });
+    if let Err(e) = spawned {
+        eprintln!("Could not show notifications: {}", e);
+    }
+}
+
+/// Shows one notification, blocking until the backend accepts it.
+fn show(title: &str, body: &str) {
+    #[cfg(feature = "notify")]
+    {
+        let result = notify_rust::Notification::new()
+            .appname("Gizmo")
+            .summary(title)
+            .body(body)
+            .show();
+        if let Err(e) = result {
+            eprintln!("Notification failed ({}): {} - {}", e, title, body);
+        }
+    }
+
+    #[cfg(not(feature = "notify"))]
+    eprintln!("Notification: {} - {}", title, body);
+}
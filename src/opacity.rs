@@ -0,0 +1,118 @@
+//! Window Opacity and Fades
+//!
+//! Scripts set the buddy's overall opacity with `opacity(0.0..1.0)`, and the
+//! window fades in when it starts and fades out when it is stopped.
+//!
+//! ## How Opacity Is Applied
+//!
+//! - **macOS / Windows**: The whole window's alpha is set natively
+//!   (`NSWindow.alphaValue` / a layered window), so frames are drawn unchanged.
+//! - **Elsewhere**: Every pixel's alpha is scaled before it is written to the
+//!   window buffer, which relies on the transparent window being composited.
+//!
+//! [`set_native_opacity`] reports which of the two applies.
+
+use std::time::{Duration, Instant};
+use winit::window::Window;
+
+/// How long fading in on start and fading out on stop take.
+pub const FADE_DURATION: Duration = Duration::from_millis(300);
+
+/// A linear opacity change over time.
+#[derive(Debug, Clone)]
+pub struct Fade {
+    from: f64,
+    to: f64,
+    started: Instant,
+    duration: Duration,
+}
+
+impl Fade {
+    /// Starts a fade from one opacity to another lasting [`FADE_DURATION`].
+    pub fn new(from: f64, to: f64, started: Instant) -> Self {
+        Self {
+            from,
+            to,
+            started,
+            duration: FADE_DURATION,
+        }
+    }
+
+    /// Returns the opacity at `now`.
+    pub fn value(&self, now: Instant) -> f64 {
+        let progress = (now.saturating_duration_since(self.started).as_secs_f64()
+            / self.duration.as_secs_f64())
+        .min(1.0);
+        self.from + (self.to - self.from) * progress
+    }
+
+    /// Returns `true` once the fade has reached its target.
+    pub fn is_finished(&self, now: Instant) -> bool {
+        now.saturating_duration_since(self.started) >= self.duration
+    }
+}
+
+/// Sets the whole window's opacity using the platform compositor, if it can.
+///
+/// # Arguments
+/// * `window` - The buddy window
+/// * `opacity` - Opacity from 0.0 (invisible) to 1.0 (opaque)
+///
+/// # Returns
+/// `true` if the platform applied it; `false` means the caller must scale
+/// per-pixel alpha instead
+pub fn set_native_opacity(window: &Window, opacity: f64) -> bool {
+    let opacity = opacity.clamp(0.0, 1.0);
+
+    #[cfg(target_os = "macos")]
+    {
+        use objc::runtime::Object;
+        use objc::*;
+        use raw_window_handle::{HasWindowHandle, RawWindowHandle};
+
+        // SAFETY: The NSView comes from winit's live window handle and
+        // setAlphaValue: is a standard NSWindow method.
+        unsafe {
+            if let Ok(handle) = window.window_handle() {
+                if let RawWindowHandle::AppKit(appkit_handle) = handle.as_raw() {
+                    let ns_view = appkit_handle.ns_view.as_ptr() as *mut Object;
+                    let ns_window: *mut Object = msg_send![ns_view, window];
+                    let _: () = msg_send![ns_window, setAlphaValue: opacity];
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    #[cfg(windows)]
+    {
+        use raw_window_handle::{HasWindowHandle, RawWindowHandle};
+        use windows_sys::Win32::UI::WindowsAndMessaging::{
+            GetWindowLongPtrW, SetLayeredWindowAttributes, SetWindowLongPtrW, GWL_EXSTYLE,
+            LWA_ALPHA, WS_EX_LAYERED,
+        };
+
+        // SAFETY: The HWND comes from winit's live window handle. Adding
+        // WS_EX_LAYERED is required before SetLayeredWindowAttributes applies.
+        unsafe {
+            if let Ok(handle) = window.window_handle() {
+                if let RawWindowHandle::Win32(win32_handle) = handle.as_raw() {
+                    let hwnd = win32_handle.hwnd.get() as _;
+                    let style = GetWindowLongPtrW(hwnd, GWL_EXSTYLE);
+                    if style & WS_EX_LAYERED as isize == 0 {
+                        SetWindowLongPtrW(hwnd, GWL_EXSTYLE, style | WS_EX_LAYERED as isize);
+                    }
+                    return SetLayeredWindowAttributes(hwnd, 0, (opacity * 255.0).round() as u8, LWA_ALPHA) != 0;
+                }
+            }
+        }
+        false
+    }
+
+    #[cfg(not(any(target_os = "macos", windows)))]
+    {
+        let _ = (window, opacity);
+        false
+    }
+}
@@ -0,0 +1,253 @@
+//! External Displays
+//!
+//! `--stream <target>` (repeatable) sends every frame the window draws to
+//! another display as well, such as an LED matrix driven by a microcontroller,
+//! so the buddy shows up there while its window runs:
+//!
+//! ```text
+//! gizmo start --stream udp://192.168.1.40:7777 buddy.gzmo
+//! gizmo start --stream serial:///dev/ttyACM0?baud=921600 buddy.gzmo
+//! ```
+//!
+//! | Target                         | Sends each frame as                                |
+//! |--------------------------------|----------------------------------------------------|
+//! | `udp://host:port`              | One datagram                                       |
+//! | `serial://<port>[?baud=N]`     | Bytes on a serial port (115200 baud by default; `serial` feature) |
+//!
+//! Displays are [`FrameOutput`]s, so other kinds only need to implement it.
+//! Each one runs on its own thread: a display that is slower than the
+//! animation (a serial line at 115200 baud moves about 11 KB a second) skips
+//! frames instead of holding up the window.
+//!
+//! ## Protocol
+//!
+//! Every frame is one self-contained packet, little-endian:
+//!
+//! | Offset | Size      | Field                                              |
+//! |--------|-----------|----------------------------------------------------|
+//! | 0      | 2         | Magic `GZ` (`0x47 0x5A`)                           |
+//! | 2      | 1         | Version, 1                                         |
+//! | 3      | 1         | Flags, 0 (reserved)                                |
+//! | 4      | 2         | Width                                              |
+//! | 6      | 2         | Height                                             |
+//! | 8      | 2         | Sequence number, counting frames and wrapping      |
+//! | 10     | w × h × 3 | RGB pixels, rows top to bottom                     |
+//! | end    | 1         | Checksum: XOR of the pixel bytes                   |
+//!
+//! Colors less than half opaque are sent as black (off). On a serial line a
+//! receiver finds the next packet by looking for the magic and version, and
+//! drops packets whose checksum doesn't match; a gap in the sequence numbers
+//! means frames were skipped.
+
+use crate::ast::Frame;
+use crate::palette::Palette;
+use std::fmt;
+use std::io;
+#[cfg(feature = "serial")]
+use std::io::Write;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::str::FromStr;
+use std::sync::mpsc::{self, SyncSender};
+
+/// First bytes of every packet
+const MAGIC: [u8; 2] = *b"GZ";
+/// Protocol version written in every packet
+const VERSION: u8 = 1;
+/// Bytes before the pixels
+const HEADER_LEN: usize = 10;
+/// Largest UDP payload (over IPv4)
+const MAX_DATAGRAM: usize = 65_507;
+/// Serial speed when the target doesn't name one
+const DEFAULT_BAUD: u32 = 115_200;
+
+/// A display frames are sent to, besides the window.
+pub trait FrameOutput: Send {
+    /// Shows a frame.
+    ///
+    /// # Arguments
+    /// * `frame` - The frame the window drew
+    /// * `palette` - Colors of the frame's palette indices
+    fn send(&mut self, frame: &Frame, palette: &Palette) -> io::Result<()>;
+}
+
+/// Encodes a frame as one packet of the streaming protocol.
+///
+/// # Arguments
+/// * `frame` - The frame to send
+/// * `palette` - Colors of its palette indices
+/// * `sequence` - The packet's sequence number
+pub fn encode(frame: &Frame, palette: &Palette, sequence: u16) -> Vec<u8> {
+    let mut packet = Vec::with_capacity(HEADER_LEN + frame.width * frame.height * 3 + 1);
+    packet.extend_from_slice(&MAGIC);
+    packet.extend_from_slice(&[VERSION, 0]);
+    packet.extend_from_slice(&(frame.width as u16).to_le_bytes());
+    packet.extend_from_slice(&(frame.height as u16).to_le_bytes());
+    packet.extend_from_slice(&sequence.to_le_bytes());
+
+    let mut checksum = 0;
+    for row in frame.pixels.iter() {
+        for &index in row {
+            let argb = palette.color(index);
+            let rgb = if argb >> 24 >= 0x80 { [(argb >> 16) as u8, (argb >> 8) as u8, argb as u8] } else { [0; 3] };
+            checksum = rgb.iter().fold(checksum, |sum, byte| sum ^ byte);
+            packet.extend_from_slice(&rgb);
+        }
+    }
+    packet.push(checksum);
+    packet
+}
+
+/// Where `--stream` sends frames.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Target {
+    /// A UDP address, `host:port`
+    Udp(String),
+    /// A serial port, e.g. `/dev/ttyACM0` or `COM3`
+    Serial { port: String, baud: u32 },
+}
+
+impl FromStr for Target {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        if let Some(address) = text.strip_prefix("udp://") {
+            if !address.contains(':') {
+                return Err(format!("'{}' names no port (udp://host:port)", text));
+            }
+            return Ok(Target::Udp(address.to_string()));
+        }
+        let Some(rest) = text.strip_prefix("serial://") else {
+            return Err(format!("Can't stream to '{}' (expected udp://host:port or serial://port)", text));
+        };
+        let (port, baud) = match rest.split_once("?baud=") {
+            Some((port, baud)) => (port, baud.parse().map_err(|_| format!("Invalid baud rate '{}'", baud))?),
+            None => (rest, DEFAULT_BAUD),
+        };
+        if port.is_empty() {
+            return Err(format!("'{}' names no serial port", text));
+        }
+        Ok(Target::Serial { port: port.to_string(), baud })
+    }
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Target::Udp(address) => write!(f, "udp://{}", address),
+            Target::Serial { port, baud } => write!(f, "serial://{}?baud={}", port, baud),
+        }
+    }
+}
+
+impl Target {
+    /// Opens the display.
+    ///
+    /// # Returns
+    /// * `Ok(output)` - Ready to send frames
+    /// * `Err` - The address doesn't resolve, the port can't be opened, or
+    ///   serial ports need the `serial` feature
+    pub fn open(&self) -> Result<Box<dyn FrameOutput>, Box<dyn std::error::Error>> {
+        match self {
+            Target::Udp(address) => {
+                let address = address
+                    .to_socket_addrs()?
+                    .next()
+                    .ok_or_else(|| format!("{} doesn't resolve to an address", address))?;
+                let local: SocketAddr = if address.is_ipv6() { "[::]:0" } else { "0.0.0.0:0" }.parse()?;
+                let socket = UdpSocket::bind(local)?;
+                socket.connect(address)?;
+                Ok(Box::new(Udp { socket, sequence: 0 }))
+            }
+            #[cfg(feature = "serial")]
+            Target::Serial { port, baud } => {
+                let port = serialport::new(port.as_str(), *baud).open()?;
+                Ok(Box::new(Serial { port, sequence: 0 }))
+            }
+            #[cfg(not(feature = "serial"))]
+            Target::Serial { .. } => {
+                Err("Streaming to a serial port needs a build with the `serial` feature (cargo build --release --features serial)".into())
+            }
+        }
+    }
+}
+
+/// Frames as UDP datagrams.
+struct Udp {
+    socket: UdpSocket,
+    /// Sequence number of the next packet
+    sequence: u16,
+}
+
+impl FrameOutput for Udp {
+    fn send(&mut self, frame: &Frame, palette: &Palette) -> io::Result<()> {
+        let packet = encode(frame, palette, self.sequence);
+        if packet.len() > MAX_DATAGRAM {
+            return Err(io::Error::other(format!(
+                "a {}x{} frame doesn't fit in one UDP packet", frame.width, frame.height
+            )));
+        }
+        self.sequence = self.sequence.wrapping_add(1);
+        // A display that isn't listening yet refuses datagrams; try the next frame
+        match self.socket.send(&packet) {
+            Err(e) if e.kind() == io::ErrorKind::ConnectionRefused => Ok(()),
+            result => result.map(|_| ()),
+        }
+    }
+}
+
+/// Frames as bytes on a serial port.
+#[cfg(feature = "serial")]
+struct Serial {
+    port: Box<dyn serialport::SerialPort>,
+    /// Sequence number of the next packet
+    sequence: u16,
+}
+
+#[cfg(feature = "serial")]
+impl FrameOutput for Serial {
+    fn send(&mut self, frame: &Frame, palette: &Palette) -> io::Result<()> {
+        let packet = encode(frame, palette, self.sequence);
+        self.sequence = self.sequence.wrapping_add(1);
+        self.port.write_all(&packet)
+    }
+}
+
+/// A display fed from its own thread.
+pub struct Stream {
+    /// Frames waiting for the thread; holds at most one
+    sender: SyncSender<(Frame, Palette)>,
+}
+
+impl Stream {
+    /// Starts sending frames to an output on a new thread.
+    ///
+    /// # Arguments
+    /// * `name` - What to call the output in error messages
+    /// * `output` - The display
+    pub fn start(name: String, mut output: Box<dyn FrameOutput>) -> Self {
+        let (sender, receiver) = mpsc::sync_channel::<(Frame, Palette)>(1);
+        std::thread::spawn(move || {
+            let mut failing = false;
+            for (frame, palette) in receiver {
+                // Report a failure once, not for every frame until it recovers
+                match output.send(&frame, &palette) {
+                    Ok(()) => failing = false,
+                    Err(e) if !failing => {
+                        eprintln!("Streaming to {} failed: {}", name, e);
+                        failing = true;
+                    }
+                    Err(_) => {}
+                }
+            }
+        });
+        Self { sender }
+    }
+
+    /// Hands a frame to the thread, or drops it if the display is still
+    /// busy with the last one.
+    pub fn send(&self, frame: &Frame, palette: &Palette) {
+        // A full channel means the display is behind; the thread only ends
+        // with the process
+        let _ = self.sender.try_send((frame.clone(), palette.clone()));
+    }
+}
@@ -0,0 +1,225 @@
+//! Frame Pacing
+//!
+//! Tracks when frames actually reach the screen and schedules wake-ups so new
+//! frames line up with the display's refresh instead of landing at arbitrary
+//! points between two refreshes.
+//!
+//! ## Refresh Alignment
+//!
+//! With a known refresh interval the pacer assumes refreshes happen on a grid
+//! anchored at the last present, and rounds each wake-up up to that grid:
+//!
+//! ```text
+//! refreshes   |-------|-------|-------|-------|     (16.7ms at 60 Hz)
+//! tick due            ^  due at 22ms
+//! wake-up                     ^  next refresh (33ms)
+//! ```
+//!
+//! This also covers animations faster than the display: a frame can't be
+//! shown more often than once per refresh, so the loop sleeps until the next
+//! one. Without a known refresh rate the window loop sleeps until the exact
+//! moment the next tick is due. Either way it never polls, and a wake-up that
+//! comes late shows the current frame, skipping the ones in between.
+//!
+//! ## Statistics
+//!
+//! Every present is recorded along with how many animation ticks passed since
+//! the previous one. More than one tick means frames were computed but never
+//! shown - those are counted as dropped. The window loop logs a summary,
+//! including the measured frame rate, to stderr every [`LOG_INTERVAL`] and when
+//! it exits.
+//!
+//! ## Falling Behind
+//!
+//! Presents are also counted in windows of [`LAG_WINDOW`]. When a whole
+//! window's frames came clearly slower than both the animation's frame
+//! duration and the display's refresh, the machine can't keep up: the clock
+//! still keeps time, but the animation skips frames. The window loop warns
+//! once when that starts and, with `--adapt-speed`, slows the animation down
+//! to the rate it actually reaches. The last window's rate is what
+//! `gizmo status` reports.
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// How often the window loop logs pacing statistics.
+pub const LOG_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How long presents are counted before deciding whether they keep up.
+pub const LAG_WINDOW: Duration = Duration::from_secs(5);
+
+/// How much slower than due frames must come to count as falling behind
+const LAG_FACTOR: f64 = 1.25;
+
+/// Counters describing how smoothly frames have been presented.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PacingStats {
+    /// Frames put on screen
+    pub presented: u64,
+    /// Animation frames skipped because a present came too late
+    pub dropped: u64,
+    /// Longest gap between two presents
+    pub worst_interval: Duration,
+    /// Sum of all gaps between presents (for the average)
+    total_interval: Duration,
+}
+
+impl PacingStats {
+    /// Counts one present.
+    ///
+    /// # Arguments
+    /// * `interval` - Time since the previous present, if there was one
+    /// * `dropped` - Animation frames skipped since the previous present
+    fn record(&mut self, interval: Option<Duration>, dropped: u64) {
+        if let Some(interval) = interval {
+            self.total_interval += interval;
+            self.worst_interval = self.worst_interval.max(interval);
+        }
+        self.dropped += dropped;
+        self.presented += 1;
+    }
+
+    /// Average time between two presents, if at least two happened.
+    pub fn average_interval(&self) -> Option<Duration> {
+        (self.presented > 1).then(|| self.total_interval / (self.presented - 1) as u32)
+    }
+
+    /// Frames presented per second on average, if at least two were presented.
+    pub fn fps(&self) -> Option<f64> {
+        self.average_interval()
+            .filter(|average| !average.is_zero())
+            .map(|average| 1.0 / average.as_secs_f64())
+    }
+}
+
+impl fmt::Display for PacingStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} presented, {} dropped", self.presented, self.dropped)?;
+        if let Some(fps) = self.fps() {
+            write!(f, ", {:.1} fps", fps)?;
+        }
+        if let Some(average) = self.average_interval() {
+            write!(
+                f,
+                ", {:.1}ms average / {:.1}ms worst between frames",
+                average.as_secs_f64() * 1000.0,
+                self.worst_interval.as_secs_f64() * 1000.0
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Records presentation times and plans refresh-aligned wake-ups.
+#[derive(Debug, Clone)]
+pub struct FramePacer {
+    /// Time between two display refreshes, when the monitor reports it
+    refresh_interval: Option<Duration>,
+    /// When the previous frame was presented
+    last_present: Option<Instant>,
+    /// Clock tick of the previous present
+    last_tick: Option<u64>,
+    stats: PacingStats,
+    /// Presents in the current lag window, and when it started
+    window: (PacingStats, Option<Instant>),
+    /// Presents in the last complete lag window
+    recent: Option<PacingStats>,
+}
+
+impl FramePacer {
+    /// Creates a pacer for a display with the given refresh rate.
+    ///
+    /// # Arguments
+    /// * `refresh_millihertz` - Monitor refresh rate in mHz (winit's unit), if known
+    pub fn new(refresh_millihertz: Option<u32>) -> Self {
+        Self {
+            refresh_interval: refresh_millihertz
+                .filter(|&rate| rate > 0)
+                .map(|rate| Duration::from_secs_f64(1000.0 / rate as f64)),
+            last_present: None,
+            last_tick: None,
+            stats: PacingStats::default(),
+            window: (PacingStats::default(), None),
+            recent: None,
+        }
+    }
+
+    /// Records that a frame was just presented.
+    ///
+    /// # Arguments
+    /// * `now` - Time of the present
+    /// * `tick` - Animation clock tick the presented frame belongs to, or
+    ///   `None` for still images, where skipped ticks don't lose anything
+    pub fn record_present(&mut self, now: Instant, tick: Option<u64>) {
+        let interval = self.last_present.map(|last| now.saturating_duration_since(last));
+        let dropped = match (self.last_tick, tick) {
+            (Some(last_tick), Some(tick)) => tick.saturating_sub(last_tick).saturating_sub(1),
+            _ => 0,
+        };
+        self.stats.record(interval, dropped);
+
+        let (window, started) = &mut self.window;
+        let started = *started.get_or_insert(now);
+        window.record(interval, dropped);
+        if now.saturating_duration_since(started) >= LAG_WINDOW {
+            self.recent = Some(std::mem::take(window));
+            self.window.1 = Some(now);
+        }
+
+        self.last_present = Some(now);
+        self.last_tick = tick;
+    }
+
+    /// Forgets the previous present, e.g. after playback was paused while
+    /// hidden, so the pause isn't counted as a slow or dropped frame.
+    pub fn restart(&mut self) {
+        self.last_present = None;
+        self.last_tick = None;
+        self.window = (PacingStats::default(), None);
+        self.recent = None;
+    }
+
+    /// Rounds a wake-up time up to the next expected display refresh.
+    ///
+    /// # Arguments
+    /// * `due` - When the next frame is due by the animation clock
+    ///
+    /// # Returns
+    /// `due` itself when the refresh rate or the last present is unknown
+    pub fn align(&self, due: Instant) -> Instant {
+        let (interval, anchor) = match (self.refresh_interval, self.last_present) {
+            (Some(interval), Some(anchor)) => (interval, anchor),
+            _ => return due,
+        };
+
+        let since_anchor = due.saturating_duration_since(anchor);
+        let refreshes = since_anchor.as_nanos().div_ceil(interval.as_nanos()).max(1);
+        anchor + interval * refreshes as u32
+    }
+
+    /// Returns the statistics gathered so far.
+    pub fn stats(&self) -> &PacingStats {
+        &self.stats
+    }
+
+    /// Returns the statistics of the last complete [`LAG_WINDOW`].
+    pub fn recent(&self) -> Option<&PacingStats> {
+        self.recent.as_ref()
+    }
+
+    /// Checks whether the last lag window's frames came too slowly for the
+    /// animation.
+    ///
+    /// # Arguments
+    /// * `frame_duration` - How long the animation shows each frame
+    ///
+    /// # Returns
+    /// * `Some(interval)` - Frames were dropped and came this far apart on
+    ///   average, clearly slower than due and than the display refreshes
+    /// * `None` - Playback keeps up, or there is no complete window yet
+    pub fn falling_behind(&self, frame_duration: Duration) -> Option<Duration> {
+        let recent = self.recent.as_ref().filter(|recent| recent.dropped > 0)?;
+        let due = frame_duration.max(self.refresh_interval.unwrap_or_default());
+        recent.average_interval().filter(|average| *average > due.mul_f64(LAG_FACTOR))
+    }
+}
@@ -0,0 +1,120 @@
+//! Persistent Pet State
+//!
+//! Lets virtual-pet scripts remember things between sessions. A script
+//! declares its counters with `persist`; the declared value is only the
+//! starting point for a brand new pet, and every later start picks up where
+//! the last session left off:
+//!
+//! ```gizmo
+//! persist hunger = 0;
+//! persist happiness = 50;
+//!
+//! // Catch up on the time the pet spent closed
+//! hunger = hunger + floor(away_seconds / 600);
+//!
+//! every 600000 do
+//!     hunger = hunger + 1;
+//! end;
+//!
+//! when clicked do
+//!     happiness = happiness < 100 ? happiness + 5 : 100;
+//! end;
+//! ```
+//!
+//! ## Storage
+//!
+//! State lives in `{config_dir}/pets/<script name>.json`, keyed by the
+//! script's file name without `.gzmo`, so editing or moving the script keeps
+//! the pet. Only numbers and strings are stored:
+//!
+//! ```json
+//! { "saved_at": 1760600000, "values": { "hunger": 12, "happiness": 55 } }
+//! ```
+//!
+//! The window saves whenever a handler changes a persisted value and once
+//! more as it exits, which also records when the pet was put away for the
+//! next session's `away_seconds`.
+
+use crate::daemon;
+use gizmo_core::persist::{Persisted, SavedState};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Everything a pet's state file holds.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct StateFile {
+    /// Seconds since the Unix epoch when the state was written
+    saved_at: u64,
+    /// Persisted variables by name
+    values: HashMap<String, Persisted>,
+}
+
+/// Returns the state file for a script.
+fn state_path(script: &Path) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let name = script
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .ok_or("Script file name is not valid UTF-8")?;
+    let dir = daemon::get_config_dir()?.join("pets");
+    fs::create_dir_all(&dir)?;
+    Ok(dir.join(format!("{}.json", name)))
+}
+
+/// Reads the state a script saved last time.
+///
+/// A missing or unreadable state file starts a new pet; an unreadable one is
+/// reported on stderr and left in place until the next save.
+///
+/// # Arguments
+/// * `script` - Path of the .gzmo file
+pub fn load(script: &Path) -> SavedState {
+    let path = match state_path(script) {
+        Ok(path) => path,
+        Err(e) => {
+            eprintln!("Can't locate saved pet state: {}", e);
+            return SavedState::default();
+        }
+    };
+    let Ok(content) = fs::read_to_string(&path) else {
+        return SavedState::default();
+    };
+    let file: StateFile = match serde_json::from_str(&content) {
+        Ok(file) => file,
+        Err(e) => {
+            eprintln!("Ignoring unreadable pet state {}: {}", path.display(), e);
+            return SavedState::default();
+        }
+    };
+
+    let saved_at = UNIX_EPOCH + Duration::from_secs(file.saved_at);
+    SavedState {
+        values: file.values,
+        away: SystemTime::now().duration_since(saved_at).unwrap_or_default(),
+    }
+}
+
+/// Writes a script's persisted variables, stamped with the current time.
+///
+/// # Arguments
+/// * `script` - Path of the .gzmo file
+/// * `values` - Persisted variables by name
+///
+/// # Returns
+/// * `Ok(())` - State written
+/// * `Err` - The config directory or state file couldn't be written
+pub fn save(script: &Path, values: &HashMap<String, Persisted>) -> Result<(), Box<dyn std::error::Error>> {
+    let path = state_path(script)?;
+    let file = StateFile {
+        saved_at: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+        values: values.clone(),
+    };
+
+    // Write then rename so a crash mid-write can't lose the pet
+    let temporary = path.with_extension("json.tmp");
+    fs::write(&temporary, serde_json::to_string_pretty(&file)?)?;
+    fs::rename(&temporary, &path)?;
+    Ok(())
+}
@@ -0,0 +1,91 @@
+//! Power Events (`sysinfo` feature)
+//!
+//! Turns the battery readings of the `stats` module into the moments scripts
+//! react to, so a buddy can yawn and dim as the laptop runs low:
+//!
+//! | Event           | Fires when                                               |
+//! |-----------------|----------------------------------------------------------|
+//! | `unplugged`     | The system switches from external power to battery       |
+//! | `plugged_in`    | The system switches back to external power               |
+//! | `battery < N`   | The charge drops below `N` percent while on battery      |
+//! | `battery_low`   | Shorthand for `battery < 20`                             |
+//!
+//! Readings are taken every [`SAMPLE_INTERVAL`](crate::stats::SAMPLE_INTERVAL).
+//!
+//! ## Thresholds
+//!
+//! A `battery < N` threshold fires once when the charge drops below it on
+//! battery power - including at startup, if the buddy is launched on an
+//! already low battery - and is re-armed once the system is plugged in or the
+//! charge climbs back to `N`:
+//!
+//! ```text
+//! charge    35 ── 25 ── 19 ── 15 ── (plugged in) ── 40 ── (unplugged) ── 18
+//! < 20                  ▲                                              ▲
+//! ```
+
+use crate::stats::SystemStats;
+
+/// A change in power state worth telling the script about.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PowerChange {
+    /// Switched to battery power
+    Unplugged,
+    /// Switched to external power
+    PluggedIn,
+    /// The charge dropped below this many percent on battery
+    BatteryBelow(u32),
+}
+
+/// Remembers the last power state to report transitions.
+#[derive(Debug, Default)]
+pub struct PowerWatch {
+    /// Whether the last sample was on battery (`None` before the first one)
+    on_battery: Option<bool>,
+    /// Thresholds that already fired and haven't been re-armed
+    fired: Vec<u32>,
+}
+
+impl PowerWatch {
+    /// Creates a watch that hasn't seen a sample yet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Compares a new sample with the previous one.
+    ///
+    /// The first sample never reports `Unplugged` / `PluggedIn` (scripts can
+    /// read `on_battery` at startup), but does report thresholds already
+    /// passed.
+    ///
+    /// # Arguments
+    /// * `stats` - The latest sample
+    /// * `thresholds` - Percentages of the script's `battery < N` handlers
+    ///
+    /// # Returns
+    /// The changes in the order handlers should run: power source first, then
+    /// thresholds from the highest down
+    pub fn changes(&mut self, stats: &SystemStats, thresholds: &[u32]) -> Vec<PowerChange> {
+        let mut changes = Vec::new();
+        match self.on_battery.replace(stats.on_battery) {
+            Some(false) if stats.on_battery => changes.push(PowerChange::Unplugged),
+            Some(true) if !stats.on_battery => changes.push(PowerChange::PluggedIn),
+            _ => {}
+        }
+
+        // Re-arm thresholds once plugged in or charged back up
+        self.fired
+            .retain(|&threshold| stats.on_battery && stats.battery_percent < threshold as f64);
+
+        let mut low: Vec<u32> = thresholds
+            .iter()
+            .copied()
+            .filter(|&threshold| stats.on_battery && stats.battery_percent < threshold as f64)
+            .filter(|threshold| !self.fired.contains(threshold))
+            .collect();
+        low.sort_unstable_by(|a, b| b.cmp(a));
+        self.fired.extend(&low);
+        changes.extend(low.into_iter().map(PowerChange::BatteryBelow));
+        changes
+    }
+}
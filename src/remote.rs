@@ -0,0 +1,203 @@
+//! Remote Messages
+//!
+//! Lets other programs poke the buddy: `--subscribe <url>` (repeatable)
+//! subscribes the GUI process to an MQTT topic or a WebSocket, and every
+//! message that arrives runs the script's `when message(text)` handlers, so a
+//! doorbell or a failed CI build can make the pet react:
+//!
+//! ```text
+//! gizmo start --subscribe mqtt://homeassistant.local/gizmo/events buddy.gzmo
+//! gizmo start --subscribe ws://localhost:8080/events buddy.gzmo
+//! ```
+//!
+//! ```gizmo
+//! when message("doorbell") do
+//!     play(jump_frames);
+//! end;
+//! ```
+//!
+//! ## Sources
+//!
+//! - `mqtt://[user:password@]host[:port]/topic` - An MQTT broker (port 1883
+//!   by default). The topic may use the `+` and `#` wildcards
+//! - `ws://host[:port]/path` - A WebSocket server; each text message is a message
+//!
+//! A message is its payload as text with surrounding whitespace removed, and
+//! matches handlers by exact text. Messages without a handler are ignored.
+//! Encrypted connections (`mqtts://`, `wss://`) aren't supported.
+//!
+//! Each subscription runs on its own thread and hands messages to the window
+//! loop as [`GuiEvent::Message`](crate::GuiEvent::Message). A lost connection
+//! is retried every 5 seconds. Subscribing needs a build with the `remote`
+//! feature.
+
+#[cfg(feature = "remote")]
+use crate::GuiEvent;
+use std::fmt;
+use std::str::FromStr;
+#[cfg(feature = "remote")]
+use std::time::Duration;
+#[cfg(feature = "remote")]
+use winit::event_loop::EventLoopProxy;
+
+/// How long to wait before connecting again after a connection failed or dropped
+#[cfg(feature = "remote")]
+const RETRY_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Port of MQTT brokers when the URL doesn't name one
+const MQTT_PORT: u16 = 1883;
+
+/// A `--subscribe <url>` option.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Subscription {
+    /// An MQTT topic on a broker
+    Mqtt {
+        host: String,
+        port: u16,
+        /// User name and password, if the broker wants them
+        credentials: Option<(String, String)>,
+        topic: String,
+    },
+    /// A WebSocket URL
+    WebSocket(String),
+}
+
+impl FromStr for Subscription {
+    type Err = String;
+
+    fn from_str(url: &str) -> Result<Self, Self::Err> {
+        if url.starts_with("ws://") {
+            return Ok(Subscription::WebSocket(url.to_string()));
+        }
+        let Some(rest) = url.strip_prefix("mqtt://") else {
+            return Err(format!("Can't subscribe to '{}' (expected an mqtt:// or ws:// URL)", url));
+        };
+
+        let (address, topic) = rest.split_once('/').unwrap_or((rest, ""));
+        if topic.is_empty() {
+            return Err(format!("'{}' names no topic (mqtt://host/topic)", url));
+        }
+        let (credentials, address) = match address.rsplit_once('@') {
+            Some((user, address)) => {
+                let (name, password) = user.split_once(':').unwrap_or((user, ""));
+                (Some((name.to_string(), password.to_string())), address)
+            }
+            None => (None, address),
+        };
+        let (host, port) = match address.rsplit_once(':') {
+            Some((host, port)) => {
+                let port = port.parse().map_err(|_| format!("Invalid port '{}' in '{}'", port, url))?;
+                (host, port)
+            }
+            None => (address, MQTT_PORT),
+        };
+        if host.is_empty() {
+            return Err(format!("'{}' names no host", url));
+        }
+
+        Ok(Subscription::Mqtt { host: host.to_string(), port, credentials, topic: topic.to_string() })
+    }
+}
+
+impl fmt::Display for Subscription {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Subscription::Mqtt { host, port, credentials, topic } => {
+                write!(f, "mqtt://")?;
+                if let Some((name, password)) = credentials {
+                    write!(f, "{}:{}@", name, password)?;
+                }
+                write!(f, "{}:{}/{}", host, port, topic)
+            }
+            Subscription::WebSocket(url) => write!(f, "{}", url),
+        }
+    }
+}
+
+/// Subscribes to a source on a new thread, forwarding its messages to the
+/// window loop until the loop exits.
+///
+/// # Arguments
+/// * `subscription` - A parsed `--subscribe` option
+/// * `proxy` - Event loop to notify
+#[cfg(feature = "remote")]
+pub fn subscribe(subscription: Subscription, proxy: EventLoopProxy<GuiEvent>) {
+    std::thread::spawn(move || {
+        let deliver = |payload: &[u8]| {
+            let text = String::from_utf8_lossy(payload).trim().to_string();
+            proxy.send_event(GuiEvent::Message(text)).is_ok()
+        };
+        match subscription {
+            Subscription::Mqtt { host, port, credentials, topic } => mqtt(&host, port, credentials, &topic, deliver),
+            Subscription::WebSocket(url) => websocket(&url, deliver),
+        }
+    });
+}
+
+/// Receives the messages published on an MQTT topic, reconnecting when the
+/// broker goes away, until `deliver` returns `false`.
+#[cfg(feature = "remote")]
+fn mqtt(host: &str, port: u16, credentials: Option<(String, String)>, topic: &str, deliver: impl Fn(&[u8]) -> bool) {
+    use rumqttc::{Client, Event, MqttOptions, Packet, QoS};
+
+    let mut options = MqttOptions::new(format!("gizmo-{}", std::process::id()), host, port);
+    options.set_keep_alive(Duration::from_secs(30));
+    if let Some((name, password)) = credentials {
+        options.set_credentials(name, password);
+    }
+    let (client, mut connection) = Client::new(options, 10);
+
+    for notification in connection.iter() {
+        match notification {
+            // A new session forgets subscriptions, so subscribe on every connect
+            Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                if let Err(e) = client.try_subscribe(topic, QoS::AtMostOnce) {
+                    eprintln!("Couldn't subscribe to {}: {}", topic, e);
+                }
+            }
+            Ok(Event::Incoming(Packet::Publish(publish))) => {
+                if !deliver(&publish.payload) {
+                    return;
+                }
+            }
+            Ok(_) => {}
+            Err(e) => {
+                eprintln!("MQTT connection to {}:{} failed: {}", host, port, e);
+                std::thread::sleep(RETRY_INTERVAL);
+            }
+        }
+    }
+}
+
+/// Receives the messages of a WebSocket, reconnecting when it closes, until
+/// `deliver` returns `false`.
+#[cfg(feature = "remote")]
+fn websocket(url: &str, deliver: impl Fn(&[u8]) -> bool) {
+    use tungstenite::Message;
+
+    loop {
+        match tungstenite::connect(url) {
+            Ok((mut socket, _)) => loop {
+                match socket.read() {
+                    Ok(Message::Text(text)) => {
+                        if !deliver(text.as_bytes()) {
+                            return;
+                        }
+                    }
+                    Ok(Message::Binary(bytes)) => {
+                        if !deliver(&bytes) {
+                            return;
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(e) => {
+                        eprintln!("WebSocket {} closed: {}", url, e);
+                        break;
+                    }
+                }
+            },
+            Err(e) => eprintln!("Couldn't connect to {}: {}", url, e),
+        }
+        std::thread::sleep(RETRY_INTERVAL);
+    }
+}
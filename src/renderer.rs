@@ -0,0 +1,429 @@
+//! Frame Presentation Backends
+//!
+//! This module puts animation frames on screen. Two backends are available:
+//!
+//! - **Software** (default): Scales the frame on the CPU into a `softbuffer`
+//!   surface with [`draw_frame_to_buffer`]. Works everywhere, no GPU needed.
+//!   Each frame is scaled once per window size and palette; showing it again
+//!   copies the cached window-sized buffer. The surface is only resized when
+//!   the window is, and a frame isn't copied at all when the buffer being
+//!   drawn into still holds it from the last present.
+//! - **GPU** (`--renderer gpu`, requires the `gpu` cargo feature): Uploads the
+//!   unscaled frame as a texture with `pixels`/`wgpu` and lets the GPU do the
+//!   scaling. Worth it for 1-5ms animations and large windows, where scaling
+//!   every window pixel on the CPU dominates frame time.
+//!
+//! Both backends use the same letterboxed integer scaling and palette colors,
+//! so switching renderers doesn't change how a buddy looks. If the GPU backend
+//! fails to initialize, the software backend is used instead.
+
+use crate::ast::Frame;
+use crate::palette::Palette;
+use softbuffer::{Context, Surface};
+use std::collections::VecDeque;
+use std::num::NonZeroU32;
+use std::rc::Rc;
+use std::sync::Arc;
+use winit::window::Window;
+
+/// Which backend the user asked for on the command line.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RendererKind {
+    /// CPU scaling into a softbuffer surface
+    Software,
+    /// GPU scaling through pixels/wgpu
+    Gpu,
+}
+
+impl RendererKind {
+    /// Parses a `--renderer` value.
+    ///
+    /// # Arguments
+    /// * `name` - `"software"` or `"gpu"`
+    ///
+    /// # Returns
+    /// * `Ok(RendererKind)` - Known renderer available in this build
+    /// * `Err` - Unknown name, or `"gpu"` in a build without the `gpu` feature
+    pub fn parse(name: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        match name {
+            "software" => Ok(RendererKind::Software),
+            "gpu" if cfg!(feature = "gpu") => Ok(RendererKind::Gpu),
+            "gpu" => Err("This build has no GPU renderer; rebuild with `cargo build --release --features gpu`".into()),
+            _ => Err(format!("Unknown renderer '{}' (expected software or gpu)", name).into()),
+        }
+    }
+
+    /// Returns the command-line name of this renderer.
+    pub fn name(&self) -> &'static str {
+        match self {
+            RendererKind::Software => "software",
+            RendererKind::Gpu => "gpu",
+        }
+    }
+}
+
+/// An initialized presentation backend bound to the buddy window.
+pub enum Renderer {
+    /// CPU scaling into a softbuffer surface
+    Software {
+        /// Keeps the display connection alive for the surface
+        _context: Context<Rc<Window>>,
+        surface: Surface<Rc<Window>, Rc<Window>>,
+        /// Current surface size (window dimensions), once there is one
+        surface_size: Option<(u32, u32)>,
+        /// What the last presented buffer shows: the frame (`None` for just
+        /// the background) and its palette
+        presented: Option<(Option<Frame>, Palette)>,
+        /// Frames already scaled to the window
+        cache: Box<ScaledCache>,
+    },
+    /// GPU scaling through pixels/wgpu
+    #[cfg(feature = "gpu")]
+    Gpu {
+        pixels: Box<pixels::Pixels>,
+        /// Current texture size (frame dimensions)
+        buffer_size: (u32, u32),
+        /// Current surface size (window dimensions)
+        surface_size: (u32, u32),
+    },
+}
+
+impl Renderer {
+    /// Creates the requested renderer for a window.
+    ///
+    /// # Arguments
+    /// * `kind` - Backend to use
+    /// * `window` - The buddy window
+    ///
+    /// # Returns
+    /// * `Ok(Renderer)` - Ready to draw; the GPU backend falls back to software on failure
+    /// * `Err` - The software surface itself could not be created
+    pub fn new(kind: RendererKind, window: &Rc<Window>) -> Result<Self, Box<dyn std::error::Error>> {
+        #[cfg(feature = "gpu")]
+        if kind == RendererKind::Gpu {
+            match Self::new_gpu(window) {
+                Ok(renderer) => return Ok(renderer),
+                Err(e) => eprintln!("GPU renderer unavailable ({}), using software rendering", e),
+            }
+        }
+        #[cfg(not(feature = "gpu"))]
+        let _ = kind;
+
+        let context = Context::new(window.clone())?;
+        let surface = Surface::new(&context, window.clone())?;
+        Ok(Renderer::Software {
+            _context: context,
+            surface,
+            surface_size: None,
+            presented: None,
+            cache: Box::default(),
+        })
+    }
+
+    #[cfg(feature = "gpu")]
+    fn new_gpu(window: &Rc<Window>) -> Result<Self, Box<dyn std::error::Error>> {
+        let size = window.inner_size();
+        let surface_texture = pixels::SurfaceTexture::new(size.width, size.height, window.as_ref());
+        let pixels = pixels::PixelsBuilder::new(1, 1, surface_texture)
+            .clear_color(pixels::wgpu::Color::TRANSPARENT)
+            .build()?;
+
+        Ok(Renderer::Gpu {
+            pixels: Box::new(pixels),
+            buffer_size: (1, 1),
+            surface_size: (size.width, size.height),
+        })
+    }
+
+    /// Draws one frame, letterboxed on the palette background, and presents it.
+    ///
+    /// # Arguments
+    /// * `frame` - Frame to show, or `None` for just the background
+    /// * `palette` - Colors for the frame's pixel indices
+    /// * `width`, `height` - Current window inner size in physical pixels
+    ///
+    /// # Returns
+    /// * `Ok(())` - Presented (or skipped because the window has no area)
+    /// * `Err` - The backend failed to present
+    pub fn draw(
+        &mut self,
+        frame: Option<&Frame>,
+        palette: &Palette,
+        width: u32,
+        height: u32,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let (Some(nz_width), Some(nz_height)) = (NonZeroU32::new(width), NonZeroU32::new(height)) else {
+            return Ok(()); // Minimized or not yet laid out
+        };
+
+        match self {
+            Renderer::Software { surface, surface_size, presented, cache, .. } => {
+                if *surface_size != Some((width, height)) {
+                    surface.resize(nz_width, nz_height)?;
+                    *surface_size = Some((width, height));
+                    *presented = None;
+                }
+                let mut buffer = surface.buffer_mut()?;
+                let frame = frame.filter(|frame| frame.width > 0 && frame.height > 0);
+
+                // An age of 1 means this buffer is the one presented last time
+                if buffer.age() != 1 || !shows(presented, frame, palette) {
+                    // Copy the scaled frame if there is one, else just the palette background
+                    match frame {
+                        Some(frame) => {
+                            buffer.copy_from_slice(cache.scaled(frame, palette, width as usize, height as usize))
+                        }
+                        None => buffer.fill(palette.pixel(0)),
+                    }
+                    match presented {
+                        Some((shown, shown_palette)) if shown_palette == palette => *shown = frame.cloned(),
+                        _ => *presented = Some((frame.cloned(), palette.clone())),
+                    }
+                }
+
+                buffer.present()?;
+            }
+            #[cfg(feature = "gpu")]
+            Renderer::Gpu { pixels, buffer_size, surface_size } => {
+                if *surface_size != (width, height) {
+                    pixels.resize_surface(width, height)?;
+                    *surface_size = (width, height);
+                }
+
+                // The texture matches the frame; the GPU scales it to the window
+                let frame_size = frame
+                    .filter(|frame| frame.width > 0 && frame.height > 0)
+                    .map_or((1, 1), |frame| (frame.width as u32, frame.height as u32));
+                if *buffer_size != frame_size {
+                    pixels.resize_buffer(frame_size.0, frame_size.1)?;
+                    *buffer_size = frame_size;
+                }
+
+                // Letterbox margins use the background color
+                let [r, g, b, a] = argb_to_rgba(palette.pixel(0));
+                pixels.clear_color(pixels::wgpu::Color {
+                    r: r as f64 / 255.0,
+                    g: g as f64 / 255.0,
+                    b: b as f64 / 255.0,
+                    a: a as f64 / 255.0,
+                });
+
+                let texture = pixels.frame_mut();
+                match frame.filter(|frame| frame.width > 0 && frame.height > 0) {
+                    Some(frame) => {
+                        for (texel, &index) in texture.chunks_exact_mut(4).zip(frame.pixels.iter().flatten()) {
+                            texel.copy_from_slice(&argb_to_rgba(palette.pixel(index)));
+                        }
+                    }
+                    // Nothing to show: a single background texel
+                    None => texture.copy_from_slice(&argb_to_rgba(palette.pixel(0))),
+                }
+
+                pixels.render()?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Whether the last presented buffer shows a frame (or just the background
+/// for `None`) in a palette.
+fn shows(presented: &Option<(Option<Frame>, Palette)>, frame: Option<&Frame>, palette: &Palette) -> bool {
+    let Some((shown, shown_palette)) = presented else {
+        return false;
+    };
+    shown_palette == palette
+        && match (shown, frame) {
+            (Some(shown), Some(frame)) => Arc::ptr_eq(&shown.pixels, &frame.pixels),
+            (None, None) => true,
+            _ => false,
+        }
+}
+
+/// Converts a packed `0xAARRGGBB` color into RGBA bytes for GPU textures.
+#[cfg(feature = "gpu")]
+fn argb_to_rgba(argb: u32) -> [u8; 4] {
+    let [a, r, g, b] = argb.to_be_bytes();
+    [r, g, b, a]
+}
+
+/// Most memory the software renderer keeps scaled frames in
+const SCALED_CACHE_BYTES: usize = 64 * 1024 * 1024;
+
+/// Frames the software renderer has scaled to the window.
+///
+/// Buffers are made for one window size and palette and thrown away when
+/// either changes. They are found by the frame's shared pixels, so a frame
+/// that is shown again (every loop of an animation) is only scaled once.
+#[derive(Default)]
+pub struct ScaledCache {
+    /// Window size and palette the buffers were made for
+    target: Option<(usize, usize, Palette)>,
+    /// Sampling positions for the last frame size scaled
+    lookup: Option<ScaleLookup>,
+    /// Window-sized buffers with the frames they show, oldest first
+    buffers: VecDeque<(Frame, Vec<u32>)>,
+}
+
+impl ScaledCache {
+    /// Returns a frame scaled into a window-sized buffer, scaling it first
+    /// if it isn't cached yet.
+    ///
+    /// # Arguments
+    /// * `frame` - Frame to show (non-empty)
+    /// * `palette` - Colors for the frame's pixel indices
+    /// * `width`, `height` - Window size in pixels
+    fn scaled(&mut self, frame: &Frame, palette: &Palette, width: usize, height: usize) -> &[u32] {
+        if !matches!(&self.target, Some((w, h, p)) if (*w, *h) == (width, height) && p == palette) {
+            self.target = Some((width, height, palette.clone()));
+            self.buffers.clear();
+        }
+
+        let index = match self.buffers.iter().position(|(cached, _)| Arc::ptr_eq(&cached.pixels, &frame.pixels)) {
+            Some(index) => index,
+            None => {
+                let frame_size = (frame.width, frame.height);
+                let lookup = match &mut self.lookup {
+                    Some(lookup) if lookup.frame_size == frame_size && lookup.window_size == (width, height) => lookup,
+                    lookup => lookup.insert(ScaleLookup::new(frame_size, width, height)),
+                };
+                let mut buffer = vec![palette.pixel(0); width * height];
+                draw_frame_to_buffer(&mut buffer, frame, palette, lookup);
+
+                // Keep at least the frame being shown
+                let per_buffer = width * height * std::mem::size_of::<u32>();
+                while !self.buffers.is_empty() && (self.buffers.len() + 1) * per_buffer > SCALED_CACHE_BYTES {
+                    self.buffers.pop_front();
+                }
+                self.buffers.push_back((frame.clone(), buffer));
+                self.buffers.len() - 1
+            }
+        };
+        &self.buffers[index].1
+    }
+}
+
+/// Which frame pixel each covered window pixel shows, for one frame size in
+/// one window size.
+#[derive(Debug, Clone)]
+struct ScaleLookup {
+    /// Frame width and height the lookup was made for
+    frame_size: (usize, usize),
+    /// Window width and height the lookup was made for
+    window_size: (usize, usize),
+    layout: FrameLayout,
+    /// Frame row of each scaled row
+    rows: Vec<usize>,
+    /// Frame column of each scaled column
+    columns: Vec<usize>,
+}
+
+impl ScaleLookup {
+    /// Maps window coordinates back to frame coordinates, nearest neighbor.
+    fn new((frame_width, frame_height): (usize, usize), width: usize, height: usize) -> Self {
+        let layout = frame_layout(frame_width, frame_height, width, height);
+        let source = |position: usize, size: usize| ((position as f32 / layout.scale) as usize).min(size - 1);
+        Self {
+            frame_size: (frame_width, frame_height),
+            window_size: (width, height),
+            rows: (0..layout.scaled_height).map(|y| source(y, frame_height)).collect(),
+            columns: (0..layout.scaled_width).map(|x| source(x, frame_width)).collect(),
+            layout,
+        }
+    }
+}
+
+/// Renders a Gizmo frame to a pixel buffer for display.
+///
+/// This function handles the conversion from Gizmo's palette-index pixel format
+/// to the 32-bit ARGB format expected by the graphics system, scaled and
+/// letterboxed as the lookup says. The caller is expected to have cleared the
+/// buffer to the background color.
+///
+/// # Arguments
+/// * `buffer` - Mutable slice of 32-bit pixels to write to (ARGB format)
+/// * `frame` - The Gizmo frame containing palette-index pixel data
+/// * `palette` - Colors for each pixel index
+/// * `lookup` - Where each frame pixel goes, made for this frame and buffer size
+///
+/// # Scaling Behavior
+/// - Scales by the largest whole factor that fits both window dimensions, so
+///   every frame pixel becomes an identical NxN block
+/// - Uses the same factor on both axes, preserving the frame's aspect ratio
+/// - Centers the scaled frame; the margins keep the background clear color
+/// - Frames larger than the window are shrunk uniformly instead
+/// - Uses nearest-neighbor sampling for pixel-perfect scaling
+///
+/// # Color Mapping
+/// Each pixel's index is looked up in the palette set by `palette()`. The
+/// default palette maps index 0 (off) to fully transparent and index 1 (on)
+/// to white. Colors are written premultiplied so the compositor can blend them.
+///
+/// # Safety
+/// Uses bounds checking when writing to the buffer to prevent crashes
+/// from mismatched buffer sizes.
+fn draw_frame_to_buffer(buffer: &mut [u32], frame: &Frame, palette: &Palette, lookup: &ScaleLookup) {
+    let FrameLayout { offset_x, offset_y, .. } = lookup.layout;
+    let (width, _) = lookup.window_size;
+
+    // Convert palette indices to premultiplied 32-bit ARGB colors once per
+    // frame row, then spread them over the scaled rows
+    let mut scaled_row = vec![0u32; lookup.columns.len()];
+    let mut last_row = None;
+    for (y, &frame_y) in lookup.rows.iter().enumerate() {
+        if last_row != Some(frame_y) {
+            let source = &frame.pixels[frame_y];
+            for (pixel, &frame_x) in scaled_row.iter_mut().zip(&lookup.columns) {
+                *pixel = palette.pixel(source[frame_x]);
+            }
+            last_row = Some(frame_y);
+        }
+
+        // Safely write to buffer with bounds checking
+        let row_start = (offset_y + y) * width + offset_x;
+        if let Some(row) = buffer.get_mut(row_start..row_start + scaled_row.len()) {
+            row.copy_from_slice(&scaled_row);
+        }
+    }
+}
+
+/// Where a frame lands inside the window after letterboxed scaling.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameLayout {
+    /// Window pixels per frame pixel (a whole number unless shrinking)
+    pub scale: f32,
+    /// Left margin in window pixels
+    pub offset_x: usize,
+    /// Top margin in window pixels
+    pub offset_y: usize,
+    /// Width of the scaled frame in window pixels
+    pub scaled_width: usize,
+    /// Height of the scaled frame in window pixels
+    pub scaled_height: usize,
+}
+
+/// Computes the letterboxed placement of a frame in a window.
+///
+/// Picks one scale factor for both axes: the largest whole number that fits,
+/// or a uniform fractional shrink when the frame is larger than the window.
+/// The scaled frame is centered, leaving margins around it.
+///
+/// # Arguments
+/// * `frame_width`, `frame_height` - Frame size in frame pixels (non-zero)
+/// * `width`, `height` - Window size in window pixels
+pub fn frame_layout(frame_width: usize, frame_height: usize, width: usize, height: usize) -> FrameLayout {
+    let fit = (width as f32 / frame_width as f32).min(height as f32 / frame_height as f32);
+    let scale = if fit >= 1.0 { fit.floor() } else { fit };
+
+    let scaled_width = ((frame_width as f32 * scale) as usize).min(width);
+    let scaled_height = ((frame_height as f32 * scale) as usize).min(height);
+
+    FrameLayout {
+        scale,
+        offset_x: (width - scaled_width) / 2,
+        offset_y: (height - scaled_height) / 2,
+        scaled_width,
+        scaled_height,
+    }
+}
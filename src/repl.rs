@@ -0,0 +1,160 @@
+//! Interactive Mode
+//!
+//! `gizmo repl` reads statements from the terminal and runs them one at a
+//! time in a single interpreter, so what one line defines is there for the
+//! next. The value of an expression is printed, frames as text like
+//! `gizmo preview` draws them:
+//!
+//! ```text
+//! gizmo> frame dot = pattern(3, 3) { return col == 1 and row == 1; }
+//! gizmo> dot
+//! 3x3 frame
+//! ...
+//! .#.
+//! ...
+//! gizmo> sqrt(2) * 10
+//! 14.142135623730951
+//! ```
+//!
+//! A line that leaves a block or bracket open, or ends with an operator or
+//! comma, is continued on the next one. An error is reported and the
+//! interpreter carries on with the variables it had. Ctrl+D quits.
+
+use crate::ast::{Frame, Value};
+use crate::frame::FrameRenderer;
+use crate::interpreter::Interpreter;
+use crate::lexer::{Lexer, Token};
+use crate::parser::Parser;
+use std::io::{self, BufRead, IsTerminal, Write};
+
+/// Shown before a new statement
+const PROMPT: &str = "gizmo> ";
+/// Shown before each further line of an unfinished statement
+const CONTINUATION_PROMPT: &str = "  ...> ";
+
+/// Reads and runs statements from standard input until it ends.
+///
+/// Prompts are only shown when standard input is a terminal, so statements
+/// can also be piped in.
+///
+/// # Returns
+/// * `Ok(())` - Standard input ended
+/// * `Err` - Reading standard input or writing standard output failed
+pub fn run() -> io::Result<()> {
+    let interactive = io::stdin().is_terminal();
+    let mut input = io::stdin().lock();
+    let mut out = io::stdout().lock();
+    let mut interpreter = Interpreter::new();
+    let mut source = String::new();
+
+    if interactive {
+        writeln!(out, "Gizmo REPL - type statements to run them, Ctrl+D to quit")?;
+    }
+
+    loop {
+        if interactive {
+            write!(out, "{}", if source.is_empty() { PROMPT } else { CONTINUATION_PROMPT })?;
+            out.flush()?;
+        }
+
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            if interactive {
+                writeln!(out)?;
+            }
+            return Ok(());
+        }
+        source.push_str(&line);
+
+        let (tokens, positions) = match Lexer::new(&source).tokenize_with_positions() {
+            Ok(tokens) => tokens,
+            Err(e) => {
+                eprintln!("{}", e.report(&source));
+                source.clear();
+                continue;
+            }
+        };
+        if is_unfinished(&tokens) {
+            continue;
+        }
+        let typed = std::mem::take(&mut source);
+
+        let program = match Parser::with_positions(tokens, positions).parse() {
+            Ok(program) => program,
+            Err(e) => {
+                eprintln!("{}", e.report(&typed));
+                continue;
+            }
+        };
+        for statement in &program.statements {
+            match interpreter.execute_line(statement) {
+                Ok(Some(value)) => write!(out, "{}", show(&value))?,
+                Ok(None) => {}
+                Err(e) => {
+                    // The statements after it probably depend on it
+                    eprintln!("{}", e.report(&typed));
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Returns `true` if the statement typed so far goes on to the next line:
+/// a block or bracket is still open, or the last token needs something after it.
+fn is_unfinished(tokens: &[Token]) -> bool {
+    let mut depth = 0isize;
+    for token in tokens {
+        match token {
+            Token::Do | Token::Then | Token::LeftParen | Token::LeftBracket | Token::LeftBrace => depth += 1,
+            Token::End | Token::RightParen | Token::RightBracket | Token::RightBrace => depth -= 1,
+            _ => {}
+        }
+    }
+
+    let last = tokens.iter().rev().find(|token| !matches!(token, Token::Newline | Token::Eof));
+    depth > 0
+        || matches!(
+            last,
+            Some(
+                Token::Plus
+                    | Token::Minus
+                    | Token::Star
+                    | Token::Slash
+                    | Token::Percent
+                    | Token::Equal
+                    | Token::EqualEqual
+                    | Token::Greater
+                    | Token::Less
+                    | Token::GreaterEqual
+                    | Token::LessEqual
+                    | Token::And
+                    | Token::Or
+                    | Token::Comma
+                    | Token::Question
+                    | Token::Colon
+            )
+        )
+}
+
+/// Describes a value the way the REPL prints it, ending with a newline.
+fn show(value: &Value) -> String {
+    let ascii = |frame: &Frame| FrameRenderer::new(frame.width, frame.height).render_ascii(frame);
+    match value {
+        Value::Number(n) => format!("{}\n", n),
+        Value::String(s) => format!("\"{}\"\n", s),
+        Value::Strings(strings) => {
+            let quoted: Vec<String> = strings.iter().map(|s| format!("\"{}\"", s)).collect();
+            format!("[{}]\n", quoted.join(", "))
+        }
+        Value::Color(index) => format!("color({})\n", index),
+        Value::Frame(frame) => format!("{}x{} frame\n{}", frame.width, frame.height, ascii(frame)),
+        Value::Frames(frames) => {
+            let mut text = format!("{} frames\n", frames.len());
+            for (index, frame) in frames.iter().enumerate() {
+                text.push_str(&format!("Frame {}/{} ({}x{})\n{}", index + 1, frames.len(), frame.width, frame.height, ascii(frame)));
+            }
+            text
+        }
+    }
+}
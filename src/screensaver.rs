@@ -0,0 +1,146 @@
+//! Full-Screen Screensaver Mode
+//!
+//! `gizmo screensaver <file>` plays an animation full-screen on every
+//! connected monitor until the user touches the keyboard or mouse. It runs in
+//! the foreground (no daemon) and reuses the normal pipeline: the script is
+//! loaded the same way, frames come from the same fixed-step clock, and each
+//! monitor gets its own [`Renderer`] drawing with the usual letterboxed integer
+//! scaling - so a 64x64 sprite on a 1080p screen becomes 16x16 blocks.
+//!
+//! ## Exiting
+//!
+//! Any key press, mouse button, scroll, or cursor movement of more than
+//! [`WAKE_DISTANCE`] pixels ends the screensaver. Tiny cursor jitter (and the
+//! position report every window gets when it first appears) is ignored.
+
+use crate::ast::Frame;
+use crate::interpreter::Interpreter;
+use crate::renderer::{Renderer, RendererKind};
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::time::Instant;
+use winit::{
+    dpi::PhysicalPosition,
+    event::{Event, WindowEvent},
+    event_loop::{ControlFlow, EventLoop},
+    window::{Fullscreen, Window, WindowBuilder, WindowId},
+};
+
+/// How far (in physical pixels) the cursor must move to end the screensaver.
+pub const WAKE_DISTANCE: f64 = 8.0;
+
+/// One monitor's full-screen window.
+struct Screen {
+    window: Rc<Window>,
+    renderer: Renderer,
+    /// What is on screen: (frame index, width, height)
+    presented: Option<(usize, u32, u32)>,
+    /// First cursor position seen on this screen, for the wake distance
+    cursor_origin: Option<PhysicalPosition<f64>>,
+}
+
+/// Runs the screensaver until any input arrives.
+///
+/// # Arguments
+/// * `animation_frames` - Frames produced by the script
+/// * `interpreter` - The interpreter that ran the script (owns clock and palette)
+/// * `renderer_kind` - Presentation backend for every screen
+///
+/// # Returns
+/// * `Ok(())` - The user woke the screen
+/// * `Err` - No monitors, or a window or renderer could not be created
+pub fn run(
+    animation_frames: Vec<Frame>,
+    mut interpreter: Interpreter,
+    renderer_kind: RendererKind,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let event_loop = EventLoop::new()?;
+
+    let mut screens: HashMap<WindowId, Screen> = HashMap::new();
+    for monitor in event_loop.available_monitors() {
+        let window = Rc::new(
+            WindowBuilder::new()
+                .with_title("Gizmo")
+                .with_decorations(false)
+                .with_fullscreen(Some(Fullscreen::Borderless(Some(monitor))))
+                .build(&event_loop)?,
+        );
+        window.set_cursor_visible(false);
+        let renderer = Renderer::new(renderer_kind, &window)?;
+        screens.insert(
+            window.id(),
+            Screen {
+                window,
+                renderer,
+                presented: None,
+                cursor_origin: None,
+            },
+        );
+    }
+    if screens.is_empty() {
+        return Err("No monitors found".into());
+    }
+
+    let mut last_clock_update = Instant::now();
+    event_loop.run(move |event, elwt| {
+        elwt.set_control_flow(ControlFlow::Wait);
+
+        match event {
+            // Any deliberate input wakes the screen
+            Event::WindowEvent {
+                event:
+                    WindowEvent::KeyboardInput { .. }
+                    | WindowEvent::MouseInput { .. }
+                    | WindowEvent::MouseWheel { .. }
+                    | WindowEvent::CloseRequested,
+                ..
+            } => elwt.exit(),
+            Event::WindowEvent { event: WindowEvent::CursorMoved { position, .. }, window_id } => {
+                if let Some(screen) = screens.get_mut(&window_id) {
+                    let origin = *screen.cursor_origin.get_or_insert(position);
+                    if (position.x - origin.x).hypot(position.y - origin.y) > WAKE_DISTANCE {
+                        elwt.exit();
+                    }
+                }
+            }
+            Event::WindowEvent { event: WindowEvent::Resized(_), window_id } => {
+                if let Some(screen) = screens.get_mut(&window_id) {
+                    screen.presented = None;
+                    screen.window.request_redraw();
+                }
+            }
+            Event::WindowEvent { event: WindowEvent::RedrawRequested, window_id } => {
+                let frame_index = interpreter.clock().frame_index(animation_frames.len());
+                if let Some(screen) = screens.get_mut(&window_id) {
+                    let size = screen.window.inner_size();
+                    let key = (frame_index, size.width, size.height);
+                    if screen.presented == Some(key) {
+                        return;
+                    }
+
+                    let frame = animation_frames.get(frame_index);
+                    match screen.renderer.draw(frame, interpreter.palette(), size.width, size.height) {
+                        Ok(()) => screen.presented = Some(key),
+                        Err(e) => eprintln!("Error drawing frame: {}", e),
+                    }
+                }
+            }
+            Event::AboutToWait => {
+                let now = Instant::now();
+                interpreter.clock_mut().advance(now - last_clock_update);
+                last_clock_update = now;
+
+                let frame_index = interpreter.clock().frame_index(animation_frames.len());
+                for screen in screens.values() {
+                    if screen.presented.map(|(index, _, _)| index) != Some(frame_index) {
+                        screen.window.request_redraw();
+                    }
+                }
+                elwt.set_control_flow(ControlFlow::WaitUntil(now + interpreter.clock().until_next_tick()));
+            }
+            _ => {}
+        }
+    })?;
+
+    Ok(())
+}
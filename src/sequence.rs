@@ -0,0 +1,89 @@
+//! Playing Sequence
+//!
+//! What the window shows over time: the script's looping frames, optionally
+//! preceded by a one-off intro. When an event handler switches animations
+//! with `play()` / `loop()`, the transition frames from `transition()` become
+//! the intro, so the change blends in once and the new frames then loop.
+//!
+//! ```text
+//! ticks since switch   0  1  2 │ 3  4  5  6  7  8 ...
+//! frame shown          t0 t1 t2│ n0 n1 n2 n0 n1 n2 ...
+//!                      intro   │ looping frames
+//! ```
+
+use crate::ast::Frame;
+
+/// The frames currently playing and when they started.
+#[derive(Debug, Clone)]
+pub struct Sequence {
+    /// Frames shown once at the start (transition frames)
+    intro: Vec<Frame>,
+    /// Frames looped after the intro
+    frames: Vec<Frame>,
+    /// Clock tick at which the sequence started
+    start_tick: u64,
+}
+
+impl Sequence {
+    /// Creates a sequence that loops `frames` from tick 0.
+    pub fn new(frames: Vec<Frame>) -> Self {
+        Self {
+            intro: Vec::new(),
+            frames,
+            start_tick: 0,
+        }
+    }
+
+    /// Switches to new looping frames, played after `intro`.
+    ///
+    /// # Arguments
+    /// * `intro` - Frames to show once first (may be empty for a hard cut)
+    /// * `frames` - New looping frames
+    /// * `tick` - Current clock tick; the intro starts here
+    pub fn switch(&mut self, intro: Vec<Frame>, frames: Vec<Frame>, tick: u64) {
+        self.intro = intro;
+        self.frames = frames;
+        self.start_tick = tick;
+    }
+
+    /// Returns which frame of the sequence is visible at a clock tick.
+    ///
+    /// Positions below the intro length are intro frames; the rest index the
+    /// looping frames offset by the intro length. A position only identifies
+    /// a frame until the next [`switch`](Self::switch).
+    pub fn position(&self, tick: u64) -> usize {
+        let elapsed = tick.saturating_sub(self.start_tick);
+        let intro_len = self.intro.len() as u64;
+        if elapsed < intro_len || self.frames.is_empty() {
+            return elapsed.min(intro_len.saturating_sub(1)) as usize;
+        }
+        (intro_len + (elapsed - intro_len) % self.frames.len() as u64) as usize
+    }
+
+    /// Returns the frame at a position from [`position`](Self::position).
+    pub fn frame(&self, position: usize) -> Option<&Frame> {
+        match position.checked_sub(self.intro.len()) {
+            None => self.intro.get(position),
+            Some(index) => self.frames.get(index),
+        }
+    }
+
+    /// Returns how many frames loop after the intro.
+    #[cfg(feature = "gui")]
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Returns how many ticks the intro and one pass of the looping frames take.
+    #[cfg(feature = "gui")]
+    pub fn cycle_len(&self) -> usize {
+        self.intro.len() + self.frames.len()
+    }
+
+    /// Returns `true` when nothing changes over time: a single looping frame
+    /// with no intro left to play.
+    #[cfg(feature = "gui")]
+    pub fn is_still(&self, tick: u64) -> bool {
+        self.frames.len() <= 1 && tick.saturating_sub(self.start_tick) >= self.intro.len() as u64
+    }
+}
@@ -0,0 +1,237 @@
+//! Click-Through Window Shape
+//!
+//! The buddy window is a square, but only the sprite should catch the mouse.
+//! After every frame is presented, the window's input region is set to the
+//! frame's visible pixels, so clicks on the transparent parts land on
+//! whatever is underneath.
+//!
+//! ## Silhouette
+//!
+//! A pixel counts as part of the silhouette when its palette color isn't
+//! fully transparent. Each frame row is split into runs of visible pixels,
+//! scaled with the same letterbox layout the renderer uses, and runs that
+//! line up across rows are merged into taller rectangles:
+//!
+//! ```text
+//!  ..##..        rows 0-1: one 2x2 rect
+//!  ..##..
+//!  ######        row 2:    one 6x1 rect
+//! ```
+//!
+//! ## Platform Support
+//!
+//! - **X11**: Input shape from the XShape extension (drawing is unaffected)
+//! - **Windows**: Window region, which clips drawing and input alike
+//! - **macOS**: Nothing to do - clicks already pass through fully
+//!   transparent pixels of a non-opaque window
+//! - **Wayland / others**: The whole window keeps catching clicks
+
+use crate::ast::Frame;
+use crate::palette::Palette;
+use crate::renderer;
+use winit::window::Window;
+
+/// A rectangle in window coordinates (physical pixels).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Computes the window area covered by a frame's visible pixels.
+///
+/// # Arguments
+/// * `frame` - The frame being shown
+/// * `palette` - Colors used to decide which pixels are visible
+/// * `width`, `height` - Window size in physical pixels
+///
+/// # Returns
+/// Rectangles covering the silhouette. An opaque background (palette index 0)
+/// covers the whole window.
+pub fn silhouette(frame: &Frame, palette: &Palette, width: u32, height: u32) -> Vec<Rect> {
+    let is_visible = |index: u8| palette.color(index) >> 24 != 0;
+
+    if is_visible(0) {
+        return vec![Rect { x: 0, y: 0, width, height }];
+    }
+
+    let data = frame.get_data();
+    let frame_height = data.len();
+    let frame_width = data.first().map_or(0, |row| row.len());
+    if frame_width == 0 || frame_height == 0 || width == 0 || height == 0 {
+        return Vec::new();
+    }
+
+    let layout = renderer::frame_layout(frame_width, frame_height, width as usize, height as usize);
+    let mut rects: Vec<Rect> = Vec::new();
+    // Indices of rects ending at the current row, which the row may extend
+    let mut open: Vec<usize> = Vec::new();
+
+    for (frame_y, row) in data.iter().enumerate() {
+        let (top, bottom) = window_span(frame_y, frame_y + 1, frame_height, layout.scale, layout.scaled_height);
+        if top >= bottom {
+            continue;
+        }
+
+        let mut next_open = Vec::new();
+        let mut run_start = None;
+        for frame_x in 0..=frame_width {
+            let visible = row.get(frame_x).is_some_and(|&index| is_visible(index));
+            match (visible, run_start) {
+                (true, None) => run_start = Some(frame_x),
+                (false, Some(start)) => {
+                    run_start = None;
+                    let (left, right) = window_span(start, frame_x, frame_width, layout.scale, layout.scaled_width);
+                    if left >= right {
+                        continue;
+                    }
+                    let (x, run_width) = ((layout.offset_x + left) as i32, (right - left) as u32);
+
+                    // Grow the rect above when this run lines up with it
+                    match open.iter().find(|&&i| rects[i].x == x && rects[i].width == run_width) {
+                        Some(&i) => {
+                            rects[i].height += (bottom - top) as u32;
+                            next_open.push(i);
+                        }
+                        None => {
+                            rects.push(Rect {
+                                x,
+                                y: (layout.offset_y + top) as i32,
+                                width: run_width,
+                                height: (bottom - top) as u32,
+                            });
+                            next_open.push(rects.len() - 1);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        open = next_open;
+    }
+
+    rects
+}
+
+/// Maps frame pixels `[start, end)` on one axis to window pixels `[lo, hi)`,
+/// matching the renderer's nearest-neighbor sampling.
+fn window_span(start: usize, end: usize, count: usize, scale: f32, scaled: usize) -> (usize, usize) {
+    let edge = |i: usize| ((i as f32 * scale).ceil() as usize).min(scaled);
+    // The renderer clamps the last frame pixel to the edge of the scaled frame
+    let hi = if end == count { scaled } else { edge(end) };
+    (edge(start), hi)
+}
+
+/// Keeps the window's input region in sync with the presented frame.
+pub struct InputShape {
+    /// Silhouette currently applied, to skip redundant updates
+    applied: Option<Vec<Rect>>,
+    /// Our own X11 connection and the buddy's window id, when on X11
+    #[cfg(target_os = "linux")]
+    x11: Option<(x11rb::rust_connection::RustConnection, u32)>,
+}
+
+impl InputShape {
+    /// Prepares shaping for the given window.
+    ///
+    /// On X11 this opens a separate connection to the X server; if that fails
+    /// the window simply keeps its full input region.
+    pub fn new(window: &Window) -> Self {
+        #[cfg(target_os = "linux")]
+        let x11 = {
+            use raw_window_handle::{HasWindowHandle, RawWindowHandle};
+
+            let window_id = match window.window_handle().map(|handle| handle.as_raw()) {
+                Ok(RawWindowHandle::Xlib(handle)) => Some(handle.window as u32),
+                Ok(RawWindowHandle::Xcb(handle)) => Some(handle.window.get()),
+                _ => None,
+            };
+            window_id.and_then(|id| x11rb::connect(None).ok().map(|(connection, _)| (connection, id)))
+        };
+        #[cfg(not(target_os = "linux"))]
+        let _ = window;
+
+        Self {
+            applied: None,
+            #[cfg(target_os = "linux")]
+            x11,
+        }
+    }
+
+    /// Restricts mouse input to the given rectangles, if they changed.
+    ///
+    /// # Arguments
+    /// * `window` - The buddy window
+    /// * `rects` - Silhouette from [`silhouette`], in window coordinates
+    pub fn update(&mut self, window: &Window, rects: Vec<Rect>) {
+        if self.applied.as_ref() == Some(&rects) {
+            return;
+        }
+
+        #[cfg(target_os = "linux")]
+        if let Some((connection, window_id)) = &self.x11 {
+            use x11rb::connection::Connection;
+            use x11rb::protocol::shape::{ConnectionExt, SK, SO};
+            use x11rb::protocol::xproto::{ClipOrdering, Rectangle};
+
+            let rectangles: Vec<Rectangle> = rects
+                .iter()
+                .map(|rect| Rectangle {
+                    x: rect.x as i16,
+                    y: rect.y as i16,
+                    width: rect.width as u16,
+                    height: rect.height as u16,
+                })
+                .collect();
+            // Only the input shape changes, so drawing and transparency are untouched
+            let _ = connection.shape_rectangles(
+                SO::SET,
+                SK::INPUT,
+                ClipOrdering::UNSORTED,
+                *window_id,
+                0,
+                0,
+                &rectangles,
+            );
+            let _ = connection.flush();
+        }
+
+        #[cfg(windows)]
+        {
+            use raw_window_handle::{HasWindowHandle, RawWindowHandle};
+            use windows_sys::Win32::Graphics::Gdi::{CombineRgn, CreateRectRgn, DeleteObject, SetWindowRgn, RGN_OR};
+
+            // SAFETY: The HWND comes from winit's live window handle. Every
+            // region part is freed after merging, and the merged region is
+            // owned by the system once SetWindowRgn succeeds.
+            unsafe {
+                if let Ok(handle) = window.window_handle() {
+                    if let RawWindowHandle::Win32(win32_handle) = handle.as_raw() {
+                        let hwnd = win32_handle.hwnd.get() as _;
+                        let region = CreateRectRgn(0, 0, 0, 0);
+                        for rect in &rects {
+                            let part = CreateRectRgn(
+                                rect.x,
+                                rect.y,
+                                rect.x + rect.width as i32,
+                                rect.y + rect.height as i32,
+                            );
+                            CombineRgn(region, region, part, RGN_OR);
+                            DeleteObject(part);
+                        }
+                        if SetWindowRgn(hwnd, region, 1) == 0 {
+                            DeleteObject(region);
+                        }
+                    }
+                }
+            }
+        }
+
+        #[cfg(not(windows))]
+        let _ = window;
+
+        self.applied = Some(rects);
+    }
+}
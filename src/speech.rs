@@ -0,0 +1,349 @@
+//! Speech Bubbles
+//!
+//! Shows the text scripts pass to `say("text", ms)` in a pixel-font speech
+//! bubble above the sprite:
+//!
+//! ```text
+//!  ┌────────────┐
+//!  │ HELLO!     │   bubble: ink border and text on paper,
+//!  └─────┐ ┌────┘   drawn at the sprite's pixel scale
+//!        └─┘
+//!      (sprite)
+//! ```
+//!
+//! The bubble is composited into the frame being shown, so both renderers
+//! draw it like any other sprite pixels: [`bubble`] renders the text once,
+//! [`compose`] stacks it on top of every frame while it is visible. The
+//! bubble uses two of the palette indices scripts can't use
+//! ([`RESERVED`](crate::palette::RESERVED) and up), [`PAPER`] and [`INK`],
+//! which [`with_bubble_colors`] adds to the script's palette.
+//!
+//! ## Window Size
+//!
+//! The window grows upward (and sideways if the bubble is wider than the
+//! sprite) while a bubble is shown, so the sprite stays where it was, and
+//! shrinks back when the bubble goes away. Near the top of the screen the
+//! window is kept on the monitor instead, which moves the sprite down.
+//!
+//! ## Text
+//!
+//! The font is 3x5 pixels, uppercase only (lowercase letters are shown in
+//! uppercase); characters it doesn't have are shown as `?`. Text wraps at
+//! word boundaries after [`LINE_CHARS`] characters, and at most
+//! [`MAX_LINES`] lines are shown.
+
+use crate::ast::Frame;
+use crate::palette::Palette;
+use crate::placement::{self, Bounds};
+use crate::renderer;
+use std::time::Instant;
+use winit::window::Window;
+
+/// Palette index of the bubble's fill.
+pub const PAPER: u8 = 254;
+
+/// Palette index of the bubble's border and text.
+pub const INK: u8 = 255;
+
+/// Longest line before the text wraps, in characters.
+pub const LINE_CHARS: usize = 16;
+
+/// Most lines a bubble shows; the rest is cut off with `...`.
+pub const MAX_LINES: usize = 4;
+
+/// Glyph width and height in frame pixels.
+const GLYPH: (usize, usize) = (3, 5);
+
+/// Space between the text and the border, in frame pixels.
+const PADDING: usize = 2;
+
+/// Rows below the bubble taken by its tail.
+const TAIL_HEIGHT: usize = 2;
+
+/// A visible bubble and how the window was enlarged for it.
+#[derive(Debug, Clone)]
+pub struct Speech {
+    /// The rendered bubble, see [`bubble`]
+    pub bubble: Frame,
+    /// When the bubble goes away
+    pub until: Instant,
+    /// How to undo the window's growth
+    growth: Growth,
+}
+
+/// A window size change made room for a bubble.
+#[derive(Debug, Clone, Copy)]
+struct Growth {
+    /// Inner size before growing
+    size: (u32, u32),
+    /// How far the window moved left and up
+    moved: (i32, i32),
+}
+
+impl Speech {
+    /// Shrinks the window back to its size before the bubble, keeping the
+    /// sprite where it is now (the window may have been dragged since).
+    ///
+    /// # Returns
+    /// The restored inner size
+    pub fn hide(self, window: &Window) -> (u32, u32) {
+        let (width, height) = self.growth.size;
+        if let Ok(position) = window.outer_position() {
+            let (x, y) = (position.x + self.growth.moved.0, position.y + self.growth.moved.1);
+            window.set_outer_position(winit::dpi::PhysicalPosition::new(x, y));
+        }
+        let _ = window.request_inner_size(winit::dpi::PhysicalSize::new(width, height));
+        (width, height)
+    }
+}
+
+/// Shows, replaces or hides the bubble after a `say()` call.
+///
+/// The window grows to fit the new bubble above the sprite. Replacing a
+/// bubble sizes the window from its size before the old one, since resizes
+/// don't take effect immediately on every platform.
+///
+/// # Arguments
+/// * `window` - The buddy window
+/// * `current` - The bubble shown now, if any
+/// * `text` - What to say; empty (or only spaces) hides the bubble
+/// * `until` - When the new bubble goes away
+/// * `sprite` - The frame currently shown, for the sprite's size and scale
+///
+/// # Returns
+/// The bubble now shown, or `None`
+pub fn say(window: &Window, current: Option<Speech>, text: &str, until: Instant, sprite: Option<&Frame>) -> Option<Speech> {
+    let Some(sprite) = sprite.filter(|_| !text.trim().is_empty()) else {
+        if let Some(current) = current {
+            current.hide(window);
+        }
+        return None;
+    };
+
+    // Start from the window as it was without a bubble
+    let inner = window.inner_size();
+    let (base_size, base_moved) = current.map_or(((inner.width, inner.height), (0, 0)), |current| {
+        (current.growth.size, current.growth.moved)
+    });
+    let border = {
+        let outer = window.outer_size();
+        (outer.width.saturating_sub(inner.width), outer.height.saturating_sub(inner.height))
+    };
+
+    let bubble = bubble(text);
+    let layout = renderer::frame_layout(
+        sprite.width.max(1),
+        sprite.height.max(1),
+        base_size.0 as usize,
+        base_size.1 as usize,
+    );
+    let scale = layout.scale.max(1.0) as u32;
+    let width = base_size.0.max(bubble.width.max(sprite.width) as u32 * scale);
+    let height = base_size.1 + bubble.height as u32 * scale;
+
+    let mut moved = (0, 0);
+    if let Ok(position) = window.outer_position() {
+        let base = (position.x + base_moved.0, position.y + base_moved.1);
+        let x = base.0 - (width - base_size.0) as i32 / 2;
+        let y = base.1 - (bubble.height as u32 * scale) as i32;
+        let monitors: Vec<Bounds> = window
+            .available_monitors()
+            .map(|monitor| {
+                let (origin, size) = (monitor.position(), monitor.size());
+                Bounds::new(origin.x, origin.y, size.width, size.height)
+            })
+            .collect();
+        let (x, y) = placement::constrain(x, y, width + border.0, height + border.1, &monitors);
+        window.set_outer_position(winit::dpi::PhysicalPosition::new(x, y));
+        moved = (base.0 - x, base.1 - y);
+    }
+    let _ = window.request_inner_size(winit::dpi::PhysicalSize::new(width, height));
+
+    Some(Speech {
+        bubble,
+        until,
+        growth: Growth { size: base_size, moved },
+    })
+}
+
+/// Renders text as a speech bubble with its tail pointing down.
+///
+/// # Returns
+/// A frame of [`PAPER`] and [`INK`] pixels on index 0 (transparent)
+pub fn bubble(text: &str) -> Frame {
+    let lines = wrap(text);
+    let longest = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0).max(1);
+    let text_width = longest * (GLYPH.0 + 1) - 1;
+    let text_height = lines.len().max(1) * (GLYPH.1 + 1) - 1;
+
+    let width = text_width + 2 * PADDING + 2;
+    let box_height = text_height + 2 * PADDING + 2;
+    let height = box_height + TAIL_HEIGHT;
+    let mut pixels = vec![vec![0u8; width]; height];
+
+    // Rounded box: ink border around paper, corners left out
+    for (y, row) in pixels.iter_mut().enumerate().take(box_height) {
+        for (x, pixel) in row.iter_mut().enumerate() {
+            let edge_x = x == 0 || x == width - 1;
+            let edge_y = y == 0 || y == box_height - 1;
+            *pixel = match (edge_x, edge_y) {
+                (true, true) => 0,
+                (false, false) => PAPER,
+                _ => INK,
+            };
+        }
+    }
+
+    // Tail from the middle of the bottom edge
+    let center = width / 2;
+    pixels[box_height - 1][center] = PAPER;
+    pixels[box_height][center - 1] = INK;
+    pixels[box_height][center] = PAPER;
+    pixels[box_height][center + 1] = INK;
+    pixels[box_height + 1][center] = INK;
+
+    for (line_index, line) in lines.iter().enumerate() {
+        let top = 1 + PADDING + line_index * (GLYPH.1 + 1);
+        for (char_index, character) in line.chars().enumerate() {
+            let left = 1 + PADDING + char_index * (GLYPH.0 + 1);
+            for (row, bits) in glyph(character).iter().enumerate() {
+                for column in 0..GLYPH.0 {
+                    if bits & (0b100 >> column) != 0 {
+                        pixels[top + row][left + column] = INK;
+                    }
+                }
+            }
+        }
+    }
+
+    Frame::new(pixels)
+}
+
+/// Stacks a bubble on top of a sprite frame, both centered horizontally.
+///
+/// # Arguments
+/// * `bubble` - From [`bubble`]
+/// * `sprite` - The animation frame being shown
+pub fn compose(bubble: &Frame, sprite: &Frame) -> Frame {
+    let width = bubble.width.max(sprite.width);
+    let mut pixels = vec![vec![0u8; width]; bubble.height + sprite.height];
+
+    let bubble_left = (width - bubble.width) / 2;
+    for (row, source) in pixels.iter_mut().zip(bubble.pixels.iter()) {
+        row[bubble_left..bubble_left + bubble.width].copy_from_slice(source);
+    }
+    let sprite_left = (width - sprite.width) / 2;
+    for (row, source) in pixels[bubble.height..].iter_mut().zip(sprite.pixels.iter()) {
+        row[sprite_left..sprite_left + sprite.width].copy_from_slice(source);
+    }
+
+    Frame::new(pixels)
+}
+
+/// Returns a copy of a palette with the bubble's paper and ink colors added.
+pub fn with_bubble_colors(palette: &Palette) -> Palette {
+    let mut palette = palette.clone();
+    palette.set_argb(PAPER, 0xFFFFFFFF);
+    palette.set_argb(INK, 0xFF000000);
+    palette
+}
+
+/// Splits text into at most [`MAX_LINES`] uppercase lines of at most
+/// [`LINE_CHARS`] characters, breaking between words where possible.
+fn wrap(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for paragraph in text.to_uppercase().lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let mut word: Vec<char> = word.chars().collect();
+            // Words longer than a line are split
+            while !word.is_empty() {
+                let used = line.chars().count();
+                let room = if used == 0 { LINE_CHARS } else { LINE_CHARS.saturating_sub(used + 1) };
+                if word.len() > room && used > 0 {
+                    lines.push(std::mem::take(&mut line));
+                    continue;
+                }
+                let taken: String = word.drain(..word.len().min(room)).collect();
+                if used > 0 {
+                    line.push(' ');
+                }
+                line.push_str(&taken);
+                if !word.is_empty() {
+                    lines.push(std::mem::take(&mut line));
+                }
+            }
+        }
+        lines.push(line);
+    }
+
+    if lines.len() > MAX_LINES {
+        lines.truncate(MAX_LINES);
+        let last: String = lines[MAX_LINES - 1].chars().take(LINE_CHARS - 3).collect();
+        lines[MAX_LINES - 1] = format!("{}...", last);
+    }
+    lines
+}
+
+/// Returns a character's 3x5 glyph, one row per entry, leftmost pixel in bit 2.
+fn glyph(character: char) -> [u8; 5] {
+    match character {
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'G' => [0b011, 0b100, 0b101, 0b101, 0b011],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'J' => [0b001, 0b001, 0b001, 0b101, 0b010],
+        'K' => [0b101, 0b101, 0b110, 0b101, 0b101],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'N' => [0b110, 0b101, 0b101, 0b101, 0b101],
+        'O' => [0b010, 0b101, 0b101, 0b101, 0b010],
+        'P' => [0b110, 0b101, 0b110, 0b100, 0b100],
+        'Q' => [0b010, 0b101, 0b101, 0b110, 0b011],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'V' => [0b101, 0b101, 0b101, 0b101, 0b010],
+        'W' => [0b101, 0b101, 0b111, 0b111, 0b101],
+        'X' => [0b101, 0b101, 0b010, 0b101, 0b101],
+        'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        'Z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b110, 0b001, 0b010, 0b100, 0b111],
+        '3' => [0b110, 0b001, 0b010, 0b001, 0b110],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b110, 0b001, 0b110],
+        '6' => [0b011, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b010, 0b010, 0b010],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b110],
+        ' ' => [0b000, 0b000, 0b000, 0b000, 0b000],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        ',' => [0b000, 0b000, 0b000, 0b010, 0b100],
+        '!' => [0b010, 0b010, 0b010, 0b000, 0b010],
+        '\'' => [0b010, 0b010, 0b000, 0b000, 0b000],
+        '"' => [0b101, 0b101, 0b000, 0b000, 0b000],
+        '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        '_' => [0b000, 0b000, 0b000, 0b000, 0b111],
+        ':' => [0b000, 0b010, 0b000, 0b010, 0b000],
+        '(' => [0b001, 0b010, 0b010, 0b010, 0b001],
+        ')' => [0b100, 0b010, 0b010, 0b010, 0b100],
+        '/' => [0b001, 0b001, 0b010, 0b100, 0b100],
+        '+' => [0b000, 0b010, 0b111, 0b010, 0b000],
+        '=' => [0b000, 0b111, 0b000, 0b111, 0b000],
+        '*' => [0b000, 0b101, 0b010, 0b101, 0b000],
+        '%' => [0b101, 0b001, 0b010, 0b100, 0b101],
+        '<' => [0b001, 0b010, 0b100, 0b010, 0b001],
+        '>' => [0b100, 0b010, 0b001, 0b010, 0b100],
+        '#' => [0b101, 0b111, 0b101, 0b111, 0b101],
+        '♥' => [0b000, 0b101, 0b111, 0b111, 0b010],
+        _ => [0b110, 0b001, 0b010, 0b000, 0b010], // '?'
+    }
+}
@@ -0,0 +1,89 @@
+//! Crash Supervisor
+//!
+//! `gizmo start --respawn N <file>` spawns a small supervisor process
+//! instead of the GUI process. The supervisor starts the GUI process as its
+//! child, registers the child's PID as the daemon's and waits for it:
+//!
+//! - **Clean exit** (`gizmo stop`, window closed, startup error): The PID
+//!   file no longer names the child, or it exited successfully; the
+//!   supervisor exits too
+//! - **Crash** (panic, killed by a signal): A crash report is written (unless
+//!   the panic already wrote one) and the GUI process is started again after
+//!   [`RESPAWN_DELAY`], up to N times; then the supervisor gives up and
+//!   leaves the crash for the next `gizmo start` to report
+//!
+//! Both processes write to the instance's log, so `gizmo logs` shows the
+//! crashes and restarts in order.
+
+use crate::daemon::{self, CrashReport};
+use std::error::Error;
+use std::path::Path;
+use std::process::{Command, ExitStatus};
+use std::time::Duration;
+
+/// How long the supervisor waits before starting a crashed GUI process again.
+pub const RESPAWN_DELAY: Duration = Duration::from_secs(1);
+
+/// Most restarts `--respawn` accepts.
+pub const MAX_RESPAWNS: u32 = 100;
+
+/// Runs the GUI process, restarting it when it crashes.
+///
+/// # Arguments
+/// * `instance` - Instance the GUI process runs
+/// * `gzmo_file` - Absolute path of the script
+/// * `gui_args` - Options forwarded to the GUI process
+/// * `respawns` - How many crashes to restart after
+///
+/// # Returns
+/// * `Ok(())` - The GUI process exited cleanly, or crashed too often
+/// * `Err` - The GUI process couldn't be started
+pub fn supervise(instance: &str, gzmo_file: &Path, gui_args: &[String], respawns: u32) -> Result<(), Box<dyn Error>> {
+    let exe = std::env::current_exe()?;
+    let file = gzmo_file.to_string_lossy();
+    let mut restarts = 0;
+    loop {
+        // The GUI writes to the same log as the supervisor
+        let mut child = Command::new(&exe).arg("--gui").arg(gzmo_file).args(gui_args).spawn()?;
+        let pid = child.id();
+        daemon::save_daemon_pid(instance, pid)?;
+        let status = child.wait()?;
+
+        // `gizmo stop` unregisters the GUI before stopping it
+        let stopped = daemon::get_daemon_pid(instance).ok() != Some(pid);
+        if status.success() || stopped {
+            return Ok(());
+        }
+
+        if daemon::get_crash_report(instance).is_none_or(|report| report.pid != pid) {
+            daemon::save_crash_report(instance, &CrashReport::new(pid, &file, describe_exit(status)))?;
+        }
+        if restarts == respawns {
+            eprintln!("Gizmo crashed ({}); gave up after {} restarts", describe_exit(status), restarts);
+            return Ok(());
+        }
+        restarts += 1;
+        eprintln!("Gizmo crashed ({}); restarting ({}/{})", describe_exit(status), restarts, respawns);
+        std::thread::sleep(RESPAWN_DELAY);
+
+        // Stopped while waiting
+        if daemon::get_daemon_pid(instance).ok() != Some(pid) {
+            return Ok(());
+        }
+    }
+}
+
+/// Describes how a process exited, for crash reports.
+fn describe_exit(status: ExitStatus) -> String {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return format!("killed by signal {}", signal);
+        }
+    }
+    match status.code() {
+        Some(code) => format!("exited with code {}", code),
+        None => "exited".to_string(),
+    }
+}
@@ -0,0 +1,133 @@
+//! Frame Rate Throttling (`--throttle`)
+//!
+//! Under pressure the window can show every other frame instead of every
+//! frame. The fixed-step clock keeps the animation's speed; only the frames
+//! in between are skipped, and the window loop wakes half as often instead
+//! of rushing to catch up.
+//!
+//! | `--throttle`    | Halves the frame rate while                           |
+//! |-----------------|-------------------------------------------------------|
+//! | `off` (default) | Never                                                 |
+//! | `battery`       | The system runs on battery (`sysinfo` feature)        |
+//! | `lag`           | Frames fall behind (see the `pacing` module)          |
+//! | `auto`          | Either (battery only where the build can tell)        |
+//!
+//! ```text
+//! ticks      0  1  2  3  4  5  6  7
+//! full rate  0  1  2  3  4  5  6  7
+//! throttled  0     2     4     6
+//! ```
+//!
+//! Falling behind may have been a passing spike, so lag throttling ends after
+//! [`LAG_HOLD`] without falling behind again, and the full rate is tried anew.
+
+use std::time::{Duration, Instant};
+
+/// Ticks a throttled window advances between the frames it shows.
+pub const STRIDE: u64 = 2;
+
+/// How long lag throttling lasts after frames last fell behind.
+pub const LAG_HOLD: Duration = Duration::from_secs(60);
+
+/// When to halve the frame rate.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Policy {
+    /// Always show every frame
+    Off,
+    /// While on battery
+    Battery,
+    /// While frames fall behind
+    Lag,
+    /// While on battery or frames fall behind
+    Auto,
+}
+
+impl Policy {
+    /// Parses a `--throttle` value.
+    ///
+    /// # Arguments
+    /// * `name` - `"off"`, `"battery"`, `"lag"` or `"auto"`
+    ///
+    /// # Returns
+    /// * `Ok(Policy)` - Known policy this build can follow
+    /// * `Err` - Unknown name, or `"battery"` in a build without the `sysinfo` feature
+    pub fn parse(name: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        match name {
+            "off" => Ok(Policy::Off),
+            "battery" if cfg!(feature = "sysinfo") => Ok(Policy::Battery),
+            "battery" => Err("This build can't tell when it runs on battery; rebuild with `cargo build --release --features sysinfo`".into()),
+            "lag" => Ok(Policy::Lag),
+            "auto" => Ok(Policy::Auto),
+            _ => Err(format!("Unknown throttle policy '{}' (expected off, battery, lag or auto)", name).into()),
+        }
+    }
+
+    /// Returns the command-line name of this policy.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Policy::Off => "off",
+            Policy::Battery => "battery",
+            Policy::Lag => "lag",
+            Policy::Auto => "auto",
+        }
+    }
+}
+
+/// Decides from the policy and the latest readings whether to throttle.
+#[derive(Debug, Clone)]
+pub struct Throttle {
+    policy: Policy,
+    /// Whether the last system sample was on battery
+    on_battery: bool,
+    /// When frames last fell behind, while lag throttling lasts
+    lagged_at: Option<Instant>,
+}
+
+impl Throttle {
+    /// Creates a throttle following a policy, at the full frame rate.
+    pub fn new(policy: Policy) -> Self {
+        Self {
+            policy,
+            on_battery: false,
+            lagged_at: None,
+        }
+    }
+
+    /// Notes whether the system runs on battery.
+    #[cfg(feature = "sysinfo")]
+    pub fn set_on_battery(&mut self, on_battery: bool) {
+        self.on_battery = on_battery;
+    }
+
+    /// Notes that frames fell behind.
+    ///
+    /// # Returns
+    /// `true` if the policy throttles for it and the window wasn't already
+    /// throttled for lag, so this starts it
+    pub fn fell_behind(&mut self, now: Instant) -> bool {
+        if !matches!(self.policy, Policy::Lag | Policy::Auto) {
+            return false;
+        }
+        self.lagged_at.replace(now).is_none()
+    }
+
+    /// Returns how many ticks to advance between shown frames: [`STRIDE`]
+    /// while throttled, 1 otherwise.
+    pub fn stride(&mut self, now: Instant) -> u64 {
+        if self.lagged_at.is_some_and(|at| now.saturating_duration_since(at) >= LAG_HOLD) {
+            self.lagged_at = None;
+        }
+        let battery = self.on_battery && matches!(self.policy, Policy::Battery | Policy::Auto);
+        if battery || self.lagged_at.is_some() {
+            STRIDE
+        } else {
+            1
+        }
+    }
+}
+
+/// Returns the tick whose frame a window advancing `stride` ticks at a time
+/// shows at `ticks`.
+pub fn shown_tick(ticks: u64, stride: u64) -> u64 {
+    ticks - ticks % stride
+}
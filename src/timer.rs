@@ -0,0 +1,78 @@
+//! Interval Timers
+//!
+//! Fires `every 60000 do ... end` handlers at a fixed interval, independent
+//! of the animation's frame rate, so scripts can re-roll randomness or check
+//! on things now and then. The window loop checks the timers on every
+//! wake-up and sleeps no longer than [`Timers::until_next`].
+//!
+//! ## Cadence
+//!
+//! Each interval keeps its own schedule, starting from the first check after
+//! it was registered. Runs are spaced by the interval from the previous
+//! deadline rather than from when the handler actually ran, so a timer
+//! doesn't drift. Deadlines missed while the buddy was hidden or the
+//! computer slept fire only once, then the schedule restarts from there:
+//!
+//! ```text
+//! every 1000   0 ──── 1000 ──── 2000 ── (asleep) ── 5300 ──── 6300 ...
+//! fires                 ▲         ▲                   ▲         ▲
+//! ```
+
+use std::time::{Duration, Instant};
+
+/// Tracks when each `every N` interval is next due.
+#[derive(Debug, Clone, Default)]
+pub struct Timers {
+    /// Interval in milliseconds and its next deadline
+    next: Vec<(u64, Instant)>,
+}
+
+impl Timers {
+    /// Creates timers with nothing scheduled yet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the intervals that came due since the last call, shortest first.
+    ///
+    /// Intervals not seen before (handlers may register more handlers) are
+    /// scheduled one interval from `now`; intervals no longer listed (spent
+    /// `once` timers) are dropped.
+    ///
+    /// # Arguments
+    /// * `now` - Current time
+    /// * `intervals` - Handler intervals in milliseconds, ascending
+    pub fn due(&mut self, now: Instant, intervals: &[u64]) -> Vec<u64> {
+        self.next.retain(|(known, _)| intervals.contains(known));
+        for &interval in intervals {
+            if !self.next.iter().any(|&(known, _)| known == interval) {
+                self.next.push((interval, now + Duration::from_millis(interval)));
+            }
+        }
+
+        let mut due = Vec::new();
+        for (interval, deadline) in &mut self.next {
+            if now < *deadline {
+                continue;
+            }
+            due.push(*interval);
+
+            let period = Duration::from_millis(*interval);
+            *deadline += period;
+            if *deadline <= now {
+                // Fell behind; skip the missed runs instead of bursting
+                *deadline = now + period;
+            }
+        }
+        due.sort_unstable();
+        due
+    }
+
+    /// Returns how long until the next interval is due, or `None` without any.
+    pub fn until_next(&self, now: Instant) -> Option<Duration> {
+        self.next
+            .iter()
+            .map(|&(_, deadline)| deadline.saturating_duration_since(now))
+            .min()
+    }
+}
@@ -0,0 +1,140 @@
+//! Terminal Playback
+//!
+//! `gizmo tty <file>` plays an animation in the terminal instead of a window,
+//! for use over SSH or on machines without a compositor. Frames are drawn in
+//! their colors with the [`TerminalRenderer`], two pixel rows per line, so a
+//! 32x32 sprite takes 32 columns and 16 lines. A terminal with 24-bit color
+//! is needed for the colors to be right.
+//!
+//! Playback uses the same fixed-step clock as the window, and the script's
+//! `when launched`, `every N` and `when time("HH:MM")` handlers run as they
+//! would on the desktop. Handlers for events a terminal doesn't have (clicks,
+//! drops, idle) never fire. Ctrl+C stops playback and restores the terminal.
+
+use crate::ast::Frame;
+use crate::frame::TerminalRenderer;
+use crate::interpreter::Interpreter;
+use crate::{schedule, sequence, timer};
+use std::error::Error;
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Longest sleep between checks for Ctrl+C and due handlers
+const MAX_SLEEP: Duration = Duration::from_millis(100);
+
+/// Switches to the alternate screen and hides the cursor
+const ENTER: &str = "\x1b[?1049h\x1b[?25l";
+/// Shows the cursor and returns to the normal screen
+const LEAVE: &str = "\x1b[?25h\x1b[?1049l";
+
+/// Plays an animation in the terminal until Ctrl+C.
+///
+/// # Arguments
+/// * `script` - The .gzmo file being played, whose `persist` state is saved on exit
+/// * `animation_frames` - Frames produced by the script
+/// * `interpreter` - The interpreter that ran the script (owns clock and palette)
+///
+/// # Returns
+/// * `Ok(())` - Playback was stopped
+/// * `Err` - Writing to the terminal failed
+pub fn run(script: &Path, animation_frames: Vec<Frame>, mut interpreter: Interpreter) -> Result<(), Box<dyn Error>> {
+    let stop = Arc::new(AtomicBool::new(false));
+    #[cfg(unix)]
+    {
+        use signal_hook::consts::{SIGINT, SIGTERM};
+        signal_hook::flag::register(SIGINT, Arc::clone(&stop))?;
+        signal_hook::flag::register(SIGTERM, Arc::clone(&stop))?;
+    }
+
+    let mut out = io::stdout().lock();
+    write!(out, "{}", ENTER)?;
+    let result = play(&mut out, &stop, animation_frames, &mut interpreter);
+    write!(out, "{}", LEAVE)?;
+    out.flush()?;
+
+    crate::save_pet_state(script, &interpreter);
+    result
+}
+
+/// Draws frames as they come due and runs the script's handlers, until `stop` is set.
+fn play(
+    out: &mut impl Write,
+    stop: &AtomicBool,
+    animation_frames: Vec<Frame>,
+    interpreter: &mut Interpreter,
+) -> Result<(), Box<dyn Error>> {
+    // Frames played by `when launched` handlers run once before the script's own
+    let mut sequence = sequence::Sequence::new(Vec::new());
+    let launch_frames = crate::run_launch_handlers(interpreter, |e| eprintln!("Error in launch handler: {}", e));
+    sequence.switch(launch_frames, animation_frames, interpreter.clock().ticks());
+    let mut sequence_version = interpreter.sequence_version();
+    let mut timers = timer::Timers::new();
+    let mut schedule = schedule::Schedule::new(chrono::Local::now().naive_local());
+    let mut last_clock_update = Instant::now();
+
+    // What is on screen: (sequence position, width, height)
+    let mut presented: Option<(usize, usize, usize)> = None;
+
+    while !stop.load(Ordering::Relaxed) {
+        let now = Instant::now();
+        interpreter.clock_mut().advance(now - last_clock_update);
+        last_clock_update = now;
+
+        // Fire `when time("HH:MM")` and `every N` handlers that are due
+        let mut handlers_ran = false;
+        for minute in schedule.due(chrono::Local::now().naive_local(), interpreter.time_triggers()) {
+            if let Err(e) = interpreter.handle_time_event(minute) {
+                eprintln!("Error in time handler: {}", e);
+            }
+            handlers_ran = true;
+        }
+        for interval in timers.due(now, interpreter.timer_intervals()) {
+            if let Err(e) = interpreter.handle_timer_event(interval) {
+                eprintln!("Error in timer handler: {}", e);
+            }
+            handlers_ran = true;
+        }
+        if handlers_ran {
+            let new_version = interpreter.sequence_version();
+            let new_frames = interpreter.get_animation_frames();
+            if new_version != sequence_version && !new_frames.is_empty() {
+                // Blend from the frame on screen into the new animation
+                let current = presented.and_then(|(position, _, _)| sequence.frame(position));
+                let intro = match current {
+                    Some(current) => interpreter.transition_frames(std::slice::from_ref(current), &new_frames),
+                    None => Vec::new(),
+                };
+                sequence.switch(intro, new_frames, interpreter.clock().ticks());
+                // The positions of the new sequence start over
+                presented = None;
+            }
+            sequence_version = new_version;
+            interpreter.end_handler_batch();
+        }
+
+        let position = sequence.position(interpreter.clock().ticks());
+        if let Some(frame) = sequence.frame(position) {
+            let key = (position, frame.width, frame.height);
+            if presented != Some(key) {
+                // Clear only when the size changes; otherwise overdraw in place
+                if presented.is_none_or(|(_, width, height)| (width, height) != (frame.width, frame.height)) {
+                    write!(out, "\x1b[2J")?;
+                }
+                write!(out, "\x1b[H{}", TerminalRenderer::new(interpreter.palette()).render(frame))?;
+                out.flush()?;
+                presented = Some(key);
+            }
+        }
+
+        let mut sleep = interpreter.clock().until_next_tick().min(MAX_SLEEP);
+        if let Some(timer) = timers.until_next(Instant::now()) {
+            sleep = sleep.min(timer);
+        }
+        thread::sleep(sleep);
+    }
+    Ok(())
+}
@@ -0,0 +1,120 @@
+//! Pausing While Nobody Can See the Buddy
+//!
+//! Fast animations keep the event loop busy, which is wasted work (and a warm
+//! laptop) while the screen is locked or the display is asleep. The window
+//! loop stops advancing the animation whenever the buddy is hidden and picks
+//! up where it left off once it is visible again.
+//!
+//! ## Hidden Signals
+//!
+//! - **macOS / X11**: winit's `WindowEvent::Occluded`, sent when the window is
+//!   fully covered - including by a lock screen - or the display sleeps
+//! - **Windows**: winit has no occlusion events there, so [`watch_display_state`]
+//!   listens for session lock/unlock and console display on/off notifications
+//!   and forwards them as [`GuiEvent::Hidden`]
+
+use crate::GuiEvent;
+use winit::event_loop::EventLoopProxy;
+use winit::window::Window;
+
+/// Forwards platform "screen locked" / "display off" notifications to the
+/// window loop as [`GuiEvent::Hidden`].
+///
+/// Only needed on Windows; elsewhere `WindowEvent::Occluded` covers this and
+/// the call does nothing.
+///
+/// # Arguments
+/// * `window` - The buddy window, which receives the notifications
+/// * `proxy` - Channel into the window loop
+pub fn watch_display_state(window: &Window, proxy: EventLoopProxy<GuiEvent>) {
+    #[cfg(windows)]
+    {
+        use raw_window_handle::{HasWindowHandle, RawWindowHandle};
+        use windows_sys::Win32::System::Power::RegisterPowerSettingNotification;
+        use windows_sys::Win32::System::RemoteDesktop::{WTSRegisterSessionNotification, NOTIFY_FOR_THIS_SESSION};
+        use windows_sys::Win32::System::SystemServices::GUID_CONSOLE_DISPLAY_STATE;
+        use windows_sys::Win32::UI::Shell::SetWindowSubclass;
+        use windows_sys::Win32::UI::WindowsAndMessaging::DEVICE_NOTIFY_WINDOW_HANDLE;
+
+        let hwnd = match window.window_handle().map(|handle| handle.as_raw()) {
+            Ok(RawWindowHandle::Win32(handle)) => handle.hwnd.get() as windows_sys::Win32::Foundation::HWND,
+            _ => return,
+        };
+
+        // The watcher lives as long as the window, i.e. until the process exits
+        let watcher = Box::into_raw(Box::new(DisplayWatcher {
+            proxy,
+            locked: std::cell::Cell::new(false),
+            display_off: std::cell::Cell::new(false),
+        }));
+
+        // SAFETY: The HWND comes from winit's live window handle and the
+        // subclass procedure only runs on this (the window's) thread, where
+        // the leaked watcher stays valid for the rest of the process.
+        unsafe {
+            SetWindowSubclass(hwnd, Some(display_state_proc), 1, watcher as usize);
+            WTSRegisterSessionNotification(hwnd, NOTIFY_FOR_THIS_SESSION);
+            RegisterPowerSettingNotification(hwnd, &GUID_CONSOLE_DISPLAY_STATE, DEVICE_NOTIFY_WINDOW_HANDLE);
+        }
+    }
+
+    #[cfg(not(windows))]
+    let _ = (window, proxy);
+}
+
+/// Lock and display state seen by the Windows subclass procedure.
+#[cfg(windows)]
+struct DisplayWatcher {
+    proxy: EventLoopProxy<GuiEvent>,
+    locked: std::cell::Cell<bool>,
+    display_off: std::cell::Cell<bool>,
+}
+
+/// Window subclass procedure turning session and power notifications into
+/// [`GuiEvent::Hidden`] events.
+#[cfg(windows)]
+unsafe extern "system" fn display_state_proc(
+    hwnd: windows_sys::Win32::Foundation::HWND,
+    message: u32,
+    wparam: windows_sys::Win32::Foundation::WPARAM,
+    lparam: windows_sys::Win32::Foundation::LPARAM,
+    _subclass_id: usize,
+    ref_data: usize,
+) -> windows_sys::Win32::Foundation::LRESULT {
+    use windows_sys::Win32::System::Power::POWERBROADCAST_SETTING;
+    use windows_sys::Win32::System::SystemServices::GUID_CONSOLE_DISPLAY_STATE;
+    use windows_sys::Win32::UI::Shell::DefSubclassProc;
+    use windows_sys::Win32::UI::WindowsAndMessaging::{
+        PBT_POWERSETTINGCHANGE, WM_POWERBROADCAST, WM_WTSSESSION_CHANGE, WTS_SESSION_LOCK, WTS_SESSION_UNLOCK,
+    };
+
+    let watcher = &*(ref_data as *const DisplayWatcher);
+    let was_hidden = watcher.locked.get() || watcher.display_off.get();
+
+    match message {
+        WM_WTSSESSION_CHANGE => match wparam as u32 {
+            WTS_SESSION_LOCK => watcher.locked.set(true),
+            WTS_SESSION_UNLOCK => watcher.locked.set(false),
+            _ => {}
+        },
+        WM_POWERBROADCAST if wparam as u32 == PBT_POWERSETTINGCHANGE => {
+            let setting = &*(lparam as *const POWERBROADCAST_SETTING);
+            let guid = setting.PowerSetting;
+            let display_state = GUID_CONSOLE_DISPLAY_STATE;
+            if (guid.data1, guid.data2, guid.data3, guid.data4)
+                == (display_state.data1, display_state.data2, display_state.data3, display_state.data4)
+            {
+                // 0 = off, 1 = on, 2 = dimmed (still visible)
+                watcher.display_off.set(setting.Data[0] == 0);
+            }
+        }
+        _ => {}
+    }
+
+    let hidden = watcher.locked.get() || watcher.display_off.get();
+    if hidden != was_hidden {
+        let _ = watcher.proxy.send_event(GuiEvent::Hidden(hidden));
+    }
+
+    DefSubclassProc(hwnd, message, wparam, lparam)
+}
@@ -0,0 +1,152 @@
+//! Monitor Work Areas
+//!
+//! The work area is the part of a monitor not covered by the taskbar, dock
+//! or panels. `fall()` and `bounce()` use its bottom edge as the ground, so
+//! a dropped buddy lands on top of the taskbar instead of behind it.
+//!
+//! ## Sources
+//!
+//! - **Windows**: `GetMonitorInfoW`'s `rcWork`
+//! - **macOS**: `NSScreen.visibleFrame` (excludes the dock and menu bar)
+//! - **X11**: The window manager's `_NET_WORKAREA`, intersected with each
+//!   monitor (one rectangle covers all monitors, so a panel on one monitor
+//!   may also trim the others)
+//! - **Wayland / others**: Unavailable; the whole monitor is used
+
+use crate::placement::Bounds;
+#[cfg(not(target_os = "linux"))]
+use winit::monitor::MonitorHandle;
+use winit::window::Window;
+
+/// Returns the work area of every connected monitor, in the same order as
+/// `window.available_monitors()`.
+///
+/// Monitors whose work area can't be read are returned whole.
+pub fn work_areas(window: &Window) -> Vec<Bounds> {
+    #[cfg(target_os = "linux")]
+    let desktop_area = x11_work_area(window);
+    #[cfg(not(target_os = "linux"))]
+    let _ = window;
+
+    window
+        .available_monitors()
+        .map(|monitor| {
+            let (origin, size) = (monitor.position(), monitor.size());
+            let whole = Bounds::new(origin.x, origin.y, size.width, size.height);
+
+            #[cfg(target_os = "linux")]
+            let area = desktop_area.and_then(|area| intersect(&whole, &area));
+            #[cfg(not(target_os = "linux"))]
+            let area = monitor_work_area(&monitor, &whole);
+
+            area.unwrap_or(whole)
+        })
+        .collect()
+}
+
+/// Reads one monitor's work area from the platform.
+#[cfg(not(target_os = "linux"))]
+fn monitor_work_area(monitor: &MonitorHandle, whole: &Bounds) -> Option<Bounds> {
+    #[cfg(windows)]
+    {
+        use windows_sys::Win32::Graphics::Gdi::{GetMonitorInfoW, MONITORINFO};
+        use winit::platform::windows::MonitorHandleExtWindows;
+
+        let _ = whole;
+        // SAFETY: `info` is a properly sized MONITORINFO and the monitor
+        // handle comes from winit's live monitor list.
+        let mut info: MONITORINFO = unsafe { std::mem::zeroed() };
+        info.cbSize = std::mem::size_of::<MONITORINFO>() as u32;
+        if unsafe { GetMonitorInfoW(monitor.hmonitor() as _, &mut info) } == 0 {
+            return None;
+        }
+        let work = info.rcWork;
+        Some(Bounds::new(
+            work.left,
+            work.top,
+            (work.right - work.left).max(0) as u32,
+            (work.bottom - work.top).max(0) as u32,
+        ))
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        use cocoa::appkit::NSScreen;
+        use cocoa::base::id;
+        use winit::platform::macos::MonitorHandleExtMacOS;
+
+        let screen = monitor.ns_screen()? as id;
+        // SAFETY: The NSScreen comes from winit's live monitor handle, and
+        // frame / visibleFrame are standard NSScreen properties.
+        let (frame, visible) = unsafe { (screen.frame(), screen.visibleFrame()) };
+
+        // AppKit measures in points from the bottom-left corner; convert the
+        // insets to physical pixels measured from the top-left
+        let scale = monitor.scale_factor();
+        let left = ((visible.origin.x - frame.origin.x) * scale).round() as i32;
+        let bottom = ((visible.origin.y - frame.origin.y) * scale).round() as i32;
+        let right = ((frame.origin.x + frame.size.width - visible.origin.x - visible.size.width) * scale).round() as i32;
+        let top = ((frame.origin.y + frame.size.height - visible.origin.y - visible.size.height) * scale).round() as i32;
+        Some(Bounds {
+            x: whole.x + left,
+            y: whole.y + top,
+            width: (whole.width - left - right).max(0),
+            height: (whole.height - top - bottom).max(0),
+        })
+    }
+
+    #[cfg(not(any(windows, target_os = "macos")))]
+    {
+        let _ = (monitor, whole);
+        None
+    }
+}
+
+/// Reads the desktop's work area from the X11 window manager.
+///
+/// # Returns
+/// `None` on Wayland, or when the window manager doesn't publish one
+#[cfg(target_os = "linux")]
+fn x11_work_area(window: &Window) -> Option<Bounds> {
+    use raw_window_handle::{HasWindowHandle, RawWindowHandle};
+    use x11rb::connection::Connection;
+    use x11rb::protocol::xproto::{AtomEnum, ConnectionExt};
+
+    let on_x11 = matches!(
+        window.window_handle().map(|handle| handle.as_raw()),
+        Ok(RawWindowHandle::Xlib(_) | RawWindowHandle::Xcb(_))
+    );
+    if !on_x11 {
+        return None;
+    }
+
+    let (connection, screen) = x11rb::connect(None).ok()?;
+    let root = connection.setup().roots[screen].root;
+    let atom = connection.intern_atom(false, b"_NET_WORKAREA").ok()?.reply().ok()?.atom;
+    // x, y, width, height for each virtual desktop; they are normally all the same
+    let reply = connection
+        .get_property(false, root, atom, AtomEnum::CARDINAL, 0, 4)
+        .ok()?
+        .reply()
+        .ok()?;
+    let values: Vec<u32> = reply.value32()?.collect();
+    match values[..] {
+        [x, y, width, height] => Some(Bounds::new(x as i32, y as i32, width, height)),
+        _ => None,
+    }
+}
+
+/// Returns the overlap of two rectangles, or `None` if they don't overlap.
+#[cfg(target_os = "linux")]
+fn intersect(a: &Bounds, b: &Bounds) -> Option<Bounds> {
+    let left = a.x.max(b.x);
+    let top = a.y.max(b.y);
+    let right = (a.x + a.width).min(b.x + b.width);
+    let bottom = (a.y + a.height).min(b.y + b.height);
+    (right > left && bottom > top).then(|| Bounds {
+        x: left,
+        y: top,
+        width: right - left,
+        height: bottom - top,
+    })
+}
@@ -0,0 +1,46 @@
+//! Runtime Zoom
+//!
+//! Pressing `+` / `-` while the buddy has focus, or scrolling over it, grows
+//! and shrinks the window in whole sprite-scale steps: a 32x32 sprite zooms
+//! through 32, 64, 96, ... pixel windows, so every sprite pixel stays a crisp
+//! square block and no letterbox margin is left over.
+//!
+//! The window keeps its center while zooming and is never made larger than
+//! the monitor it is on.
+
+use crate::renderer;
+
+/// Scroll distance (in pixels, for touchpads) that counts as one zoom step.
+pub const PIXELS_PER_STEP: f64 = 50.0;
+
+/// Computes the window size after zooming by whole scale steps.
+///
+/// # Arguments
+/// * `frame_size` - Size of the sprite being shown, in frame pixels
+/// * `window_size` - Current inner window size in physical pixels
+/// * `steps` - Scale steps to add (positive) or remove (negative)
+/// * `limit` - Largest allowed window size, usually the monitor size
+///
+/// # Returns
+/// * `Some((width, height))` - New inner window size, an exact multiple of the frame
+/// * `None` - The size wouldn't change (already at the smallest or largest scale)
+pub fn zoomed_size(frame_size: (usize, usize), window_size: (u32, u32), steps: i32, limit: (u32, u32)) -> Option<(u32, u32)> {
+    let (frame_width, frame_height) = (frame_size.0 as u32, frame_size.1 as u32);
+    if frame_width == 0 || frame_height == 0 {
+        return None;
+    }
+
+    // A frame shrunk below 1:1 counts as scale 0, so zooming in lands on 1:1
+    let layout = renderer::frame_layout(
+        frame_size.0,
+        frame_size.1,
+        window_size.0 as usize,
+        window_size.1 as usize,
+    );
+    let current = if layout.scale >= 1.0 { layout.scale as i64 } else { 0 };
+    let largest = (limit.0 / frame_width).min(limit.1 / frame_height).max(1) as i64;
+    let scale = (current + steps as i64).clamp(1, largest) as u32;
+
+    let size = (frame_width * scale, frame_height * scale);
+    (size != window_size).then_some(size)
+}
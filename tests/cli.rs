@@ -0,0 +1,130 @@
+//! End-to-end tests of the `gizmo` commands that run scripts without a window.
+
+mod common;
+
+use common::{assert_frames_eq, frame, script};
+use gizmo_core::ast::Frame;
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+use std::time::{Duration, Instant};
+
+/// Runs the gizmo binary with the given arguments.
+fn gizmo(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_gizmo")).args(args).output().expect("gizmo should start")
+}
+
+/// Returns everything a command printed, stdout then stderr.
+fn printed(output: &Output) -> String {
+    format!("{}{}", String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr))
+}
+
+#[test]
+fn check_passes_working_scripts() {
+    let blink = script("blink.gzmo");
+    let output = gizmo(&["check", blink.to_str().unwrap()]);
+
+    assert!(output.status.success(), "{}", printed(&output));
+    assert!(printed(&output).contains("blink.gzmo: ok"));
+}
+
+#[test]
+fn check_reports_errors_in_handlers() {
+    let broken = script("broken.gzmo");
+    let output = gizmo(&["check", broken.to_str().unwrap()]);
+    let printed = printed(&output);
+
+    assert!(!output.status.success());
+    assert!(printed.contains("broken.gzmo:3:5: Type error: sin argument 1 must be a number, not a string"), "{}", printed);
+}
+
+#[test]
+fn emitted_frames_match_the_script() {
+    let stripes = script("stripes.gzmo");
+    let output = gizmo(&["check", stripes.to_str().unwrap(), "--emit", "frames-json"]);
+    assert!(output.status.success(), "{}", printed(&output));
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let frames: Vec<Frame> = json["frames"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|frame| Frame::new(serde_json::from_value(frame["pixels"].clone()).unwrap()))
+        .collect();
+
+    assert_frames_eq(
+        &frames,
+        &[
+            frame(&[".23.", "23.2", "3.23"]),
+            frame(&["23.2", "3.23", ".23."]),
+            frame(&["3.23", ".23.", "23.2"]),
+        ],
+    );
+    assert_eq!(json["frame_ms"], 80);
+    assert_eq!(json["palette"][2], "#ff0000ff");
+}
+
+#[test]
+fn examples_check() {
+    for example in ["spinner.gzmo", "waves.gzmo", "morph.gzmo"] {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("examples").join(example);
+        let output = gizmo(&["check", path.to_str().unwrap()]);
+
+        assert!(output.status.success(), "{}", printed(&output));
+    }
+}
+
+#[test]
+fn looking_up_an_instance_leaves_no_trace() {
+    let home = std::env::temp_dir().join(format!("gizmo-cli-test-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&home);
+    for command in ["status", "stop", "logs", "restart"] {
+        Command::new(env!("CARGO_BIN_EXE_gizmo"))
+            .args([command, "--name", "ghost"])
+            .env("HOME", &home)
+            .env("XDG_CONFIG_HOME", &home)
+            .output()
+            .expect("gizmo should start");
+    }
+
+    let instances = home.join("gizmo").join("instances");
+    let created = instances.join("ghost").exists();
+    let _ = std::fs::remove_dir_all(&home);
+    assert!(!created, "{} was created", instances.display());
+}
+
+#[test]
+fn language_server_diagnoses_quickly_in_utf16_columns() {
+    let message = |body: serde_json::Value| {
+        let body = body.to_string();
+        format!("Content-Length: {}\r\n\r\n{}", body.len(), body)
+    };
+    let open = |uri: &str, text: &str| {
+        message(serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didOpen",
+            "params": { "textDocument": { "uri": uri, "text": text } },
+        }))
+    };
+    let input = [
+        open("file:///cat.gzmo", "name = \"\u{1F431}\" @;"),
+        open("file:///loop.gzmo", "repeat 1000000000 times do\n    x = 1;\nend;"),
+        message(serde_json::json!({ "jsonrpc": "2.0", "method": "exit" })),
+    ]
+    .concat();
+
+    let started = Instant::now();
+    let mut server = Command::new(env!("CARGO_BIN_EXE_gizmo"))
+        .arg("lsp")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("gizmo should start");
+    server.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+    let output = server.wait_with_output().unwrap();
+    let printed = printed(&output);
+
+    // The cat takes two UTF-16 code units, so the `@` is at 12
+    assert!(printed.contains(r#""start":{"character":12,"line":0}"#), "{}", printed);
+    assert!(printed.contains("script stopped after"), "{}", printed);
+    assert!(started.elapsed() < Duration::from_secs(10), "took {:?}", started.elapsed());
+}
@@ -0,0 +1,131 @@
+//! Helpers shared by the end-to-end tests.
+//!
+//! Frames are written as ASCII art, one string per row, with one character
+//! per pixel:
+//!
+//! | Character   | Palette index |
+//! |-------------|---------------|
+//! | `.`         | 0 (off)       |
+//! | `#`         | 1 (on)        |
+//! | `2` - `9`   | 2 - 9         |
+//! | `a` - `z`   | 10 - 35       |
+//!
+//! Higher indices are shown as `?` in failure output and can't be written.
+
+#![allow(dead_code)]
+
+use gizmo_core::ast::Frame;
+use std::path::{Path, PathBuf};
+
+/// Builds a frame from ASCII art rows (see the module docs).
+pub fn frame(rows: &[&str]) -> Frame {
+    let pixels = rows
+        .iter()
+        .map(|row| row.chars().map(|pixel| index(pixel).unwrap_or_else(|| panic!("no palette index for '{}'", pixel))).collect())
+        .collect();
+    Frame::new(pixels)
+}
+
+/// Compiles a script that must run, panicking with the error report if it doesn't.
+pub fn compile(source: &str) -> gizmo_core::Animation {
+    gizmo_core::compile(source).unwrap_or_else(|e| panic!("script failed:\n{}", e.report(source)))
+}
+
+/// Returns the path of a sample script in `tests/scripts`.
+pub fn script(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("scripts").join(name)
+}
+
+/// Asserts that two frame sequences are the same, showing the first frame
+/// that differs as ASCII art with the differing pixels marked on failure:
+///
+/// ```text
+/// frame 2 of 3 differs
+///   expected  actual    diff
+///   .##.      .##.      ....
+///   #..#      #.##      ..^.
+/// ```
+#[track_caller]
+pub fn assert_frames_eq(actual: &[Frame], expected: &[Frame]) {
+    if actual.len() != expected.len() {
+        panic!(
+            "expected {} frames, got {}\n\nexpected:\n{}\nactual:\n{}",
+            expected.len(),
+            actual.len(),
+            sequence_ascii(expected),
+            sequence_ascii(actual)
+        );
+    }
+    for (number, (actual_frame, expected_frame)) in actual.iter().zip(expected).enumerate() {
+        if actual_frame != expected_frame {
+            panic!("frame {} of {} differs\n{}", number + 1, expected.len(), diff(actual_frame, expected_frame));
+        }
+    }
+}
+
+/// Renders a frame as ASCII art rows.
+pub fn ascii(frame: &Frame) -> Vec<String> {
+    frame.pixels.iter().map(|row| row.iter().map(|&pixel| character(pixel)).collect()).collect()
+}
+
+/// Renders frames one after another, numbered.
+fn sequence_ascii(frames: &[Frame]) -> String {
+    let mut text = String::new();
+    for (number, frame) in frames.iter().enumerate() {
+        text.push_str(&format!("  frame {} ({}x{})\n", number + 1, frame.width, frame.height));
+        for row in ascii(frame) {
+            text.push_str(&format!("  {}\n", row));
+        }
+    }
+    text
+}
+
+/// Shows two frames side by side with a third grid marking where they differ.
+fn diff(actual: &Frame, expected: &Frame) -> String {
+    let (expected_rows, actual_rows) = (ascii(expected), ascii(actual));
+    let width = expected.width.max(actual.width);
+    let height = expected.height.max(actual.height);
+    let column = width.max("expected".len()) + 2;
+
+    let mut text = String::new();
+    if (actual.width, actual.height) != (expected.width, expected.height) {
+        text.push_str(&format!(
+            "  size: expected {}x{}, got {}x{}\n",
+            expected.width, expected.height, actual.width, actual.height
+        ));
+    }
+    text.push_str(&format!("  {:<column$}{:<column$}diff\n", "expected", "actual"));
+    for y in 0..height {
+        let marks: String = (0..width)
+            .map(|x| {
+                let pixel = |frame: &Frame| frame.pixels.get(y).and_then(|row| row.get(x)).copied();
+                if pixel(actual) == pixel(expected) { '.' } else { '^' }
+            })
+            .collect();
+        let row = |rows: &[String]| rows.get(y).cloned().unwrap_or_default();
+        text.push_str(&format!("  {:<column$}{:<column$}{}\n", row(&expected_rows), row(&actual_rows), marks));
+    }
+    text
+}
+
+/// Returns the character a palette index is written as.
+fn character(index: u8) -> char {
+    match index {
+        0 => '.',
+        1 => '#',
+        2..=9 => (b'0' + index) as char,
+        10..=35 => (b'a' + index - 10) as char,
+        _ => '?',
+    }
+}
+
+/// Returns the palette index a character stands for.
+fn index(character: char) -> Option<u8> {
+    match character {
+        '.' => Some(0),
+        '#' => Some(1),
+        '2'..='9' => Some(character as u8 - b'0'),
+        'a'..='z' => Some(character as u8 - b'a' + 10),
+        _ => None,
+    }
+}
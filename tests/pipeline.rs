@@ -0,0 +1,213 @@
+//! End-to-end tests of the script pipeline: source in, frames and timing out.
+
+mod common;
+
+use common::{assert_frames_eq, compile, frame, script};
+use std::fs;
+
+#[test]
+fn frame_literals_play_in_order() {
+    let source = fs::read_to_string(script("blink.gzmo")).unwrap();
+    let animation = compile(&source);
+
+    let open = frame(&[".##.", "#..#", ".##."]);
+    let closed = frame(&["....", "####", "...."]);
+    assert_frames_eq(animation.frames(), &[open.clone(), open, closed]);
+    assert_eq!(animation.frame_duration_ms(), 250);
+}
+
+#[test]
+fn patterns_use_time_and_palette_colors() {
+    let source = fs::read_to_string(script("stripes.gzmo")).unwrap();
+    let animation = compile(&source);
+
+    assert_frames_eq(
+        animation.frames(),
+        &[
+            frame(&[".23.", "23.2", "3.23"]),
+            frame(&["23.2", "3.23", ".23."]),
+            frame(&["3.23", ".23.", "23.2"]),
+        ],
+    );
+    assert_eq!(animation.frame_duration_ms(), 80);
+    assert_eq!(animation.palette().color(2), 0xFFFF0000);
+    assert_eq!(animation.palette().color(3), 0xFF00FF00);
+}
+
+#[test]
+fn frame_duration_defaults_to_100ms() {
+    let animation = compile("frame dot = [[1]];\nloop(dot);");
+
+    assert_frames_eq(animation.frames(), &[frame(&["#"])]);
+    assert_eq!(animation.frame_duration_ms(), 100);
+}
+
+#[test]
+fn scripts_without_play_have_no_frames() {
+    let animation = compile("frame dot = [[1]];");
+
+    assert_frames_eq(animation.frames(), &[]);
+}
+
+#[test]
+fn last_play_wins() {
+    let animation = compile("frame a = [[1, 0], [1, 0]];\nframe b = [[0, 1], [0, 1]];\nframes both = [b, a];\nplay(a);\nloop_speed(both, 40);");
+
+    assert_frames_eq(animation.frames(), &[frame(&[".#", ".#"]), frame(&["#.", "#."])]);
+    assert_eq!(animation.frame_duration_ms(), 40);
+}
+
+#[test]
+fn if_and_ternary_pick_pixels() {
+    let animation = compile(
+        "
+frames grid = [];
+repeat 2 times do
+    if time == 0 then
+        add_frame(grid, pattern(3, 2) { return col == row ? color(5) : 0; });
+    else
+        add_frame(grid, pattern(3, 2) { return col > row; });
+    end;
+end;
+loop(grid);
+",
+    );
+
+    assert_frames_eq(animation.frames(), &[frame(&["5..", ".5."]), frame(&[".##", "..#"])]);
+}
+
+#[test]
+fn click_handlers_switch_frames() {
+    let mut animation = compile(
+        "
+frame idle = [[1, 1], [1, 1]];
+frame happy = [[2, 2], [0, 0]];
+frames wiggle = [happy, idle];
+loop(idle);
+when clicked do
+    loop_speed(wiggle, 60);
+end;
+",
+    );
+    let interpreter = animation.interpreter_mut();
+    let version = interpreter.sequence_version();
+    interpreter.handle_click_event().unwrap();
+
+    assert_ne!(interpreter.sequence_version(), version);
+    assert_frames_eq(&interpreter.get_animation_frames(), &[frame(&["22", ".."]), frame(&["##", "##"])]);
+    assert_eq!(interpreter.get_frame_duration_ms(), 60);
+}
+
+#[test]
+fn failing_handlers_keep_the_last_animation() {
+    let mut animation = compile(
+        "
+frame idle = [[1]];
+frame broken = [[3]];
+loop(idle);
+when clicked do
+    play(broken);
+    oops = 1 / 0;
+end;
+",
+    );
+    let interpreter = animation.interpreter_mut();
+    let version = interpreter.sequence_version();
+    let error = interpreter.handle_click_event().unwrap_err();
+
+    assert_eq!(error.position(), Some((7, 5)));
+    assert_eq!(interpreter.sequence_version(), version);
+    assert_frames_eq(&interpreter.get_animation_frames(), &[frame(&["#"])]);
+}
+
+#[test]
+fn runtime_errors_are_located() {
+    let source = "frame dot = [[1]];\nx = 2;\ny = x / 0;\nloop(dot);";
+    let error = gizmo_core::compile(source).err().unwrap();
+
+    assert_eq!(error.position(), Some((3, 1)));
+    assert_eq!(error.to_string(), "Division by zero at line 3, column 1");
+    assert!(error.report(source).contains("  3 | y = x / 0;"));
+}
+
+#[test]
+fn syntax_errors_are_located() {
+    let error = gizmo_core::compile("frame dot = [[1]];\nframe = 3;").err().unwrap();
+
+    assert_eq!(error.position(), Some((2, 7)));
+}
+
+#[test]
+fn previous_is_only_defined_after_the_first_pass() {
+    let first_pass = "frame f = pipeline(2, 2) {\n    pass { return get_pixel(previous, col, row); }\n};";
+    let error = gizmo_core::compile(first_pass).err().unwrap();
+    assert_eq!(error.without_position().to_string(), "Undefined variable: previous");
+
+    // Nor does it outlive the pipeline, even for the next one's first pass
+    let afterwards = "
+frame f = pipeline(2, 2) {
+    pass { return 1; }
+    pass { return get_pixel(previous, col, row); }
+};
+frame g = pipeline(2, 2) {
+    pass { return get_pixel(previous, col, row); }
+};
+";
+    let error = gizmo_core::compile(afterwards).err().unwrap();
+    assert_eq!(error.position(), Some((6, 1)));
+}
+
+#[test]
+fn pipelines_leave_a_previous_variable_alone() {
+    let animation = compile(
+        "
+frame previous = [[2, 2], [2, 2]];
+frame f = pipeline(2, 2) {
+    pass { return col; }
+    pass { return get_pixel(previous, col, row) ? color(3) : 0; }
+};
+frames both = [f, previous];
+loop(both);
+",
+    );
+
+    assert_frames_eq(animation.frames(), &[frame(&[".3", ".3"]), frame(&["22", "22"])]);
+}
+
+#[test]
+fn frame_literals_are_held_to_the_limits() {
+    let run = |source: &str| {
+        let mut interpreter = gizmo_core::Interpreter::new();
+        interpreter.set_limits(gizmo_core::Limits { max_frames: Some(2), max_frame_size: Some(3), ..gizmo_core::Limits::default() });
+        interpreter.execute(&gizmo_core::parse(source).unwrap()).err().map(|error| error.without_position().to_string())
+    };
+
+    assert_eq!(run("frame wide = [[1, 1, 1]];"), None);
+    assert_eq!(run("frame wide = [[1, 1, 1, 1]];").unwrap(), "Runtime error: frame size 4x1 is over the 3x3 limit");
+    assert_eq!(run("frame tall = [[1], [1], [1], [1]];").unwrap(), "Runtime error: frame size 1x4 is over the 3x3 limit");
+    assert_eq!(
+        run("frame a = [[1, 0], [0, 1]];\nframes many = [a, a, a];").unwrap(),
+        "Runtime error: a frames array would hold more than 2 frames"
+    );
+}
+
+#[test]
+fn scripts_cannot_use_the_reserved_palette_indices() {
+    let animation = compile("frame high = [[251, 300], [1, 0]];\nloop(high);");
+    assert_eq!(animation.frames()[0].pixels[0], [251, 251]);
+
+    let error = gizmo_core::compile("frame f = pattern(1, 1) { return color(252); };").err().unwrap();
+    assert_eq!(error.without_position().to_string(), "Argument error: color index must be between 0 and 251, got 252");
+
+    let colors = vec!["\"#000000\""; 253].join(", ");
+    let error = gizmo_core::compile(&format!("palette([{}]);", colors)).err().unwrap();
+    assert!(error.to_string().contains("at most 252 colors"), "{}", error);
+}
+
+#[test]
+fn wander_speeds_must_be_walkable() {
+    let error = gizmo_core::compile("wander(0.000001);").err().unwrap();
+    assert_eq!(error.without_position().to_string(), "Argument error: wander speed must be at least 1 pixel per second, got 0.000001");
+
+    compile("wander(40);");
+}